@@ -36,11 +36,47 @@ fn main() {
     command.arg("-d");
     command.arg("resource");
     command
-        .spawn()
+        .status()
         .expect("Failed to unzip lila-public-piece.zip");
 
     let mut command = std::process::Command::new("python3");
     command.arg("extra/convert.py");
 
-    command.spawn().expect("Failed to run convert.py");
+    command.status().expect("Failed to run convert.py");
+
+    embed_pieces();
+}
+
+/// Writes `embedded_pieces.rs` to the output directory, including the rendered images of the
+/// default piece set in the binary for the builds that can't read them from disk. The list is
+/// empty when they weren't rendered
+fn embed_pieces() {
+    let dir = std::env::current_dir()
+        .expect("Failed to read the current directory")
+        .join("resource")
+        .join("alpha");
+    let stems = [
+        "wP", "wR", "wN", "wB", "wQ", "wK", "bP", "bR", "bN", "bB", "bQ", "bK",
+    ];
+    let images = stems
+        .iter()
+        .map(|stem| (stem, dir.join(format!("{}.svg.png", stem))))
+        .collect::<Vec<_>>();
+    let entries = if images.iter().all(|(_, path)| path.is_file()) {
+        images
+            .iter()
+            .map(|(stem, path)| format!("    ({:?}, include_bytes!({:?})),\n", stem, path))
+            .collect::<String>()
+    } else {
+        String::new()
+    };
+
+    let out = PathBuf::from(std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo"));
+    let code = format!(
+        "/// Images of the default piece set as PNG, by file name without its ending\n\
+         pub const EMBEDDED_PIECES: &[(&str, &[u8])] = &[\n{}];\n",
+        entries
+    );
+    std::fs::write(out.join("embedded_pieces.rs"), code)
+        .expect("Failed to write embedded_pieces.rs");
 }
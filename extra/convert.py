@@ -6,25 +6,25 @@ import subprocess
 
 def main():
     """
-    Main function to convert HEIC to JPG
-    Creates a new directory to store the converted files
+    Main function to convert the SVG piece sets to PNG
+    Writes the PNG of each piece next to its SVG
 
-    "INPUT" directory is the directory where the HEIC files are located
-    "OUTPUT" directory is the directory where the JPG files are located
+    Every directory under "./resource" holding SVG pieces is a piece set, as unzipped from
+    lila-public-piece.zip
     """
-    print("Converting HEIC to JPG")
+    print("Converting SVG to PNG")
 
-    input_dir = "./resource/alpha"
+    resource_dir = "./resource"
 
-    # Create a new directory to store the converted files
-    output_dir = "./resource/output"
-    os.makedirs(output_dir, exist_ok=True)
+    for piece_set in sorted(os.listdir(resource_dir)):
+        input_dir = f"{resource_dir}/{piece_set}"
+        if not os.path.isdir(input_dir):
+            continue
 
-    # Convert HEIC to JPG
-    for filename in os.listdir(input_dir):
-        if filename.endswith(".svg"):
-            print(f"Converting {filename} to jpg")
-            subprocess.run(["ffmpeg", "-i", f"{input_dir}/{filename}", f"{output_dir}/{filename}.png"])
+        for filename in os.listdir(input_dir):
+            if filename.endswith(".svg") and not os.path.exists(f"{input_dir}/{filename}.png"):
+                print(f"Converting {piece_set}/{filename} to png")
+                subprocess.run(["ffmpeg", "-i", f"{input_dir}/{filename}", f"{input_dir}/{filename}.png"])
 
     print("Conversion complete")
 
@@ -0,0 +1,2443 @@
+//! The board and the rules of chess: pieces, moves, move generation and FEN
+
+#![deny(missing_docs)]
+
+use std::fmt::Display;
+
+use crate::zobrist;
+
+mod builder;
+mod fen;
+#[cfg(test)]
+mod fen_tests;
+mod odds;
+#[cfg(test)]
+mod perft_tests;
+mod pocket;
+#[cfg(feature = "serde")]
+mod serial;
+mod variant;
+#[cfg(debug_assertions)]
+mod watchdog;
+
+pub use builder::BoardBuilder;
+pub use odds::Odds;
+pub use pocket::{Pocket, POCKET_PIECES};
+pub use variant::{Variant, VariantEnd, HORDE_FEN};
+#[cfg(debug_assertions)]
+pub use watchdog::watchdog;
+
+/// Position every standard game starts from
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Represents the color of a given piece
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PieceColor {
+    /// the side that moves first
+    White = 0,
+    /// the side that moves second
+    Black = 8,
+}
+
+impl PieceColor {
+    /// Returns the other color
+    pub fn opposite(self) -> Self {
+        match self {
+            PieceColor::White => PieceColor::Black,
+            PieceColor::Black => PieceColor::White,
+        }
+    }
+}
+
+/// Represents the type of a given piece
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PieceType {
+    /// moves one square ahead, captures diagonally
+    Pawn = 1,
+    /// jumps in an L
+    Knight = 2,
+    /// slides along the diagonals
+    Bishop = 3,
+    /// slides along ranks and files
+    Rook = 4,
+    /// slides along ranks, files and diagonals
+    Queen = 5,
+    /// moves one square, castles
+    King = 6,
+
+    /// an empty square
+    None = -1,
+}
+
+/// Represents a move on a board from idex to idex with a movetype
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    /// square the piece leaves
+    pub from: usize,
+    /// square the piece lands on
+    pub to: usize,
+    /// how the piece moves, captures and promotes
+    pub move_type: MoveType,
+}
+
+impl Move {
+    /// A move that only passes the turn, see [Board::make_null_move]
+    pub fn null() -> Self {
+        Move {
+            from: 0,
+            to: 0,
+            move_type: MoveType::None,
+        }
+    }
+
+    /// Returns true if this is a [Move::null]
+    pub fn is_null(&self) -> bool {
+        self.move_type == MoveType::None
+    }
+
+    /// Dropping a `piece_type` from the pocket on `to`, in crazyhouse. A drop has no square to
+    /// come from, `from` is `to`
+    pub fn new_drop(piece_type: PieceType, to: usize) -> Self {
+        Move {
+            from: to,
+            to,
+            move_type: MoveType::Drop(piece_type),
+        }
+    }
+
+    /// Returns the piece dropped from the pocket if this move is a drop
+    pub fn dropped_piece(&self) -> Option<PieceType> {
+        match self.move_type {
+            MoveType::Drop(piece_type) => Some(piece_type),
+            _ => None,
+        }
+    }
+
+    /// Returns the piece a pawn promotes to if this move is a promotion
+    pub fn promotion_piece(&self) -> Option<PieceType> {
+        match self.move_type {
+            MoveType::PawnPush { promotion_piece } | MoveType::PawnCapture { promotion_piece } => {
+                promotion_piece
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Formats the move in long algebraic notation e.g. `e2e4`, `e7e8q`, null moves are `0000` and
+/// drops `P@e4`
+impl Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_null() {
+            return write!(f, "0000");
+        }
+        if let Some(piece) = self.dropped_piece() {
+            let c = Piece::new(PieceColor::White, piece)
+                .to_fen_char()
+                .expect("dropped piece is never None");
+            return write!(f, "{}@{}", c, square_to_algebraic(self.to));
+        }
+        write!(
+            f,
+            "{}{}",
+            square_to_algebraic(self.from),
+            square_to_algebraic(self.to)
+        )?;
+        if let Some(piece) = self.promotion_piece() {
+            let c = Piece::new(PieceColor::Black, piece)
+                .to_fen_char()
+                .expect("promotion piece is never None");
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which castling moves are still allowed, kept by the board as the rooks that haven't moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CastlingRights {
+    /// white can castle short
+    pub white_king_side: bool,
+    /// white can castle long
+    pub white_queen_side: bool,
+    /// black can castle short
+    pub black_king_side: bool,
+    /// black can castle long
+    pub black_queen_side: bool,
+}
+
+impl CastlingRights {
+    /// Parses the castling field of a FEN e.g. `KQkq`, `-` for none
+    pub fn from_fen(field: &str) -> Option<Self> {
+        let mut rights = CastlingRights::default();
+        if field == "-" {
+            return Some(rights);
+        }
+        for c in field.chars() {
+            match c {
+                'K' => rights.white_king_side = true,
+                'Q' => rights.white_queen_side = true,
+                'k' => rights.black_king_side = true,
+                'q' => rights.black_queen_side = true,
+                _ => return None,
+            }
+        }
+        Some(rights)
+    }
+}
+
+/// Formats the rights as in a FEN, e.g. `KQkq` or `-`
+impl Display for CastlingRights {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let flags = [
+            (self.white_king_side, 'K'),
+            (self.white_queen_side, 'Q'),
+            (self.black_king_side, 'k'),
+            (self.black_queen_side, 'q'),
+        ];
+        let mut any = false;
+        for (set, c) in flags {
+            if set {
+                write!(f, "{}", c)?;
+                any = true;
+            }
+        }
+        if !any {
+            write!(f, "-")?;
+        }
+        Ok(())
+    }
+}
+
+/// Pieces a pawn can promote to, in order of preference
+pub const PROMOTION_PIECES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+
+/// Represents different types of moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MoveType {
+    #[default]
+    /// no move, the null move and placeholders
+    None,
+    /// a pawn one square ahead, promoting on the last rank
+    PawnPush {
+        /// piece the pawn becomes on the last rank
+        promotion_piece: Option<PieceType>,
+    },
+    /// a pawn two squares ahead from its first rank
+    PawnDoublePush,
+    /// a pawn taking diagonally, promoting on the last rank
+    PawnCapture {
+        /// piece the pawn becomes on the last rank
+        promotion_piece: Option<PieceType>,
+    }, // When a pawn captures a piece
+    /// a pawn taking the pawn that just passed it, which stands on the given square
+    PawnEnPassant(Coordinate), // When a pawn captures a piece en passant
+
+    /// a queen, capturing or not
+    QueenMove,
+    /// a rook, capturing or not
+    RookMove,
+    /// a bishop, capturing or not
+    BishopMove,
+    /// a knight, capturing or not
+    KnightMove,
+    /// a king moving one square, capturing or not
+    KingMove,
+
+    /// castling on the king's side
+    CastelKingSide,
+    /// castling on the queen's side
+    CastelQueenSide,
+
+    /// a piece of the pocket put on an empty square, in crazyhouse
+    Drop(PieceType),
+}
+
+/// Represents a single piece.
+///
+/// a 4bit integer is used to represent the piece
+///
+/// 0000
+///
+/// first bit is used as colour bit and last 3 bit is used as piece type bit
+#[derive(Eq, Hash, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Piece {
+    /// side the piece belongs to
+    pub piece_color: PieceColor,
+    /// what the piece is, [PieceType::None] for an empty square
+    pub piece_type: PieceType,
+}
+
+impl Piece {
+    /// A piece of `piece_color` and `piece_type`
+    pub fn new(piece_color: PieceColor, piece_type: PieceType) -> Self {
+        Piece {
+            piece_color,
+            piece_type,
+        }
+    }
+
+    /// creates a new none type of piece
+    pub fn new_none() -> Self {
+        Piece {
+            piece_type: PieceType::None,
+            piece_color: PieceColor::White,
+        }
+    }
+
+    /// sets the type of the piece
+    fn set_type(&mut self, piece_type: PieceType) {
+        self.piece_type = piece_type;
+    }
+
+    /// sets the color of the piece
+    fn set_color(&mut self, piece_color: PieceColor) {
+        self.piece_color = piece_color;
+    }
+
+    /// Returns true if this is an empty square
+    pub fn is_none(&self) -> bool {
+        self.piece_type == PieceType::None
+    }
+
+    /// Parses a FEN piece character, upper case for white, `None` if it isn't a piece
+    pub fn from_fen_char(c: char) -> Option<Self> {
+        let piece_type = match c.to_ascii_lowercase() {
+            'p' => PieceType::Pawn,
+            'n' => PieceType::Knight,
+            'b' => PieceType::Bishop,
+            'r' => PieceType::Rook,
+            'q' => PieceType::Queen,
+            'k' => PieceType::King,
+            _ => return None,
+        };
+        let piece_color = if c.is_ascii_uppercase() {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        };
+        Some(Piece::new(piece_color, piece_type))
+    }
+
+    /// Returns the FEN character of the piece, upper case for white, `None` for an empty square
+    pub fn to_fen_char(self) -> Option<char> {
+        let c = match self.piece_type {
+            PieceType::Pawn => 'p',
+            PieceType::Knight => 'n',
+            PieceType::Bishop => 'b',
+            PieceType::Rook => 'r',
+            PieceType::Queen => 'q',
+            PieceType::King => 'k',
+            PieceType::None => return None,
+        };
+        match self.piece_color {
+            PieceColor::White => Some(c.to_ascii_uppercase()),
+            PieceColor::Black => Some(c),
+        }
+    }
+}
+
+/// The direction a pawn is able to capture
+enum PawnCaptureDirection {
+    Left,
+    Right,
+}
+
+impl Piece {
+    /// Returns colors
+    pub fn get_color(&self) -> PieceColor {
+        self.piece_color
+    }
+
+    /// Returns type
+    pub fn get_type(&self) -> PieceType {
+        self.piece_type
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// Coordinates of the board as x and y
+/// where (0, 0) -> h1 (aka idx 0)
+/// where (7, 7) -> a8 (aka idx 63)
+pub struct Coordinate {
+    x: usize,
+    y: usize,
+}
+
+impl From<usize> for Coordinate {
+    fn from(idx: usize) -> Self {
+        let x = idx % 8;
+        let y = idx / 8;
+        Coordinate { x, y }
+    }
+}
+
+/// Returns the algebraic name of the square at `idx` e.g. 0 -> "h1", 63 -> "a8"
+pub fn square_to_algebraic(idx: usize) -> String {
+    let co = Coordinate::from(idx);
+    let file = (b'h' - co.x as u8) as char;
+    format!("{}{}", file, co.y + 1)
+}
+
+/// Returns the index of an algebraic square name e.g. "h1" -> 0, "a8" -> 63
+pub fn algebraic_to_square(name: &str) -> Option<usize> {
+    let &[file, rank] = name.as_bytes() else {
+        return None;
+    };
+    if !(b'a'..=b'h').contains(&file) || !(b'1'..=b'8').contains(&rank) {
+        return None;
+    }
+    Some((rank - b'1') as usize * 8 + (b'h' - file) as usize)
+}
+
+/// Uses isize instea of usize to safely determine can an index be out of bounds or not
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
+struct SafeCoordinate {
+    x: isize,
+    y: isize,
+}
+
+impl SafeCoordinate {
+    // TODO: pawn promote
+    fn new(x: isize, y: isize) -> Self {
+        SafeCoordinate { x, y }
+    }
+
+    fn is_out_of_bounds(&self) -> bool {
+        self.x < 0 || self.x > 7 || self.y < 0 || self.y > 7
+    }
+
+    /// Convertes [SafeCoordinate] to [Coordinate]
+    /// will assert the inner `x` and `y` are in bounds see [SafeCoordinate::is_out_of_bounds]
+    fn to_coordinate(self) -> Coordinate {
+        assert!(!self.is_out_of_bounds());
+        Coordinate {
+            x: self.x as usize,
+            y: self.y as usize,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Hash, Eq, PartialEq)]
+/// a 64 bit integer matrix to represent the board
+pub struct BitBoard {
+    inner: u64,
+}
+
+impl From<u64> for BitBoard {
+    fn from(value: u64) -> Self {
+        BitBoard { inner: value }
+    }
+}
+
+impl BitBoard {
+    /// Sets a bit to 1 at idx
+    pub fn set_bit(&mut self, idx: usize) {
+        self.inner |= 1u64 << (idx);
+    }
+
+    /// Sets a bit to 0 at idx
+    pub fn clear_bit(&mut self, idx: usize) {
+        self.inner &= !(1u64 << (idx));
+    }
+
+    /// Gets the bit at idx
+    pub fn get_bit(&self, idx: usize) -> bool {
+        (self.inner & (1u64 << (idx))) != 0
+    }
+
+    /// Replaces the `inner` value to new `value`
+    pub fn set(&mut self, value: u64) {
+        self.inner = value;
+    }
+
+    /// Sets bitboard to Zero
+    pub fn zero(&mut self) {
+        self.inner = 0;
+    }
+}
+
+impl From<Piece> for u16 {
+    fn from(value: Piece) -> Self {
+        value.get_type() as u16 | value.get_color() as u16
+    }
+}
+
+impl From<u16> for Piece {
+    fn from(value: u16) -> Self {
+        let color_mask: u16 = 0b1000;
+        let type_mask: u16 = 0b0111;
+
+        let piece_color = match value & color_mask {
+            0 => PieceColor::White,
+            _ => PieceColor::Black,
+        };
+
+        let piece_type = match value & type_mask {
+            1 => PieceType::Pawn,
+            2 => PieceType::Knight,
+            3 => PieceType::Bishop,
+            4 => PieceType::Rook,
+            5 => PieceType::Queen,
+            6 => PieceType::King,
+            _ => PieceType::None,
+        };
+
+        Piece {
+            piece_color,
+            piece_type,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+/// Board Representation
+pub struct Board {
+    white_pawn_bitboard: BitBoard,
+    white_rook_bitboard: BitBoard,
+    white_knight_bitboard: BitBoard,
+    white_bishop_bitboard: BitBoard,
+    white_queen_bitboard: BitBoard,
+    white_king_bitboard: BitBoard,
+
+    black_pawn_bitboard: BitBoard,
+    black_rook_bitboard: BitBoard,
+    black_knight_bitboard: BitBoard,
+    black_bishop_bitboard: BitBoard,
+    black_queen_bitboard: BitBoard,
+    black_king_bitboard: BitBoard,
+
+    /// Squares occupied by any piece of each color
+    white_occupancy: BitBoard,
+    black_occupancy: BitBoard,
+
+    black_control_bitboard: BitBoard,
+    white_control_bitboard: BitBoard,
+
+    white_castling_right: BitBoard,
+    black_castling_right: BitBoard,
+
+    /// Each cell holds a value which represents a piece
+    board: [u16; 64],
+
+    /// Current avaliable moves
+    current_moves: Vec<Move>,
+
+    move_history: Vec<Move>,
+
+    white_current_moves: Vec<Move>,
+    black_current_moves: Vec<Move>,
+
+    /// The current turn
+    is_white_turn: bool,
+
+    /// Square a pawn can capture en passant on, set after a double push
+    en_passant: Option<usize>,
+    /// En passant squares cleared by null moves, restored when they are unmade
+    null_move_en_passant: Vec<Option<usize>>,
+    /// What each move of the history changed, to take it back with [Board::unmake_move]
+    undo_history: Vec<MoveUndo>,
+
+    /// Zobrist hash of the position, refreshed whenever the turn changes
+    hash: u64,
+
+    /// Plies since the last capture or pawn move
+    halfmove_clock: u32,
+    /// Plies played before the loaded position, so the move number carries on from its FEN
+    start_ply: usize,
+
+    /// Rules the position is played by, only crazyhouse can be told from a FEN
+    variant: Variant,
+    /// White's and black's pockets
+    pockets: [Pocket; 2],
+    /// Pieces that were pawns before promoting, which go back to the pocket as pawns
+    promoted: BitBoard,
+    /// Checks given by white and by black, counted in three-check
+    checks: [u8; 2],
+}
+
+/// Why a move can't be played
+pub enum MoveError {
+    /// the move isn't legal in the position
+    InvalidMove,
+}
+
+/// State a move loses that can't be worked out from the move itself
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct MoveUndo {
+    /// piece taken and the square it stood on, which differs from the target en passant
+    captured: Option<(usize, Piece)>,
+    white_castling_right: u64,
+    black_castling_right: u64,
+    en_passant: Option<usize>,
+    halfmove_clock: u32,
+    pockets: [Pocket; 2],
+    promoted: u64,
+    /// pieces an atomic capture blew up with their squares, the capturing piece among them
+    exploded: Vec<(usize, Piece)>,
+    checks: [u8; 2],
+}
+
+/// Splits the pockets off a crazyhouse placement, written after the ranks in brackets or as a
+/// 9th rank
+fn split_pocket(placement: &str) -> (&str, Option<&str>) {
+    if let Some((ranks, pocket)) = placement.split_once('[') {
+        return (ranks, Some(pocket.trim_end_matches(']')));
+    }
+    match placement.rsplit_once('/') {
+        Some((ranks, pocket)) if placement.matches('/').count() == 8 => (ranks, Some(pocket)),
+        _ => (placement, None),
+    }
+}
+
+/// Index of `color` in the values the board keeps for each side, such as the pockets
+fn side_index(color: PieceColor) -> usize {
+    match color {
+        PieceColor::White => 0,
+        PieceColor::Black => 1,
+    }
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Board {
+    /// The piece of each square, as `u16::from(piece)` encodes it
+    pub fn clone_board(&self) -> Vec<u16> {
+        self.board.to_vec().clone()
+    }
+    /// An empty board with white to move, load a position with [Board::load_position]
+    pub fn new() -> Self {
+        let mut board = Board {
+            board: [0; 64],
+            is_white_turn: true,
+            current_moves: Vec::new(),
+
+            white_current_moves: Vec::new(),
+            black_current_moves: Vec::new(),
+
+            move_history: Vec::new(),
+            en_passant: None,
+            null_move_en_passant: Vec::new(),
+            undo_history: Vec::new(),
+            white_pawn_bitboard: BitBoard { inner: 0 },
+            white_rook_bitboard: BitBoard { inner: 0 },
+            white_knight_bitboard: BitBoard { inner: 0 },
+            white_bishop_bitboard: BitBoard { inner: 0 },
+            white_queen_bitboard: BitBoard { inner: 0 },
+            white_king_bitboard: BitBoard { inner: 0 },
+
+            black_pawn_bitboard: BitBoard { inner: 0 },
+            black_rook_bitboard: BitBoard { inner: 0 },
+            black_knight_bitboard: BitBoard { inner: 0 },
+            black_bishop_bitboard: BitBoard { inner: 0 },
+            black_queen_bitboard: BitBoard { inner: 0 },
+            black_king_bitboard: BitBoard { inner: 0 },
+
+            white_occupancy: BitBoard { inner: 0 },
+            black_occupancy: BitBoard { inner: 0 },
+
+            white_control_bitboard: BitBoard { inner: 0 },
+            black_control_bitboard: BitBoard { inner: 0 },
+
+            // specified values for right and left rooks on each colour complex
+            white_castling_right: BitBoard { inner: 129 },
+            black_castling_right: BitBoard {
+                inner: 9295429630892703744,
+            },
+            hash: 0,
+            halfmove_clock: 0,
+            start_ply: 0,
+            variant: Variant::Standard,
+            pockets: [Pocket::default(); 2],
+            promoted: BitBoard { inner: 0 },
+            checks: [0; 2],
+        };
+        board.hash = board.compute_hash();
+        board
+    }
+
+    /// Returns all the moves played so far
+    pub fn get_move_history(&self) -> &[Move] {
+        &self.move_history
+    }
+
+    /// Returns the pseudo legal moves of the side to move
+    pub fn get_moves_for_turn(&self) -> &[Move] {
+        if self.is_white_turn {
+            &self.white_current_moves
+        } else {
+            &self.black_current_moves
+        }
+    }
+
+    /// Plies since the last capture or pawn move, for the fifty-move rule
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    /// Returns the pseudo legal moves of `color`, whoever is to move
+    pub fn moves_of(&self, color: PieceColor) -> &[Move] {
+        match color {
+            PieceColor::White => &self.white_current_moves,
+            PieceColor::Black => &self.black_current_moves,
+        }
+    }
+
+    /// Gets all the moves that match the same [to] and [from], there is more than one only when
+    /// a pawn promotes as every promotion piece is its own move
+    pub fn get_all_avaliable_moves(&self, from: usize, to: usize) -> Vec<Move> {
+        self.get_moves_for_turn()
+            .iter()
+            .filter(|m| m.from == from && m.to == to)
+            .cloned()
+            .collect::<Vec<_>>()
+    }
+
+    /// Returns true if playing `mo` doesn't leave the mover's king in check, nor castles out of
+    /// or through check
+    pub fn is_legal(&self, mo: &Move) -> bool {
+        let mover = self.get_turn();
+        let crossed = match mo.move_type {
+            MoveType::CastelKingSide => Some(mo.from - 1),
+            MoveType::CastelQueenSide => Some(mo.from + 1),
+            _ => None,
+        };
+        if let Some(crossed) = crossed {
+            // the king may castle only if it could stop on the square it crosses
+            let step = Move {
+                from: mo.from,
+                to: crossed,
+                move_type: MoveType::KingMove,
+            };
+            if self.is_in_check(mover) || !self.is_legal(&step) {
+                return false;
+            }
+        }
+        let mut board = self.clone();
+        if board.play(mo).is_err() {
+            return false;
+        }
+        !board.is_in_check(mover)
+    }
+
+    /// Iterates over the legal moves of the side to move
+    pub fn legal_moves(&self) -> impl Iterator<Item = Move> + '_ {
+        self.get_moves_for_turn()
+            .iter()
+            .filter(|m| self.is_legal(m))
+            .cloned()
+    }
+
+    /// Iterates over the legal moves of the piece on `square`, empty if it isn't the turn of
+    /// that piece
+    pub fn moves_from(&self, square: usize) -> impl Iterator<Item = Move> + '_ {
+        self.get_moves_for_turn()
+            .iter()
+            .filter(move |m| m.from == square && m.dropped_piece().is_none() && self.is_legal(m))
+            .cloned()
+    }
+
+    /// Counts the leaf nodes of the legal move tree `depth` plies deep, compared against known
+    /// counts to validate the move generator
+    pub fn perft(&self, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        self.legal_moves()
+            .map(|m| {
+                let mut board = self.clone();
+                match board.play(&m) {
+                    Ok(()) => board.perft(depth - 1),
+                    Err(_) => 0,
+                }
+            })
+            .sum()
+    }
+
+    /// Adds moves to `self.current_moves` whilest updating the white/black board control bitboard
+    fn update_color_control_square_for_move(&mut self, mov: Move, color: &PieceColor) {
+        let bitboard = match color {
+            PieceColor::White => &mut self.white_control_bitboard,
+            PieceColor::Black => &mut self.black_control_bitboard,
+        };
+        // updating the control bit board, as king and pawn pushes cannot be used to check the
+        // opponent, we can simply ignore them
+        match mov.move_type {
+            MoveType::PawnPush { .. }
+            | MoveType::PawnDoublePush
+            | MoveType::KingMove
+            | MoveType::Drop(_) => { /* Do nothing */ }
+            _ => bitboard.set_bit(mov.to),
+        }
+    }
+
+    /// Moves the piece on `from` to `to` without passing the turn, promoting a pawn to
+    /// `promoting_pawn_type`. Use [Board::play] to play a move in a game
+    pub fn make_move(
+        &mut self,
+        from: usize,
+        to: usize,
+        promoting_pawn_type: Option<PieceType>,
+    ) -> Result<(), MoveError> {
+        let piece = self.get_piece_at_index(from);
+        let target = self.get_piece_at_index(to);
+
+        if piece.get_type() == PieceType::None {
+            tracing::error!("Invalid piece type");
+            return Err(MoveError::InvalidMove);
+        }
+
+        if piece.get_color() != self.get_turn() {
+            tracing::error!("Invalid turn");
+            return Err(MoveError::InvalidMove);
+        }
+
+        // promotions are generated as one move per piece, so the requested piece picks the move
+        let mo = match self
+            .get_all_avaliable_moves(from, to)
+            .into_iter()
+            .find(|m| m.promotion_piece() == promoting_pawn_type)
+        {
+            Some(mo) => mo,
+            None => {
+                tracing::warn!("Move Not avaliable");
+                return Err(MoveError::InvalidMove);
+            }
+        };
+        // a drop has nothing to move, it comes out of the pocket
+        if let MoveType::Drop(piece_type) = mo.move_type {
+            return self.make_drop(piece_type, to);
+        }
+
+        let captured = match mo.move_type {
+            MoveType::PawnEnPassant(capture_piece) => {
+                let idx = self.get_square(capture_piece.x, capture_piece.y);
+                Some((idx, self.get_piece_at_index(idx)))
+            }
+            _ => (!target.is_none()).then_some((to, target)),
+        };
+        // in atomic a capture can't blow up the capturer's own king, so a king can't capture
+        if self.variant == Variant::Atomic && captured.is_some() {
+            let king = self.king_square(piece.get_color());
+            if king.is_some_and(|king| variant::blast_squares(to).any(|sq| sq == king)) {
+                tracing::warn!("Capture blows up its own king");
+                return Err(MoveError::InvalidMove);
+            }
+        }
+        self.undo_history.push(MoveUndo {
+            captured,
+            white_castling_right: self.white_castling_right.inner,
+            black_castling_right: self.black_castling_right.inner,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            pockets: self.pockets,
+            promoted: self.promoted.inner,
+            exploded: vec![],
+            checks: self.checks,
+        });
+
+        match mo.move_type {
+            MoveType::PawnDoublePush => {
+                self.move_piece(&mo);
+            }
+            MoveType::PawnPush { promotion_piece } => {
+                if let Some(promoting_to) = promotion_piece {
+                    self.promote_pawn(&mo, promoting_to);
+                } else {
+                    self.move_piece(&mo);
+                }
+            }
+            MoveType::PawnEnPassant(capture_piece) => {
+                let pawn_to_capture_idx = self.get_square(capture_piece.x, capture_piece.y);
+                let pawn_to_capture = self.get_piece_at_index(pawn_to_capture_idx);
+                assert!(pawn_to_capture.get_type() == PieceType::Pawn);
+                assert!(pawn_to_capture.get_color() != piece.get_color());
+                assert!(target.get_type() == PieceType::None);
+                self.remove_piece(pawn_to_capture_idx);
+                self.move_piece(&mo);
+            }
+            MoveType::Drop(_) => unreachable!("drops are played by make_drop"),
+            MoveType::None => unreachable!("the null move is never among the generated moves"),
+            MoveType::PawnCapture { promotion_piece } => {
+                if let Some(promoting_to) = promotion_piece {
+                    self.promote_pawn(&mo, promoting_to);
+                } else {
+                    self.capture_piece(&mo);
+                    self.move_piece(&mo);
+                }
+            }
+            MoveType::KingMove => {
+                // if the target square is not empty we need to capture the piece
+                if target.get_type() != PieceType::None {
+                    self.capture_piece(&mo);
+                }
+                self.move_piece(&mo);
+                // Setting casteling right for both side to none
+                if piece.get_color() == PieceColor::White {
+                    self.white_castling_right.set(0);
+                } else {
+                    self.black_castling_right.set(0);
+                }
+            }
+            MoveType::RookMove => {
+                // if the target square is not empty we need to capture the piece
+                if target.get_type() != PieceType::None {
+                    self.capture_piece(&mo);
+                }
+                self.move_piece(&mo);
+                // Setting casteling right for both side to none
+                if piece.get_color() == PieceColor::White {
+                    self.white_castling_right.clear_bit(from);
+                } else {
+                    self.black_castling_right.clear_bit(from);
+                }
+            }
+
+            MoveType::QueenMove | MoveType::BishopMove | MoveType::KnightMove => {
+                // if the target square is not empty we need to capture the piece
+                if target.get_type() != PieceType::None {
+                    self.capture_piece(&mo);
+                }
+                self.move_piece(&mo);
+            }
+            MoveType::CastelKingSide => {
+                assert!(target.get_type() == PieceType::None);
+                assert!(piece.get_type() == PieceType::King);
+                self.move_piece(&mo);
+                let (rook_pos, new_rook_pos) = if piece.get_color() == PieceColor::White {
+                    (0, to + 1)
+                } else {
+                    (56, to + 1)
+                };
+
+                let rook_mov = Move {
+                    from: rook_pos,
+                    to: new_rook_pos,
+                    move_type: MoveType::None,
+                };
+                self.move_piece(&rook_mov);
+
+                // Setting casteling right for both side to none
+                if piece.get_color() == PieceColor::White {
+                    self.white_castling_right.set(0);
+                } else {
+                    self.black_castling_right.set(0);
+                }
+            }
+            MoveType::CastelQueenSide => {
+                assert!(target.get_type() == PieceType::None);
+                assert!(piece.get_type() == PieceType::King);
+                self.move_piece(&mo);
+                let (rook_pos, new_rook_pos) = if piece.get_color() == PieceColor::White {
+                    (7, to - 1)
+                } else {
+                    (63, to - 1)
+                };
+                let rook_mov = Move {
+                    from: rook_pos,
+                    to: new_rook_pos,
+                    move_type: MoveType::None,
+                };
+                self.move_piece(&rook_mov);
+
+                // Setting casteling right for both side to none
+                if piece.get_color() == PieceColor::White {
+                    self.white_castling_right.set(0);
+                } else {
+                    self.black_castling_right.set(0);
+                }
+            }
+        }
+        match self.variant {
+            Variant::Crazyhouse => self.update_pockets(&mo, piece.get_color(), captured),
+            Variant::Atomic if captured.is_some() => {
+                let exploded = self.explode(mo.to);
+                if let Some(undo) = self.undo_history.last_mut() {
+                    undo.exploded = exploded;
+                }
+            }
+            _ => {}
+        }
+        // a rook captured in its corner takes the castling right of that side with it
+        self.white_castling_right.clear_bit(to);
+        self.black_castling_right.clear_bit(to);
+        self.halfmove_clock = if piece.get_type() == PieceType::Pawn || !target.is_none() {
+            0
+        } else {
+            self.halfmove_clock + 1
+        };
+        // a horde pawn pushed two squares from the first rank can't be taken en passant
+        self.en_passant = if mo.move_type == MoveType::PawnDoublePush && mo.from / 8 != 0 {
+            Some((mo.from + mo.to) / 2)
+        } else {
+            None
+        };
+        self.count_check(piece.get_color());
+        self.move_history.push(mo.clone());
+
+        Ok(())
+    }
+
+    /// Puts the piece `color` captured with `mo` in its pocket, a promoted piece going back as
+    /// a pawn, and follows the promoted pieces to their new squares
+    fn update_pockets(&mut self, mo: &Move, color: PieceColor, captured: Option<(usize, Piece)>) {
+        if let Some((idx, piece)) = captured {
+            let piece_type = if self.promoted.get_bit(idx) {
+                PieceType::Pawn
+            } else {
+                piece.get_type()
+            };
+            self.promoted.clear_bit(idx);
+            self.pockets[side_index(color)].add(piece_type);
+        }
+        if mo.promotion_piece().is_some() {
+            self.promoted.set_bit(mo.to);
+        } else if self.promoted.get_bit(mo.from) {
+            self.promoted.clear_bit(mo.from);
+            self.promoted.set_bit(mo.to);
+        }
+    }
+
+    /// Drops a `piece_type` of the pocket of the side to move on the empty square `to` without
+    /// passing the turn. Use [Board::play] to play a move in a game
+    pub fn make_drop(&mut self, piece_type: PieceType, to: usize) -> Result<(), MoveError> {
+        let mo = Move::new_drop(piece_type, to);
+        if !self.get_moves_for_turn().contains(&mo) {
+            tracing::warn!("Drop Not avaliable");
+            return Err(MoveError::InvalidMove);
+        }
+        self.undo_history.push(MoveUndo {
+            captured: None,
+            white_castling_right: self.white_castling_right.inner,
+            black_castling_right: self.black_castling_right.inner,
+            en_passant: self.en_passant,
+            halfmove_clock: self.halfmove_clock,
+            pockets: self.pockets,
+            promoted: self.promoted.inner,
+            exploded: vec![],
+            checks: self.checks,
+        });
+        let color = self.get_turn();
+        self.pockets[side_index(color)].take(piece_type);
+        self.put_piece(to, Piece::new(color, piece_type));
+        self.halfmove_clock = if piece_type == PieceType::Pawn {
+            0
+        } else {
+            self.halfmove_clock + 1
+        };
+        self.en_passant = None;
+        self.count_check(color);
+        self.move_history.push(mo);
+        Ok(())
+    }
+
+    /// Counts the check `mover` gave with the move just made, in three-check
+    fn count_check(&mut self, mover: PieceColor) {
+        if self.variant != Variant::ThreeCheck {
+            return;
+        }
+        self.generate_moves_current_position();
+        if self.is_in_check(mover.opposite()) {
+            self.checks[side_index(mover)] += 1;
+        }
+    }
+
+    /// Drops of `color`: each piece type of its pocket on every empty square, pawns leaving out
+    /// the first and last ranks
+    fn drop_moves(&self, color: PieceColor) -> Vec<Move> {
+        let empty = !self.occupied();
+        let mut moves = vec![];
+        for (piece_type, _) in self.pockets[side_index(color)].pieces() {
+            for idx in (0..64).filter(|idx| empty & (1 << idx) != 0) {
+                if piece_type == PieceType::Pawn && (idx / 8 == 0 || idx / 8 == 7) {
+                    continue;
+                }
+                moves.push(Move::new_drop(piece_type, idx));
+            }
+        }
+        moves
+    }
+
+    /// Blows up the piece on `square` and the pieces around it but pawns, after an atomic
+    /// capture, returning them with their squares
+    fn explode(&mut self, square: usize) -> Vec<(usize, Piece)> {
+        let mut exploded = vec![];
+        for idx in variant::blast_squares(square) {
+            let piece = self.get_piece_at_index(idx);
+            if piece.is_none() || (idx != square && piece.get_type() == PieceType::Pawn) {
+                continue;
+            }
+            self.remove_piece(idx);
+            // a rook blown up on its corner takes its castling right with it
+            self.white_castling_right.clear_bit(idx);
+            self.black_castling_right.clear_bit(idx);
+            exploded.push((idx, piece));
+        }
+        exploded
+    }
+
+    /// Rules the position is played by
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Returns true in a crazyhouse game, where captured pieces can be dropped back
+    pub fn is_crazyhouse(&self) -> bool {
+        self.variant == Variant::Crazyhouse
+    }
+
+    /// Plays the position by the rules of `variant` from now on. The pockets start empty when
+    /// crazyhouse is switched on, and so do the checks in three-check. There is no castling in
+    /// antichess
+    pub fn set_variant(&mut self, variant: Variant) {
+        if variant == self.variant {
+            return;
+        }
+        self.variant = variant;
+        self.pockets = [Pocket::default(); 2];
+        self.promoted.zero();
+        self.checks = [0; 2];
+        if variant == Variant::Antichess {
+            self.white_castling_right.zero();
+            self.black_castling_right.zero();
+        }
+        self.hash = self.compute_hash();
+        self.generate_moves_current_position();
+    }
+
+    /// Checks `color` gave so far, counted in three-check
+    pub fn checks_given(&self, color: PieceColor) -> u8 {
+        self.checks[side_index(color)]
+    }
+
+    /// The winner and the rule when the rules of the variant ended the game, rather than mate:
+    /// a king blown up in atomic, a king on the hill, a third check, the side to move out of
+    /// moves in antichess or the horde captured
+    pub fn result(&self) -> Option<(PieceColor, VariantEnd)> {
+        let mut sides = [PieceColor::White, PieceColor::Black].into_iter();
+        match self.variant {
+            Variant::Standard | Variant::Crazyhouse => None,
+            Variant::Atomic => sides
+                .find(|color| self.king_square(*color).is_none())
+                .map(|color| (color.opposite(), VariantEnd::KingExploded)),
+            Variant::KingOfTheHill => sides
+                .find(|color| {
+                    self.king_square(*color)
+                        .is_some_and(|king| variant::HILL & (1 << king) != 0)
+                })
+                .map(|color| (color, VariantEnd::KingOfTheHill)),
+            Variant::ThreeCheck => sides
+                .find(|color| self.checks_given(*color) >= variant::CHECKS_TO_WIN)
+                .map(|color| (color, VariantEnd::ThreeChecks)),
+            Variant::Antichess => self
+                .get_moves_for_turn()
+                .is_empty()
+                .then(|| (self.get_turn(), VariantEnd::OutOfMoves)),
+            Variant::Horde => (self.occupancy(PieceColor::White) == 0)
+                .then_some((PieceColor::Black, VariantEnd::HordeCaptured)),
+        }
+    }
+
+    /// The pieces `color` captured and can drop, empty outside crazyhouse
+    pub fn pocket(&self, color: PieceColor) -> &Pocket {
+        &self.pockets[side_index(color)]
+    }
+
+    /// Replaces the pawn with a `promoting_to` piece on the target square, capturing the piece
+    /// on it if there is one
+    fn promote_pawn(&mut self, mo: &Move, promoting_to: PieceType) {
+        if !matches!(
+            promoting_to,
+            PieceType::Queen | PieceType::Bishop | PieceType::Knight | PieceType::Rook
+        ) {
+            tracing::error!("Invalid Piece to promote");
+            tracing::error!("PieceType: {:?}", promoting_to);
+            tracing::error!("Move: {:?}", mo);
+            panic!("Invalid Piece Type for promotion");
+        }
+
+        let pawn_promoting = self.get_piece_at_index(mo.from);
+        assert!(pawn_promoting.get_type() == PieceType::Pawn);
+
+        // capture-promotion
+        if !self.get_piece_at_index(mo.to).is_none() {
+            self.capture_piece(mo);
+        }
+
+        //1: remove the pawn
+        self.remove_piece(mo.from);
+
+        //2: add the new_promoted piece on the target square
+        let mut promoted_piece = Piece::new_none();
+        promoted_piece.set_type(promoting_to);
+        promoted_piece.set_color(pawn_promoting.get_color());
+        self.put_piece(mo.to, promoted_piece);
+    }
+
+    /// Puts `piece` on the empty square `idx`, keeping the mailbox, its bitboard and the
+    /// occupancy in step
+    fn put_piece(&mut self, idx: usize, piece: Piece) {
+        self.get_bitboard_from_piece(piece).set_bit(idx);
+        match piece.get_color() {
+            PieceColor::White => self.white_occupancy.set_bit(idx),
+            PieceColor::Black => self.black_occupancy.set_bit(idx),
+        }
+        self.board[idx] = piece.into();
+    }
+
+    /// Takes the piece off `idx` and returns it
+    fn remove_piece(&mut self, idx: usize) -> Piece {
+        let piece = self.get_piece_at_index(idx);
+        self.get_bitboard_from_piece(piece).clear_bit(idx);
+        match piece.get_color() {
+            PieceColor::White => self.white_occupancy.clear_bit(idx),
+            PieceColor::Black => self.black_occupancy.clear_bit(idx),
+        }
+        self.board[idx] = 0;
+        piece
+    }
+
+    /// Only moves the piece on the board
+    /// does not perform a capture and will fail the assert otherwise
+    fn move_piece(&mut self, current_move: &Move) {
+        let target = self.get_piece_at_index(current_move.to);
+        assert!(target.get_type() == PieceType::None);
+        let piece = self.remove_piece(current_move.from);
+        self.put_piece(current_move.to, piece);
+    }
+
+    /// Captures the piece from the move
+    /// does not move the piece in question
+    fn capture_piece(&mut self, current_move: &Move) {
+        let target = self.get_piece_at_index(current_move.to);
+        assert!(target.get_type() != PieceType::None);
+        self.remove_piece(current_move.to);
+    }
+
+    fn clear_moves(&mut self) {
+        self.white_current_moves.clear();
+        self.black_current_moves.clear();
+    }
+
+    /// Clears the moves list and generates all possible moves for the current position
+    /// This function should be called after each move
+    pub fn generate_moves_current_position(&mut self) {
+        self.clear_moves();
+        assert!(self.white_current_moves.is_empty());
+        assert!(self.black_current_moves.is_empty());
+
+        let board = self
+            .board
+            .iter()
+            .map(|p| Piece::from(*p))
+            .collect::<Vec<_>>();
+
+        // Filters over the current turn pieces and generates all possible moves
+        for (i, piece) in board.iter().enumerate() {
+            // if piece.get_color() != turn {
+            //     continue;
+            // }
+            let moves = match piece.piece_type {
+                PieceType::Pawn => self.generate_pawn_moves(i, *piece),
+                PieceType::Rook => self.generate_rook_moves(i, *piece),
+                PieceType::Bishop => self.generate_bishop_moves(i, *piece),
+                PieceType::Queen => self.generate_queen_moves(i, *piece),
+                PieceType::Knight => self.generate_knight_moves(i, *piece),
+                PieceType::King => self.generate_king_moves(i, *piece),
+                PieceType::None => {
+                    continue;
+                }
+            };
+
+            match piece.get_color() {
+                PieceColor::White => self.white_current_moves.extend(moves),
+                PieceColor::Black => self.black_current_moves.extend(moves),
+            }
+        }
+        if self.variant == Variant::Crazyhouse {
+            let white_drops = self.drop_moves(PieceColor::White);
+            let black_drops = self.drop_moves(PieceColor::Black);
+            self.white_current_moves.extend(white_drops);
+            self.black_current_moves.extend(black_drops);
+        }
+        self.white_control_bitboard.zero();
+        self.black_control_bitboard.zero();
+
+        // for x in self.all_moves() {
+        //     self.update_color_control_square_for_move(x.clone(), &turn);
+        // }
+
+        for x in self.white_current_moves.clone().iter() {
+            self.update_color_control_square_for_move(x.clone(), &PieceColor::White);
+        }
+
+        for x in self.black_current_moves.clone().iter() {
+            self.update_color_control_square_for_move(x.clone(), &PieceColor::Black);
+        }
+
+        if self.variant == Variant::Antichess {
+            let white = std::mem::take(&mut self.white_current_moves);
+            let black = std::mem::take(&mut self.black_current_moves);
+            self.white_current_moves = self.forced_captures(white);
+            self.black_current_moves = self.forced_captures(black);
+        }
+    }
+
+    /// Only the captures of `moves` if there is one, as captures are forced in antichess
+    fn forced_captures(&self, mut moves: Vec<Move>) -> Vec<Move> {
+        if moves.iter().any(|m| self.is_capture(m)) {
+            moves.retain(|m| self.is_capture(m));
+        }
+        moves
+    }
+
+    fn generate_queen_moves(&mut self, current_piece_idx: usize, piece: Piece) -> Vec<Move> {
+        assert!(piece.piece_type == PieceType::Queen);
+        let directions = [
+            SafeCoordinate::new(1, 1),
+            SafeCoordinate::new(-1, 1),
+            SafeCoordinate::new(1, -1),
+            SafeCoordinate::new(-1, -1),
+            SafeCoordinate::new(0, 1),
+            SafeCoordinate::new(0, -1),
+            SafeCoordinate::new(1, 0),
+            SafeCoordinate::new(-1, 0),
+        ];
+        self.generate_moves_for_direction(
+            current_piece_idx,
+            piece,
+            &directions,
+            MoveType::QueenMove,
+        )
+    }
+
+    // TODO: checks
+    fn generate_king_moves(&mut self, current_piece_idx: usize, piece: Piece) -> Vec<Move> {
+        assert!(piece.piece_type == PieceType::King);
+        let directions = [
+            SafeCoordinate::new(1, 1),
+            SafeCoordinate::new(-1, 1),
+            SafeCoordinate::new(1, -1),
+            SafeCoordinate::new(-1, -1),
+            SafeCoordinate::new(0, 1),
+            SafeCoordinate::new(0, -1),
+            SafeCoordinate::new(1, 0),
+            SafeCoordinate::new(-1, 0),
+        ];
+        let mut res = vec![];
+        for dir in directions.iter() {
+            let current = self.get_safe_coordinates_from_index(current_piece_idx);
+            let target = SafeCoordinate {
+                x: current.x + dir.x,
+                y: current.y + dir.y,
+            };
+            if target.is_out_of_bounds() {
+                continue;
+            }
+            let idx = self.get_square_isize(target.x, target.y);
+            let target_piece = self.get_piece_at_index(idx);
+            if target_piece.get_type() != PieceType::None
+                && target_piece.get_color() == piece.get_color()
+            {
+                continue;
+            }
+            res.push(Move {
+                from: current_piece_idx,
+                to: idx,
+                move_type: MoveType::KingMove,
+            });
+        }
+
+        res.extend(self.generate_king_castle_moves(current_piece_idx, piece));
+
+        res
+    }
+
+    /// Generates king moves for both sides,
+    /// NOTE: without regards to checks
+    fn generate_king_castle_moves(&mut self, current_piece_idx: usize, piece: Piece) -> Vec<Move> {
+        let mut res = vec![];
+        let expected_king_pos = if piece.get_color() == PieceColor::White {
+            3
+        } else {
+            59
+        };
+
+        // Return early if king is not at the correct position to castle
+        if current_piece_idx != expected_king_pos {
+            return res;
+        }
+        let king_side_path_idx: [usize; 2] = if piece.get_color() == PieceColor::White {
+            [1, 2]
+        } else {
+            [57, 58]
+        };
+        let queen_side_path_idx: [usize; 3] = if piece.get_color() == PieceColor::White {
+            [4, 5, 6]
+        } else {
+            [60, 61, 62]
+        };
+
+        /// checks if the path to the finish line has no piece
+        fn all_clear(path: &[usize], board: &[u16; 64]) -> bool {
+            for x in path.iter() {
+                let piece = Piece::from(board[*x]);
+                if !piece.is_none() {
+                    tracing::debug!("Not Clear{:?}", path);
+                    return false;
+                }
+            }
+            tracing::debug!("All clear for this side {:?}", path);
+            true
+        }
+        // the king may not castle out of, through or into check
+        let opponent = piece.get_color().opposite();
+        let safe = |squares: &[usize]| squares.iter().all(|sq| !self.is_attacked(*sq, opponent));
+
+        let rights = self.castling_rights();
+        let (king_side, queen_side) = match piece.get_color() {
+            PieceColor::White => (rights.white_king_side, rights.white_queen_side),
+            PieceColor::Black => (rights.black_king_side, rights.black_queen_side),
+        };
+
+        let (rook, h_file_idx, a_file_idx) = if piece.get_color() == PieceColor::White {
+            (
+                Piece {
+                    piece_color: PieceColor::White,
+                    piece_type: PieceType::Rook,
+                },
+                0,
+                7,
+            )
+        } else {
+            (
+                Piece {
+                    piece_color: PieceColor::Black,
+                    piece_type: PieceType::Rook,
+                },
+                56,
+                63,
+            )
+        };
+
+        let king = expected_king_pos;
+        if king_side
+            && self.get_piece_at_index(h_file_idx) == rook
+            && all_clear(&king_side_path_idx, &self.board)
+            && safe(&[king, king - 1, king - 2])
+        {
+            let mov = Move {
+                from: expected_king_pos,
+                to: expected_king_pos - 2,
+                move_type: MoveType::CastelKingSide,
+            };
+            res.push(mov);
+        }
+
+        if queen_side
+            && self.get_piece_at_index(a_file_idx) == rook
+            && all_clear(&queen_side_path_idx, &self.board)
+            && safe(&[king, king + 1, king + 2])
+        {
+            let mov = Move {
+                from: expected_king_pos,
+                to: expected_king_pos + 2,
+                move_type: MoveType::CastelQueenSide,
+            };
+            res.push(mov);
+        }
+
+        res
+    }
+
+    fn generate_knight_moves(&mut self, current_piece_idx: usize, piece: Piece) -> Vec<Move> {
+        assert!(piece.piece_type == PieceType::Knight);
+        let directions = [
+            SafeCoordinate::new(1, 2),
+            SafeCoordinate::new(-1, 2),
+            SafeCoordinate::new(1, -2),
+            SafeCoordinate::new(-1, -2),
+            SafeCoordinate::new(2, 1),
+            SafeCoordinate::new(-2, 1),
+            SafeCoordinate::new(2, -1),
+            SafeCoordinate::new(-2, -1),
+        ];
+        let current_cord = self.get_safe_coordinates_from_index(current_piece_idx);
+        let mut res = vec![];
+        for dir in directions.iter() {
+            let target_cord = SafeCoordinate {
+                x: current_cord.x + dir.x,
+                y: current_cord.y + dir.y,
+            };
+
+            if target_cord.is_out_of_bounds() {
+                continue;
+            }
+            let target_cord = target_cord.to_coordinate();
+
+            let target_piece =
+                self.get_piece_at_index(self.get_square(target_cord.x, target_cord.y));
+
+            if target_piece.get_type() != PieceType::None
+                && target_piece.get_color() == piece.get_color()
+            {
+                continue;
+            }
+
+            res.push(Move {
+                from: current_piece_idx,
+                to: self.get_square(target_cord.x, target_cord.y),
+                move_type: MoveType::KnightMove,
+            });
+        }
+        res
+    }
+
+    fn generate_bishop_moves(&mut self, current_piece_idx: usize, piece: Piece) -> Vec<Move> {
+        assert!(piece.piece_type == PieceType::Bishop);
+        let direction = [
+            SafeCoordinate::new(1, 1),
+            SafeCoordinate::new(-1, 1),
+            SafeCoordinate::new(1, -1),
+            SafeCoordinate::new(-1, -1),
+        ];
+        self.generate_moves_for_direction(
+            current_piece_idx,
+            piece,
+            &direction,
+            MoveType::BishopMove,
+        )
+    }
+
+    fn generate_moves_for_direction(
+        &mut self,
+        current_piece_idx: usize,
+        piece: Piece,
+        directions: &[SafeCoordinate],
+        move_type: MoveType,
+    ) -> Vec<Move> {
+        let mut res = vec![];
+        let piece_cord = self.get_safe_coordinates_from_index(current_piece_idx);
+
+        for dir in directions.iter() {
+            let mut current_look_up_cord = SafeCoordinate {
+                x: piece_cord.x + dir.x,
+                y: piece_cord.y + dir.y,
+            };
+            'beyond: loop {
+                if current_look_up_cord.is_out_of_bounds() {
+                    break 'beyond;
+                }
+                let cluc = current_look_up_cord.to_coordinate();
+
+                let current_look_up_piece = self.get_piece_at_index_from_cord(&cluc);
+
+                if !current_look_up_piece.is_none() {
+                    // fiendly piece cannot attack nor move, abandon the search for this
+                    // direction
+                    if current_look_up_piece.get_color() == piece.get_color() {
+                        break 'beyond;
+                    } else {
+                        // enemy piece add it and move to the next direction - we exit afterwards
+                        let mov = Move {
+                            from: current_piece_idx,
+                            to: self.get_index_from_coordinates(cluc),
+                            move_type,
+                        };
+                        res.push(mov);
+                        break 'beyond;
+                    }
+                }
+
+                let mov = Move {
+                    from: current_piece_idx,
+                    to: self.get_index_from_coordinates(cluc),
+                    move_type,
+                };
+                res.push(mov);
+                current_look_up_cord = SafeCoordinate {
+                    x: current_look_up_cord.x + dir.x,
+                    y: current_look_up_cord.y + dir.y,
+                };
+            }
+        }
+        res
+    }
+
+    fn get_piece_at_index_from_cord(&self, cord: &Coordinate) -> Piece {
+        self.get_piece_at_index(self.get_square(cord.x, cord.y))
+    }
+
+    fn generate_rook_moves(&mut self, current_piece_idx: usize, piece: Piece) -> Vec<Move> {
+        assert!(piece.piece_type == PieceType::Rook || piece.piece_type == PieceType::Queen);
+        let directions = [
+            SafeCoordinate::new(0, 1),
+            SafeCoordinate::new(0, -1),
+            SafeCoordinate::new(1, 0),
+            SafeCoordinate::new(-1, 0),
+        ];
+        self.generate_moves_for_direction(current_piece_idx, piece, &directions, MoveType::RookMove)
+    }
+
+    fn generate_pawn_moves(&mut self, current_piece_idx: usize, piece: Piece) -> Vec<Move> {
+        assert!(piece.piece_type == PieceType::Pawn);
+
+        // Pawns can move forward one square if the square is empty
+        // Pawns can move forward two squares if the square is empty and the pawn is on the starting rank
+        // Pawns can capture diagonally
+        // Pawns can capture en passant
+        // Pawns can promote
+
+        // if there a piece in front of the pawn it shall not move
+
+        let co = self.get_safe_coordinates_from_index(current_piece_idx);
+        let mut res = vec![];
+
+        let front_co = SafeCoordinate {
+            x: co.x,
+            y: if piece.piece_color == PieceColor::White {
+                co.y + 1
+            } else {
+                co.y - 1
+            },
+        };
+
+        if !front_co.is_out_of_bounds() {
+            // calculates the front of the pawn if it's white or black
+            let front = self.get_square(front_co.x as usize, front_co.y as usize);
+            let front_piece = self.get_piece_at_index(front);
+            if front_piece.get_type() == PieceType::None {
+                // Add front move to the list
+
+                if front_co.y == 7 || front_co.y == 0 {
+                    for p in PROMOTION_PIECES.iter() {
+                        res.push(Move {
+                            from: current_piece_idx,
+                            to: front,
+                            move_type: MoveType::PawnPush {
+                                promotion_piece: Some(*p),
+                            },
+                        });
+                    }
+                } else {
+                    res.push(Move {
+                        from: current_piece_idx,
+                        to: front,
+                        move_type: MoveType::PawnPush {
+                            promotion_piece: None,
+                        },
+                    });
+                }
+                // checking for double push, from the first rank too for the horde
+                let start = co.y == 1 || (co.y == 0 && self.variant == Variant::Horde);
+                if start && piece.piece_color == PieceColor::White {
+                    let double_front = self.get_square_isize(front_co.x, front_co.y + 1);
+                    let double_front_piece = self.get_piece_at_index(double_front);
+                    if double_front_piece.get_type() == PieceType::None {
+                        res.push(Move {
+                            from: current_piece_idx,
+                            to: double_front,
+                            move_type: MoveType::PawnDoublePush,
+                        });
+                    }
+                } else if co.y == 6 && piece.piece_color == PieceColor::Black {
+                    let double_front = self.get_square_isize(front_co.x, front_co.y - 1);
+                    let double_front_piece = self.get_piece_at_index(double_front);
+                    if double_front_piece.get_type() == PieceType::None {
+                        res.push(Move {
+                            from: current_piece_idx,
+                            to: double_front,
+                            move_type: MoveType::PawnDoublePush,
+                        });
+                    }
+                }
+            }
+        }
+
+        fn capture_promotion(m: Move, res: &mut Vec<Move>) {
+            let front_co = Coordinate::from(m.to);
+            if front_co.y == 7 || front_co.y == 0 {
+                for p in PROMOTION_PIECES.iter() {
+                    res.push(Move {
+                        from: m.from,
+                        to: m.to,
+                        move_type: MoveType::PawnCapture {
+                            promotion_piece: Some(*p),
+                        },
+                    });
+                }
+            } else {
+                res.push(m);
+            }
+        }
+
+        if let Some(m) = self.pawn_capture(piece, &co, PawnCaptureDirection::Right) {
+            capture_promotion(m, &mut res)
+        }
+
+        if let Some(m) = self.pawn_capture(piece, &co, PawnCaptureDirection::Left) {
+            capture_promotion(m, &mut res)
+        }
+
+        if let Some(m) = self.enpassant_capture(piece, &co) {
+            res.push(m);
+        }
+        res
+    }
+
+    fn enpassant_capture(&self, piece: Piece, current_cord: &SafeCoordinate) -> Option<Move> {
+        // the pawn that just double pushed stands next to the current pawn, on the file of the
+        // en passant square
+        let target = self.en_passant?;
+        let target_cord = self.get_coordinates_from_index(target);
+
+        // white captures towards the 6th rank, black towards the 3rd
+        let (capture_rank, forward) = match piece.get_color() {
+            PieceColor::White => (5, 1),
+            PieceColor::Black => (2, -1),
+        };
+        if target_cord.y != capture_rank || current_cord.y + forward != capture_rank as isize {
+            return None;
+        }
+        if (target_cord.x as isize - current_cord.x).abs() != 1 {
+            return None;
+        }
+
+        let captured = Coordinate {
+            x: target_cord.x,
+            y: current_cord.y as usize,
+        };
+        let captured_piece = self.get_piece_at_index_from_cord(&captured);
+        if captured_piece.get_type() != PieceType::Pawn
+            || captured_piece.get_color() == piece.get_color()
+        {
+            return None;
+        }
+
+        Some(Move {
+            from: self.get_index_from_coordinates(current_cord.to_coordinate()),
+            to: target,
+            move_type: MoveType::PawnEnPassant(captured),
+        })
+    }
+
+    fn pawn_capture(
+        &self,
+        piece: Piece,
+        current_cord: &SafeCoordinate,
+        side: PawnCaptureDirection,
+    ) -> Option<Move> {
+        if current_cord.is_out_of_bounds() {
+            return None;
+        }
+        let right_co = SafeCoordinate {
+            x: match side {
+                PawnCaptureDirection::Right => current_cord.x + 1,
+                PawnCaptureDirection::Left => current_cord.x - 1,
+            },
+            y: if piece.get_color() == PieceColor::White {
+                current_cord.y + 1
+            } else {
+                current_cord.y - 1
+            },
+        };
+        if right_co.is_out_of_bounds() {
+            return None;
+        }
+
+        let right_piece = self.get_piece_at_index(
+            self.get_square(right_co.to_coordinate().x, right_co.to_coordinate().y),
+        );
+
+        if right_piece.get_color() == piece.get_color() {
+            return None;
+        }
+
+        if right_piece.get_type() == PieceType::None {
+            return None;
+        }
+
+        let right = self.get_square_isize(right_co.x, right_co.y);
+        let mov = Move {
+            from: self.get_index_from_coordinates(current_cord.to_coordinate()),
+            to: right,
+            move_type: MoveType::PawnCapture {
+                promotion_piece: None,
+            },
+        };
+        Some(mov)
+    }
+
+    /// Returns the index of the square given the x and y coordinates
+    fn get_index_from_coordinates(&self, co: Coordinate) -> usize {
+        self.get_square(co.x, co.y)
+    }
+
+    /// Returns the coordinate of the given index
+    fn get_coordinates_from_index(&self, idx: usize) -> Coordinate {
+        let x = idx % 8;
+        let y = idx / 8;
+        Coordinate { x, y }
+    }
+
+    fn get_safe_coordinates_from_index(&self, idx: usize) -> SafeCoordinate {
+        let x = idx % 8;
+        let y = idx / 8;
+        SafeCoordinate::new(x as isize, y as isize)
+    }
+
+    /// Returns the square a pawn can capture en passant on, if the last move was a double push
+    pub fn en_passant_square(&self) -> Option<usize> {
+        self.en_passant
+    }
+
+    /// Returns the castling rights still available
+    pub fn castling_rights(&self) -> CastlingRights {
+        // the castling right bitboards hold the rooks that haven't moved yet
+        CastlingRights {
+            white_king_side: self.white_castling_right.get_bit(0),
+            white_queen_side: self.white_castling_right.get_bit(7),
+            black_king_side: self.black_castling_right.get_bit(56),
+            black_queen_side: self.black_castling_right.get_bit(63),
+        }
+    }
+
+    /// Replaces the castling rights, the caller is responsible for them matching the position
+    fn set_castling_rights(&mut self, rights: CastlingRights) {
+        self.white_castling_right.zero();
+        self.black_castling_right.zero();
+        if rights.white_king_side {
+            self.white_castling_right.set_bit(0);
+        }
+        if rights.white_queen_side {
+            self.white_castling_right.set_bit(7);
+        }
+        if rights.black_king_side {
+            self.black_castling_right.set_bit(56);
+        }
+        if rights.black_queen_side {
+            self.black_castling_right.set_bit(63);
+        }
+    }
+
+    /// Exports the current position as a FEN string
+    pub fn to_fen(&self) -> String {
+        let mut placement = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                let piece = self.get_piece_at_index(rank * 8 + (7 - file));
+                match piece.to_fen_char() {
+                    Some(c) => {
+                        if empty > 0 {
+                            placement.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        placement.push(c);
+                        if self.variant == Variant::Crazyhouse
+                            && self.promoted.get_bit(rank * 8 + (7 - file))
+                        {
+                            placement.push('~');
+                        }
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                placement.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                placement.push('/');
+            }
+        }
+        if self.variant == Variant::Crazyhouse {
+            placement.push('[');
+            placement.push_str(&self.pocket(PieceColor::White).to_fen(PieceColor::White));
+            placement.push_str(&self.pocket(PieceColor::Black).to_fen(PieceColor::Black));
+            placement.push(']');
+        }
+
+        let turn = if self.is_white_turn { 'w' } else { 'b' };
+        let en_passant = self
+            .en_passant_square()
+            .map(square_to_algebraic)
+            .unwrap_or("-".to_string());
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement,
+            turn,
+            self.castling_rights(),
+            en_passant,
+            self.halfmove_clock,
+            self.fullmove_number()
+        )
+    }
+
+    /// Returns the number of the current move, starting at 1 and increasing after black moves
+    pub fn fullmove_number(&self) -> usize {
+        (self.start_ply + self.move_history.len()) / 2 + 1
+    }
+
+    /// Dumps the FEN along with the raw piece and control bitboards, meant for the logs
+    pub fn debug_string(&self) -> String {
+        format!(
+            "fen: {} hash: {:#018x}\n\
+             white pawn: {:#018x} rook: {:#018x} knight: {:#018x} bishop: {:#018x} queen: {:#018x} king: {:#018x}\n\
+             black pawn: {:#018x} rook: {:#018x} knight: {:#018x} bishop: {:#018x} queen: {:#018x} king: {:#018x}\n\
+             white control: {:#018x} black control: {:#018x}\n\
+             white moves: {} black moves: {}",
+            self.to_fen(),
+            self.zobrist_hash(),
+            self.white_pawn_bitboard.inner,
+            self.white_rook_bitboard.inner,
+            self.white_knight_bitboard.inner,
+            self.white_bishop_bitboard.inner,
+            self.white_queen_bitboard.inner,
+            self.white_king_bitboard.inner,
+            self.black_pawn_bitboard.inner,
+            self.black_rook_bitboard.inner,
+            self.black_knight_bitboard.inner,
+            self.black_bishop_bitboard.inner,
+            self.black_queen_bitboard.inner,
+            self.black_king_bitboard.inner,
+            self.white_control_bitboard.inner,
+            self.black_control_bitboard.inner,
+            self.white_current_moves.len(),
+            self.black_current_moves.len(),
+        )
+    }
+
+    /// Returns the current turn
+    pub fn get_turn(&self) -> PieceColor {
+        if self.is_white_turn {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        }
+    }
+
+    /// Returns a mutable reference to the bitboard of the given piece
+    fn get_bitboard_from_piece(&mut self, piece: Piece) -> &mut BitBoard {
+        match piece.get_color() {
+            PieceColor::White => match piece.piece_type {
+                PieceType::Pawn => &mut self.white_pawn_bitboard,
+                PieceType::Rook => &mut self.white_rook_bitboard,
+                PieceType::Knight => &mut self.white_knight_bitboard,
+                PieceType::Bishop => &mut self.white_bishop_bitboard,
+                PieceType::Queen => &mut self.white_queen_bitboard,
+                PieceType::King => &mut self.white_king_bitboard,
+                PieceType::None => panic!("Invalid Piece Type, {:?}", piece.piece_type),
+            },
+            PieceColor::Black => match piece.piece_type {
+                PieceType::Pawn => &mut self.black_pawn_bitboard,
+                PieceType::Rook => &mut self.black_rook_bitboard,
+                PieceType::Knight => &mut self.black_knight_bitboard,
+                PieceType::Bishop => &mut self.black_bishop_bitboard,
+                PieceType::Queen => &mut self.black_queen_bitboard,
+                PieceType::King => &mut self.black_king_bitboard,
+                PieceType::None => panic!("Invalid Piece Type, {:?}", piece.piece_type),
+            },
+        }
+    }
+
+    /// Changes the turn of the board
+    pub fn toggle_turn(&mut self) {
+        self.is_white_turn = !self.is_white_turn;
+        self.hash = self.compute_hash();
+    }
+
+    /// Passes the turn without moving a piece, clearing en passant. Moves are regenerated for the
+    /// new side, undo with [Board::unmake_null_move]
+    pub fn make_null_move(&mut self) {
+        if let Some(sq) = self.hashed_en_passant_square() {
+            self.hash ^= zobrist::en_passant_key(sq);
+        }
+        self.null_move_en_passant.push(self.en_passant.take());
+        self.move_history.push(Move::null());
+        self.is_white_turn = !self.is_white_turn;
+        self.hash ^= zobrist::turn_key();
+        self.generate_moves_current_position();
+    }
+
+    /// Takes back a [Board::make_null_move], restoring the en passant square
+    pub fn unmake_null_move(&mut self) {
+        match self.move_history.pop() {
+            Some(mo) if mo.is_null() => {}
+            Some(mo) => {
+                self.move_history.push(mo);
+                tracing::error!("unmake_null_move called after a regular move");
+                return;
+            }
+            None => return,
+        }
+        self.en_passant = self.null_move_en_passant.pop().flatten();
+        self.is_white_turn = !self.is_white_turn;
+        self.hash ^= zobrist::turn_key();
+        if let Some(sq) = self.hashed_en_passant_square() {
+            self.hash ^= zobrist::en_passant_key(sq);
+        }
+        self.generate_moves_current_position();
+    }
+
+    /// Returns the Zobrist hash of the position, using the Polyglot keys
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Computes the Zobrist hash of the position from scratch
+    fn compute_hash(&self) -> u64 {
+        let mut hash = 0;
+        for idx in 0..64 {
+            hash ^= zobrist::piece_key(self.get_piece_at_index(idx), idx);
+        }
+        let rights = self.castling_rights();
+        if rights.white_king_side {
+            hash ^= zobrist::castling_key(PieceColor::White, true);
+        }
+        if rights.white_queen_side {
+            hash ^= zobrist::castling_key(PieceColor::White, false);
+        }
+        if rights.black_king_side {
+            hash ^= zobrist::castling_key(PieceColor::Black, true);
+        }
+        if rights.black_queen_side {
+            hash ^= zobrist::castling_key(PieceColor::Black, false);
+        }
+        if let Some(sq) = self.hashed_en_passant_square() {
+            hash ^= zobrist::en_passant_key(sq);
+        }
+        if self.is_white_turn {
+            hash ^= zobrist::turn_key();
+        }
+        if self.variant == Variant::Crazyhouse {
+            for color in [PieceColor::White, PieceColor::Black] {
+                for (piece_type, count) in self.pocket(color).pieces() {
+                    hash ^= zobrist::pocket_key(Piece::new(color, piece_type), count);
+                }
+            }
+        }
+        if self.variant == Variant::ThreeCheck {
+            for color in [PieceColor::White, PieceColor::Black] {
+                hash ^= zobrist::checks_key(color, self.checks_given(color));
+            }
+        }
+        hash
+    }
+
+    /// Like [Board::en_passant_square], but only when a pawn of the side to move stands next to
+    /// the double pushed pawn, as Polyglot only hashes en passant squares that can be captured on
+    fn hashed_en_passant_square(&self) -> Option<usize> {
+        let sq = self.en_passant_square()?;
+        // the pushed pawn stands one rank past the en passant square
+        let pushed = if sq / 8 == 2 { sq + 8 } else { sq - 8 };
+        let capturer = Piece::new(self.get_turn(), PieceType::Pawn);
+        let x = pushed % 8;
+        let left = x > 0 && self.get_piece_at_index(pushed - 1) == capturer;
+        let right = x < 7 && self.get_piece_at_index(pushed + 1) == capturer;
+        (left || right).then_some(sq)
+    }
+
+    /// Plays `mo` and passes the turn, regenerating the moves for the new position
+    pub fn play(&mut self, mo: &Move) -> Result<(), MoveError> {
+        match mo.dropped_piece() {
+            Some(piece_type) => self.make_drop(piece_type, mo.to)?,
+            None => self.make_move(mo.from, mo.to, mo.promotion_piece())?,
+        }
+        self.toggle_turn();
+        self.generate_moves_current_position();
+        Ok(())
+    }
+
+    /// Takes back the last move [Board::play]ed, restoring the turn, castling rights, en passant
+    /// square and halfmove clock, and returns it
+    pub fn unmake_move(&mut self) -> Option<Move> {
+        let mo = match self.move_history.last() {
+            Some(mo) if mo.is_null() => {
+                tracing::error!("unmake_move called after a null move");
+                return None;
+            }
+            Some(_) => self.move_history.pop()?,
+            None => return None,
+        };
+        let Some(undo) = self.undo_history.pop() else {
+            tracing::error!("No undo information for {}", mo);
+            self.move_history.push(mo);
+            return None;
+        };
+
+        self.is_white_turn = !self.is_white_turn;
+        let color = self.get_turn();
+        // the capturing piece comes back on the square it blew up on, then moves back as usual
+        for (idx, piece) in &undo.exploded {
+            self.put_piece(*idx, *piece);
+        }
+        match mo.move_type {
+            MoveType::CastelKingSide | MoveType::CastelQueenSide => {
+                let (rook_pos, new_rook_pos) = match (mo.move_type, color) {
+                    (MoveType::CastelKingSide, PieceColor::White) => (0, mo.to + 1),
+                    (MoveType::CastelKingSide, PieceColor::Black) => (56, mo.to + 1),
+                    (_, PieceColor::White) => (7, mo.to - 1),
+                    (_, PieceColor::Black) => (63, mo.to - 1),
+                };
+                let rook = self.remove_piece(new_rook_pos);
+                self.put_piece(rook_pos, rook);
+                let king = self.remove_piece(mo.to);
+                self.put_piece(mo.from, king);
+            }
+            MoveType::Drop(_) => {
+                self.remove_piece(mo.to);
+            }
+            _ if mo.promotion_piece().is_some() => {
+                self.remove_piece(mo.to);
+                self.put_piece(mo.from, Piece::new(color, PieceType::Pawn));
+            }
+            _ => {
+                let piece = self.remove_piece(mo.to);
+                self.put_piece(mo.from, piece);
+            }
+        }
+        if let Some((idx, piece)) = undo.captured {
+            self.put_piece(idx, piece);
+        }
+
+        self.white_castling_right.set(undo.white_castling_right);
+        self.black_castling_right.set(undo.black_castling_right);
+        self.en_passant = undo.en_passant;
+        self.halfmove_clock = undo.halfmove_clock;
+        self.pockets = undo.pockets;
+        self.promoted.set(undo.promoted);
+        self.checks = undo.checks;
+        self.hash = self.compute_hash();
+        self.generate_moves_current_position();
+        Some(mo)
+    }
+
+    /// Returns true if `mo` captures a piece
+    pub fn is_capture(&self, mo: &Move) -> bool {
+        matches!(mo.move_type, MoveType::PawnEnPassant(_))
+            || !self.get_piece_at_index(mo.to).is_none()
+    }
+
+    /// Returns the squares occupied by `piece`
+    pub fn pieces(&self, piece: Piece) -> u64 {
+        let bitboard = match piece.get_color() {
+            PieceColor::White => match piece.piece_type {
+                PieceType::Pawn => &self.white_pawn_bitboard,
+                PieceType::Rook => &self.white_rook_bitboard,
+                PieceType::Knight => &self.white_knight_bitboard,
+                PieceType::Bishop => &self.white_bishop_bitboard,
+                PieceType::Queen => &self.white_queen_bitboard,
+                PieceType::King => &self.white_king_bitboard,
+                PieceType::None => return 0,
+            },
+            PieceColor::Black => match piece.piece_type {
+                PieceType::Pawn => &self.black_pawn_bitboard,
+                PieceType::Rook => &self.black_rook_bitboard,
+                PieceType::Knight => &self.black_knight_bitboard,
+                PieceType::Bishop => &self.black_bishop_bitboard,
+                PieceType::Queen => &self.black_queen_bitboard,
+                PieceType::King => &self.black_king_bitboard,
+                PieceType::None => return 0,
+            },
+        };
+        bitboard.inner
+    }
+
+    /// Returns the squares occupied by the pieces of `color`
+    pub fn occupancy(&self, color: PieceColor) -> u64 {
+        match color {
+            PieceColor::White => self.white_occupancy.inner,
+            PieceColor::Black => self.black_occupancy.inner,
+        }
+    }
+
+    /// Returns the squares occupied by any piece
+    pub fn occupied(&self) -> u64 {
+        self.white_occupancy.inner | self.black_occupancy.inner
+    }
+
+    /// Returns the square of the king of `color`
+    pub fn king_square(&self, color: PieceColor) -> Option<usize> {
+        let king = match color {
+            PieceColor::White => &self.white_king_bitboard,
+            PieceColor::Black => &self.black_king_bitboard,
+        };
+        if king.inner == 0 {
+            return None;
+        }
+        Some(king.inner.trailing_zeros() as usize)
+    }
+
+    /// Returns true if a piece of `by` attacks `square`, whether or not anything stands on it
+    pub fn is_attacked(&self, square: usize, by: PieceColor) -> bool {
+        let (x, y) = ((square % 8) as isize, (square / 8) as isize);
+        let piece_at = |dx: isize, dy: isize| {
+            let (x, y) = (x + dx, y + dy);
+            ((0..8).contains(&x) && (0..8).contains(&y))
+                .then(|| self.get_piece_at_index((y * 8 + x) as usize))
+        };
+        let is = |piece: Option<Piece>, types: &[PieceType]| {
+            piece.is_some_and(|p| p.get_color() == by && types.contains(&p.get_type()))
+        };
+
+        // a pawn attacks one rank forward, so its attackers stand one rank back
+        let back = match by {
+            PieceColor::White => -1,
+            PieceColor::Black => 1,
+        };
+        if [-1, 1]
+            .into_iter()
+            .any(|dx| is(piece_at(dx, back), &[PieceType::Pawn]))
+        {
+            return true;
+        }
+        let knight = [
+            (1, 2),
+            (2, 1),
+            (2, -1),
+            (1, -2),
+            (-1, -2),
+            (-2, -1),
+            (-2, 1),
+            (-1, 2),
+        ];
+        if knight
+            .into_iter()
+            .any(|(dx, dy)| is(piece_at(dx, dy), &[PieceType::Knight]))
+        {
+            return true;
+        }
+        let around = (-1..=1).flat_map(|dx| (-1..=1).map(move |dy| (dx, dy)));
+        if around
+            .filter(|step| *step != (0, 0))
+            .any(|(dx, dy)| is(piece_at(dx, dy), &[PieceType::King]))
+        {
+            return true;
+        }
+
+        let sliders = [
+            (
+                [(1, 0), (-1, 0), (0, 1), (0, -1)],
+                [PieceType::Rook, PieceType::Queen],
+            ),
+            (
+                [(1, 1), (1, -1), (-1, 1), (-1, -1)],
+                [PieceType::Bishop, PieceType::Queen],
+            ),
+        ];
+        for (directions, types) in sliders {
+            for (dx, dy) in directions {
+                let mut distance = 1;
+                while let Some(piece) = piece_at(dx * distance, dy * distance) {
+                    if !piece.is_none() {
+                        if is(Some(piece), &types) {
+                            return true;
+                        }
+                        break;
+                    }
+                    distance += 1;
+                }
+            }
+        }
+        false
+    }
+
+    /// Returns true if the king of `color` can be captured by the opponent
+    pub fn is_in_check(&self, color: PieceColor) -> bool {
+        // the king is an ordinary piece in antichess
+        if self.variant == Variant::Antichess {
+            return false;
+        }
+        let Some(king) = self.king_square(color) else {
+            return false;
+        };
+        if self.variant == Variant::Atomic {
+            // kings side by side can't take each other, and the game is over once a king is gone
+            let Some(theirs) = self.king_square(color.opposite()) else {
+                return false;
+            };
+            if variant::blast_squares(king).any(|sq| sq == theirs) {
+                return false;
+            }
+        }
+        let opponent_moves = match color {
+            PieceColor::White => &self.black_current_moves,
+            PieceColor::Black => &self.white_current_moves,
+        };
+        opponent_moves.iter().any(|m| m.to == king)
+    }
+
+    /// Returns true if neither side has the material to mate, a lone knight or bishop at most
+    pub fn has_insufficient_material(&self) -> bool {
+        if !self.variant.needs_kings() {
+            return false;
+        }
+        let mut minors = 0;
+        for idx in 0..64 {
+            match self.get_piece_at_index(idx).get_type() {
+                PieceType::King | PieceType::None => {}
+                PieceType::Knight | PieceType::Bishop => minors += 1,
+                _ => return false,
+            }
+        }
+        minors <= 1
+    }
+
+    /// Returns the index of the square given the x and y coordinates
+    /// asserts that the index is within the board 0 > idx < 64
+    pub fn get_square(&self, x: usize, y: usize) -> usize {
+        let res = (y * 8) + x;
+        assert!((0..64).contains(&res));
+        res
+    }
+
+    /// Index of the square at (`x`, `y`), which must be on the board
+    pub fn get_square_isize(&self, x: isize, y: isize) -> usize {
+        let res = (y * 8) + x;
+        assert!((0..64).contains(&res));
+        res as usize
+    }
+
+    /// Gets the piece at the given index as a Piece struct
+    pub fn get_piece_at_index(&self, idx: usize) -> Piece {
+        self.board[idx].into()
+    }
+
+    /// Loads a position from a FEN string, replacing whatever the board held. Missing fields
+    /// default to white to move, no castling right, no en passant square and move 1. A
+    /// placement with pockets, `.../RNBQKBNR[Pn]`, loads a crazyhouse position
+    /// ```no_run
+    /// # use ruche_core::board::Board;
+    /// let mut board = Board::new();
+    /// board.load_position("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq".to_string());
+    /// ```
+    pub fn load_position(&mut self, fen: String) {
+        *self = Board::new();
+
+        let mut fields = fen.split_whitespace();
+        let (placement, pocket) = split_pocket(fields.next().unwrap_or_default());
+
+        // ranks are listed from the 8th down, files from a to h
+        for (row, rank_field) in placement.split('/').enumerate() {
+            if row >= 8 {
+                tracing::error!("Invalid FEN, more than 8 ranks: {}", placement);
+                break;
+            }
+            let rank = 7 - row;
+            let mut file = 0;
+            for c in rank_field.chars() {
+                if c == '~' {
+                    // the piece before was a pawn that promoted
+                    if file > 0 {
+                        self.promoted.set_bit(rank * 8 + (8 - file));
+                    }
+                    continue;
+                }
+                if let Some(empty) = c.to_digit(10) {
+                    file += empty as usize;
+                    continue;
+                }
+                let Some(piece) = Piece::from_fen_char(c) else {
+                    tracing::error!("Invalid FEN character: {}", c);
+                    continue;
+                };
+                if file >= 8 {
+                    tracing::error!("Invalid FEN, more than 8 files: {}", rank_field);
+                    break;
+                }
+                self.put_piece(rank * 8 + (7 - file), piece);
+                file += 1;
+            }
+        }
+
+        if let Some(pocket) = pocket {
+            self.variant = Variant::Crazyhouse;
+            for c in pocket.chars() {
+                match Piece::from_fen_char(c) {
+                    Some(piece) => {
+                        self.pockets[side_index(piece.get_color())].add(piece.get_type())
+                    }
+                    None => tracing::error!("Invalid FEN pocket character: {}", c),
+                }
+            }
+        }
+
+        self.is_white_turn = fields.next() != Some("b");
+
+        // a missing castling field is read as `-`, as the FEN parser does
+        let castling = fields.next().unwrap_or("-");
+        match CastlingRights::from_fen(castling) {
+            Some(rights) => self.set_castling_rights(rights),
+            None => tracing::error!("Invalid FEN castling rights: {}", castling),
+        }
+
+        self.en_passant = match fields.next() {
+            None | Some("-") => None,
+            Some(field) => {
+                let square = algebraic_to_square(field);
+                if square.is_none() {
+                    tracing::error!("Invalid FEN en passant square: {}", field);
+                }
+                square
+            }
+        };
+
+        self.halfmove_clock = fields.next().and_then(|f| f.parse().ok()).unwrap_or(0);
+        let fullmove = fields
+            .next()
+            .and_then(|f| f.parse::<usize>().ok())
+            .unwrap_or(1)
+            .max(1);
+        self.start_ply = (fullmove - 1) * 2 + usize::from(!self.is_white_turn);
+
+        tracing::debug!("Loaded position: {}", self.to_fen());
+
+        self.generate_moves_current_position();
+
+        self.hash = self.compute_hash();
+    }
+}
+
+/// Renders the board as an 8x8 diagram from white's point of view
+impl Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "  +-----------------+")?;
+        for rank in (0..8).rev() {
+            write!(f, "{} |", rank + 1)?;
+            for file in 0..8 {
+                let piece = self.get_piece_at_index(rank * 8 + (7 - file));
+                write!(f, " {}", piece.to_fen_char().unwrap_or('.'))?;
+            }
+            writeln!(f, " |")?;
+        }
+        writeln!(f, "  +-----------------+")?;
+        writeln!(f, "    a b c d e f g h")?;
+
+        let turn = if self.is_white_turn { "White" } else { "Black" };
+        writeln!(f, "Side to move: {}", turn)?;
+        writeln!(f, "Castling: {}", self.castling_rights())?;
+        write!(
+            f,
+            "En passant: {}",
+            self.en_passant_square()
+                .map(square_to_algebraic)
+                .unwrap_or("-".to_string())
+        )
+    }
+}
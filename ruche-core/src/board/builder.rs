@@ -0,0 +1,243 @@
+use std::fmt::Display;
+
+use super::{square_to_algebraic, Board, CastlingRights, Piece, PieceColor, PieceType};
+
+/// Why a position can't be built
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetupError {
+    /// `color` doesn't have exactly one king
+    KingCount(PieceColor),
+    /// A castling right is set but the king or the rook isn't on its starting square
+    CastlingRights { color: PieceColor, king_side: bool },
+    /// The en passant square doesn't follow a double push of the side not to move
+    EnPassantSquare(usize),
+    /// A pawn stands on the first or last rank
+    PawnOnBackRank(usize),
+    /// The side not to move is in check, its king could be taken
+    OpponentInCheck(PieceColor),
+}
+
+impl Display for SetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SetupError::KingCount(color) => write!(f, "{:?} needs exactly one king", color),
+            SetupError::CastlingRights { color, king_side } => write!(
+                f,
+                "{:?} can't castle {}: king or rook has moved",
+                color,
+                if *king_side {
+                    "king side"
+                } else {
+                    "queen side"
+                }
+            ),
+            SetupError::EnPassantSquare(idx) => write!(
+                f,
+                "{} isn't a valid en passant square",
+                square_to_algebraic(*idx)
+            ),
+            SetupError::PawnOnBackRank(idx) => {
+                write!(
+                    f,
+                    "pawn on {} can't be on the back rank",
+                    square_to_algebraic(*idx)
+                )
+            }
+            SetupError::OpponentInCheck(color) => {
+                write!(f, "{:?} is in check but it isn't its move", color)
+            }
+        }
+    }
+}
+
+/// Builds a position piece by piece, along with the state that can't be inferred from the
+/// placement alone: side to move, castling rights and en passant square
+#[derive(Debug, Clone)]
+pub struct BoardBuilder {
+    pieces: [Piece; 64],
+    turn: PieceColor,
+    castling: CastlingRights,
+    en_passant: Option<usize>,
+}
+
+impl Default for BoardBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BoardBuilder {
+    /// An empty board, white to move without castling rights
+    pub fn new() -> Self {
+        Self {
+            pieces: [Piece::new_none(); 64],
+            turn: PieceColor::White,
+            castling: CastlingRights::default(),
+            en_passant: None,
+        }
+    }
+
+    /// Starts from the current state of `board`
+    pub fn from_board(board: &Board) -> Self {
+        let mut pieces = [Piece::new_none(); 64];
+        for (idx, piece) in pieces.iter_mut().enumerate() {
+            *piece = board.get_piece_at_index(idx);
+        }
+        Self {
+            pieces,
+            turn: board.get_turn(),
+            castling: board.castling_rights(),
+            en_passant: board.en_passant_square(),
+        }
+    }
+
+    /// Puts `piece` on `idx`, [Piece::new_none] empties the square
+    pub fn piece(mut self, idx: usize, piece: Piece) -> Self {
+        self.pieces[idx] = piece;
+        self
+    }
+
+    /// Sets the side to move
+    pub fn side_to_move(mut self, color: PieceColor) -> Self {
+        self.turn = color;
+        self
+    }
+
+    /// Sets the castling rights
+    pub fn castling(mut self, rights: CastlingRights) -> Self {
+        self.castling = rights;
+        self
+    }
+
+    /// Sets the square a pawn can capture en passant on
+    pub fn en_passant(mut self, square: Option<usize>) -> Self {
+        self.en_passant = square;
+        self
+    }
+
+    /// The piece on each square, [PieceType::None] where empty
+    pub fn pieces(&self) -> &[Piece; 64] {
+        &self.pieces
+    }
+
+    /// The side to move
+    pub fn turn(&self) -> PieceColor {
+        self.turn
+    }
+
+    /// The castling rights set so far
+    pub fn castling_rights(&self) -> CastlingRights {
+        self.castling
+    }
+
+    /// The en passant square set so far
+    pub fn en_passant_square(&self) -> Option<usize> {
+        self.en_passant
+    }
+
+    /// Checks that the pieces can stand where they are and that the castling rights and en
+    /// passant square match their placement
+    pub fn validate(&self) -> Result<(), SetupError> {
+        for color in [PieceColor::White, PieceColor::Black] {
+            let king = Piece::new(color, PieceType::King);
+            if self.pieces.iter().filter(|p| **p == king).count() != 1 {
+                return Err(SetupError::KingCount(color));
+            }
+        }
+        if let Some(idx) = (0..64).find(|idx| {
+            (idx / 8 == 0 || idx / 8 == 7) && self.pieces[*idx].get_type() == PieceType::Pawn
+        }) {
+            return Err(SetupError::PawnOnBackRank(idx));
+        }
+
+        // (color, king side, right, king square, rook square)
+        let castling = [
+            (PieceColor::White, true, self.castling.white_king_side, 3, 0),
+            (
+                PieceColor::White,
+                false,
+                self.castling.white_queen_side,
+                3,
+                7,
+            ),
+            (
+                PieceColor::Black,
+                true,
+                self.castling.black_king_side,
+                59,
+                56,
+            ),
+            (
+                PieceColor::Black,
+                false,
+                self.castling.black_queen_side,
+                59,
+                63,
+            ),
+        ];
+        for (color, king_side, right, king, rook) in castling {
+            if right
+                && (self.pieces[king] != Piece::new(color, PieceType::King)
+                    || self.pieces[rook] != Piece::new(color, PieceType::Rook))
+            {
+                return Err(SetupError::CastlingRights { color, king_side });
+            }
+        }
+
+        if let Some(square) = self.en_passant {
+            self.validate_en_passant(square)?;
+        }
+        Ok(())
+    }
+
+    /// The side not to move must have just double pushed past `square`: the square and the one
+    /// the pawn came from are empty and the pawn stands right in front
+    fn validate_en_passant(&self, square: usize) -> Result<(), SetupError> {
+        let invalid = Err(SetupError::EnPassantSquare(square));
+        if square >= 64 {
+            return invalid;
+        }
+
+        let pusher = self.turn.opposite();
+        let (rank, pawn, origin) = match pusher {
+            PieceColor::White => (2, square + 8, square.wrapping_sub(8)),
+            PieceColor::Black => (5, square.wrapping_sub(8), square + 8),
+        };
+        if square / 8 != rank {
+            return invalid;
+        }
+        if !self.pieces[square].is_none()
+            || !self.pieces[origin].is_none()
+            || self.pieces[pawn] != Piece::new(pusher, PieceType::Pawn)
+        {
+            return invalid;
+        }
+        Ok(())
+    }
+
+    /// Validates and builds the position, moves are generated for both sides. A position where
+    /// the side to move could take the king is refused
+    pub fn build(self) -> Result<Board, SetupError> {
+        self.validate()?;
+
+        let mut board = Board::new();
+        for (idx, piece) in self.pieces.iter().enumerate() {
+            if piece.is_none() {
+                continue;
+            }
+            board.put_piece(idx, *piece);
+        }
+        board.set_castling_rights(self.castling);
+        board.en_passant = self.en_passant;
+
+        // the control bitboards castling relies on are only up to date after a first pass
+        board.is_white_turn = self.turn == PieceColor::White;
+        board.generate_moves_current_position();
+        board.generate_moves_current_position();
+        board.hash = board.compute_hash();
+        if board.is_in_check(self.turn.opposite()) {
+            return Err(SetupError::OpponentInCheck(self.turn.opposite()));
+        }
+        Ok(board)
+    }
+}
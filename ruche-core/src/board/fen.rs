@@ -0,0 +1,140 @@
+use std::fmt::Display;
+
+use super::builder::SetupError;
+use super::{
+    algebraic_to_square, split_pocket, Board, BoardBuilder, CastlingRights, Piece, PieceType,
+};
+
+/// Why a FEN typed or pasted by the user can't be loaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// The FEN doesn't have between 2 and 6 fields, with the number it has
+    FieldCount(usize),
+    /// The piece placement doesn't describe 8 ranks of 8 squares
+    Placement(String),
+    /// The crazyhouse pockets hold something else than pawns, knights, bishops, rooks and queens
+    Pocket(String),
+    /// The side to move isn't `w` nor `b`
+    SideToMove(String),
+    /// The castling field isn't `-` nor made of `KQkq`
+    Castling(String),
+    /// The en passant field isn't `-` nor a square
+    EnPassant(String),
+    /// One of the move counters isn't a number
+    Counter(String),
+    /// The fields are well formed but the position can't be played
+    Position(SetupError),
+}
+
+impl Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::FieldCount(n) => write!(f, "expected 2 to 6 fields, got {}", n),
+            FenError::Placement(rank) => write!(f, "invalid rank {:?}", rank),
+            FenError::Pocket(pocket) => write!(f, "invalid pocket {:?}", pocket),
+            FenError::SideToMove(side) => write!(f, "invalid side to move {:?}", side),
+            FenError::Castling(field) => write!(f, "invalid castling rights {:?}", field),
+            FenError::EnPassant(field) => write!(f, "invalid en passant square {:?}", field),
+            FenError::Counter(field) => write!(f, "invalid move counter {:?}", field),
+            FenError::Position(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<SetupError> for FenError {
+    fn from(value: SetupError) -> Self {
+        FenError::Position(value)
+    }
+}
+
+/// Checks that a rank of the placement covers exactly 8 squares with known pieces, each maybe
+/// marked `~` as promoted
+fn check_rank(rank: &str) -> Result<(), FenError> {
+    let invalid = || FenError::Placement(rank.to_string());
+    let mut squares = 0;
+    let mut after_piece = false;
+    for c in rank.chars() {
+        if c == '~' {
+            if !after_piece {
+                return Err(invalid());
+            }
+            after_piece = false;
+            continue;
+        }
+        after_piece = c.to_digit(10).is_none();
+        squares += match c.to_digit(10) {
+            Some(empty @ 1..=8) => empty,
+            Some(_) => return Err(invalid()),
+            None => {
+                Piece::from_fen_char(c).ok_or_else(invalid)?;
+                1
+            }
+        };
+    }
+    if squares != 8 {
+        return Err(invalid());
+    }
+    Ok(())
+}
+
+/// Checks that the pockets hold only pieces that can be dropped
+fn check_pocket(pocket: &str) -> Result<(), FenError> {
+    let droppable = pocket
+        .chars()
+        .all(|c| Piece::from_fen_char(c).is_some_and(|piece| piece.get_type() != PieceType::King));
+    if !droppable {
+        return Err(FenError::Pocket(pocket.to_string()));
+    }
+    Ok(())
+}
+
+impl Board {
+    /// Loads `fen` after checking every field, unlike [Board::load_position] which makes the
+    /// best of what it is given. The fields after the side to move can be left out, meaning no
+    /// castling rights nor en passant square and the first move. Pockets after the placement make
+    /// it a crazyhouse position
+    pub fn parse_fen(fen: &str) -> Result<Board, FenError> {
+        let fields = fen.split_whitespace().collect::<Vec<_>>();
+        if !(2..=6).contains(&fields.len()) {
+            return Err(FenError::FieldCount(fields.len()));
+        }
+
+        let (placement, pocket) = split_pocket(fields[0]);
+        let ranks = placement.split('/').collect::<Vec<_>>();
+        if ranks.len() != 8 {
+            return Err(FenError::Placement(placement.to_string()));
+        }
+        for rank in ranks {
+            check_rank(rank)?;
+        }
+        if let Some(pocket) = pocket {
+            check_pocket(pocket)?;
+        }
+        if !matches!(fields[1], "w" | "b") {
+            return Err(FenError::SideToMove(fields[1].to_string()));
+        }
+        let castling = fields.get(2).copied().unwrap_or("-");
+        if CastlingRights::from_fen(castling).is_none() {
+            return Err(FenError::Castling(castling.to_string()));
+        }
+        let en_passant = fields.get(3).copied().unwrap_or("-");
+        if en_passant != "-" && algebraic_to_square(en_passant).is_none() {
+            return Err(FenError::EnPassant(en_passant.to_string()));
+        }
+        let halfmove = fields.get(4).copied().unwrap_or("0");
+        let fullmove = fields.get(5).copied().unwrap_or("1");
+        for counter in [halfmove, fullmove] {
+            if counter.parse::<u32>().is_err() {
+                return Err(FenError::Counter(counter.to_string()));
+            }
+        }
+
+        let mut board = Board::new();
+        board.load_position(format!(
+            "{} {} {} {} {} {}",
+            fields[0], fields[1], castling, en_passant, halfmove, fullmove
+        ));
+        BoardBuilder::from_board(&board).build()?;
+        Ok(board)
+    }
+}
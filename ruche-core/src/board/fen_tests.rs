@@ -0,0 +1,285 @@
+//! Round trips between the FEN loader and exporter, every position must come back unchanged
+
+use super::fen::FenError;
+use super::{Board, BoardBuilder, Piece, PieceColor, PieceType, START_FEN};
+
+/// Positions covering both sides to move, every combination of castling rights, en passant
+/// squares for both colors, promotions about to happen and non-zero move counters
+const CORPUS: [&str; 14] = [
+    START_FEN,
+    "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+    "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "4k3/8/8/8/8/8/8/4K2R w K - 0 1",
+    "r3k3/8/8/8/8/8/8/4K3 b q - 12 40",
+    "4k2r/8/8/8/8/8/8/R3K3 w Qk - 3 21",
+    "8/8/8/8/8/8/8/K6k b - - 99 150",
+    "8/P7/8/8/8/8/7p/K6k w - - 0 60",
+];
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+fn load(fen: &str) -> Board {
+    let mut board = Board::new();
+    board.load_position(fen.to_string());
+    board
+}
+
+#[test]
+fn export_matches_the_loaded_fen() {
+    for fen in CORPUS {
+        assert_eq!(load(fen).to_fen(), fen);
+    }
+}
+
+#[test]
+fn reloading_the_export_gives_the_same_board() {
+    for fen in CORPUS {
+        let board = load(fen);
+        let reloaded = load(&board.to_fen());
+        assert_eq!(board, reloaded, "{}", fen);
+        assert_eq!(board.zobrist_hash(), reloaded.zobrist_hash(), "{}", fen);
+    }
+}
+
+#[test]
+fn positions_reached_by_moves_round_trip() {
+    // castling both ways, and rooks taken in their corners
+    let games: [(&str, &[&str], &str); 3] = [
+        (
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            &["e1g1", "e8c8"],
+            "2kr3r/8/8/8/8/8/8/R4RK1 w - - 2 2",
+        ),
+        (
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            &["a1a8"],
+            "R3k2r/8/8/8/8/8/8/4K2R b Kk - 0 1",
+        ),
+        (
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+            &["h1h8", "e8e7"],
+            "r6R/4k3/8/8/8/8/8/R3K3 w Q - 1 2",
+        ),
+    ];
+    for (start, moves, fen) in games {
+        let mut board = load(start);
+        for text in moves {
+            let mv = board
+                .legal_moves()
+                .find(|m| m.to_string() == *text)
+                .unwrap_or_else(|| panic!("{} is legal", text));
+            board
+                .play(&mv)
+                .unwrap_or_else(|_| panic!("{} is legal", text));
+        }
+        assert_eq!(board.to_fen(), fen);
+        let reloaded = load(&board.to_fen());
+        assert_eq!(board.zobrist_hash(), reloaded.zobrist_hash(), "{}", fen);
+        assert!(board.legal_moves().eq(reloaded.legal_moves()), "{}", fen);
+    }
+}
+
+#[test]
+fn bitboards_agree_with_the_mailbox() {
+    for fen in CORPUS {
+        let board = load(fen);
+        for color in [PieceColor::White, PieceColor::Black] {
+            let mut occupancy = 0;
+            for piece_type in PIECE_TYPES {
+                let piece = Piece::new(color, piece_type);
+                let bitboard = board.pieces(piece);
+                for idx in 0..64 {
+                    let in_mailbox = board.get_piece_at_index(idx) == piece;
+                    assert_eq!(bitboard & (1 << idx) != 0, in_mailbox, "{}", fen);
+                }
+                occupancy |= bitboard;
+            }
+            assert_eq!(board.occupancy(color), occupancy, "{}", fen);
+        }
+    }
+}
+
+#[test]
+fn loading_replaces_the_previous_position() {
+    let mut board = load(CORPUS[4]);
+    board.load_position(CORPUS[12].to_string());
+    assert_eq!(board, load(CORPUS[12]));
+}
+
+#[test]
+fn missing_fields_take_their_defaults() {
+    assert_eq!(
+        load("4k3/8/8/8/8/8/8/4K3").to_fen(),
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1"
+    );
+    // no castling field means no castling right, even with the kings and rooks at home
+    assert_eq!(
+        load("r3k2r/8/8/8/8/8/8/R3K2R").to_fen(),
+        "r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1"
+    );
+    assert_eq!(
+        load("4k3/8/8/8/8/8/8/4K3 b -").to_fen(),
+        "4k3/8/8/8/8/8/8/4K3 b - - 0 1"
+    );
+}
+
+#[test]
+fn move_counters_carry_on_after_a_move() {
+    let mut board = load("r3k3/8/8/8/8/8/8/4K3 b q - 12 40");
+    let mv = board
+        .legal_moves()
+        .find(|m| m.to_string() == "a8a1")
+        .unwrap();
+    board.play(&mv).unwrap_or_else(|_| panic!("a8a1 is legal"));
+    assert_eq!(board.to_fen(), "4k3/8/8/8/8/8/8/r3K3 w - - 13 41");
+
+    let mut board = load(START_FEN);
+    let mv = board
+        .legal_moves()
+        .find(|m| m.to_string() == "e2e4")
+        .unwrap();
+    board.play(&mv).unwrap_or_else(|_| panic!("e2e4 is legal"));
+    assert_eq!(
+        board.to_fen(),
+        "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+    );
+}
+
+#[test]
+fn unmaking_a_move_restores_the_position() {
+    for fen in CORPUS {
+        let board = load(fen);
+        for mv in board.legal_moves() {
+            let mut played = board.clone();
+            played
+                .play(&mv)
+                .unwrap_or_else(|_| panic!("{} is legal in {}", mv, fen));
+            assert_eq!(played.unmake_move(), Some(mv.clone()), "{}", fen);
+            assert_eq!(played.to_fen(), fen, "{} in {}", mv, fen);
+            assert_eq!(
+                played.zobrist_hash(),
+                board.zobrist_hash(),
+                "{} in {}",
+                mv,
+                fen
+            );
+            assert!(
+                played.legal_moves().eq(board.legal_moves()),
+                "{} in {}",
+                mv,
+                fen
+            );
+        }
+    }
+}
+
+#[test]
+fn capturing_a_rook_in_its_corner_takes_its_castling_right() {
+    let mut board = load("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1");
+    let mv = board
+        .legal_moves()
+        .find(|m| m.to_string() == "h1h8")
+        .unwrap();
+    board.play(&mv).unwrap_or_else(|_| panic!("h1h8 is legal"));
+    let fen = "r3k2R/8/8/8/8/8/8/R3K3 b Qq - 0 1";
+    assert_eq!(board.to_fen(), fen);
+    assert_eq!(board.zobrist_hash(), load(fen).zobrist_hash());
+    // the king can't castle with the rook gone
+    assert!(board.legal_moves().all(|m| m.to_string() != "e8g8"));
+}
+
+#[test]
+fn a_null_move_generates_the_moves_of_the_other_side() {
+    // with the bishop on g3 checking the king, white can't castle once black has passed
+    for fen in [
+        "4k3/8/8/8/8/8/8/4K2R b K - 0 1",
+        "4k3/8/8/8/8/6b1/8/4K2R b K - 0 1",
+        "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 0 1",
+    ] {
+        let mut board = load(fen);
+        board.make_null_move();
+        let passed = load(&fen.replace(" b ", " w "));
+        assert!(board.legal_moves().eq(passed.legal_moves()), "{}", fen);
+        board.unmake_null_move();
+        assert!(board.legal_moves().eq(load(fen).legal_moves()), "{}", fen);
+    }
+}
+
+#[test]
+fn edited_positions_must_be_reachable() {
+    let build = |fen: &str| {
+        BoardBuilder::from_board(&load(fen))
+            .build()
+            .map(|b| b.to_fen())
+    };
+    assert_eq!(
+        build("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1"),
+        Ok("4k3/8/8/8/8/8/8/4R1K1 b - - 0 1".to_string())
+    );
+    // black's king can be taken with white to move
+    assert!(build("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1").is_err());
+    assert!(build("P3k3/8/8/8/8/8/8/4K3 w - - 0 1").is_err());
+    assert!(build("4k3/8/8/8/8/8/8/4K1p1 w - - 0 1").is_err());
+}
+
+#[test]
+fn parsing_checks_every_field() {
+    for fen in CORPUS {
+        assert_eq!(
+            Board::parse_fen(fen).map(|b| b.to_fen()),
+            Ok(fen.to_string())
+        );
+    }
+    assert_eq!(
+        Board::parse_fen("4k3/8/8/8/8/8/8/4K3 b").map(|b| b.to_fen()),
+        Ok("4k3/8/8/8/8/8/8/4K3 b - - 0 1".to_string())
+    );
+
+    let error = |fen: &str| Board::parse_fen(fen).err();
+    assert_eq!(error("4k3/8/8/8"), Some(FenError::FieldCount(1)));
+    assert!(matches!(
+        error("4k3/8/8/8/8/8/8 w - - 0 1"),
+        Some(FenError::Placement(_))
+    ));
+    assert!(matches!(
+        error("4k3/9/8/8/8/8/8/4K3 w - - 0 1"),
+        Some(FenError::Placement(_))
+    ));
+    assert!(matches!(
+        error("4k3/8/8/8/8/8/8/4X3 w - - 0 1"),
+        Some(FenError::Placement(_))
+    ));
+    assert!(matches!(
+        error("4k3/8/8/8/8/8/8/4K3 x - - 0 1"),
+        Some(FenError::SideToMove(_))
+    ));
+    assert!(matches!(
+        error("4k3/8/8/8/8/8/8/4K3 w KX - 0 1"),
+        Some(FenError::Castling(_))
+    ));
+    assert!(matches!(
+        error("4k3/8/8/8/8/8/8/4K3 w - z9 0 1"),
+        Some(FenError::EnPassant(_))
+    ));
+    assert!(matches!(
+        error("4k3/8/8/8/8/8/8/4K3 w - - x 1"),
+        Some(FenError::Counter(_))
+    ));
+    assert!(matches!(
+        error("8/8/8/8/8/8/8/4K3 w - - 0 1"),
+        Some(FenError::Position(_))
+    ));
+}
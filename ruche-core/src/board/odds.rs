@@ -0,0 +1,51 @@
+use std::fmt::Display;
+
+use super::START_FEN;
+
+/// Material given away at the start of a handicap game. Black gives it, the side the engine
+/// plays when the player moves first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Odds {
+    /// the standard position
+    #[default]
+    None,
+    /// without the queen's knight
+    Knight,
+    /// without the queen's rook
+    Rook,
+    /// without the king's bishop pawn, white still moving first
+    PawnAndMove,
+}
+
+impl Odds {
+    /// Every handicap, in the order they are offered
+    pub const ALL: [Odds; 4] = [Odds::None, Odds::Knight, Odds::Rook, Odds::PawnAndMove];
+
+    /// The handicap after this one in [Odds::ALL], back to the first after the last
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|o| *o == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Position a game with this handicap starts from
+    pub fn fen(self) -> &'static str {
+        match self {
+            Odds::None => START_FEN,
+            Odds::Knight => "r1bqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            Odds::Rook => "1nbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQk - 0 1",
+            Odds::PawnAndMove => "rnbqkbnr/ppppp1pp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        }
+    }
+}
+
+impl Display for Odds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Odds::None => "None",
+            Odds::Knight => "Knight",
+            Odds::Rook => "Rook",
+            Odds::PawnAndMove => "Pawn and move",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,158 @@
+//! Perft counts of the standard test positions and of the variants, compared against the
+//! published numbers so the move generator, the legality filter and unmaking moves are checked
+//! together
+
+use super::{Board, PieceColor, Variant, VariantEnd, START_FEN};
+
+/// Positions with their leaf counts at the depth given, from the chessprogramming wiki
+const SUITE: [(&str, u32, u64); 5] = [
+    (START_FEN, 4, 197_281),
+    (
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        3,
+        97_862,
+    ),
+    ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 5, 674_624),
+    (
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        3,
+        9_467,
+    ),
+    (
+        "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+        3,
+        62_379,
+    ),
+];
+
+/// Leaf counts of the variants from their start positions, at depth 1 and on
+const VARIANT_SUITE: [(Variant, &[u64]); 6] = [
+    (Variant::Crazyhouse, &[20, 400, 8_902, 197_281, 4_888_832]),
+    (Variant::Atomic, &[20, 400, 8_902, 197_326]),
+    (Variant::Antichess, &[20, 400, 8_067, 153_299]),
+    (Variant::Horde, &[8, 128, 1_274, 23_310, 265_223]),
+    (Variant::KingOfTheHill, &[20, 400, 8_902, 197_281]),
+    (Variant::ThreeCheck, &[20, 400, 8_902, 197_281]),
+];
+
+fn load(fen: &str) -> Board {
+    let mut board = Board::new();
+    board.load_position(fen.to_string());
+    board
+}
+
+fn load_variant(variant: Variant, fen: &str) -> Board {
+    let mut board = load(fen);
+    board.set_variant(variant);
+    board
+}
+
+/// Plays the moves, written from and to square like `e2e4`, on `board`
+fn play_moves(board: &mut Board, moves: &[&str]) {
+    for text in moves {
+        let mv = board
+            .legal_moves()
+            .find(|m| m.to_string() == *text)
+            .unwrap_or_else(|| panic!("{} is legal in {}", text, board.to_fen()));
+        board
+            .play(&mv)
+            .unwrap_or_else(|_| panic!("{} is legal", text));
+    }
+}
+
+/// Like [Board::perft], but playing and unmaking the moves on a single board
+fn perft_in_place(board: &mut Board, depth: u32) -> u64 {
+    let moves = board.legal_moves().collect::<Vec<_>>();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut nodes = 0;
+    for mv in moves {
+        board
+            .play(&mv)
+            .unwrap_or_else(|_| panic!("{} is legal in {}", mv, board.to_fen()));
+        nodes += perft_in_place(board, depth - 1);
+        board.unmake_move();
+    }
+    nodes
+}
+
+#[test]
+fn perft_matches_the_known_counts() {
+    for (fen, depth, nodes) in SUITE {
+        assert_eq!(load(fen).perft(depth), nodes, "{}", fen);
+    }
+}
+
+#[test]
+fn perft_matches_through_play_and_unmake() {
+    for (fen, depth, nodes) in SUITE {
+        let mut board = load(fen);
+        assert_eq!(perft_in_place(&mut board, depth), nodes, "{}", fen);
+        assert_eq!(board.to_fen(), fen);
+    }
+}
+
+#[test]
+fn variant_perft_matches_the_known_counts() {
+    for (variant, counts) in VARIANT_SUITE {
+        let mut board = load_variant(variant, variant.start_fen());
+        let fen = board.to_fen();
+        for (depth, nodes) in (1..).zip(counts) {
+            assert_eq!(perft_in_place(&mut board, depth), *nodes, "{:?}", variant);
+        }
+        assert_eq!(board.to_fen(), fen);
+    }
+}
+
+#[test]
+fn a_king_blown_up_ends_atomic() {
+    let mut board = load_variant(Variant::Atomic, "4k3/4p3/8/8/8/8/8/4R1K1 w - - 0 1");
+    play_moves(&mut board, &["e1e7"]);
+    assert_eq!(
+        board.result(),
+        Some((PieceColor::White, VariantEnd::KingExploded))
+    );
+}
+
+#[test]
+fn a_king_on_the_hill_wins() {
+    let mut board = load_variant(Variant::KingOfTheHill, "4k3/8/8/8/8/4K3/8/8 w - - 0 1");
+    play_moves(&mut board, &["e3e4"]);
+    assert_eq!(
+        board.result(),
+        Some((PieceColor::White, VariantEnd::KingOfTheHill))
+    );
+}
+
+#[test]
+fn a_third_check_wins() {
+    let mut board = load_variant(Variant::ThreeCheck, "4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    play_moves(&mut board, &["a1a8", "e8e7", "a8a7", "e7e6", "a7a6"]);
+    assert_eq!(
+        board.result(),
+        Some((PieceColor::White, VariantEnd::ThreeChecks))
+    );
+}
+
+#[test]
+fn running_out_of_moves_wins_antichess() {
+    // black's last piece is taken and black, to move, has nothing left
+    let mut board = load_variant(Variant::Antichess, "8/8/8/8/8/8/p7/R7 w - - 0 1");
+    play_moves(&mut board, &["a1a2"]);
+    assert_eq!(
+        board.result(),
+        Some((PieceColor::Black, VariantEnd::OutOfMoves))
+    );
+}
+
+#[test]
+fn capturing_the_horde_wins() {
+    let mut board = load_variant(Variant::Horde, "4k3/8/8/8/8/8/3P4/8 b - - 0 1");
+    assert_eq!(board.result(), None);
+    play_moves(&mut board, &["e8e7", "d2d4", "e7e6", "d4d5", "e6d5"]);
+    assert_eq!(
+        board.result(),
+        Some((PieceColor::Black, VariantEnd::HordeCaptured))
+    );
+}
@@ -0,0 +1,69 @@
+use super::{Piece, PieceColor, PieceType};
+
+/// Piece types a pocket holds, in the order they are listed
+pub const POCKET_PIECES: [PieceType; 5] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+];
+
+/// Pieces a side captured in crazyhouse, which it may drop back on the board as its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Pocket {
+    counts: [u8; 5],
+}
+
+impl Pocket {
+    fn slot(piece_type: PieceType) -> Option<usize> {
+        POCKET_PIECES.iter().position(|t| *t == piece_type)
+    }
+
+    /// Number of `piece_type` in the pocket
+    pub fn count(&self, piece_type: PieceType) -> u8 {
+        Self::slot(piece_type).map_or(0, |i| self.counts[i])
+    }
+
+    /// Returns true if the pocket holds no piece
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|c| *c == 0)
+    }
+
+    /// Puts a `piece_type` in the pocket, kings can't be captured and aren't kept
+    pub fn add(&mut self, piece_type: PieceType) {
+        if let Some(i) = Self::slot(piece_type) {
+            self.counts[i] = self.counts[i].saturating_add(1);
+        }
+    }
+
+    /// Takes a `piece_type` out of the pocket, returns false if there was none
+    pub fn take(&mut self, piece_type: PieceType) -> bool {
+        match Self::slot(piece_type) {
+            Some(i) if self.counts[i] > 0 => {
+                self.counts[i] -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The piece types held with their counts, leaving out those there are none of
+    pub fn pieces(&self) -> impl Iterator<Item = (PieceType, u8)> + '_ {
+        POCKET_PIECES
+            .iter()
+            .zip(self.counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(t, count)| (*t, count))
+    }
+
+    /// Writes the pocket of `color` as FEN letters, uppercase for white, e.g. `PPn`
+    pub fn to_fen(&self, color: PieceColor) -> String {
+        self.pieces()
+            .flat_map(|(t, count)| {
+                let c = Piece::new(color, t).to_fen_char();
+                std::iter::repeat_n(c, count as usize).flatten()
+            })
+            .collect()
+    }
+}
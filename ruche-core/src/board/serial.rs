@@ -0,0 +1,70 @@
+//! Serde support for [Board], behind the `serde` feature. A board is written as the position
+//! it started from and the moves played since, so that reading it back replays them and the
+//! history, the undo information and the hash all come out as they were:
+//!
+//! ```json
+//! {"start": "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1", "moves": [...]}
+//! ```
+//!
+//! along with the variant, which boards written before variants existed leave out
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Board, Move, Variant};
+
+#[derive(Serialize, Deserialize)]
+struct BoardRepr {
+    /// FEN of the position before the first move of the history
+    start: String,
+    moves: Vec<Move>,
+    #[serde(default)]
+    variant: Variant,
+}
+
+impl Serialize for Board {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut start = self.clone();
+        while start.unmake_move().is_some() {}
+        // a null move can't be taken back, the position is written without its history then
+        let repr = if start.get_move_history().is_empty() {
+            BoardRepr {
+                start: start.to_fen(),
+                moves: self.get_move_history().to_vec(),
+                variant: self.variant,
+            }
+        } else {
+            BoardRepr {
+                start: self.to_fen(),
+                moves: vec![],
+                variant: self.variant,
+            }
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Board {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = BoardRepr::deserialize(deserializer)?;
+        let mut board = Board::new();
+        board.load_position(repr.start.clone());
+        // a crazyhouse position tells its variant by its pockets already
+        if repr.variant != Variant::Standard {
+            board.set_variant(repr.variant);
+        }
+        if board.variant().needs_kings() && board.king_square(board.get_turn()).is_none() {
+            return Err(D::Error::custom(format!("invalid FEN {:?}", repr.start)));
+        }
+        for (i, mo) in repr.moves.iter().enumerate() {
+            if board.play(mo).is_err() {
+                return Err(D::Error::custom(format!(
+                    "illegal move {} at ply {}",
+                    mo,
+                    i + 1
+                )));
+            }
+        }
+        Ok(board)
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt::Display;
+
+use super::START_FEN;
+
+/// The center squares d4, e4, d5 and e5, a king reaching one wins in king of the hill
+pub const HILL: u64 = 1 << 27 | 1 << 28 | 1 << 35 | 1 << 36;
+/// Checks a side gives to win in three-check
+pub const CHECKS_TO_WIN: u8 = 3;
+/// Position a horde game starts from, white's pawns against black's usual army
+pub const HORDE_FEN: &str =
+    "rnbqkbnr/pppppppp/8/1PP2PP1/PPPPPPPP/PPPPPPPP/PPPPPPPP/PPPPPPPP w kq - 0 1";
+
+/// Rules a position is played by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Variant {
+    /// standard chess
+    #[default]
+    Standard,
+    /// captured pieces go to the capturer's pocket and can be dropped back, see [super::Pocket]
+    Crazyhouse,
+    /// a capture blows up the capturing piece and the pieces around it but pawns, and blowing
+    /// up the enemy king wins
+    Atomic,
+    /// bringing the king to one of the four center squares wins, see [HILL]
+    KingOfTheHill,
+    /// giving a third check wins
+    ThreeCheck,
+    /// captures are forced, the king is a piece like any other, and the side left without a
+    /// move, having lost its pieces or being stalemated, wins
+    Antichess,
+    /// white's kingless horde of pawns, which may push two squares from the first rank too,
+    /// wins by mating black, and black by capturing all of it
+    Horde,
+}
+
+impl Variant {
+    /// Every variant, in the order they are offered
+    pub const ALL: [Variant; 7] = [
+        Variant::Standard,
+        Variant::Crazyhouse,
+        Variant::Atomic,
+        Variant::KingOfTheHill,
+        Variant::ThreeCheck,
+        Variant::Antichess,
+        Variant::Horde,
+    ];
+
+    /// The variant after this one in [Variant::ALL], back to the first after the last
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|v| *v == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// Position a game of this variant starts from
+    pub fn start_fen(self) -> &'static str {
+        match self {
+            Variant::Horde => HORDE_FEN,
+            _ => START_FEN,
+        }
+    }
+
+    /// Returns true if both sides have a king to lose, which isn't so in antichess nor for the
+    /// horde
+    pub fn needs_kings(self) -> bool {
+        !matches!(self, Variant::Antichess | Variant::Horde)
+    }
+}
+
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Variant::Standard => "Standard",
+            Variant::Crazyhouse => "Crazyhouse",
+            Variant::Atomic => "Atomic",
+            Variant::KingOfTheHill => "King of the Hill",
+            Variant::ThreeCheck => "Three-check",
+            Variant::Antichess => "Antichess",
+            Variant::Horde => "Horde",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How the rules of a variant ended a game, rather than mate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VariantEnd {
+    /// the loser's king was blown up, in atomic
+    KingExploded,
+    /// the winner's king reached the center, in king of the hill
+    KingOfTheHill,
+    /// the winner gave a third check, in three-check
+    ThreeChecks,
+    /// the winner had no move left, in antichess
+    OutOfMoves,
+    /// the winner captured every piece of the horde
+    HordeCaptured,
+}
+
+/// The way the winner won e.g. `by blowing up the king`
+impl Display for VariantEnd {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let how = match self {
+            VariantEnd::KingExploded => "by blowing up the king",
+            VariantEnd::KingOfTheHill => "with the king on the hill",
+            VariantEnd::ThreeChecks => "by giving three checks",
+            VariantEnd::OutOfMoves => "with no move left",
+            VariantEnd::HordeCaptured => "by capturing the whole horde",
+        };
+        write!(f, "{}", how)
+    }
+}
+
+/// Squares an atomic capture on `square` blows up: the square itself and the ones around it
+pub fn blast_squares(square: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = ((square % 8) as isize, (square / 8) as isize);
+    (-1..=1)
+        .flat_map(move |dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+        .filter(|(x, y)| (0..8).contains(x) && (0..8).contains(y))
+        .map(|(x, y)| (y * 8 + x) as usize)
+}
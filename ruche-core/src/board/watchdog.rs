@@ -0,0 +1,131 @@
+//! Debug build consistency checks: after a move the incrementally updated state is compared
+//! with the same position built from scratch, so a bad update is caught on the move that made
+//! it rather than many moves later
+
+use std::collections::HashSet;
+
+use super::{Board, Move, Piece, PieceColor, PieceType};
+
+const PIECE_TYPES: [PieceType; 6] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+    PieceType::King,
+];
+
+impl Board {
+    /// Describes every way the board state disagrees with itself or with the position rebuilt
+    /// from its FEN, empty if it is consistent
+    fn divergences(&self) -> Vec<String> {
+        let mut found = vec![];
+
+        for color in [PieceColor::White, PieceColor::Black] {
+            for piece_type in PIECE_TYPES {
+                let piece = Piece::new(color, piece_type);
+                let bitboard = self.pieces(piece);
+                for idx in 0..64 {
+                    let in_bitboard = bitboard & (1 << idx) != 0;
+                    let in_mailbox = self.get_piece_at_index(idx) == piece;
+                    if in_bitboard != in_mailbox {
+                        found.push(format!(
+                            "{:?} {:?} on {}: bitboard {} mailbox {}",
+                            color,
+                            piece_type,
+                            super::square_to_algebraic(idx),
+                            in_bitboard,
+                            in_mailbox
+                        ));
+                    }
+                }
+            }
+
+            let occupancy = PIECE_TYPES
+                .iter()
+                .fold(0, |acc, t| acc | self.pieces(Piece::new(color, *t)));
+            if self.occupancy(color) != occupancy {
+                found.push(format!(
+                    "{:?} occupancy {:#018x}, from the pieces {:#018x}",
+                    color,
+                    self.occupancy(color),
+                    occupancy
+                ));
+            }
+        }
+
+        let hash = self.compute_hash();
+        if self.hash != hash {
+            found.push(format!(
+                "hash {:#018x}, from scratch {:#018x}",
+                self.hash, hash
+            ));
+        }
+
+        let mut fresh = Board::new();
+        fresh.load_position(self.to_fen());
+        let controls = [
+            (
+                "white control",
+                self.white_control_bitboard.inner,
+                fresh.white_control_bitboard.inner,
+            ),
+            (
+                "black control",
+                self.black_control_bitboard.inner,
+                fresh.black_control_bitboard.inner,
+            ),
+        ];
+        for (name, incremental, scratch) in controls {
+            if incremental != scratch {
+                found.push(format!(
+                    "{} {:#018x}, from scratch {:#018x}",
+                    name, incremental, scratch
+                ));
+            }
+        }
+
+        let moves = [
+            (
+                "white moves",
+                &self.white_current_moves,
+                &fresh.white_current_moves,
+            ),
+            (
+                "black moves",
+                &self.black_current_moves,
+                &fresh.black_current_moves,
+            ),
+        ];
+        for (name, incremental, scratch) in moves {
+            let incremental = incremental.iter().collect::<HashSet<_>>();
+            let scratch = scratch.iter().collect::<HashSet<_>>();
+            let missing = scratch.difference(&incremental).collect::<Vec<_>>();
+            let extra = incremental.difference(&scratch).collect::<Vec<_>>();
+            if !missing.is_empty() || !extra.is_empty() {
+                found.push(format!(
+                    "{}: missing {:?}, extra {:?}",
+                    name, missing, extra
+                ));
+            }
+        }
+
+        found
+    }
+}
+
+/// Checks `board` after `mv` was applied to it, logging a full dump of the board if its state
+/// diverged
+pub fn watchdog(board: &Board, mv: &Move) {
+    let divergences = board.divergences();
+    if divergences.is_empty() {
+        return;
+    }
+    tracing::error!(
+        "Board state diverged after {}:\n{}\n{}\n{}",
+        mv,
+        divergences.join("\n"),
+        board,
+        board.debug_string()
+    );
+}
@@ -0,0 +1,117 @@
+//! Strength of the engine as an opponent. The weaker levels search shallower, evaluate with
+//! noise and play any move close enough to the best one, so they blunder the way people do
+//! rather than playing perfectly for a while and then giving a piece away
+
+use std::time::Duration;
+
+use super::search::{SearchInfo, SearchLimits};
+use crate::board::Move;
+
+/// Levels of play, from weakest to strongest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Difficulty {
+    Beginner,
+    Casual,
+    Intermediate,
+    Advanced,
+    /// the engine at full strength
+    #[default]
+    Master,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Beginner,
+        Difficulty::Casual,
+        Difficulty::Intermediate,
+        Difficulty::Advanced,
+        Difficulty::Master,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Beginner => "Beginner",
+            Difficulty::Casual => "Casual",
+            Difficulty::Intermediate => "Intermediate",
+            Difficulty::Advanced => "Advanced",
+            Difficulty::Master => "Master",
+        }
+    }
+
+    /// The next level, or the previous one for a negative `delta`, stopping at either end
+    pub fn step(self, delta: isize) -> Self {
+        let idx = Self::ALL.iter().position(|d| *d == self).unwrap_or(0) as isize;
+        Self::ALL[(idx + delta).clamp(0, Self::ALL.len() as isize - 1) as usize]
+    }
+
+    /// Deepest the search may go, `None` for no limit other than time
+    fn max_depth(self) -> Option<u32> {
+        match self {
+            Difficulty::Beginner => Some(1),
+            Difficulty::Casual => Some(2),
+            Difficulty::Intermediate => Some(3),
+            Difficulty::Advanced => Some(5),
+            Difficulty::Master => None,
+        }
+    }
+
+    /// Best moves the engine chooses from
+    fn candidates(self) -> usize {
+        match self {
+            Difficulty::Beginner => 5,
+            Difficulty::Casual => 4,
+            Difficulty::Intermediate => 3,
+            Difficulty::Advanced => 2,
+            Difficulty::Master => 1,
+        }
+    }
+
+    /// Centipawns a candidate may be behind the best move and still be played
+    fn margin(self) -> i32 {
+        match self {
+            Difficulty::Beginner => 200,
+            Difficulty::Casual => 100,
+            Difficulty::Intermediate => 40,
+            Difficulty::Advanced => 15,
+            Difficulty::Master => 0,
+        }
+    }
+
+    /// Most centipawns added to or taken from each evaluation
+    pub fn eval_noise(self) -> i32 {
+        match self {
+            Difficulty::Beginner => 150,
+            Difficulty::Casual => 80,
+            Difficulty::Intermediate => 30,
+            Difficulty::Advanced | Difficulty::Master => 0,
+        }
+    }
+
+    /// Limits of a search for a move thinking at most `think_time`, or until stopped
+    pub fn limits(self, think_time: Option<Duration>) -> SearchLimits {
+        let limits = match think_time {
+            Some(think_time) => SearchLimits::movetime(think_time),
+            None => SearchLimits::infinite(),
+        };
+        let mut limits = limits.with_multipv(self.candidates());
+        if let Some(depth) = self.max_depth() {
+            limits.depth = depth;
+        }
+        limits
+    }
+
+    /// Picks the move to play among the `lines` of the deepest search, any within the margin of
+    /// the best one, `seed` being any random number
+    pub fn pick(self, lines: &[SearchInfo], seed: u64) -> Option<Move> {
+        let best = lines.iter().map(|l| l.score).max()?;
+        let close = lines
+            .iter()
+            .filter(|l| best - l.score <= self.margin())
+            .filter_map(|l| l.pv.first())
+            .collect::<Vec<_>>();
+        if close.is_empty() {
+            return None;
+        }
+        Some(close[seed as usize % close.len()].clone())
+    }
+}
@@ -0,0 +1,139 @@
+//! Endgame knowledge: material configurations the stronger side can't win, or rarely does, get
+//! their score scaled toward zero so the search doesn't chase a win that isn't there
+
+use super::search::piece_value;
+use crate::board::{Board, Piece, PieceColor, PieceType};
+
+/// Scale factor leaving the score as is
+pub const SCALE_NORMAL: i32 = 64;
+/// The position is a known draw
+const SCALE_DRAW: i32 = 0;
+/// No pawns and at most a minor piece up, e.g. KRvKB
+const SCALE_MINOR_UP: i32 = 16;
+/// Bishops on opposite colors, the defender can usually blockade
+const SCALE_OPPOSITE_BISHOPS: i32 = 32;
+
+/// Files of the rook pawns, `x` being 0 on the h-file
+const H_FILE: u64 = 0x0101_0101_0101_0101;
+const A_FILE: u64 = H_FILE << 7;
+
+fn count(board: &Board, color: PieceColor, piece_type: PieceType) -> u32 {
+    board.pieces(Piece::new(color, piece_type)).count_ones()
+}
+
+/// Value of the pieces of `color` other than pawns and the king
+fn non_pawn_material(board: &Board, color: PieceColor) -> i32 {
+    [
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ]
+    .into_iter()
+    .map(|t| count(board, color, t) as i32 * piece_value(t))
+    .sum()
+}
+
+/// Returns true if `color` has nothing but its king and pieces of `piece_type`
+fn only_has(board: &Board, color: PieceColor, piece_type: PieceType) -> bool {
+    [
+        PieceType::Pawn,
+        PieceType::Knight,
+        PieceType::Bishop,
+        PieceType::Rook,
+        PieceType::Queen,
+    ]
+    .into_iter()
+    .all(|t| t == piece_type || count(board, color, t) == 0)
+}
+
+/// Color of a square, equal for squares of the same color
+fn square_color(idx: usize) -> usize {
+    (idx % 8 + idx / 8) % 2
+}
+
+/// Number of king moves between two squares
+fn distance(a: usize, b: usize) -> usize {
+    let dx = (a % 8).abs_diff(b % 8);
+    let dy = (a / 8).abs_diff(b / 8);
+    dx.max(dy)
+}
+
+/// King, bishop and pawns on a single rook file against a bare king sitting in front of the
+/// promotion square, when the bishop can't cover the promotion square
+fn is_wrong_bishop_draw(board: &Board, strong: PieceColor) -> bool {
+    let weak = strong.opposite();
+    let pawns = board.pieces(Piece::new(strong, PieceType::Pawn));
+    let bishops = board.pieces(Piece::new(strong, PieceType::Bishop));
+    let non_pawn = non_pawn_material(board, strong);
+    if pawns == 0 || bishops.count_ones() != 1 || non_pawn != piece_value(PieceType::Bishop) {
+        return false;
+    }
+    if non_pawn_material(board, weak) != 0 || count(board, weak, PieceType::Pawn) != 0 {
+        return false;
+    }
+
+    let x = if pawns & !H_FILE == 0 {
+        0
+    } else if pawns & !A_FILE == 0 {
+        7
+    } else {
+        return false;
+    };
+    let promotion_square = match strong {
+        PieceColor::White => 56 + x,
+        PieceColor::Black => x,
+    };
+    let bishop_square = bishops.trailing_zeros() as usize;
+    if square_color(bishop_square) == square_color(promotion_square) {
+        return false;
+    }
+
+    board
+        .king_square(weak)
+        .is_some_and(|king| distance(king, promotion_square) <= 1)
+}
+
+/// Returns how much of the score of `strong`, the side ahead, should be kept, out of
+/// [SCALE_NORMAL]
+pub fn scale_factor(board: &Board, strong: PieceColor) -> i32 {
+    let weak = strong.opposite();
+    let strong_pawns = count(board, strong, PieceType::Pawn);
+    let strong_material = non_pawn_material(board, strong);
+    let weak_material = non_pawn_material(board, weak);
+
+    if strong_pawns == 0 {
+        // a lone minor piece can't mate, and two knights can't force it
+        if strong_material <= piece_value(PieceType::Bishop) {
+            return SCALE_DRAW;
+        }
+        if only_has(board, strong, PieceType::Knight)
+            && count(board, strong, PieceType::Knight) == 2
+            && count(board, weak, PieceType::Pawn) == 0
+        {
+            return SCALE_DRAW;
+        }
+        if strong_material - weak_material <= piece_value(PieceType::Bishop) {
+            return SCALE_MINOR_UP;
+        }
+    }
+
+    if is_wrong_bishop_draw(board, strong) {
+        return SCALE_DRAW;
+    }
+
+    // one bishop each on different colors and nothing else but pawns
+    let strong_bishop = board.pieces(Piece::new(strong, PieceType::Bishop));
+    let weak_bishop = board.pieces(Piece::new(weak, PieceType::Bishop));
+    if strong_material == piece_value(PieceType::Bishop)
+        && weak_material == piece_value(PieceType::Bishop)
+        && strong_bishop.count_ones() == 1
+        && weak_bishop.count_ones() == 1
+        && square_color(strong_bishop.trailing_zeros() as usize)
+            != square_color(weak_bishop.trailing_zeros() as usize)
+    {
+        return SCALE_OPPOSITE_BISHOPS;
+    }
+
+    SCALE_NORMAL
+}
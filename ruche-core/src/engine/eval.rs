@@ -0,0 +1,274 @@
+//! Static evaluation: material and piece-square tables, interpolated between the middlegame and
+//! the endgame by the material left on the board, plus pawn structure, rook files, king safety
+//! and mobility. Scores are in centipawns from white's point of view
+//!
+//! The tables are written from white's side as a board is printed, a8 first and h1 last
+
+use super::endgame;
+use super::params::Params;
+use super::pawns;
+use crate::board::{Board, Piece, PieceColor, PieceType, Variant};
+
+/// Phase of the starting position, nothing but kings and pawns being 0
+const MAX_PHASE: i32 = 24;
+
+#[rustfmt::skip]
+const PAWN_MG: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     10,  10,  20,  30,  30,  20,  10,  10,
+      5,   5,  10,  25,  25,  10,   5,   5,
+      0,   0,   0,  20,  20,   0,   0,   0,
+      5,  -5, -10,   0,   0, -10,  -5,   5,
+      5,  10,  10, -20, -20,  10,  10,   5,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+/// pawns are worth pushing in the endgame, wherever they stand
+#[rustfmt::skip]
+const PAWN_EG: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+     80,  80,  80,  80,  80,  80,  80,  80,
+     50,  50,  50,  50,  50,  50,  50,  50,
+     30,  30,  30,  30,  30,  30,  30,  30,
+     20,  20,  20,  20,  20,  20,  20,  20,
+     10,  10,  10,  10,  10,  10,  10,  10,
+      0,   0,   0,   0,   0,   0,   0,   0,
+      0,   0,   0,   0,   0,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+    -50, -40, -30, -30, -30, -30, -40, -50,
+    -40, -20,   0,   0,   0,   0, -20, -40,
+    -30,   0,  10,  15,  15,  10,   0, -30,
+    -30,   5,  15,  20,  20,  15,   5, -30,
+    -30,   0,  15,  20,  20,  15,   0, -30,
+    -30,   5,  10,  15,  15,  10,   5, -30,
+    -40, -20,   0,   5,   5,   0, -20, -40,
+    -50, -40, -30, -30, -30, -30, -40, -50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+    -20, -10, -10, -10, -10, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,  10,  10,   5,   0, -10,
+    -10,   5,   5,  10,  10,   5,   5, -10,
+    -10,   0,  10,  10,  10,  10,   0, -10,
+    -10,  10,  10,  10,  10,  10,  10, -10,
+    -10,   5,   0,   0,   0,   0,   5, -10,
+    -20, -10, -10, -10, -10, -10, -10, -20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+      0,   0,   0,   0,   0,   0,   0,   0,
+      5,  10,  10,  10,  10,  10,  10,   5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+     -5,   0,   0,   0,   0,   0,   0,  -5,
+      0,   0,   0,   5,   5,   0,   0,   0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+    -10,   0,   0,   0,   0,   0,   0, -10,
+    -10,   0,   5,   5,   5,   5,   0, -10,
+     -5,   0,   5,   5,   5,   5,   0,  -5,
+      0,   0,   5,   5,   5,   5,   0,  -5,
+    -10,   5,   5,   5,   5,   5,   0, -10,
+    -10,   0,   5,   0,   0,   0,   0, -10,
+    -20, -10, -10,  -5,  -5, -10, -10, -20,
+];
+
+/// the king hides behind its pawns while there are pieces to attack it
+#[rustfmt::skip]
+const KING_MG: [i32; 64] = [
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -30, -40, -40, -50, -50, -40, -40, -30,
+    -20, -30, -30, -40, -40, -30, -30, -20,
+    -10, -20, -20, -20, -20, -20, -20, -10,
+     20,  20,   0,   0,   0,   0,  20,  20,
+     20,  30,  10,   0,   0,  10,  30,  20,
+];
+
+/// and comes to the center once they are gone
+#[rustfmt::skip]
+const KING_EG: [i32; 64] = [
+    -50, -40, -30, -20, -20, -30, -40, -50,
+    -30, -20, -10,   0,   0, -10, -20, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  30,  40,  40,  30, -10, -30,
+    -30, -10,  20,  30,  30,  20, -10, -30,
+    -30, -30,   0,   0,   0,   0, -30, -30,
+    -50, -30, -30, -30, -30, -30, -30, -50,
+];
+
+/// Middlegame and endgame tables of a piece
+fn tables(piece_type: PieceType) -> (&'static [i32; 64], &'static [i32; 64]) {
+    match piece_type {
+        PieceType::Pawn => (&PAWN_MG, &PAWN_EG),
+        PieceType::Knight => (&KNIGHT, &KNIGHT),
+        PieceType::Bishop => (&BISHOP, &BISHOP),
+        PieceType::Rook => (&ROOK, &ROOK),
+        PieceType::Queen => (&QUEEN, &QUEEN),
+        PieceType::King | PieceType::None => (&KING_MG, &KING_EG),
+    }
+}
+
+/// Index in the tables of the square `idx` for a piece of `color`, black's being mirrored
+fn table_index(idx: usize, color: PieceColor) -> usize {
+    let (x, y) = (idx % 8, idx / 8);
+    match color {
+        PieceColor::White => (7 - y) * 8 + 7 - x,
+        PieceColor::Black => y * 8 + 7 - x,
+    }
+}
+
+fn material_value(piece_type: PieceType, params: &Params) -> i32 {
+    match piece_type {
+        PieceType::Pawn => params.pawn_value,
+        PieceType::Knight => params.knight_value,
+        PieceType::Bishop => params.bishop_value,
+        PieceType::Rook => params.rook_value,
+        PieceType::Queen => params.queen_value,
+        PieceType::King | PieceType::None => 0,
+    }
+}
+
+/// How much of the middlegame is left, from [MAX_PHASE] with every piece on the board down to 0
+fn phase(board: &Board) -> i32 {
+    let weight = |piece_type| match piece_type {
+        PieceType::Knight | PieceType::Bishop => 1,
+        PieceType::Rook => 2,
+        PieceType::Queen => 4,
+        _ => 0,
+    };
+    let phase = (0..64)
+        .map(|idx| weight(board.get_piece_at_index(idx).get_type()))
+        .sum::<i32>();
+    phase.min(MAX_PHASE)
+}
+
+/// Own pawns sheltering the king of `color` on the two ranks in front of it, and enemy moves
+/// reaching the squares around it
+fn king_safety(board: &Board, color: PieceColor, params: &Params) -> i32 {
+    let Some(king) = board.king_square(color) else {
+        return 0;
+    };
+    let (x, y) = ((king % 8) as isize, (king / 8) as isize);
+    let forward = match color {
+        PieceColor::White => 1,
+        PieceColor::Black => -1,
+    };
+    let pawn = Piece::new(color, PieceType::Pawn);
+
+    let mut shield = 0;
+    for dx in -1..=1 {
+        for step in 1..=2 {
+            let (sx, sy) = (x + dx, y + forward * step);
+            if (0..8).contains(&sx)
+                && (0..8).contains(&sy)
+                && board.get_piece_at_index((sy * 8 + sx) as usize) == pawn
+            {
+                shield += 1;
+            }
+        }
+    }
+
+    let zone = |square: usize| {
+        let (zx, zy) = ((square % 8) as isize, (square / 8) as isize);
+        (zx - x).abs() <= 1 && (zy - y).abs() <= 1
+    };
+    let attacks = board
+        .moves_of(color.opposite())
+        .iter()
+        .filter(|m| zone(m.to) && m.dropped_piece().is_none())
+        .count() as i32;
+
+    shield * params.shield_bonus - attacks * params.king_attack_penalty
+}
+
+/// Moves of the knights, bishops, rooks and queens of `color`
+fn mobility(board: &Board, color: PieceColor) -> i32 {
+    board
+        .moves_of(color)
+        .iter()
+        .filter(|m| {
+            m.dropped_piece().is_none()
+                && !matches!(
+                    board.get_piece_at_index(m.from).get_type(),
+                    PieceType::Pawn | PieceType::King
+                )
+        })
+        .count() as i32
+}
+
+/// Evaluates `board` with the default parameters
+pub fn evaluate(board: &Board) -> i32 {
+    let params = Params::default();
+    evaluate_with(board, &params, pawns::evaluate_pawns(board, &params))
+}
+
+/// Score of an antichess position, where the side with fewer pieces left is closer to winning
+/// whatever they are worth
+fn antichess(board: &Board) -> i32 {
+    let count = |color| board.occupancy(color).count_ones() as i32;
+    (count(PieceColor::Black) - count(PieceColor::White)) * 100
+}
+
+/// Evaluates `board` with `params`, `pawn_score` being the pawn structure score so the search
+/// can take it from its cache
+pub fn evaluate_with(board: &Board, params: &Params, pawn_score: i32) -> i32 {
+    if board.variant() == Variant::Antichess {
+        return antichess(board);
+    }
+    let phase = phase(board);
+    let (mut mg, mut eg) = (0, 0);
+    for idx in 0..64 {
+        let piece = board.get_piece_at_index(idx);
+        if piece.is_none() {
+            continue;
+        }
+        let sign = match piece.get_color() {
+            PieceColor::White => 1,
+            PieceColor::Black => -1,
+        };
+        let (mg_table, eg_table) = tables(piece.get_type());
+        let square = table_index(idx, piece.get_color());
+        let value = material_value(piece.get_type(), params);
+        mg += sign * (value + mg_table[square]);
+        eg += sign * (value + eg_table[square]);
+    }
+    // in crazyhouse the pieces in hand are as good as on the board
+    for (color, sign) in [(PieceColor::White, 1), (PieceColor::Black, -1)] {
+        for (piece_type, count) in board.pocket(color).pieces() {
+            let value = sign * count as i32 * material_value(piece_type, params);
+            mg += value;
+            eg += value;
+        }
+    }
+    // king safety only matters while there is something to attack the king with
+    mg += king_safety(board, PieceColor::White, params)
+        - king_safety(board, PieceColor::Black, params);
+
+    let score = (mg * phase + eg * (MAX_PHASE - phase)) / MAX_PHASE
+        + pawn_score
+        + pawns::evaluate_rook_files(board, params)
+        + params.mobility_bonus
+            * (mobility(board, PieceColor::White) - mobility(board, PieceColor::Black));
+
+    let strong = if score >= 0 {
+        PieceColor::White
+    } else {
+        PieceColor::Black
+    };
+    score * endgame::scale_factor(board, strong) / endgame::SCALE_NORMAL
+}
@@ -0,0 +1,118 @@
+mod difficulty;
+mod endgame;
+mod eval;
+mod params;
+mod pawns;
+mod search;
+#[cfg(test)]
+mod search_tests;
+mod see;
+mod tt;
+mod tune;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::board::{Board, Move};
+
+pub use difficulty::Difficulty;
+pub use eval::evaluate;
+pub use params::{params_path, Params};
+pub use pawns::passed_pawns;
+pub use search::{SearchInfo, SearchLimits, Searcher, MAX_THREADS};
+pub use tune::{random_opening, tune, SpsaConfig};
+
+/// Messages sent by a search running in the background
+#[derive(Debug, Clone)]
+pub enum EngineMessage {
+    /// progress report after each completed depth
+    Info(SearchInfo),
+    /// the search is over, `None` if the side to move has no legal move
+    BestMove(Option<Move>),
+}
+
+/// Handle to a search running on its own thread so the render loop never blocks
+pub struct EngineHandle {
+    rx: Receiver<EngineMessage>,
+    stop: Arc<AtomicBool>,
+}
+
+impl EngineHandle {
+    /// Starts searching `board` in the background
+    pub fn spawn(board: Board, limits: SearchLimits, params: Params) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        std::thread::spawn(move || {
+            let mut searcher = search::Searcher::new(thread_stop, params);
+            let best = searcher.search(&board, limits, |info| {
+                let _ = tx.send(EngineMessage::Info(info.clone()));
+            });
+            let _ = tx.send(EngineMessage::BestMove(best));
+        });
+
+        Self { rx, stop }
+    }
+
+    /// Starts looking for a move to play in `board` at `difficulty`, thinking for at most
+    /// `think_time`, or until stopped, on `threads` threads. Progress is reported for the best
+    /// line only
+    pub fn spawn_opponent(
+        board: Board,
+        think_time: Option<Duration>,
+        difficulty: Difficulty,
+        threads: usize,
+        params: Params,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+
+        std::thread::spawn(move || {
+            let mut searcher = search::Searcher::new(thread_stop, params);
+            searcher.set_eval_noise(difficulty.eval_noise(), seed);
+            // the lines of the deepest depth reached
+            let mut lines: Vec<SearchInfo> = vec![];
+            let limits = difficulty.limits(think_time).with_threads(threads);
+            let best = searcher.search(&board, limits, |info| {
+                if lines.first().is_some_and(|l| l.depth != info.depth) {
+                    lines.clear();
+                }
+                lines.push(info.clone());
+                if info.multipv == 1 {
+                    let _ = tx.send(EngineMessage::Info(info.clone()));
+                }
+            });
+            let best = difficulty.pick(&lines, seed.rotate_left(32)).or(best);
+            let _ = tx.send(EngineMessage::BestMove(best));
+        });
+
+        Self { rx, stop }
+    }
+
+    /// Returns the next message from the search without blocking
+    pub fn poll(&self) -> Option<EngineMessage> {
+        match self.rx.try_recv() {
+            Ok(msg) => Some(msg),
+            Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => None,
+        }
+    }
+
+    /// Asks the search to stop as soon as possible
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for EngineHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,237 @@
+//! Search and evaluation constants, gathered in one registry so they can be loaded from a file
+//! and tuned. The file has one `name = value` line per parameter, `#` starting a comment
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+/// Parameters file loaded when `RUCHE_PARAMS` isn't set
+const DEFAULT_PARAMS: &str = "params.txt";
+
+/// Number of parameters in the registry
+pub const PARAM_COUNT: usize = 21;
+
+/// Returns the path of the parameters file, `RUCHE_PARAMS` or `params.txt` in the working
+/// directory
+pub fn params_path() -> PathBuf {
+    std::env::var("RUCHE_PARAMS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_PARAMS))
+}
+
+/// Name and allowed range of a parameter
+#[derive(Debug, Clone, Copy)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub min: i32,
+    pub max: i32,
+}
+
+const fn spec(name: &'static str, min: i32, max: i32) -> ParamSpec {
+    ParamSpec { name, min, max }
+}
+
+/// Every parameter, in the order of [Params::slots]
+pub const SPECS: [ParamSpec; PARAM_COUNT] = [
+    spec("pawn_value", 50, 200),
+    spec("knight_value", 200, 500),
+    spec("bishop_value", 200, 500),
+    spec("rook_value", 350, 750),
+    spec("queen_value", 700, 1300),
+    spec("doubled_penalty", 0, 60),
+    spec("isolated_penalty", 0, 60),
+    spec("chain_bonus", 0, 40),
+    spec("passed_bonus_2", 0, 60),
+    spec("passed_bonus_3", 0, 80),
+    spec("passed_bonus_4", 0, 100),
+    spec("passed_bonus_5", 0, 150),
+    spec("passed_bonus_6", 0, 200),
+    spec("passed_bonus_7", 0, 300),
+    spec("rook_open_file_bonus", 0, 60),
+    spec("rook_half_open_file_bonus", 0, 40),
+    spec("shield_bonus", 0, 40),
+    spec("king_attack_penalty", 0, 30),
+    spec("mobility_bonus", 0, 10),
+    spec("max_extensions", 0, 32),
+    spec("delta_margin", 0, 500),
+];
+
+/// Why a parameters file couldn't be loaded
+#[derive(Debug)]
+pub enum ParamsError {
+    Io(std::io::Error),
+    /// A line that isn't `name = value`
+    InvalidLine(String),
+    /// A name that isn't in the registry
+    UnknownParameter(String),
+}
+
+impl Display for ParamsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParamsError::Io(e) => write!(f, "{}", e),
+            ParamsError::InvalidLine(line) => write!(f, "expected `name = value`, got {:?}", line),
+            ParamsError::UnknownParameter(name) => write!(f, "unknown parameter {:?}", name),
+        }
+    }
+}
+
+impl From<std::io::Error> for ParamsError {
+    fn from(value: std::io::Error) -> Self {
+        ParamsError::Io(value)
+    }
+}
+
+/// Values of the search and evaluation constants, scores being in centipawns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub pawn_value: i32,
+    pub knight_value: i32,
+    pub bishop_value: i32,
+    pub rook_value: i32,
+    pub queen_value: i32,
+    pub doubled_penalty: i32,
+    pub isolated_penalty: i32,
+    /// bonus of a pawn defended by another pawn
+    pub chain_bonus: i32,
+    /// bonus of a passed pawn by rank, from its own side's point of view
+    pub passed_bonus: [i32; 8],
+    /// bonus of a rook on a file without pawns
+    pub rook_open_file_bonus: i32,
+    /// bonus of a rook on a file with only enemy pawns
+    pub rook_half_open_file_bonus: i32,
+    /// bonus of each pawn sheltering its king, in the middlegame
+    pub shield_bonus: i32,
+    /// penalty of each enemy move reaching the squares around the king, in the middlegame
+    pub king_attack_penalty: i32,
+    /// bonus of each move of a knight, bishop, rook or queen
+    pub mobility_bonus: i32,
+    /// most plies a single line can be extended by
+    pub max_extensions: i32,
+    /// margin over the captured piece's value under which quiescence skips a capture
+    pub delta_margin: i32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            pawn_value: 100,
+            knight_value: 320,
+            bishop_value: 330,
+            rook_value: 500,
+            queen_value: 900,
+            doubled_penalty: 15,
+            isolated_penalty: 12,
+            chain_bonus: 5,
+            passed_bonus: [0, 5, 10, 20, 35, 60, 100, 0],
+            rook_open_file_bonus: 20,
+            rook_half_open_file_bonus: 10,
+            shield_bonus: 10,
+            king_attack_penalty: 5,
+            mobility_bonus: 2,
+            max_extensions: 16,
+            delta_margin: 200,
+        }
+    }
+}
+
+impl Params {
+    /// Every tunable value, in the order of [SPECS]. A pawn can't be passed on its first or
+    /// last rank so those bonuses aren't in the registry
+    fn slots(&mut self) -> [&mut i32; PARAM_COUNT] {
+        let [_, passed_2, passed_3, passed_4, passed_5, passed_6, passed_7, _] =
+            &mut self.passed_bonus;
+        [
+            &mut self.pawn_value,
+            &mut self.knight_value,
+            &mut self.bishop_value,
+            &mut self.rook_value,
+            &mut self.queen_value,
+            &mut self.doubled_penalty,
+            &mut self.isolated_penalty,
+            &mut self.chain_bonus,
+            passed_2,
+            passed_3,
+            passed_4,
+            passed_5,
+            passed_6,
+            passed_7,
+            &mut self.rook_open_file_bonus,
+            &mut self.rook_half_open_file_bonus,
+            &mut self.shield_bonus,
+            &mut self.king_attack_penalty,
+            &mut self.mobility_bonus,
+            &mut self.max_extensions,
+            &mut self.delta_margin,
+        ]
+    }
+
+    /// Returns the values in the order of [SPECS]
+    pub fn values(&self) -> [i32; PARAM_COUNT] {
+        self.clone().slots().map(|value| *value)
+    }
+
+    /// Sets the values in the order of [SPECS], each clamped to its range
+    pub fn set_values(&mut self, values: &[i32; PARAM_COUNT]) {
+        for ((slot, value), spec) in self.slots().into_iter().zip(values).zip(SPECS.iter()) {
+            *slot = (*value).clamp(spec.min, spec.max);
+        }
+    }
+
+    /// Reads the parameters at `path`, the ones missing from the file keep their default
+    pub fn load(path: &Path) -> Result<Self, ParamsError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, ParamsError> {
+        let mut params = Self::default();
+        let mut values = params.values();
+
+        for line in contents.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || ParamsError::InvalidLine(line.to_string());
+            let (name, value) = line.split_once('=').ok_or_else(invalid)?;
+            let (name, value) = (name.trim(), value.trim());
+            let idx = SPECS
+                .iter()
+                .position(|spec| spec.name == name)
+                .ok_or_else(|| ParamsError::UnknownParameter(name.to_string()))?;
+            values[idx] = value.parse().map_err(|_| invalid())?;
+        }
+
+        params.set_values(&values);
+        Ok(params)
+    }
+
+    /// Reads the parameters file if there is one, falling back to the defaults
+    pub fn load_or_default(path: &Path) -> Self {
+        match Self::load(path) {
+            Ok(params) => {
+                tracing::info!("Loaded engine parameters {:?}", path);
+                params
+            }
+            Err(ParamsError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                tracing::warn!("Ignoring engine parameters {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes every parameter to `path`
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+/// Writes the parameters file contents
+impl Display for Params {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (spec, value) in SPECS.iter().zip(self.values()) {
+            writeln!(f, "{} = {}", spec.name, value)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+//! Pawn structure evaluation: doubled, isolated and passed pawns, pawn chains and rooks on
+//! half-open files. The pawns change rarely compared to the other pieces, so their score is
+//! cached by a pawn-only hash
+
+use super::params::Params;
+use crate::board::{Board, Piece, PieceColor, PieceType};
+use crate::zobrist;
+
+/// Number of entries of the pawn hash table
+const TABLE_SIZE: usize = 1 << 14;
+
+/// Squares of the file `x` (0 is the h-file)
+const fn file_mask(x: usize) -> u64 {
+    0x0101_0101_0101_0101 << x
+}
+
+/// Squares of the files next to `x`
+const fn adjacent_files_mask(x: usize) -> u64 {
+    let mut mask = 0;
+    if x > 0 {
+        mask |= file_mask(x - 1);
+    }
+    if x < 7 {
+        mask |= file_mask(x + 1);
+    }
+    mask
+}
+
+/// Squares of the ranks in front of `y` from `color`'s point of view
+const fn ranks_ahead_mask(y: usize, color: PieceColor) -> u64 {
+    match color {
+        PieceColor::White if y < 7 => u64::MAX << ((y + 1) * 8),
+        PieceColor::Black if y > 0 => u64::MAX >> ((8 - y) * 8),
+        _ => 0,
+    }
+}
+
+/// Iterates over the squares set in `bitboard`
+fn squares(mut bitboard: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        if bitboard == 0 {
+            return None;
+        }
+        let idx = bitboard.trailing_zeros() as usize;
+        bitboard &= bitboard - 1;
+        Some(idx)
+    })
+}
+
+fn pawns(board: &Board, color: PieceColor) -> u64 {
+    board.pieces(Piece::new(color, PieceType::Pawn))
+}
+
+/// Returns true if no enemy pawn can stop the pawn of `color` on `idx`
+fn is_passed(idx: usize, color: PieceColor, enemy_pawns: u64) -> bool {
+    let (x, y) = (idx % 8, idx / 8);
+    let front_span = (file_mask(x) | adjacent_files_mask(x)) & ranks_ahead_mask(y, color);
+    enemy_pawns & front_span == 0
+}
+
+/// Returns the passed pawns of both sides
+pub fn passed_pawns(board: &Board) -> u64 {
+    let white = pawns(board, PieceColor::White);
+    let black = pawns(board, PieceColor::Black);
+    let white_passed = squares(white).filter(|idx| is_passed(*idx, PieceColor::White, black));
+    let black_passed = squares(black).filter(|idx| is_passed(*idx, PieceColor::Black, white));
+    white_passed
+        .chain(black_passed)
+        .fold(0, |passed, idx| passed | 1 << idx)
+}
+
+/// Pawn structure score of one side
+fn side_score(pawns: u64, enemy_pawns: u64, color: PieceColor, params: &Params) -> i32 {
+    let mut score = 0;
+
+    for x in 0..8 {
+        let on_file = (pawns & file_mask(x)).count_ones() as i32;
+        if on_file > 1 {
+            score -= params.doubled_penalty * (on_file - 1);
+        }
+    }
+
+    for idx in squares(pawns) {
+        let (x, y) = (idx % 8, idx / 8);
+        if pawns & adjacent_files_mask(x) == 0 {
+            score -= params.isolated_penalty;
+        }
+
+        let behind = match color {
+            PieceColor::White if y > 0 => 0xff << ((y - 1) * 8),
+            PieceColor::Black if y < 7 => 0xff << ((y + 1) * 8),
+            _ => 0,
+        };
+        if pawns & adjacent_files_mask(x) & behind != 0 {
+            score += params.chain_bonus;
+        }
+
+        if is_passed(idx, color, enemy_pawns) {
+            let rank = match color {
+                PieceColor::White => y,
+                PieceColor::Black => 7 - y,
+            };
+            score += params.passed_bonus[rank];
+        }
+    }
+    score
+}
+
+/// Pawn structure score from white's point of view
+pub fn evaluate_pawns(board: &Board, params: &Params) -> i32 {
+    let white = pawns(board, PieceColor::White);
+    let black = pawns(board, PieceColor::Black);
+    side_score(white, black, PieceColor::White, params)
+        - side_score(black, white, PieceColor::Black, params)
+}
+
+/// Bonus of the rooks on open and half-open files from white's point of view. Depends on the
+/// rooks as well, so it isn't cached with the pawn structure
+pub fn evaluate_rook_files(board: &Board, params: &Params) -> i32 {
+    let all_pawns = pawns(board, PieceColor::White) | pawns(board, PieceColor::Black);
+    let mut score = 0;
+    for color in [PieceColor::White, PieceColor::Black] {
+        let own_pawns = pawns(board, color);
+        let bonus = squares(board.pieces(Piece::new(color, PieceType::Rook)))
+            .map(|idx| file_mask(idx % 8))
+            .map(|file| {
+                if all_pawns & file == 0 {
+                    params.rook_open_file_bonus
+                } else if own_pawns & file == 0 {
+                    params.rook_half_open_file_bonus
+                } else {
+                    0
+                }
+            })
+            .sum::<i32>();
+        match color {
+            PieceColor::White => score += bonus,
+            PieceColor::Black => score -= bonus,
+        }
+    }
+    score
+}
+
+/// Zobrist hash of the pawns alone
+fn pawn_hash(board: &Board) -> u64 {
+    let mut hash = 0;
+    for color in [PieceColor::White, PieceColor::Black] {
+        let pawn = Piece::new(color, PieceType::Pawn);
+        for idx in squares(board.pieces(pawn)) {
+            hash ^= zobrist::piece_key(pawn, idx);
+        }
+    }
+    hash
+}
+
+/// Cache of pawn structure scores indexed by the pawn hash, replacing on collision
+pub struct PawnTable {
+    entries: Vec<Option<(u64, i32)>>,
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PawnTable {
+    pub fn new() -> Self {
+        Self {
+            entries: vec![None; TABLE_SIZE],
+        }
+    }
+
+    /// Pawn structure score from white's point of view, computed only on a cache miss. The
+    /// table must always be probed with the same `params`
+    pub fn probe(&mut self, board: &Board, params: &Params) -> i32 {
+        let key = pawn_hash(board);
+        let slot = &mut self.entries[key as usize % TABLE_SIZE];
+        match slot {
+            Some((entry_key, score)) if *entry_key == key => *score,
+            _ => {
+                let score = evaluate_pawns(board, params);
+                *slot = Some((key, score));
+                score
+            }
+        }
+    }
+}
@@ -0,0 +1,668 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::eval;
+use super::params::Params;
+use super::pawns::{self, PawnTable};
+use super::see::see;
+use super::tt::{Bound, Entry, TranspositionTable, TtStats};
+use crate::board::{Board, Move, MoveType, PieceColor, PieceType};
+
+/// Score of a checkmate at the root, mates further away score lower
+pub const MATE: i32 = 100_000;
+const INFINITY: i32 = MATE + 1;
+/// Deepest iteration of a search limited by time only
+const MAX_DEPTH: u32 = 64;
+/// Time kept on the clock when it runs low, for the overhead of answering
+const CLOCK_SAFETY_MARGIN: Duration = Duration::from_millis(50);
+/// Nodes searched between two looks at the clock
+const TIME_CHECK_NODES: u64 = 1024;
+/// Most threads a search may run on
+pub const MAX_THREADS: usize = 64;
+/// Shallowest depth the stored best move is checked for being singular at
+const SINGULAR_DEPTH: u32 = 6;
+/// Centipawns per ply of depth the other moves must stay under the stored score by
+const SINGULAR_MARGIN: i32 = 2;
+
+/// Limits of a single search, it stops at whichever is reached first
+#[derive(Debug, Clone, Copy)]
+pub struct SearchLimits {
+    pub depth: u32,
+    /// time budget, the search returns the best move of the last completed depth once spent
+    pub movetime: Option<Duration>,
+    /// number of best lines reported at each depth, each searched without the moves leading the
+    /// ones before it
+    pub multipv: usize,
+    /// threads searching the position together, sharing what they found through the
+    /// transposition table
+    pub threads: usize,
+}
+
+impl SearchLimits {
+    /// Searches exactly `depth` plies however long it takes
+    pub fn depth(depth: u32) -> Self {
+        Self {
+            depth,
+            movetime: None,
+            multipv: 1,
+            threads: 1,
+        }
+    }
+
+    /// Searches as deep as possible in `movetime`
+    pub fn movetime(movetime: Duration) -> Self {
+        Self {
+            depth: MAX_DEPTH,
+            movetime: Some(movetime),
+            multipv: 1,
+            threads: 1,
+        }
+    }
+
+    /// Searches until stopped
+    pub fn infinite() -> Self {
+        Self {
+            depth: MAX_DEPTH,
+            movetime: None,
+            multipv: 1,
+            threads: 1,
+        }
+    }
+
+    /// Reports the `lines` best lines instead of the best one only
+    pub fn with_multipv(self, lines: usize) -> Self {
+        Self {
+            multipv: lines.max(1),
+            ..self
+        }
+    }
+
+    /// Searches on `threads` threads instead of one
+    pub fn with_threads(self, threads: usize) -> Self {
+        Self {
+            threads: threads.clamp(1, MAX_THREADS),
+            ..self
+        }
+    }
+
+    /// Spends a share of the `remaining` clock time on the move, the clock gaining `increment`
+    /// after each move, assuming `moves_to_go` moves until the next time control or 30 more
+    pub fn for_clock(remaining: Duration, increment: Duration, moves_to_go: Option<u32>) -> Self {
+        let moves_to_go = moves_to_go.unwrap_or(30).max(1);
+        // keep something for the moves still to come, and for the time it takes to answer
+        let budget = remaining / moves_to_go + increment / 2;
+        let budget = budget.min(remaining.saturating_sub(CLOCK_SAFETY_MARGIN));
+        Self::movetime(budget.max(Duration::from_millis(1)))
+    }
+}
+
+impl Default for SearchLimits {
+    fn default() -> Self {
+        Self::movetime(Duration::from_secs(2))
+    }
+}
+
+/// Progress of a search, reported after each completed depth
+#[derive(Debug, Clone, Default)]
+pub struct SearchInfo {
+    pub depth: u32,
+    /// rank of the line among the best lines, 1 for the best
+    pub multipv: usize,
+    pub nodes: u64,
+    /// nodes per second
+    pub nps: u64,
+    /// score in centipawns from the side to move's point of view
+    pub score: i32,
+    /// principal variation, the best line found
+    pub pv: Vec<Move>,
+    pub elapsed: Duration,
+    pub tt: TtStats,
+}
+
+impl SearchInfo {
+    /// Moves until mate, negative when the side to move gets mated, if the score is a mate
+    pub fn mate_in(&self) -> Option<i32> {
+        if self.score.abs() <= MATE - 1000 {
+            return None;
+        }
+        let moves = (MATE - self.score.abs() + 1) / 2;
+        Some(if self.score > 0 { moves } else { -moves })
+    }
+
+    /// The same report with the score seen by white, the search having `turn` to move
+    pub fn seen_by_white(&self, turn: PieceColor) -> SearchInfo {
+        let score = match turn {
+            PieceColor::White => self.score,
+            PieceColor::Black => -self.score,
+        };
+        SearchInfo {
+            score,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the score as shown to the user e.g. `+0.35` or `#3`
+    pub fn score_string(&self) -> String {
+        match self.mate_in() {
+            Some(moves) => format!("#{}", moves),
+            None => format!("{:+.2}", self.score as f32 / 100.0),
+        }
+    }
+
+    /// Returns the principal variation as space separated moves
+    pub fn pv_string(&self) -> String {
+        self.pv
+            .iter()
+            .map(|m| m.to_string())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Returns the value of a piece in centipawns
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 100,
+        PieceType::Knight => 320,
+        PieceType::Bishop => 330,
+        PieceType::Rook => 500,
+        PieceType::Queen => 900,
+        PieceType::King | PieceType::None => 0,
+    }
+}
+
+/// Plays `mv` on a copy of `board`, returning `None` if it isn't legal, see [Board::is_legal]
+fn play(board: &Board, mv: &Move) -> Option<Board> {
+    // a castle also needs the king out of check and the square it crosses safe, other moves only
+    // the king safe once played, so only castles are checked by the board before playing them
+    let castles = matches!(
+        mv.move_type,
+        MoveType::CastelKingSide | MoveType::CastelQueenSide
+    );
+    if castles && !board.is_legal(mv) {
+        return None;
+    }
+    let mover = board.get_turn();
+    let mut child = board.clone();
+    child.play(mv).ok()?;
+    if child.is_in_check(mover) {
+        return None;
+    }
+    Some(child)
+}
+
+/// Score for the side to move of a game the rules of its variant ended, a win or loss as far
+/// away as a mate `ply` plies from the root
+fn variant_score(board: &Board, ply: i32) -> Option<i32> {
+    let (winner, _) = board.result()?;
+    Some(if winner == board.get_turn() {
+        MATE - ply
+    } else {
+        -MATE + ply
+    })
+}
+
+/// Returns true if `mv`, played to reach `child`, pushes a passed pawn to the sixth or seventh
+/// rank where it is close enough to promoting that the search shouldn't stop short of it
+fn is_dangerous_pawn_push(board: &Board, child: &Board, mv: &Move) -> bool {
+    let piece = board.get_piece_at_index(mv.from);
+    if piece.get_type() != PieceType::Pawn || mv.promotion_piece().is_some() {
+        return false;
+    }
+    let rank = match piece.get_color() {
+        PieceColor::White => mv.to / 8,
+        PieceColor::Black => 7 - mv.to / 8,
+    };
+    rank >= 5 && pawns::passed_pawns(child) & (1 << mv.to) != 0
+}
+
+/// Orders captures first, most valuable victim first
+fn order_moves(board: &Board, moves: &mut [Move]) {
+    moves.sort_by_key(|m| -piece_value(board.get_piece_at_index(m.to).get_type()));
+}
+
+/// Alpha-beta searcher, runs on the engine thread
+pub struct Searcher {
+    stop: Arc<AtomicBool>,
+    nodes: u64,
+    params: Params,
+    pawns: PawnTable,
+    tt: Arc<TranspositionTable>,
+    /// nodes searched by the helper threads of the running search
+    helper_nodes: Arc<AtomicU64>,
+    /// when the time budget of the current search runs out
+    deadline: Option<Instant>,
+    /// set once the deadline is seen, so the clock isn't read again
+    timed_out: bool,
+    /// best move of the last completed depth, searched first at the root
+    root_best: Option<Move>,
+    /// plies the line being searched has been extended by, at most `params.max_extensions`
+    extended: i32,
+    /// root moves leading the better lines of a multi-PV search, skipped by the next ones
+    excluded: Vec<Move>,
+    /// move left out at the given ply while checking that it is singular, the only good one
+    singular_skip: Option<(i32, Move)>,
+    /// most centipawns added to or taken from an evaluation, 0 for exact evaluations
+    eval_noise: i32,
+    /// mixed into the hash of a position to draw its noise, so it is the same each time the
+    /// position is evaluated
+    noise_seed: u64,
+}
+
+impl Searcher {
+    pub fn new(stop: Arc<AtomicBool>, params: Params) -> Self {
+        Self {
+            stop,
+            nodes: 0,
+            params,
+            pawns: PawnTable::new(),
+            tt: Arc::new(TranspositionTable::new()),
+            helper_nodes: Arc::new(AtomicU64::new(0)),
+            deadline: None,
+            timed_out: false,
+            root_best: None,
+            extended: 0,
+            excluded: vec![],
+            singular_skip: None,
+            eval_noise: 0,
+            noise_seed: 0,
+        }
+    }
+
+    /// Adds up to `amplitude` centipawns either way to every evaluation to play weaker, `seed`
+    /// being any random number
+    pub fn set_eval_noise(&mut self, amplitude: i32, seed: u64) {
+        self.eval_noise = amplitude.max(0);
+        self.noise_seed = seed;
+    }
+
+    /// Noise of the evaluation of the position with `hash`
+    fn noise(&self, hash: u64) -> i32 {
+        if self.eval_noise == 0 {
+            return 0;
+        }
+        let mixed = (hash ^ self.noise_seed).wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 32;
+        (mixed % (2 * self.eval_noise as u64 + 1)) as i32 - self.eval_noise
+    }
+
+    /// Static evaluation from the side to move's point of view, plus the noise of weaker levels
+    fn evaluate(&mut self, board: &Board) -> i32 {
+        let pawn_score = self.pawns.probe(board, &self.params);
+        let score = eval::evaluate_with(board, &self.params, pawn_score);
+        let score = match board.get_turn() {
+            PieceColor::White => score,
+            PieceColor::Black => -score,
+        };
+        score + self.noise(board.zobrist_hash())
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if !self.timed_out && self.nodes.is_multiple_of(TIME_CHECK_NODES) {
+            self.timed_out = self.deadline.is_some_and(|d| Instant::now() >= d);
+        }
+        self.timed_out || self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Searches `board` depth by depth until the depth limit or the time budget is reached,
+    /// calling `on_info` for each line of each completed depth, and returns the best move of the
+    /// last completed depth.
+    ///
+    /// With more than one thread the extra ones search the same position without reporting
+    /// anything (Lazy SMP), their results reaching this thread through the shared
+    /// transposition table. They start at alternating depths so they don't all search the same
+    /// nodes at once
+    pub fn search<F>(&mut self, board: &Board, limits: SearchLimits, on_info: F) -> Option<Move>
+    where
+        F: FnMut(&SearchInfo),
+    {
+        self.tt.new_search();
+        self.helper_nodes.store(0, Ordering::Relaxed);
+        if limits.threads <= 1 {
+            return self.iterate(board, limits, on_info);
+        }
+
+        let helpers_stop = Arc::new(AtomicBool::new(false));
+        std::thread::scope(|scope| {
+            for i in 1..limits.threads {
+                let mut helper = self.helper(helpers_stop.clone());
+                scope.spawn(move || helper.help(board, 1 + i as u32 % 2));
+            }
+            let best = self.iterate(board, limits, on_info);
+            helpers_stop.store(true, Ordering::Relaxed);
+            best
+        })
+    }
+
+    /// A searcher sharing the transposition table of this one, stopped by `stop`
+    fn helper(&self, stop: Arc<AtomicBool>) -> Searcher {
+        Searcher {
+            stop,
+            nodes: 0,
+            params: self.params.clone(),
+            pawns: PawnTable::new(),
+            tt: self.tt.clone(),
+            helper_nodes: self.helper_nodes.clone(),
+            deadline: None,
+            timed_out: false,
+            root_best: None,
+            extended: 0,
+            excluded: vec![],
+            singular_skip: None,
+            eval_noise: self.eval_noise,
+            noise_seed: self.noise_seed,
+        }
+    }
+
+    /// Searches `board` deeper and deeper from `first_depth` until stopped, for the main thread
+    /// to find the results in the transposition table
+    fn help(&mut self, board: &Board, first_depth: u32) {
+        let mut counted = 0;
+        for depth in first_depth..=MAX_DEPTH {
+            let mut pv = vec![];
+            self.negamax(board, depth, -INFINITY, INFINITY, 0, &mut pv);
+            self.helper_nodes
+                .fetch_add(self.nodes - counted, Ordering::Relaxed);
+            counted = self.nodes;
+            if self.should_stop() {
+                break;
+            }
+            self.root_best = pv.first().cloned();
+        }
+    }
+
+    /// Iterative deepening of [Searcher::search] on the calling thread
+    fn iterate<F>(&mut self, board: &Board, limits: SearchLimits, mut on_info: F) -> Option<Move>
+    where
+        F: FnMut(&SearchInfo),
+    {
+        let start = Instant::now();
+        self.nodes = 0;
+        self.extended = 0;
+        self.deadline = limits.movetime.map(|t| start + t);
+        self.timed_out = false;
+        // something to play even if the search is stopped before depth 1 completes
+        let mut best = board.legal_moves().next();
+        self.root_best = None;
+
+        for depth in 1..=limits.depth.max(1) {
+            // the next depth takes longer than all the previous ones, don't start what can't end
+            if limits
+                .movetime
+                .is_some_and(|t| depth > 1 && start.elapsed() * 2 > t)
+            {
+                break;
+            }
+
+            self.excluded.clear();
+            let mut stopped = false;
+            for line in 1..=limits.multipv.max(1) {
+                let mut pv = vec![];
+                let score = self.negamax(board, depth, -INFINITY, INFINITY, 0, &mut pv);
+                if self.should_stop() {
+                    stopped = true;
+                    break;
+                }
+
+                if line == 1 {
+                    best = pv.first().cloned();
+                    self.root_best = best.clone();
+                }
+                // fewer legal moves than lines
+                let Some(first) = pv.first().cloned() else {
+                    break;
+                };
+
+                let elapsed = start.elapsed();
+                let nodes = self.nodes + self.helper_nodes.load(Ordering::Relaxed);
+                let info = SearchInfo {
+                    depth,
+                    multipv: line,
+                    nodes,
+                    nps: (nodes as f64 / elapsed.as_secs_f64().max(0.001)) as u64,
+                    score,
+                    pv,
+                    elapsed,
+                    tt: self.tt.stats(),
+                };
+                tracing::info!(
+                    "depth {} multipv {} nodes {} nps {} score {} pv {} tt hits {:.0}%",
+                    info.depth,
+                    info.multipv,
+                    info.nodes,
+                    info.nps,
+                    info.score_string(),
+                    info.pv_string(),
+                    info.tt.hit_rate * 100.0
+                );
+                on_info(&info);
+                self.excluded.push(first);
+            }
+
+            // no legal moves, deeper searches won't change that
+            if stopped || best.is_none() {
+                break;
+            }
+        }
+        best
+    }
+
+    fn negamax(
+        &mut self,
+        board: &Board,
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+        ply: i32,
+        pv: &mut Vec<Move>,
+    ) -> i32 {
+        // the rules of a variant ended the game, by the last move or the side to move being out of
+        // moves in antichess
+        if let Some(score) = variant_score(board, ply) {
+            return score;
+        }
+        if depth == 0 {
+            return self.quiescence(board, alpha, beta, ply);
+        }
+        self.nodes += 1;
+        if self.should_stop() {
+            return 0;
+        }
+
+        let key = board.zobrist_hash();
+        let original_alpha = alpha;
+        // the node searched without its singular move isn't the position the table knows of
+        let skip = match &self.singular_skip {
+            Some((at, mv)) if *at == ply => Some(mv.clone()),
+            _ => None,
+        };
+        let entry = self.tt.probe(key).filter(|_| skip.is_none());
+        let mut tt_move = None;
+        if let Some(entry) = &entry {
+            tt_move = entry.best.clone();
+            // the root always searches so it has a move to return
+            if ply > 0 && entry.depth >= depth {
+                let score = entry.score(ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut moves = board.get_moves_for_turn().to_vec();
+        order_moves(board, &mut moves);
+        // the best move of the last depth at the root, the stored best move below it
+        let first = if ply == 0 {
+            self.root_best.clone()
+        } else {
+            tt_move
+        };
+        if let Some(idx) = moves.iter().position(|m| Some(m) == first.as_ref()) {
+            moves[..=idx].rotate_right(1);
+        }
+
+        let singular = entry
+            .filter(|_| ply > 0 && self.extended < self.params.max_extensions)
+            .and_then(|entry| self.singular_move(board, depth, ply, &entry));
+
+        let mut legal_moves = 0;
+        let mut best_move = None;
+        for mv in moves.iter() {
+            if (ply == 0 && self.excluded.contains(mv)) || skip.as_ref() == Some(mv) {
+                continue;
+            }
+            let Some(child) = play(board, mv) else {
+                continue;
+            };
+            legal_moves += 1;
+
+            let extension = if self.extended < self.params.max_extensions
+                && (singular.as_ref() == Some(mv)
+                    || child.is_in_check(child.get_turn())
+                    || is_dangerous_pawn_push(board, &child, mv))
+            {
+                1
+            } else {
+                0
+            };
+
+            let mut child_pv = vec![];
+            self.extended += extension;
+            let score = -self.negamax(
+                &child,
+                depth - 1 + extension as u32,
+                -beta,
+                -alpha,
+                ply + 1,
+                &mut child_pv,
+            );
+            self.extended -= extension;
+
+            if score > alpha {
+                alpha = score;
+                best_move = Some(mv.clone());
+                pv.clear();
+                pv.push(mv.clone());
+                pv.extend(child_pv);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if legal_moves == 0 {
+            // every other move being illegal, the one left out is singular
+            if skip.is_some() {
+                return -INFINITY;
+            }
+            return if board.is_in_check(board.get_turn()) {
+                -MATE + ply
+            } else {
+                0
+            };
+        }
+
+        // a stopped search returns made up scores, don't keep them, nor the score of a root
+        // missing some of its moves
+        if !self.should_stop() && (ply > 0 || self.excluded.is_empty()) && skip.is_none() {
+            let bound = if alpha >= beta {
+                Bound::Lower
+            } else if alpha > original_alpha {
+                Bound::Exact
+            } else {
+                Bound::Upper
+            };
+            self.tt.store(key, depth, alpha, bound, best_move, ply);
+        }
+        alpha
+    }
+
+    /// The stored best move of `board` when every other move fails low against its score less
+    /// a margin, searched at half the depth. Only looked for deep enough, where the stored score
+    /// is a lower bound from a search nearly as deep
+    fn singular_move(
+        &mut self,
+        board: &Board,
+        depth: u32,
+        ply: i32,
+        entry: &Entry,
+    ) -> Option<Move> {
+        let mv = entry.best.clone()?;
+        let score = entry.score(ply);
+        if depth < SINGULAR_DEPTH
+            || entry.bound != Bound::Lower
+            || entry.depth + 3 < depth
+            || score.abs() >= MATE - MAX_DEPTH as i32
+        {
+            return None;
+        }
+        let singular_beta = score - SINGULAR_MARGIN * depth as i32;
+        let outer = self.singular_skip.replace((ply, mv.clone()));
+        let others = self.negamax(
+            board,
+            depth / 2,
+            singular_beta - 1,
+            singular_beta,
+            ply,
+            &mut vec![],
+        );
+        self.singular_skip = outer;
+        (others < singular_beta && !self.should_stop()).then_some(mv)
+    }
+
+    /// Searches captures only until the position is quiet, so the evaluation isn't taken in the
+    /// middle of an exchange. Captures are tried best exchange first, the ones losing material
+    /// or unable to bring the score back up to alpha are skipped
+    fn quiescence(&mut self, board: &Board, mut alpha: i32, beta: i32, ply: i32) -> i32 {
+        self.nodes += 1;
+        if let Some(score) = variant_score(board, ply) {
+            return score;
+        }
+        // the side to move can usually do at least as well as not capturing
+        let stand_pat = self.evaluate(board);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        if stand_pat + self.params.queen_value + self.params.delta_margin < alpha {
+            return alpha;
+        }
+        alpha = alpha.max(stand_pat);
+        if self.should_stop() {
+            return alpha;
+        }
+
+        let mut captures = board
+            .get_moves_for_turn()
+            .iter()
+            .filter(|m| board.is_capture(m))
+            .map(|m| (see(board, m), m.clone()))
+            .filter(|(exchange, _)| *exchange >= 0)
+            .collect::<Vec<_>>();
+        captures.sort_by_key(|(exchange, _)| -exchange);
+
+        for (_, mv) in captures {
+            let captured = match mv.move_type {
+                MoveType::PawnEnPassant(_) => PieceType::Pawn,
+                _ => board.get_piece_at_index(mv.to).get_type(),
+            };
+            if mv.promotion_piece().is_none()
+                && stand_pat + piece_value(captured) + self.params.delta_margin < alpha
+            {
+                continue;
+            }
+            let Some(child) = play(board, &mv) else {
+                continue;
+            };
+
+            let score = -self.quiescence(&child, -beta, -alpha, ply + 1);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
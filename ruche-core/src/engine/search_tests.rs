@@ -0,0 +1,61 @@
+//! Moves the search picks must be legal, castles included, and the best ones found
+
+use super::{Params, SearchLimits, Searcher};
+use crate::board::{Board, MoveType};
+
+fn load(fen: &str) -> Board {
+    let mut board = Board::new();
+    board.load_position(fen.to_string());
+    board
+}
+
+/// Searches `fen` at every depth up to `max_depth`, asserting each best move is legal and not a
+/// castle
+fn assert_no_castle(fen: &str, max_depth: u32) {
+    let board = load(fen);
+    for depth in 1..=max_depth {
+        let mut searcher = Searcher::new(Default::default(), Params::default());
+        let best = searcher
+            .search(&board, SearchLimits::depth(depth), |_| {})
+            .unwrap_or_else(|| panic!("no move found in {}", fen));
+        assert!(
+            board.legal_moves().any(|m| m == best),
+            "{} isn't legal in {}",
+            best,
+            fen
+        );
+        assert!(
+            !matches!(
+                best.move_type,
+                MoveType::CastelKingSide | MoveType::CastelQueenSide
+            ),
+            "{} castles in {}",
+            best,
+            fen
+        );
+    }
+}
+
+#[test]
+fn never_castles_out_of_check() {
+    // the rook on e4 checks the king, castling either way would leave it safe
+    assert_no_castle("r3k2r/8/8/8/4r3/8/8/R3K2R w KQkq - 0 1", 3);
+}
+
+#[test]
+fn never_castles_through_check() {
+    // the rooks on f8 and d8 cover the squares the king would cross
+    assert_no_castle("3rkr2/8/8/8/8/8/8/R3K2R w KQ - 0 1", 3);
+}
+
+#[test]
+fn takes_the_queen_when_singular_moves_are_looked_for() {
+    // d8d4 wins the queen and every other move leaves its own hanging, searched deep enough
+    // for the stored best move to be checked for being singular
+    let board = load("3qk3/8/8/8/3Q4/8/8/4K3 b - - 0 1");
+    let mut searcher = Searcher::new(Default::default(), Params::default());
+    let best = searcher
+        .search(&board, SearchLimits::depth(7), |_| {})
+        .expect("black has moves");
+    assert_eq!(best.to_string(), "d8d4");
+}
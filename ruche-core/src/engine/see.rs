@@ -0,0 +1,143 @@
+//! Static exchange evaluation: the material won or lost by a capture once every piece attacking
+//! the square has recaptured, least valuable first, each side free to stop when it would lose
+
+use super::search::piece_value;
+use crate::board::{Board, Move, MoveType, PieceColor, PieceType};
+
+/// Value of the king in an exchange, it can only capture last
+const KING_VALUE: i32 = 20_000;
+
+const KNIGHT_OFFSETS: [(isize, isize); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_OFFSETS: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+const ORTHOGONAL: [(isize, isize); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const DIAGONAL: [(isize, isize); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+fn exchange_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::King => KING_VALUE,
+        piece_type => piece_value(piece_type),
+    }
+}
+
+/// Square `(dx, dy)` away from `idx`, if on the board
+fn offset(idx: usize, (dx, dy): (isize, isize)) -> Option<usize> {
+    let x = (idx % 8) as isize + dx;
+    let y = (idx / 8) as isize + dy;
+    ((0..8).contains(&x) && (0..8).contains(&y)).then(|| (y * 8 + x) as usize)
+}
+
+/// Least valuable piece of `color` among `occupied` attacking `target`, as its square and value
+fn least_valuable_attacker(
+    board: &Board,
+    target: usize,
+    color: PieceColor,
+    occupied: u64,
+) -> Option<(usize, i32)> {
+    let is = |idx: usize, types: &[PieceType]| {
+        let piece = board.get_piece_at_index(idx);
+        occupied & (1 << idx) != 0
+            && piece.get_color() == color
+            && types.contains(&piece.get_type())
+    };
+    let mut best: Option<(usize, i32)> = None;
+    let mut consider = |idx: usize| {
+        let value = exchange_value(board.get_piece_at_index(idx).get_type());
+        if best.is_none_or(|(_, v)| value < v) {
+            best = Some((idx, value));
+        }
+    };
+
+    // a pawn attacks one rank forward, so its attackers stand one rank back
+    let back = match color {
+        PieceColor::White => -1,
+        PieceColor::Black => 1,
+    };
+    for dx in [-1, 1] {
+        if let Some(idx) = offset(target, (dx, back)).filter(|i| is(*i, &[PieceType::Pawn])) {
+            consider(idx);
+        }
+    }
+    for step in KNIGHT_OFFSETS {
+        if let Some(idx) = offset(target, step).filter(|i| is(*i, &[PieceType::Knight])) {
+            consider(idx);
+        }
+    }
+    for step in KING_OFFSETS {
+        if let Some(idx) = offset(target, step).filter(|i| is(*i, &[PieceType::King])) {
+            consider(idx);
+        }
+    }
+
+    let sliders = [
+        (ORTHOGONAL, [PieceType::Rook, PieceType::Queen]),
+        (DIAGONAL, [PieceType::Bishop, PieceType::Queen]),
+    ];
+    for (directions, types) in sliders {
+        for step in directions {
+            let mut square = target;
+            while let Some(next) = offset(square, step) {
+                square = next;
+                if occupied & (1 << square) != 0 {
+                    if is(square, &types) {
+                        consider(square);
+                    }
+                    break;
+                }
+            }
+        }
+    }
+    best
+}
+
+/// Material won by `mv`, a capture, once the exchange on its destination is over, in
+/// centipawns. Negative when the capturing piece is worth more than what it can get back
+pub fn see(board: &Board, mv: &Move) -> i32 {
+    let mover = board.get_piece_at_index(mv.from);
+    let captured = match mv.move_type {
+        MoveType::PawnEnPassant(_) => PieceType::Pawn,
+        _ => board.get_piece_at_index(mv.to).get_type(),
+    };
+
+    let mut gain = vec![piece_value(captured)];
+    let mut occupied = board.occupied() & !(1 << mv.from);
+    if let MoveType::PawnEnPassant(_) = mv.move_type {
+        let pushed = match mover.get_color() {
+            PieceColor::White => mv.to - 8,
+            PieceColor::Black => mv.to + 8,
+        };
+        occupied &= !(1 << pushed);
+    }
+    let mut on_square = exchange_value(mover.get_type());
+    let mut side = mover.get_color().opposite();
+
+    while let Some((idx, value)) = least_valuable_attacker(board, mv.to, side, occupied) {
+        gain.push(on_square - gain[gain.len() - 1]);
+        on_square = value;
+        occupied &= !(1 << idx);
+        side = side.opposite();
+    }
+
+    // each side picks between recapturing and stopping, from the last capture back
+    for d in (1..gain.len()).rev() {
+        gain[d - 1] = -(-gain[d - 1]).max(gain[d]);
+    }
+    gain[0]
+}
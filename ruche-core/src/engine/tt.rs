@@ -0,0 +1,162 @@
+//! Transposition table: results of positions already searched, found again through a different
+//! move order or at the next depth, keyed by the Zobrist hash of the position
+
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::sync::Mutex;
+
+use super::search::MATE;
+use crate::board::Move;
+
+/// Number of entries of the table
+const TABLE_SIZE: usize = 1 << 18;
+/// Entries looked at to estimate how full the table is
+const FILL_SAMPLE: usize = 1000;
+/// Scores further from zero than this are mates, stored relative to the node
+const MATE_BOUND: i32 = MATE - 1000;
+
+/// How a stored score relates to the real score of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    /// the score is exact
+    Exact,
+    /// the search failed high, the real score is at least this
+    Lower,
+    /// no move raised alpha, the real score is at most this
+    Upper,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    key: u64,
+    pub depth: u32,
+    score: i32,
+    pub bound: Bound,
+    pub best: Option<Move>,
+    /// search the entry was stored by
+    generation: u8,
+}
+
+impl Entry {
+    /// Score of the entry seen from a node `ply` plies from the root
+    pub fn score(&self, ply: i32) -> i32 {
+        if self.score > MATE_BOUND {
+            self.score - ply
+        } else if self.score < -MATE_BOUND {
+            self.score + ply
+        } else {
+            self.score
+        }
+    }
+}
+
+/// Usage of the table, shown in the engine overlay
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TtStats {
+    /// share of the probes that found their position
+    pub hit_rate: f32,
+    /// share of the entries in use
+    pub fill: f32,
+}
+
+/// Shared by the threads of a search, each entry behind a lock of its own so they seldom wait
+/// on each other
+pub struct TranspositionTable {
+    entries: Vec<Mutex<Option<Entry>>>,
+    generation: AtomicU8,
+    probes: AtomicU64,
+    hits: AtomicU64,
+}
+
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranspositionTable {
+    pub fn new() -> Self {
+        Self {
+            entries: (0..TABLE_SIZE).map(|_| Mutex::new(None)).collect(),
+            generation: AtomicU8::new(0),
+            probes: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+        }
+    }
+
+    /// The entry of `key`, a thread that panicked holding it can't have left it half written
+    fn slot(&self, key: u64) -> std::sync::MutexGuard<'_, Option<Entry>> {
+        self.entries[key as usize % TABLE_SIZE]
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Starts a new search, entries of the previous ones get replaced first
+    pub fn new_search(&self) {
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        self.probes.store(0, Ordering::Relaxed);
+        self.hits.store(0, Ordering::Relaxed);
+    }
+
+    pub fn probe(&self, key: u64) -> Option<Entry> {
+        self.probes.fetch_add(1, Ordering::Relaxed);
+        let entry = self.slot(key).clone().filter(|e| e.key == key)?;
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(entry)
+    }
+
+    /// Stores the result of searching a node `ply` plies from the root. An entry of the current
+    /// search is only replaced by one searched at least as deep
+    pub fn store(
+        &self,
+        key: u64,
+        depth: u32,
+        score: i32,
+        bound: Bound,
+        best: Option<Move>,
+        ply: i32,
+    ) {
+        let generation = self.generation.load(Ordering::Relaxed);
+        let mut slot = self.slot(key);
+        let replace = match &*slot {
+            Some(old) => old.generation != generation || old.key == key || depth >= old.depth,
+            None => true,
+        };
+        if !replace {
+            return;
+        }
+
+        let score = if score > MATE_BOUND {
+            score + ply
+        } else if score < -MATE_BOUND {
+            score - ply
+        } else {
+            score
+        };
+        // keep the old best move rather than none, it is still the best guess
+        let best = best.or_else(|| {
+            slot.as_ref()
+                .filter(|old| old.key == key)
+                .and_then(|old| old.best.clone())
+        });
+        *slot = Some(Entry {
+            key,
+            depth,
+            score,
+            bound,
+            best,
+            generation,
+        });
+    }
+
+    pub fn stats(&self) -> TtStats {
+        let used = self.entries[..FILL_SAMPLE]
+            .iter()
+            .filter(|e| e.lock().is_ok_and(|e| e.is_some()))
+            .count();
+        let probes = self.probes.load(Ordering::Relaxed);
+        TtStats {
+            hit_rate: self.hits.load(Ordering::Relaxed) as f32 / probes.max(1) as f32,
+            fill: used as f32 / FILL_SAMPLE as f32,
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! SPSA tuning of the engine parameters: each iteration nudges every parameter up or down at
+//! random, plays the nudged-up parameters against the nudged-down ones and moves every
+//! parameter toward the side that scored better
+
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::params::{Params, PARAM_COUNT, SPECS};
+use super::search::{piece_value, SearchLimits, Searcher};
+use crate::board::{Board, PieceColor, START_FEN};
+
+/// Random plies played from the starting position so the games of an iteration differ
+const OPENING_PLIES: usize = 4;
+/// Games still going after this many plies are adjudicated on material
+const MAX_PLIES: usize = 160;
+/// Material lead, in centipawns, that wins an adjudicated game
+const ADJUDICATION_MARGIN: i32 = 300;
+/// Stability constant of the learning rate, usually a tenth of the iterations
+const STABILITY: f64 = 10.0;
+/// Exponents of the decay of the learning rate and of the perturbation size
+const ALPHA: f64 = 0.602;
+const GAMMA: f64 = 0.101;
+
+/// Settings of a tuning session
+#[derive(Debug, Clone, Copy)]
+pub struct SpsaConfig {
+    pub iterations: u32,
+    /// game pairs played per iteration, each pair plays both colors from the same opening
+    pub game_pairs: u32,
+    /// limits of every move of the self-games, keep them small
+    pub limits: SearchLimits,
+    /// learning rate
+    pub learning_rate: f64,
+}
+
+impl Default for SpsaConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            game_pairs: 2,
+            limits: SearchLimits::depth(2),
+            learning_rate: 0.5,
+        }
+    }
+}
+
+/// xorshift64, good enough to pick perturbations and openings
+struct Rng(u64);
+
+impl Rng {
+    fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        Self(nanos | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Plays a few random legal plies from the starting position, `seed` being any random number.
+/// The plies are kept in the move history of the board
+pub fn random_opening(seed: u64) -> Board {
+    let mut rng = Rng(seed | 1);
+    let mut board = Board::new();
+    board.load_position(START_FEN.to_string());
+    for _ in 0..OPENING_PLIES {
+        let moves = board.legal_moves().collect::<Vec<_>>();
+        if moves.is_empty() {
+            break;
+        }
+        let mv = moves[rng.next() as usize % moves.len()].clone();
+        if board.play(&mv).is_err() {
+            break;
+        }
+    }
+    board
+}
+
+/// Material balance from white's point of view
+fn material_balance(board: &Board) -> i32 {
+    (0..64)
+        .map(|idx| board.get_piece_at_index(idx))
+        .map(|piece| match piece.get_color() {
+            PieceColor::White => piece_value(piece.get_type()),
+            PieceColor::Black => -piece_value(piece.get_type()),
+        })
+        .sum()
+}
+
+/// Plays a game from `start`, returning white's score: 1 for a win, 0.5 for a draw
+fn play_game(start: &Board, white: &Params, black: &Params, limits: SearchLimits) -> f64 {
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut white = Searcher::new(stop.clone(), white.clone());
+    let mut black = Searcher::new(stop, black.clone());
+    let mut board = start.clone();
+
+    for _ in 0..MAX_PLIES {
+        let searcher = match board.get_turn() {
+            PieceColor::White => &mut white,
+            PieceColor::Black => &mut black,
+        };
+        let Some(mv) = searcher.search(&board, limits, |_| {}) else {
+            break;
+        };
+        if board.play(&mv).is_err() {
+            break;
+        }
+    }
+
+    let mover = board.get_turn();
+    if board.legal_moves().next().is_none() {
+        return match (board.is_in_check(mover), mover) {
+            (false, _) => 0.5,
+            (true, PieceColor::White) => 0.0,
+            (true, PieceColor::Black) => 1.0,
+        };
+    }
+    match material_balance(&board) {
+        m if m >= ADJUDICATION_MARGIN => 1.0,
+        m if m <= -ADJUDICATION_MARGIN => 0.0,
+        _ => 0.5,
+    }
+}
+
+/// Runs SPSA from `params`, writing the parameters to `path` after every iteration so the
+/// session can be stopped at any time, and returns the tuned parameters
+pub fn tune(params: Params, config: SpsaConfig, path: &Path) -> std::io::Result<Params> {
+    let mut rng = Rng::from_time();
+    let mut theta = params.values().map(|v| v as f64);
+    let mut params = params;
+
+    for k in 0..config.iterations {
+        let k = k as f64 + 1.0;
+        let step_scale = 1.0 / k.powf(GAMMA);
+        let rate = config.learning_rate / (k + STABILITY).powf(ALPHA);
+
+        // perturbation of a twentieth of the range, at least one centipawn
+        let mut delta = [0.0; PARAM_COUNT];
+        for (i, spec) in SPECS.iter().enumerate() {
+            let sign = if rng.next() & 1 == 0 { 1.0 } else { -1.0 };
+            delta[i] = sign * (((spec.max - spec.min) as f64 / 20.0) * step_scale).max(1.0);
+        }
+
+        let mut plus = params.clone();
+        let mut minus = params.clone();
+        plus.set_values(&std::array::from_fn(|i| {
+            (theta[i] + delta[i]).round() as i32
+        }));
+        minus.set_values(&std::array::from_fn(|i| {
+            (theta[i] - delta[i]).round() as i32
+        }));
+
+        // score of `plus` minus the score of `minus`, over every game
+        let mut result = 0.0;
+        for _ in 0..config.game_pairs {
+            let opening = random_opening(rng.next());
+            let first = play_game(&opening, &plus, &minus, config.limits);
+            let second = 1.0 - play_game(&opening, &minus, &plus, config.limits);
+            result += (first - 0.5) + (second - 0.5);
+        }
+
+        for (i, spec) in SPECS.iter().enumerate() {
+            theta[i] =
+                (theta[i] + rate * result * delta[i]).clamp(spec.min as f64, spec.max as f64);
+        }
+        params.set_values(&theta.map(|v| v.round() as i32));
+        params.save(path)?;
+
+        println!(
+            "iteration {}/{} result {:+.1}, parameters written to {}",
+            k,
+            config.iterations,
+            result,
+            path.display()
+        );
+    }
+    Ok(params)
+}
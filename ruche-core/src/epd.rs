@@ -0,0 +1,306 @@
+//! Extended Position Description: a position without move counters followed by opcodes, e.g.
+//! `2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";`
+
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::board::Board;
+use crate::engine::{Params, SearchLimits, Searcher};
+use crate::san;
+
+/// Why an EPD record couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    /// Fewer than the four position fields
+    MissingFields,
+    /// An operation with no opcode or an unterminated string
+    InvalidOperation(String),
+}
+
+/// A single `opcode operand...;` operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpdOperation {
+    pub opcode: String,
+    pub operands: Vec<String>,
+}
+
+/// A position along with its operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpdRecord {
+    /// piece placement, side to move, castling rights and en passant square
+    pub position: String,
+    pub operations: Vec<EpdOperation>,
+}
+
+/// Splits an operation into words, keeping quoted strings whole
+fn split_operands(op: &str) -> Result<Vec<String>, EpdError> {
+    let mut words = vec![];
+    let mut chars = op.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '"' => {
+                let mut word = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(EpdError::InvalidOperation(op.to_string())),
+                    }
+                }
+                words.push(word);
+            }
+            c => {
+                let mut word = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    word.push(c);
+                }
+                words.push(word);
+            }
+        }
+    }
+    Ok(words)
+}
+
+/// Splits the operations on `;`, ignoring the ones inside quoted strings
+fn split_operations(ops: &str) -> Vec<&str> {
+    let mut res = vec![];
+    let mut in_string = false;
+    let mut start = 0;
+    for (i, c) in ops.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            ';' if !in_string => {
+                res.push(&ops[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    res.push(&ops[start..]);
+    res.into_iter().filter(|op| !op.trim().is_empty()).collect()
+}
+
+impl EpdRecord {
+    pub fn parse(line: &str) -> Result<Self, EpdError> {
+        let mut rest = line.trim();
+        let mut fields = vec![];
+        for _ in 0..4 {
+            let (field, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            if field.is_empty() {
+                return Err(EpdError::MissingFields);
+            }
+            fields.push(field);
+            rest = tail.trim_start();
+        }
+
+        let operations = split_operations(rest)
+            .into_iter()
+            .map(|op| {
+                let mut words = split_operands(op)?;
+                if words.is_empty() {
+                    return Err(EpdError::InvalidOperation(op.to_string()));
+                }
+                let opcode = words.remove(0);
+                Ok(EpdOperation {
+                    opcode,
+                    operands: words,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            position: fields.join(" "),
+            operations,
+        })
+    }
+
+    /// Returns the operands of the first operation with `opcode`
+    pub fn operands(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|op| op.opcode == opcode)
+            .map(|op| op.operands.as_slice())
+    }
+
+    /// Adds an operation, replacing any with the same opcode
+    pub fn set_operation(&mut self, opcode: &str, operands: Vec<String>) {
+        self.operations.retain(|op| op.opcode != opcode);
+        self.operations.push(EpdOperation {
+            opcode: opcode.to_string(),
+            operands,
+        });
+    }
+
+    /// The `id` operation, naming the position
+    pub fn id(&self) -> Option<&str> {
+        self.operands("id")?.first().map(|id| id.as_str())
+    }
+
+    /// Loads the position, using the `hmvc` and `fmvn` operations for the move counters
+    pub fn board(&self) -> Board {
+        let counter = |opcode, default| {
+            self.operands(opcode)
+                .and_then(|o| o.first())
+                .map(|c| c.as_str())
+                .unwrap_or(default)
+                .to_string()
+        };
+
+        let mut board = Board::new();
+        board.load_position(format!(
+            "{} {} {}",
+            self.position,
+            counter("hmvc", "0"),
+            counter("fmvn", "1")
+        ));
+        board
+    }
+}
+
+/// Writes the record back as a single EPD line
+impl Display for EpdRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.position)?;
+        for op in self.operations.iter() {
+            write!(f, " {}", op.opcode)?;
+            for operand in op.operands.iter() {
+                if operand.is_empty() || operand.contains(char::is_whitespace) || op.opcode == "id"
+                {
+                    write!(f, " \"{}\"", operand)?;
+                } else {
+                    write!(f, " {}", operand)?;
+                }
+            }
+            write!(f, ";")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of running one position of a suite
+#[derive(Debug, Clone)]
+pub struct SuiteResult {
+    pub id: String,
+    pub passed: bool,
+    /// what was checked and found, e.g. `bm Qg6 found Qg6`
+    pub details: String,
+}
+
+/// Checks one record: the best move found by the search against `bm`/`am`, and the perft
+/// counts against the `D1`, `D2`... operations
+pub fn run_record(record: &EpdRecord, limits: SearchLimits, params: &Params) -> SuiteResult {
+    let board = record.board();
+    let mut passed = true;
+    let mut details = vec![];
+
+    // perft counts, as found in perftsuite.epd
+    for op in record.operations.iter() {
+        let Some(depth) = op
+            .opcode
+            .strip_prefix('D')
+            .and_then(|d| d.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        let expected = op.operands.first().and_then(|n| n.parse::<u64>().ok());
+        let found = board.perft(depth);
+        passed &= expected == Some(found);
+        details.push(format!(
+            "D{} {} found {}",
+            depth,
+            op.operands.join(" "),
+            found
+        ));
+    }
+
+    let best_moves = record.operands("bm");
+    let avoid_moves = record.operands("am");
+    if best_moves.is_some() || avoid_moves.is_some() {
+        let mut searcher = Searcher::new(Arc::new(AtomicBool::new(false)), params.clone());
+        let found = searcher
+            .search(&board, limits, |_| {})
+            .map(|mv| san::to_san(&board, &mv));
+        let found_name = found.clone().unwrap_or("none".to_string());
+
+        // compare resolved moves rather than text so `Qg6` matches the `Qg6+` we write
+        let resolve = |moves: &[String]| {
+            moves
+                .iter()
+                .filter_map(|m| san::parse_san(&board, m).ok())
+                .collect::<Vec<_>>()
+        };
+        let found_move = found.and_then(|f| san::parse_san(&board, &f).ok());
+
+        if let Some(bm) = best_moves {
+            passed &= found_move.as_ref().is_some_and(|f| resolve(bm).contains(f));
+            details.push(format!("bm {} found {}", bm.join(" "), found_name));
+        }
+        if let Some(am) = avoid_moves {
+            passed &= found_move.as_ref().is_none_or(|f| !resolve(am).contains(f));
+            details.push(format!("am {} found {}", am.join(" "), found_name));
+        }
+    }
+
+    if details.is_empty() {
+        passed = false;
+        details.push("nothing to check (no bm, am or D<n>)".to_string());
+    }
+
+    SuiteResult {
+        id: record.id().unwrap_or(&record.position).to_string(),
+        passed,
+        details: details.join(", "),
+    }
+}
+
+/// Runs every record of the EPD file at `path`, printing pass/fail per position and a summary.
+/// The failed records are written to `<path>.failed.epd` with what was found as a `c0` comment
+pub fn run_suite(
+    path: &Path,
+    limits: SearchLimits,
+    params: &Params,
+) -> std::io::Result<Vec<SuiteResult>> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut results = vec![];
+    let mut failed = String::new();
+
+    for (line_number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let record = match EpdRecord::parse(line) {
+            Ok(record) => record,
+            Err(e) => {
+                println!("line {}: skipped, {:?}", line_number + 1, e);
+                continue;
+            }
+        };
+
+        let result = run_record(&record, limits, params);
+        println!(
+            "{} {}: {}",
+            if result.passed { "PASS" } else { "FAIL" },
+            result.id,
+            result.details
+        );
+        if !result.passed {
+            let mut record = record;
+            record.set_operation("c0", vec![result.details.clone()]);
+            failed.push_str(&format!("{}\n", record));
+        }
+        results.push(result);
+    }
+
+    let passed = results.iter().filter(|r| r.passed).count();
+    println!("{}/{} passed", passed, results.len());
+
+    if !failed.is_empty() {
+        let failed_path = path.with_extension("failed.epd");
+        std::fs::write(&failed_path, failed)?;
+        println!("failed positions written to {}", failed_path.display());
+    }
+    Ok(results)
+}
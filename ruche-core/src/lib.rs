@@ -0,0 +1,10 @@
+//! The chess of ruche without its window: the board and its rules, the engine and the
+//! notations positions and games are written in. Nothing here depends on raylib, so it builds
+//! and is tested headless
+
+pub mod board;
+pub mod engine;
+pub mod epd;
+pub mod pgn;
+pub mod san;
+pub mod zobrist;
@@ -0,0 +1,237 @@
+//! Games in Portable Game Notation: tag pairs followed by the moves in SAN. Comments,
+//! variations and numeric annotation glyphs are skipped when reading
+
+use std::fmt::Display;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::board::{Board, Move, Variant, START_FEN};
+use crate::san::{self, SanError};
+
+/// Why the moves of a game couldn't be replayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgnError {
+    /// A move that isn't legal where it is played, with its ply counted from 1
+    Move(usize, SanError),
+    /// The `FEN` tag doesn't hold a position
+    InvalidFen(String),
+    /// The text holds no game
+    NoGame,
+}
+
+impl Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PgnError::Move(ply, e) => write!(f, "ply {}: {}", ply, e),
+            PgnError::InvalidFen(fen) => write!(f, "invalid FEN tag {:?}", fen),
+            PgnError::NoGame => write!(f, "no game found"),
+        }
+    }
+}
+
+/// A game as written in the file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    /// moves in SAN, without numbers nor annotations
+    pub moves: Vec<String>,
+    /// `1-0`, `0-1`, `1/2-1/2` or `*` for a game that isn't over
+    pub result: String,
+}
+
+impl PgnGame {
+    /// Value of the tag `name`
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Position the game starts from, the `FEN` tag or the standard starting position, played
+    /// by the rules of the `Variant` tag
+    pub fn start(&self) -> Result<Board, PgnError> {
+        let fen = self.tag("FEN").unwrap_or(START_FEN);
+        let mut board = Board::new();
+        board.load_position(fen.to_string());
+        let variant = self.tag("Variant").and_then(|name| {
+            Variant::ALL
+                .into_iter()
+                .find(|v| v.to_string().eq_ignore_ascii_case(name))
+        });
+        if let Some(variant) = variant {
+            board.set_variant(variant);
+        }
+        if board.variant().needs_kings() && board.king_square(board.get_turn()).is_none() {
+            return Err(PgnError::InvalidFen(fen.to_string()));
+        }
+        Ok(board)
+    }
+
+    /// Plays the moves from the start of the game, the board keeping them in its move history
+    pub fn replay(&self) -> Result<Board, PgnError> {
+        let mut board = self.start()?;
+        for (i, text) in self.moves.iter().enumerate() {
+            let mv = san::parse_san(&board, text).map_err(|e| PgnError::Move(i + 1, e))?;
+            board
+                .play(&mv)
+                .map_err(|_| PgnError::Move(i + 1, SanError::Illegal(text.clone())))?;
+        }
+        Ok(board)
+    }
+}
+
+fn is_result(token: &str) -> bool {
+    matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*")
+}
+
+/// Parses a tag pair line e.g. `[White "Morphy, Paul"]`
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((name.to_string(), value.replace("\\\"", "\"")))
+}
+
+/// Splits the movetext into SAN moves, leaving out move numbers, comments, variations and
+/// annotations, and returns them with the result if the movetext ends with one
+fn parse_movetext(text: &str) -> (Vec<String>, Option<String>) {
+    let mut moves = vec![];
+    let mut chars = text.chars().peekable();
+    let mut variations = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                for c in chars.by_ref() {
+                    if c == '}' {
+                        break;
+                    }
+                }
+            }
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '(' => variations += 1,
+            ')' => variations -= 1,
+            c if c.is_whitespace() => {}
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}();".contains(*c)) {
+                    token.push(c);
+                }
+                if variations > 0 || token.starts_with('$') {
+                    continue;
+                }
+                if is_result(&token) {
+                    return (moves, Some(token));
+                }
+                // `12.` and `12...` as well as `12.e4`
+                let token = token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.');
+                let token = token.trim_end_matches(['!', '?']);
+                if !token.is_empty() {
+                    moves.push(token.to_string());
+                }
+            }
+        }
+    }
+    (moves, None)
+}
+
+/// Reads every game of a PGN file. A game with no result is still read, its result being `*`
+pub fn parse_games(text: &str) -> Vec<PgnGame> {
+    let mut games = vec![];
+    let mut tags = vec![];
+    let mut movetext = String::new();
+
+    let mut finish = |tags: &mut Vec<_>, movetext: &mut String| {
+        let (moves, result) = parse_movetext(movetext);
+        if !moves.is_empty() || !tags.is_empty() {
+            games.push(PgnGame {
+                tags: std::mem::take(tags),
+                moves,
+                result: result.unwrap_or_else(|| "*".to_string()),
+            });
+        }
+        movetext.clear();
+    };
+
+    for line in text.lines() {
+        match parse_tag(line) {
+            // tags after moves start the next game
+            Some(tag) => {
+                if !movetext.trim().is_empty() {
+                    finish(&mut tags, &mut movetext);
+                }
+                tags.push(tag);
+            }
+            None => {
+                movetext.push_str(line);
+                movetext.push('\n');
+                if line.split_whitespace().last().is_some_and(is_result) {
+                    finish(&mut tags, &mut movetext);
+                }
+            }
+        }
+    }
+    finish(&mut tags, &mut movetext);
+    games
+}
+
+/// Writes a game of `moves` played from `start` as PGN, the moves wrapped at 80 columns. A
+/// `start` other than the standard position is written in the `FEN` tag, and the rules of a
+/// variant in the `Variant` tag
+pub fn write_game(tags: &[(&str, String)], start: &Board, moves: &[Move], result: &str) -> String {
+    let mut pgn = tags
+        .iter()
+        .map(|(tag, value)| format!("[{} \"{}\"]\n", tag, value.replace('"', "'")))
+        .collect::<String>();
+    let fen = start.to_fen();
+    if start.variant() != Variant::Standard {
+        pgn.push_str(&format!("[Variant \"{}\"]\n", start.variant()));
+    }
+    if fen != START_FEN {
+        pgn.push_str("[SetUp \"1\"]\n");
+        pgn.push_str(&format!("[FEN \"{}\"]\n", fen));
+    }
+    pgn.push('\n');
+
+    let line = san::line_to_san(start, moves);
+    let mut row = String::new();
+    for word in line.split(' ').filter(|w| !w.is_empty()).chain([result]) {
+        if !row.is_empty() && row.len() + word.len() + 1 > 80 {
+            pgn.push_str(&row);
+            pgn.push('\n');
+            row.clear();
+        }
+        if !row.is_empty() {
+            row.push(' ');
+        }
+        row.push_str(word);
+    }
+    pgn.push_str(&row);
+    pgn.push_str("\n\n");
+    pgn
+}
+
+/// Today's date as PGN writes it e.g. `2024.03.09`
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+        / 86_400;
+    // days since 1970-01-01 to a civil date, counting from 0000-03-01 so leap days end the year
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
@@ -0,0 +1,261 @@
+//! Standard algebraic notation, e.g. `Nf3`, `exd5`, `O-O`, `e8=Q+`, and `N@f3` for crazyhouse
+//! drops
+
+use std::fmt::Display;
+
+use crate::board::{
+    algebraic_to_square, square_to_algebraic, Board, Move, MoveType, PieceColor, PieceType,
+};
+
+/// Why a SAN move couldn't be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanError {
+    /// The text isn't a SAN move
+    Invalid(String),
+    /// No legal move matches
+    Illegal(String),
+    /// More than one legal move matches, the origin square must be given
+    Ambiguous(String),
+}
+
+impl Display for SanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SanError::Invalid(san) => write!(f, "{:?} isn't a move", san),
+            SanError::Illegal(san) => write!(f, "{} isn't legal here", san),
+            SanError::Ambiguous(san) => write!(f, "{} is ambiguous", san),
+        }
+    }
+}
+
+fn piece_letter(piece_type: PieceType) -> Option<char> {
+    match piece_type {
+        PieceType::King => Some('K'),
+        PieceType::Queen => Some('Q'),
+        PieceType::Rook => Some('R'),
+        PieceType::Bishop => Some('B'),
+        PieceType::Knight => Some('N'),
+        PieceType::Pawn | PieceType::None => None,
+    }
+}
+
+fn letter_piece(c: char) -> Option<PieceType> {
+    match c {
+        'K' => Some(PieceType::King),
+        'Q' => Some(PieceType::Queen),
+        'R' => Some(PieceType::Rook),
+        'B' => Some(PieceType::Bishop),
+        'N' => Some(PieceType::Knight),
+        _ => None,
+    }
+}
+
+/// Writes `line`, moves played one after the other from `board`, in SAN with move numbers e.g.
+/// `12. Nf3 Nc6 13. d4` or `12... Nc6 13. d4`. Stops at the first illegal move
+pub fn line_to_san(board: &Board, line: &[Move]) -> String {
+    let mut board = board.clone();
+    let mut text = vec![];
+    for (i, mv) in line.iter().enumerate() {
+        let number = board.fullmove_number();
+        match board.get_turn() {
+            PieceColor::White => text.push(format!("{}.", number)),
+            PieceColor::Black if i == 0 => text.push(format!("{}...", number)),
+            PieceColor::Black => {}
+        }
+        text.push(to_san(&board, mv));
+        if board.play(mv).is_err() {
+            break;
+        }
+    }
+    text.join(" ")
+}
+
+/// Writes `mv`, a legal move on `board`, in SAN
+pub fn to_san(board: &Board, mv: &Move) -> String {
+    let mut san = match mv.move_type {
+        MoveType::CastelKingSide => "O-O".to_string(),
+        MoveType::CastelQueenSide => "O-O-O".to_string(),
+        MoveType::Drop(piece_type) => format!(
+            "{}@{}",
+            piece_letter(piece_type).unwrap_or('P'),
+            square_to_algebraic(mv.to)
+        ),
+        _ => {
+            let piece_type = board.get_piece_at_index(mv.from).get_type();
+            let from = square_to_algebraic(mv.from);
+            let mut san = String::new();
+
+            match piece_letter(piece_type) {
+                Some(letter) => {
+                    san.push(letter);
+                    san.push_str(&disambiguation(board, mv, piece_type));
+                    if board.is_capture(mv) {
+                        san.push('x');
+                    }
+                }
+                None if board.is_capture(mv) => {
+                    san.push_str(&from[..1]);
+                    san.push('x');
+                }
+                None => {}
+            }
+
+            san.push_str(&square_to_algebraic(mv.to));
+            if let Some(piece) = mv.promotion_piece().and_then(piece_letter) {
+                san.push('=');
+                san.push(piece);
+            }
+            san
+        }
+    };
+
+    let mut after = board.clone();
+    if after.play(mv).is_ok() && after.is_in_check(after.get_turn()) {
+        san.push(if after.legal_moves().next().is_none() {
+            '#'
+        } else {
+            '+'
+        });
+    }
+    san
+}
+
+/// Origin file and/or rank needed to tell `mv` apart from the other moves of the same kind of
+/// piece to the same square
+fn disambiguation(board: &Board, mv: &Move, piece_type: PieceType) -> String {
+    let from = square_to_algebraic(mv.from);
+    let others = board
+        .legal_moves()
+        .filter(|m| {
+            m.to == mv.to
+                && m.from != mv.from
+                && board.get_piece_at_index(m.from).get_type() == piece_type
+        })
+        .map(|m| square_to_algebraic(m.from))
+        .collect::<Vec<_>>();
+
+    if others.is_empty() {
+        String::new()
+    } else if others.iter().all(|o| o[..1] != from[..1]) {
+        from[..1].to_string()
+    } else if others.iter().all(|o| o[1..] != from[1..]) {
+        from[1..].to_string()
+    } else {
+        from
+    }
+}
+
+/// Resolves a move typed by the user, in coordinates as UCI writes them e.g. `e2e4`, `e7e8q`, or
+/// in SAN
+pub fn parse_move(board: &Board, text: &str) -> Result<Move, SanError> {
+    let text = text.trim();
+    let square = |range| text.get(range).and_then(algebraic_to_square);
+    let coordinates = square(0..2).is_some() && square(2..4).is_some();
+    if !coordinates {
+        return parse_san(board, text);
+    }
+    let lower = text.to_ascii_lowercase();
+    board
+        .legal_moves()
+        .find(|m| m.to_string() == lower)
+        .ok_or_else(|| SanError::Illegal(text.to_string()))
+}
+
+/// Resolves a SAN move against the legal moves of `board`. Check and annotation suffixes are
+/// ignored, and so is a missing `x` or `=`
+pub fn parse_san(board: &Board, san: &str) -> Result<Move, SanError> {
+    let invalid = || SanError::Invalid(san.to_string());
+    let text = san.trim().trim_end_matches(['+', '#', '!', '?']);
+
+    let castle = match text {
+        "O-O" | "0-0" => Some(MoveType::CastelKingSide),
+        "O-O-O" | "0-0-0" => Some(MoveType::CastelQueenSide),
+        _ => None,
+    };
+    if let Some(castle) = castle {
+        return board
+            .legal_moves()
+            .find(|m| m.move_type == castle)
+            .ok_or_else(|| SanError::Illegal(san.to_string()));
+    }
+
+    // a drop, `N@f3`, or `@e4` for a pawn
+    if let Some((piece, square)) = text.split_once('@') {
+        let piece_type = match piece {
+            "" | "P" => PieceType::Pawn,
+            _ => piece
+                .chars()
+                .next()
+                .filter(|_| piece.len() == 1)
+                .and_then(letter_piece)
+                .filter(|t| *t != PieceType::King)
+                .ok_or_else(invalid)?,
+        };
+        let to = algebraic_to_square(square).ok_or_else(invalid)?;
+        return board
+            .legal_moves()
+            .find(|m| m.move_type == MoveType::Drop(piece_type) && m.to == to)
+            .ok_or_else(|| SanError::Illegal(san.to_string()));
+    }
+
+    let mut chars = text
+        .chars()
+        .filter(|c| *c != 'x' && *c != '=')
+        .collect::<Vec<_>>();
+    let piece_type = match chars.first().copied().and_then(letter_piece) {
+        Some(piece_type) => {
+            chars.remove(0);
+            piece_type
+        }
+        None => PieceType::Pawn,
+    };
+    let promotion = match chars.last().copied() {
+        Some(c) if piece_type == PieceType::Pawn && letter_piece(c).is_some() => {
+            chars.pop();
+            letter_piece(c)
+        }
+        _ => None,
+    };
+
+    if chars.len() < 2 {
+        return Err(invalid());
+    }
+    let dest = chars
+        .split_off(chars.len() - 2)
+        .into_iter()
+        .collect::<String>();
+    let to = algebraic_to_square(&dest).ok_or_else(invalid)?;
+
+    // whatever is left is the origin file and/or rank
+    let mut from_file = None;
+    let mut from_rank = None;
+    for c in chars {
+        match c {
+            'a'..='h' if from_file.is_none() => from_file = Some(c),
+            '1'..='8' if from_rank.is_none() => from_rank = Some(c),
+            _ => return Err(invalid()),
+        }
+    }
+
+    let candidates = board
+        .legal_moves()
+        .filter(|m| {
+            let from = square_to_algebraic(m.from);
+            m.to == to
+                && board.get_piece_at_index(m.from).get_type() == piece_type
+                && m.promotion_piece() == promotion
+                && !matches!(
+                    m.move_type,
+                    MoveType::CastelKingSide | MoveType::CastelQueenSide
+                )
+                && from_file.is_none_or(|f| from.starts_with(f))
+                && from_rank.is_none_or(|r| from.ends_with(r))
+        })
+        .collect::<Vec<_>>();
+
+    match candidates.len() {
+        0 => Err(SanError::Illegal(san.to_string())),
+        1 => Ok(candidates[0].clone()),
+        _ => Err(SanError::Ambiguous(san.to_string())),
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt::Display;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::board::{square_to_algebraic, PieceColor};
+
+/// Reason a clock was adjusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockAdjustment {
+    /// increment added after a move
+    Increment,
+    /// the peer's reading taken over, making up for the time the moves spent on the network
+    LagCompensation,
+}
+
+/// Represents a single auditable event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuditEvent {
+    /// A move has been received and applied to the board
+    MoveReceived {
+        ply: usize,
+        color: PieceColor,
+        from: usize,
+        to: usize,
+    },
+    /// The clock of `color` has been changed by `delta_ms` milliseconds
+    ClockAdjusted {
+        color: PieceColor,
+        delta_ms: i64,
+        reason: ClockAdjustment,
+    },
+    /// The clock of `color` ran out
+    Flagged {
+        color: PieceColor,
+        remaining_ms: i64,
+    },
+}
+
+/// An [AuditEvent] along with the time it was recorded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// time since the log was started
+    pub elapsed: Duration,
+    pub event: AuditEvent,
+}
+
+/// Append-only log of move receipt timestamps and clock adjustments for a timed game,
+/// so disputes about lag compensation or flagging can be reviewed afterwards
+#[derive(Debug)]
+pub struct ClockAuditLog {
+    started_at: SystemTime,
+    started: Instant,
+    entries: Vec<AuditEntry>,
+}
+
+impl Default for ClockAuditLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ClockAuditLog {
+    pub fn new() -> Self {
+        Self {
+            started_at: SystemTime::now(),
+            started: Instant::now(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Records an event with the current timestamp
+    pub fn record(&mut self, event: AuditEvent) {
+        self.entries.push(AuditEntry {
+            elapsed: self.started.elapsed(),
+            event,
+        });
+    }
+
+    /// Writes the log to `path` as plain text, one entry per line
+    pub fn export(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+}
+
+fn color_name(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "white",
+        PieceColor::Black => "black",
+    }
+}
+
+impl Display for AuditEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditEvent::MoveReceived {
+                ply,
+                color,
+                from,
+                to,
+            } => write!(
+                f,
+                "move ply={} side={} {}{}",
+                ply,
+                color_name(*color),
+                square_to_algebraic(*from),
+                square_to_algebraic(*to)
+            ),
+            AuditEvent::ClockAdjusted {
+                color,
+                delta_ms,
+                reason,
+            } => write!(
+                f,
+                "clock side={} delta_ms={:+} reason={:?}",
+                color_name(*color),
+                delta_ms,
+                reason
+            ),
+            AuditEvent::Flagged {
+                color,
+                remaining_ms,
+            } => write!(
+                f,
+                "flag side={} remaining_ms={}",
+                color_name(*color),
+                remaining_ms
+            ),
+        }
+    }
+}
+
+impl Display for ClockAuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let started = self
+            .started_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        writeln!(
+            f,
+            "# ruche clock audit log, started at unix {}",
+            started.as_secs()
+        )?;
+        for entry in self.entries.iter() {
+            writeln!(
+                f,
+                "+{}.{:03}s {}",
+                entry.elapsed.as_secs(),
+                entry.elapsed.subsec_millis(),
+                entry.event
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Default time between two autosaves
+const DEFAULT_INTERVAL: Duration = Duration::from_secs(60);
+/// Default number of backups kept on disk
+const DEFAULT_KEEP: usize = 5;
+
+/// Settings for [Autosave]
+#[derive(Debug, Clone)]
+pub struct AutosaveConfig {
+    /// directory the backups are written to
+    pub dir: PathBuf,
+    /// time between two saves
+    pub interval: Duration,
+    /// number of backups to keep, older ones are rotated out
+    pub keep: usize,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("autosave"),
+            interval: DEFAULT_INTERVAL,
+            keep: DEFAULT_KEEP,
+        }
+    }
+}
+
+impl AutosaveConfig {
+    /// Reads the config from the `RUCHE_AUTOSAVE_DIR`, `RUCHE_AUTOSAVE_INTERVAL` (in seconds) and
+    /// `RUCHE_AUTOSAVE_KEEP` environment variables, falling back to the defaults
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(dir) = std::env::var("RUCHE_AUTOSAVE_DIR") {
+            config.dir = PathBuf::from(dir);
+        }
+        if let Some(secs) = env_number("RUCHE_AUTOSAVE_INTERVAL") {
+            config.interval = Duration::from_secs(secs as u64);
+        }
+        if let Some(keep) = env_number("RUCHE_AUTOSAVE_KEEP") {
+            config.keep = keep.max(1);
+        }
+        config
+    }
+}
+
+fn env_number(name: &str) -> Option<usize> {
+    let value = std::env::var(name).ok()?;
+    match value.parse() {
+        Ok(n) => Some(n),
+        Err(_) => {
+            tracing::warn!("Ignoring invalid {}: {:?}", name, value);
+            None
+        }
+    }
+}
+
+/// A backup found on disk
+#[derive(Debug, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+}
+
+/// Periodically writes the active session to disk, keeping the last `keep` saves as rotating
+/// backups: `session.0.fen` is always the newest one
+pub struct Autosave {
+    config: AutosaveConfig,
+    last_save: Instant,
+}
+
+impl Autosave {
+    pub fn new(config: AutosaveConfig) -> Self {
+        Self {
+            config,
+            last_save: Instant::now(),
+        }
+    }
+
+    fn backup_path(&self, idx: usize) -> PathBuf {
+        self.config.dir.join(format!("session.{}.fen", idx))
+    }
+
+    /// Where the game left unfinished on quit is saved, see [crate::saved_game]
+    pub fn resume_path(&self) -> PathBuf {
+        self.config.dir.join("resume.pgn")
+    }
+
+    /// Saves the session returned by `contents` if the interval has elapsed since the last save
+    pub fn tick<F>(&mut self, contents: F)
+    where
+        F: FnOnce() -> String,
+    {
+        if self.last_save.elapsed() < self.config.interval {
+            return;
+        }
+        self.last_save = Instant::now();
+
+        match self.save(&contents()) {
+            Ok(path) => tracing::debug!("Autosaved to {:?}", path),
+            Err(e) => tracing::error!("Autosave failed: {:?}", e),
+        }
+    }
+
+    /// Writes a new backup, shifting the existing ones and dropping the oldest
+    pub fn save(&self, contents: &str) -> std::io::Result<PathBuf> {
+        std::fs::create_dir_all(&self.config.dir)?;
+
+        let oldest = self.backup_path(self.config.keep - 1);
+        if oldest.exists() {
+            std::fs::remove_file(oldest)?;
+        }
+        for idx in (0..self.config.keep - 1).rev() {
+            let path = self.backup_path(idx);
+            if path.exists() {
+                std::fs::rename(path, self.backup_path(idx + 1))?;
+            }
+        }
+
+        let path = self.backup_path(0);
+        std::fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    /// Returns the backups currently on disk, newest first
+    pub fn backups(&self) -> Vec<Backup> {
+        (0..self.config.keep)
+            .map(|idx| self.backup_path(idx))
+            .filter_map(|path| {
+                let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+                Some(Backup { path, modified })
+            })
+            .collect()
+    }
+
+    /// Reads a backup back, returning the saved session
+    pub fn restore(path: &Path) -> std::io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+}
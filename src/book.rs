@@ -0,0 +1,139 @@
+use std::path::{Path, PathBuf};
+
+use crate::board::{Board, Move, PieceType};
+use crate::zobrist;
+
+/// Size of an entry in a Polyglot book, in bytes
+const ENTRY_SIZE: usize = 16;
+/// Book loaded when `RUCHE_BOOK` isn't set
+const DEFAULT_BOOK: &str = "book.bin";
+
+/// Returns the path of the opening book, `RUCHE_BOOK` or `book.bin` in the working directory
+pub fn book_path() -> PathBuf {
+    std::env::var("RUCHE_BOOK")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_BOOK))
+}
+
+/// A move found in the book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookMove {
+    pub mv: Move,
+    /// how often the move should be played relative to the other book moves
+    pub weight: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    key: u64,
+    mv: u16,
+    weight: u16,
+}
+
+/// An opening book in the Polyglot `.bin` format
+#[derive(Debug, Clone)]
+pub struct Book {
+    /// sorted by key
+    entries: Vec<Entry>,
+}
+
+impl Book {
+    /// Reads the book at `path`
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Parses a book, entries are big endian `key: u64, move: u16, weight: u16, learn: u32`
+    pub fn from_bytes(bytes: &[u8]) -> std::io::Result<Self> {
+        if !bytes.len().is_multiple_of(ENTRY_SIZE) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "truncated polyglot book",
+            ));
+        }
+
+        let mut entries = bytes
+            .chunks_exact(ENTRY_SIZE)
+            .map(|e| Entry {
+                key: u64::from_be_bytes(e[0..8].try_into().unwrap()),
+                mv: u16::from_be_bytes(e[8..10].try_into().unwrap()),
+                weight: u16::from_be_bytes(e[10..12].try_into().unwrap()),
+            })
+            .collect::<Vec<_>>();
+        // books are written sorted, but don't rely on it for the binary search
+        entries.sort_by_key(|e| e.key);
+        Ok(Self { entries })
+    }
+
+    /// Returns the book moves for the current position, most played first. Moves that aren't
+    /// legal on `board` (e.g. a key collision) are skipped
+    pub fn probe(&self, board: &Board) -> Vec<BookMove> {
+        let key = board.zobrist_hash();
+        let start = self.entries.partition_point(|e| e.key < key);
+
+        let mut moves = self.entries[start..]
+            .iter()
+            .take_while(|e| e.key == key)
+            .filter_map(|e| {
+                let mv = decode_move(board, e.mv)?;
+                Some(BookMove {
+                    mv,
+                    weight: e.weight,
+                })
+            })
+            .collect::<Vec<_>>();
+        moves.sort_by_key(|m| std::cmp::Reverse(m.weight));
+        moves
+    }
+}
+
+/// Picks one of `moves` proportionally to its weight, `seed` being any random number
+pub fn pick(moves: &[BookMove], seed: u64) -> Option<&BookMove> {
+    let total = moves.iter().map(|m| m.weight as u64).sum::<u64>();
+    if total == 0 {
+        return moves.first();
+    }
+    let mut target = seed % total;
+    moves.iter().find(|m| {
+        if target < m.weight as u64 {
+            return true;
+        }
+        target -= m.weight as u64;
+        false
+    })
+}
+
+/// Converts a Polyglot move into the matching legal move on `board`.
+///
+/// Bits 0-5 hold the destination and 6-11 the origin, both as `8 * rank + file`, bits 12-14 the
+/// promotion piece. Castling is written as the king capturing its own rook
+fn decode_move(board: &Board, raw: u16) -> Option<Move> {
+    // flipping the file works both ways
+    let to = zobrist::square((raw & 0x3f) as usize);
+    let from = zobrist::square(((raw >> 6) & 0x3f) as usize);
+    let promotion = match (raw >> 12) & 0x7 {
+        0 => None,
+        1 => Some(PieceType::Knight),
+        2 => Some(PieceType::Bishop),
+        3 => Some(PieceType::Rook),
+        4 => Some(PieceType::Queen),
+        _ => return None,
+    };
+
+    let to = if board.get_piece_at_index(from).get_type() == PieceType::King {
+        match (from, to) {
+            // e1h1, e1a1, e8h8, e8a8
+            (3, 0) => 1,
+            (3, 7) => 5,
+            (59, 56) => 57,
+            (59, 63) => 61,
+            _ => to,
+        }
+    } else {
+        to
+    };
+
+    board
+        .moves_from(from)
+        .find(|m| m.to == to && m.promotion_piece() == promotion)
+}
@@ -0,0 +1,227 @@
+//! Playing in the terminal, without a window: `ruche --cli [--engine white|black] [--fen <fen>]
+//! [--unicode] [--movetime 1000]`. The board is drawn after each move and moves are typed on
+//! stdin in SAN or as UCI writes them, along with a few commands:
+//!
+//! ```text
+//! undo    takes back the last move, and the engine's reply
+//! fen     prints the position
+//! moves   prints the game so far
+//! help    lists the commands
+//! quit    ends the session
+//! ```
+
+use std::io::{BufRead, Write};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::board::{Board, PieceColor, PieceType};
+use crate::engine::{Params, SearchLimits, Searcher};
+use crate::lifecycle;
+use crate::san;
+
+/// Time the engine thinks per move unless `--movetime` says otherwise
+const DEFAULT_MOVETIME: Duration = Duration::from_secs(1);
+
+const HELP: &str = "Type a move in SAN (Nf3, exd5, O-O) or coordinates (g1f3, e7e8q), or:
+  undo   take back the last move
+  fen    print the position
+  moves  print the game so far
+  help   show this help
+  quit   leave";
+
+/// Settings of a terminal session
+#[derive(Debug, Clone)]
+pub struct CliOptions {
+    /// position the game starts from
+    pub fen: String,
+    /// side the engine plays, none for two players at the terminal
+    pub engine: Option<PieceColor>,
+    /// chess figurines instead of letters for the pieces
+    pub unicode: bool,
+    pub movetime: Duration,
+}
+
+impl Default for CliOptions {
+    fn default() -> Self {
+        Self {
+            fen: crate::board::START_FEN.to_string(),
+            engine: None,
+            unicode: false,
+            movetime: DEFAULT_MOVETIME,
+        }
+    }
+}
+
+/// Letter or figurine of the piece on `square`, `.` when it is empty
+fn piece_char(board: &Board, square: usize, unicode: bool) -> char {
+    let piece = board.get_piece_at_index(square);
+    if !unicode || piece.is_none() {
+        return piece.to_fen_char().unwrap_or('.');
+    }
+    let figurines = match piece.get_color() {
+        PieceColor::White => ['♙', '♘', '♗', '♖', '♕', '♔'],
+        PieceColor::Black => ['♟', '♞', '♝', '♜', '♛', '♚'],
+    };
+    match piece.get_type() {
+        PieceType::Pawn => figurines[0],
+        PieceType::Knight => figurines[1],
+        PieceType::Bishop => figurines[2],
+        PieceType::Rook => figurines[3],
+        PieceType::Queen => figurines[4],
+        PieceType::King => figurines[5],
+        PieceType::None => '.',
+    }
+}
+
+/// The board as text, `bottom`'s pieces at the bottom, with the ranks and files around it
+pub fn render(board: &Board, bottom: PieceColor, unicode: bool) -> String {
+    let (ranks, files): (Vec<usize>, Vec<usize>) = match bottom {
+        PieceColor::White => ((0..8).rev().collect(), (0..8).collect()),
+        PieceColor::Black => ((0..8).collect(), (0..8).rev().collect()),
+    };
+    let mut text = String::new();
+    for &rank in &ranks {
+        text.push_str(&format!("{} ", rank + 1));
+        for &file in &files {
+            // squares count from h1, files from a
+            text.push(' ');
+            text.push(piece_char(board, rank * 8 + (7 - file), unicode));
+        }
+        text.push('\n');
+    }
+    text.push_str("  ");
+    for &file in &files {
+        text.push(' ');
+        text.push((b'a' + file as u8) as char);
+    }
+    text
+}
+
+/// The game being played in the terminal
+struct CliGame {
+    options: CliOptions,
+    start: Board,
+    board: Board,
+    searcher: Searcher,
+}
+
+impl CliGame {
+    fn new(options: CliOptions) -> Result<Self, String> {
+        let mut board = Board::new();
+        board.load_position(options.fen.clone());
+        if board.king_square(board.get_turn()).is_none() {
+            return Err(format!("Invalid FEN {:?}", options.fen));
+        }
+        let params = Params::load_or_default(&crate::engine::params_path());
+        Ok(Self {
+            options,
+            start: board.clone(),
+            board,
+            searcher: Searcher::new(Arc::new(AtomicBool::new(false)), params),
+        })
+    }
+
+    fn print_board(&self, out: &mut impl Write) -> std::io::Result<()> {
+        let bottom = self
+            .options
+            .engine
+            .map_or(PieceColor::White, PieceColor::opposite);
+        writeln!(out, "{}", render(&self.board, bottom, self.options.unicode))
+    }
+
+    /// Plays `text` as a move
+    fn play(&mut self, text: &str, out: &mut impl Write) -> std::io::Result<()> {
+        let mo = match san::parse_move(&self.board, text) {
+            Ok(mo) => mo,
+            Err(e) => return writeln!(out, "{}", e),
+        };
+        if self.board.play(&mo).is_err() {
+            return writeln!(out, "{} can't be played", text);
+        }
+        self.print_board(out)
+    }
+
+    /// Lets the engine move if it is its turn and the game goes on
+    fn engine_move(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.options.engine != Some(self.board.get_turn()) || self.is_over() {
+            return Ok(());
+        }
+        let limits = SearchLimits::movetime(self.options.movetime);
+        let Some(mo) = self.searcher.search(&self.board, limits, |_| {}) else {
+            return Ok(());
+        };
+        let text = san::to_san(&self.board, &mo);
+        if self.board.play(&mo).is_err() {
+            return writeln!(out, "The engine found no move");
+        }
+        writeln!(out, "ruche plays {}", text)?;
+        self.print_board(out)
+    }
+
+    fn is_over(&self) -> bool {
+        lifecycle::board_state(&self.start, &self.board).is_some()
+    }
+
+    /// Takes back the last move, and the one before when it was the engine's so that it is the
+    /// player's turn again. Taking back the engine's first move lets it play again
+    fn undo(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.board.unmake_move().is_none() {
+            return writeln!(out, "No move to take back");
+        }
+        if self.options.engine == Some(self.board.get_turn()) {
+            self.board.unmake_move();
+        }
+        self.print_board(out)?;
+        // with no move of the player's before it, the engine is still the one to move
+        self.engine_move(out)
+    }
+
+    /// Handles one line typed by the player, returning false once they quit
+    fn command(&mut self, line: &str, out: &mut impl Write) -> std::io::Result<bool> {
+        match line {
+            "" => {}
+            "quit" | "exit" => return Ok(false),
+            "help" => writeln!(out, "{}", HELP)?,
+            "fen" => writeln!(out, "{}", self.board.to_fen())?,
+            "moves" => {
+                let line = san::line_to_san(&self.start, self.board.get_move_history());
+                writeln!(out, "{}", if line.is_empty() { "-" } else { &line })?;
+            }
+            "undo" => self.undo(out)?,
+            _ if self.is_over() => writeln!(out, "The game is over, undo or quit")?,
+            text => {
+                self.play(text, out)?;
+                self.engine_move(out)?;
+                if let Some(state) = lifecycle::board_state(&self.start, &self.board) {
+                    writeln!(out, "{} {}", state, state.result())?;
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Plays in the terminal until the player quits or stdin closes
+pub fn run(options: CliOptions) -> Result<(), String> {
+    let mut game = CliGame::new(options)?;
+    let stdin = std::io::stdin();
+    let mut out = std::io::stdout();
+    let io = |e: std::io::Error| e.to_string();
+
+    writeln!(out, "{}", HELP).map_err(io)?;
+    game.print_board(&mut out).map_err(io)?;
+    game.engine_move(&mut out).map_err(io)?;
+    loop {
+        let side = format!("{:?}", game.board.get_turn());
+        write!(out, "{}> ", side.to_lowercase()).map_err(io)?;
+        out.flush().map_err(io)?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).map_err(io)? == 0 {
+            return Ok(());
+        }
+        if !game.command(line.trim(), &mut out).map_err(io)? {
+            return Ok(());
+        }
+    }
+}
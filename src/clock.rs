@@ -0,0 +1,147 @@
+//! Chess clock: the time each side has left, ticking only for the side to move
+
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use crate::board::PieceColor;
+use crate::lifecycle::TimeControl;
+
+/// Time left under which a clock is shown in red, and in the window title
+pub const LOW_TIME: Duration = Duration::from_secs(10);
+
+/// Time controls offered by the clock setup, `None` for an untimed game
+pub const PRESETS: [Option<TimeControl>; 8] = [
+    None,
+    Some(TimeControl::new(60, 0, 0)),
+    Some(TimeControl::new(180, 0, 0)),
+    Some(TimeControl::new(180, 2, 0)),
+    Some(TimeControl::new(300, 0, 0)),
+    Some(TimeControl::new(300, 0, 3)),
+    Some(TimeControl::new(600, 5, 0)),
+    Some(TimeControl::new(900, 10, 0)),
+];
+
+/// How a time control is usually written, e.g. `3+2` or `5 d3` with a delay
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let minutes = self.base.as_secs_f32() / 60.0;
+        write!(f, "{}", minutes)?;
+        if !self.increment.is_zero() {
+            write!(f, "+{}", self.increment.as_secs())?;
+        }
+        if !self.delay.is_zero() {
+            write!(f, " d{}", self.delay.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `time` as a clock shows it, e.g. `4:59`, with tenths under ten seconds
+pub fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    if secs < 10 {
+        format!("0:{:02}.{}", secs, time.subsec_millis() / 100)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Both sides' clocks
+#[derive(Debug, Clone)]
+pub struct Clock {
+    white: Duration,
+    black: Duration,
+    increment: Duration,
+    /// time each move may take before the clock starts counting down
+    delay: Duration,
+    /// side whose time is running, and since when
+    running: Option<(PieceColor, Instant)>,
+}
+
+impl Clock {
+    pub fn new(time_control: TimeControl) -> Self {
+        Self {
+            white: time_control.base,
+            black: time_control.base,
+            increment: time_control.increment,
+            delay: time_control.delay,
+            running: None,
+        }
+    }
+
+    /// Clocks of a game picked up again, `white` and `black` being the time each side had left
+    pub fn resumed(time_control: TimeControl, white: Duration, black: Duration) -> Self {
+        Self {
+            white,
+            black,
+            ..Self::new(time_control)
+        }
+    }
+
+    pub fn increment(&self) -> Duration {
+        self.increment
+    }
+
+    fn stored(&mut self, color: PieceColor) -> &mut Duration {
+        match color {
+            PieceColor::White => &mut self.white,
+            PieceColor::Black => &mut self.black,
+        }
+    }
+
+    /// Time `color` has spent on the move it is thinking about, the delay not counting
+    fn spent(&self, color: PieceColor) -> Duration {
+        match self.running {
+            Some((running, since)) if running == color => {
+                since.elapsed().saturating_sub(self.delay)
+            }
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Time left to `color`
+    pub fn remaining(&self, color: PieceColor) -> Duration {
+        let stored = match color {
+            PieceColor::White => self.white,
+            PieceColor::Black => self.black,
+        };
+        stored.saturating_sub(self.spent(color))
+    }
+
+    /// Returns true if the time of `color` is running
+    pub fn is_running(&self, color: PieceColor) -> bool {
+        self.running.is_some_and(|(running, _)| running == color)
+    }
+
+    /// Starts the time of `color`, stopping the other side's
+    pub fn start(&mut self, color: PieceColor) {
+        self.stop();
+        self.running = Some((color, Instant::now()));
+    }
+
+    /// Stops the time of whichever side it is running for, keeping what it spent
+    pub fn stop(&mut self) {
+        if let Some((color, _)) = self.running {
+            let left = self.remaining(color);
+            *self.stored(color) = left;
+            self.running = None;
+        }
+    }
+
+    /// Ends the turn of `color` after its move: stops its time, adds the increment and starts the
+    /// other side's time
+    pub fn press(&mut self, color: PieceColor) {
+        if self.is_running(color) {
+            self.stop();
+        }
+        let increment = self.increment;
+        *self.stored(color) += increment;
+        self.start(color.opposite());
+    }
+
+    /// Side whose time ran out, if any
+    pub fn flagged(&self) -> Option<PieceColor> {
+        let (color, _) = self.running?;
+        self.remaining(color).is_zero().then_some(color)
+    }
+}
@@ -0,0 +1,115 @@
+//! Conditional moves for correspondence and network games: replies set in advance, "if the
+//! opponent plays X, answer Y", played as soon as the opponent's move arrives
+
+use crate::board::Move;
+
+/// A reply to one opponent move, followed by the conditions for the moves after it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    pub trigger: Move,
+    pub reply: Move,
+    pub then: Vec<Condition>,
+}
+
+/// Every condition set for the coming opponent move, and recursively the ones after
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConditionTree {
+    /// turn number the next opponent move is played on
+    turn: u32,
+    conditions: Vec<Condition>,
+}
+
+impl ConditionTree {
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.conditions.clear();
+    }
+
+    /// Adds a line of alternating opponent moves and replies starting on `turn`, a trailing
+    /// opponent move without a reply is ignored. Where the line meets an existing condition
+    /// with a different reply the new reply replaces it, along with what followed
+    pub fn add(&mut self, turn: u32, line: &[Move]) {
+        if self.turn != turn {
+            self.conditions.clear();
+            self.turn = turn;
+        }
+
+        let mut level = &mut self.conditions;
+        for pair in line.chunks_exact(2) {
+            let (trigger, reply) = (&pair[0], &pair[1]);
+            let idx = match level.iter().position(|c| &c.trigger == trigger) {
+                Some(idx) if &level[idx].reply == reply => idx,
+                Some(idx) => {
+                    level[idx].reply = reply.clone();
+                    level[idx].then.clear();
+                    idx
+                }
+                None => {
+                    level.push(Condition {
+                        trigger: trigger.clone(),
+                        reply: reply.clone(),
+                        then: vec![],
+                    });
+                    level.len() - 1
+                }
+            };
+            level = &mut level[idx].then;
+        }
+    }
+
+    /// Every line from the coming opponent move to the end of a branch
+    pub fn lines(&self) -> Vec<Vec<Move>> {
+        fn collect(conditions: &[Condition], prefix: &mut Vec<Move>, lines: &mut Vec<Vec<Move>>) {
+            for c in conditions {
+                prefix.push(c.trigger.clone());
+                prefix.push(c.reply.clone());
+                if c.then.is_empty() {
+                    lines.push(prefix.clone());
+                } else {
+                    collect(&c.then, prefix, lines);
+                }
+                prefix.truncate(prefix.len() - 2);
+            }
+        }
+
+        let mut lines = vec![];
+        collect(&self.conditions, &mut vec![], &mut lines);
+        lines
+    }
+
+    /// Removes the end of the last line returned by [ConditionTree::lines]. The moves before it
+    /// stay, as a shorter line
+    pub fn remove_last(&mut self) {
+        fn remove(conditions: &mut Vec<Condition>) {
+            let Some(last) = conditions.last_mut() else {
+                return;
+            };
+            if last.then.is_empty() {
+                conditions.pop();
+            } else {
+                remove(&mut last.then);
+            }
+        }
+        remove(&mut self.conditions);
+    }
+
+    /// Returns the reply to `mv`, the opponent move played on `turn`, and moves on to the
+    /// conditions that follow it. Any other move ends the conditions
+    pub fn take_reply(&mut self, turn: u32, mv: &Move) -> Option<Move> {
+        let idx = self
+            .conditions
+            .iter()
+            .position(|c| turn == self.turn && &c.trigger == mv);
+        let Some(idx) = idx else {
+            self.conditions.clear();
+            return None;
+        };
+        let condition = self.conditions.swap_remove(idx);
+        self.conditions = condition.then;
+        self.turn += 2;
+        Some(condition.reply)
+    }
+}
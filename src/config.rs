@@ -0,0 +1,315 @@
+//! Settings kept between runs, in `ruche/config.toml` under the platform's config directory.
+//! The file is a flat TOML table, one `name = value` line per setting, strings quoted
+//!
+//! ```text
+//! theme = "default"
+//! palette = "green"
+//! piece_set = "alpha"
+//! sounds = true
+//! animation = "normal"
+//! think_time = 1.0
+//! key.flip_board = "Shift+F"
+//! ```
+//!
+//! Only the shortcuts bound to other keys than their default ones are written, see
+//! [crate::input]
+
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::engine::{Difficulty, SearchLimits};
+use crate::input::{self, Action, InputMap};
+use crate::packs::DEFAULT_PACK;
+use crate::pieces::DEFAULT_PIECE_SET;
+use crate::theme::{IndicatorSize, Palette};
+
+/// Name of the file in the config directory
+const CONFIG_FILE: &str = "config.toml";
+
+/// Returns the path of the config file, `RUCHE_CONFIG` if set, else `ruche/config.toml` in the
+/// platform's config directory, falling back to the working directory when there is none
+pub fn config_path() -> PathBuf {
+    if let Ok(path) = std::env::var("RUCHE_CONFIG") {
+        return PathBuf::from(path);
+    }
+    let env = |name: &str| std::env::var_os(name).map(PathBuf::from);
+    let dir = if cfg!(windows) {
+        env("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env("HOME").map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env("XDG_CONFIG_HOME").or_else(|| env("HOME").map(|home| home.join(".config")))
+    };
+    match dir {
+        Some(dir) => dir.join("ruche").join(CONFIG_FILE),
+        None => PathBuf::from(CONFIG_FILE),
+    }
+}
+
+/// How long a move takes to slide from its square to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnimationSpeed {
+    /// pieces jump to their square
+    Off,
+    Fast,
+    #[default]
+    Normal,
+    Slow,
+}
+
+impl AnimationSpeed {
+    pub const ALL: [AnimationSpeed; 4] = [
+        AnimationSpeed::Off,
+        AnimationSpeed::Fast,
+        AnimationSpeed::Normal,
+        AnimationSpeed::Slow,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AnimationSpeed::Off => "Off",
+            AnimationSpeed::Fast => "Fast",
+            AnimationSpeed::Normal => "Normal",
+            AnimationSpeed::Slow => "Slow",
+        }
+    }
+
+    /// The next speed, or the previous one for a negative `delta`, stopping at either end
+    pub fn step(self, delta: isize) -> Self {
+        let idx = Self::ALL.iter().position(|a| *a == self).unwrap_or(0) as isize;
+        Self::ALL[(idx + delta).clamp(0, Self::ALL.len() as isize - 1) as usize]
+    }
+
+    pub fn duration(self) -> Duration {
+        match self {
+            AnimationSpeed::Off => Duration::ZERO,
+            AnimationSpeed::Fast => Duration::from_millis(100),
+            AnimationSpeed::Normal => Duration::from_millis(200),
+            AnimationSpeed::Slow => Duration::from_millis(350),
+        }
+    }
+}
+
+/// Why a config file couldn't be loaded
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    /// A line that isn't `name = value`
+    InvalidLine(String),
+    /// A value that doesn't fit its setting
+    InvalidValue {
+        name: String,
+        value: String,
+    },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "{}", e),
+            ConfigError::InvalidLine(line) => write!(f, "expected `name = value`, got {:?}", line),
+            ConfigError::InvalidValue { name, value } => {
+                write!(f, "invalid value {} for {}", value, name)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(value: std::io::Error) -> Self {
+        ConfigError::Io(value)
+    }
+}
+
+/// The user's settings, the ones missing from the file keep their default
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// theme pack the board and its sounds come from
+    pub theme: String,
+    /// colors of the board
+    pub palette: Palette,
+    /// size of the legal move dots and of the outlines
+    pub indicator_size: IndicatorSize,
+    /// set the piece images are read from, a directory under `./resource`
+    pub piece_set: String,
+    pub sounds: bool,
+    /// files and ranks written along the edges of the board
+    pub coordinates: bool,
+    /// promotes to a queen without asking
+    pub auto_queen: bool,
+    /// holds each move until it is confirmed
+    pub confirm_moves: bool,
+    pub animation: AnimationSpeed,
+    pub difficulty: Difficulty,
+    /// time budget of each engine move
+    pub think_time: Duration,
+    pub threads: usize,
+    pub ponder: bool,
+    /// turns the board to the side to move in hotseat games
+    pub hotseat_flip: bool,
+    /// keys and mouse buttons of the actions
+    pub input: InputMap,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            theme: DEFAULT_PACK.to_string(),
+            palette: Palette::default(),
+            indicator_size: IndicatorSize::default(),
+            piece_set: DEFAULT_PIECE_SET.to_string(),
+            sounds: true,
+            coordinates: true,
+            auto_queen: false,
+            confirm_moves: false,
+            animation: AnimationSpeed::default(),
+            difficulty: Difficulty::default(),
+            think_time: SearchLimits::default().movetime.unwrap_or_default(),
+            threads: 1,
+            ponder: true,
+            hotseat_flip: false,
+            input: InputMap::default(),
+        }
+    }
+}
+
+/// `value` without its surrounding quotes, if it is a TOML basic string
+fn unquote(value: &str) -> Option<&str> {
+    value.strip_prefix('"')?.strip_suffix('"')
+}
+
+impl Config {
+    /// Reads the config at `path`
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+
+        for line in contents.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| ConfigError::InvalidLine(line.to_string()))?;
+            let (name, value) = (name.trim(), value.trim());
+            let invalid = || ConfigError::InvalidValue {
+                name: name.to_string(),
+                value: value.to_string(),
+            };
+            let string = || unquote(value).map(str::to_string).ok_or_else(invalid);
+            let boolean = || value.parse::<bool>().map_err(|_| invalid());
+
+            match name {
+                "theme" => config.theme = string()?,
+                "palette" => config.palette = Palette::named(&string()?).ok_or_else(invalid)?,
+                "indicator_size" => {
+                    let size = string()?;
+                    config.indicator_size = IndicatorSize::ALL
+                        .into_iter()
+                        .find(|s| s.name().eq_ignore_ascii_case(&size))
+                        .ok_or_else(invalid)?;
+                }
+                "piece_set" => config.piece_set = string()?,
+                "sounds" => config.sounds = boolean()?,
+                "coordinates" => config.coordinates = boolean()?,
+                "auto_queen" => config.auto_queen = boolean()?,
+                "confirm_moves" => config.confirm_moves = boolean()?,
+                "animation" => {
+                    let speed = string()?;
+                    config.animation = AnimationSpeed::ALL
+                        .into_iter()
+                        .find(|a| a.name().eq_ignore_ascii_case(&speed))
+                        .ok_or_else(invalid)?;
+                }
+                "difficulty" => {
+                    let level = string()?;
+                    config.difficulty = Difficulty::ALL
+                        .into_iter()
+                        .find(|d| d.name().eq_ignore_ascii_case(&level))
+                        .ok_or_else(invalid)?;
+                }
+                "think_time" => {
+                    let secs = value.parse::<f32>().map_err(|_| invalid())?;
+                    config.think_time = Duration::try_from_secs_f32(secs).map_err(|_| invalid())?;
+                }
+                "threads" => config.threads = value.parse::<usize>().map_err(|_| invalid())?.max(1),
+                "ponder" => config.ponder = boolean()?,
+                "hotseat_flip" => config.hotseat_flip = boolean()?,
+                _ if name.starts_with("key.") => {
+                    let action = Action::named(&name["key.".len()..]).ok_or_else(invalid)?;
+                    let bindings = input::parse_bindings(&string()?).ok_or_else(invalid)?;
+                    config.input.bind(action, bindings);
+                }
+                // a setting of a newer version, kept working with this one
+                _ => tracing::warn!("Ignoring unknown setting {:?}", name),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Reads the config file if there is one, falling back to the defaults
+    pub fn load_or_default(path: &Path) -> Self {
+        match Self::load(path) {
+            Ok(config) => {
+                tracing::info!("Loaded settings {:?}", path);
+                config
+            }
+            Err(ConfigError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                tracing::warn!("Ignoring settings {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Writes every setting to `path`, creating its directory
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_string())
+    }
+}
+
+/// Writes the config file contents
+impl Display for Config {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "theme = {:?}", self.theme)?;
+        writeln!(f, "palette = {:?}", self.palette.name.to_ascii_lowercase())?;
+        writeln!(
+            f,
+            "indicator_size = {:?}",
+            self.indicator_size.name().to_ascii_lowercase()
+        )?;
+        writeln!(f, "piece_set = {:?}", self.piece_set)?;
+        writeln!(f, "sounds = {}", self.sounds)?;
+        writeln!(f, "coordinates = {}", self.coordinates)?;
+        writeln!(f, "auto_queen = {}", self.auto_queen)?;
+        writeln!(f, "confirm_moves = {}", self.confirm_moves)?;
+        writeln!(
+            f,
+            "animation = {:?}",
+            self.animation.name().to_ascii_lowercase()
+        )?;
+        writeln!(f, "difficulty = {:?}", self.difficulty.name())?;
+        writeln!(f, "think_time = {}", self.think_time.as_secs_f32())?;
+        writeln!(f, "threads = {}", self.threads)?;
+        writeln!(f, "ponder = {}", self.ponder)?;
+        writeln!(f, "hotseat_flip = {}", self.hotseat_flip)?;
+        for (action, bindings) in self.input.remapped() {
+            writeln!(
+                f,
+                "key.{} = {:?}",
+                action.name(),
+                input::format_bindings(bindings)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+//! Games kept going side by side, correspondence style: each one waits in its own file of the
+//! games directory, written like the game left unfinished on quit (see [crate::saved_game]),
+//! until it is picked up again from the game switcher. A game leaves the directory once it is
+//! over. Network games belong to their connection and aren't kept
+
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::saved_game::SavedGame;
+
+/// Directory the games are kept in when `RUCHE_GAMES_DIR` isn't set
+const DEFAULT_DIR: &str = "games";
+
+/// Returns the directory of the ongoing games, `RUCHE_GAMES_DIR` or `games` in the working
+/// directory
+pub fn games_dir() -> PathBuf {
+    std::env::var("RUCHE_GAMES_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_DIR))
+}
+
+/// A game waiting in the games directory
+#[derive(Debug, Clone)]
+pub struct OngoingGame {
+    pub path: PathBuf,
+    pub game: SavedGame,
+}
+
+/// Reads the games of `dir`, oldest first. Files that can't be read are skipped with a
+/// warning
+pub fn list(dir: &Path) -> Vec<OngoingGame> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "pgn"))
+        .collect::<Vec<_>>();
+    // the names are the times the games were first put aside
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| match read(&path) {
+            Ok(game) => Some(OngoingGame { path, game }),
+            Err(e) => {
+                tracing::warn!("Skipped the game {:?}: {}", path, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Reads the game kept at `path`
+pub fn read(path: &Path) -> Result<SavedGame, crate::saved_game::SavedGameError> {
+    SavedGame::parse(&std::fs::read_to_string(path)?)
+}
+
+/// A file of `dir` for a game put aside for the first time
+pub fn new_path(dir: &Path) -> PathBuf {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    dir.join(format!("game-{}.pgn", millis))
+}
@@ -0,0 +1,77 @@
+//! Names of the openings and their ECO codes, from the table bundled in `extra/eco.tsv`.
+//! Openings are recognised by position, so a line reached by transposition is named all the
+//! same
+
+use std::collections::HashMap;
+
+use crate::board::{Board, Move, START_FEN};
+use crate::san;
+
+/// Tab separated `eco`, `name` and `pgn` columns under a header row
+const ECO_TABLE: &str = include_str!("../extra/eco.tsv");
+
+/// An opening of the table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    /// code of the Encyclopaedia of Chess Openings, `C50` for instance
+    pub eco: String,
+    pub name: String,
+}
+
+/// The openings of the table by the Zobrist hash of the position their line leads to
+pub struct EcoTable {
+    positions: HashMap<u64, Opening>,
+}
+
+impl EcoTable {
+    /// Parses the bundled table, leaving out the lines that can't be played. Of two lines
+    /// reaching the same position, the first one names it
+    pub fn bundled() -> Self {
+        let mut positions = HashMap::new();
+        for row in ECO_TABLE.lines().skip(1) {
+            let mut columns = row.split('\t');
+            let (Some(eco), Some(name), Some(pgn)) =
+                (columns.next(), columns.next(), columns.next())
+            else {
+                continue;
+            };
+
+            let mut board = Board::new();
+            board.load_position(START_FEN.to_string());
+            let played = pgn
+                .split_whitespace()
+                .filter(|token| !token.ends_with('.'))
+                .try_for_each(|token| {
+                    let mv = san::parse_san(&board, token).map_err(|e| e.to_string())?;
+                    board
+                        .play(&mv)
+                        .map_err(|_| format!("{} can't be played", token))
+                });
+            if let Err(e) = played {
+                tracing::warn!("Skipped opening {} {}: {}", eco, name, e);
+                continue;
+            }
+            positions
+                .entry(board.zobrist_hash())
+                .or_insert_with(|| Opening {
+                    eco: eco.to_string(),
+                    name: name.to_string(),
+                });
+        }
+        Self { positions }
+    }
+
+    /// The opening of the last position of the game named in the table, `history` being played
+    /// from `start`
+    pub fn classify(&self, start: &Board, history: &[Move]) -> Option<&Opening> {
+        let mut board = start.clone();
+        let mut opening = self.positions.get(&board.zobrist_hash());
+        for mv in history {
+            if board.play(mv).is_err() {
+                break;
+            }
+            opening = self.positions.get(&board.zobrist_hash()).or(opening);
+        }
+        opening
+    }
+}
@@ -0,0 +1,415 @@
+//! Matches between two engines without a window: ruche with its parameters or another set of
+//! them, or any engine speaking UCI. Each opening is played twice with the colors swapped, on a
+//! clock that is enforced, and the games are written as PGN
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::board::{Board, Move, PieceColor, START_FEN};
+use crate::engine::{self, Params, SearchLimits, Searcher};
+use crate::pgn;
+use crate::uci_client::{UciClient, UciEvent};
+
+/// Name of the built-in engine in an engine spec
+const BUILT_IN: &str = "ruche";
+/// Games still going after this many plies are drawn
+const MAX_PLIES: usize = 400;
+/// Time between two looks at an external engine's output
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Why a match couldn't be played
+#[derive(Debug)]
+pub enum MatchError {
+    Io(std::io::Error),
+    /// A time control that isn't `base+increment` in seconds
+    InvalidTimeControl(String),
+}
+
+impl Display for MatchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchError::Io(e) => write!(f, "{}", e),
+            MatchError::InvalidTimeControl(tc) => {
+                write!(f, "expected `base+increment` in seconds, got {:?}", tc)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for MatchError {
+    fn from(value: std::io::Error) -> Self {
+        MatchError::Io(value)
+    }
+}
+
+/// Time each side starts with and gains after each of its moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration,
+}
+
+impl TimeControl {
+    /// Parses `base+increment` in seconds e.g. `10+0.1`, or `base` alone without increment
+    pub fn parse(text: &str) -> Result<Self, MatchError> {
+        let invalid = || MatchError::InvalidTimeControl(text.to_string());
+        let (base, increment) = text.split_once('+').unwrap_or((text, "0"));
+        let secs = |s: &str| {
+            s.trim()
+                .parse::<f32>()
+                .ok()
+                .filter(|s| s.is_finite() && *s >= 0.0)
+                .map(Duration::from_secs_f32)
+        };
+        Ok(Self {
+            base: secs(base).ok_or_else(invalid)?,
+            increment: secs(increment).ok_or_else(invalid)?,
+        })
+    }
+}
+
+/// The PGN `TimeControl` tag e.g. `10+0.1`
+impl Display for TimeControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}+{}",
+            self.base.as_secs_f32(),
+            self.increment.as_secs_f32()
+        )
+    }
+}
+
+/// An engine of the match, as given on the command line
+#[derive(Debug, Clone)]
+pub enum Player {
+    /// `ruche` for the parameters file in use, `ruche:<file>` for another one
+    BuiltIn {
+        params: Params,
+        file: Option<PathBuf>,
+    },
+    /// anything else, the path of an engine speaking UCI
+    External(PathBuf),
+}
+
+impl Player {
+    pub fn parse(spec: &str) -> Self {
+        if spec == BUILT_IN {
+            return Player::BuiltIn {
+                params: Params::load_or_default(&engine::params_path()),
+                file: None,
+            };
+        }
+        match spec
+            .strip_prefix(BUILT_IN)
+            .and_then(|s| s.strip_prefix(':'))
+        {
+            Some(file) => Player::BuiltIn {
+                params: Params::load_or_default(Path::new(file)),
+                file: Some(PathBuf::from(file)),
+            },
+            None => Player::External(PathBuf::from(spec)),
+        }
+    }
+
+    fn start(&self) -> Result<Engine, MatchError> {
+        Ok(match self {
+            Player::BuiltIn { params, file } => {
+                let name = match file {
+                    Some(file) => format!("{} ({})", BUILT_IN, file.display()),
+                    None => BUILT_IN.to_string(),
+                };
+                let searcher = Searcher::new(Arc::new(AtomicBool::new(false)), params.clone());
+                Engine::BuiltIn(name, Box::new(searcher))
+            }
+            Player::External(path) => Engine::External(Box::new(UciClient::spawn(path)?)),
+        })
+    }
+}
+
+/// A running engine of the match
+enum Engine {
+    BuiltIn(String, Box<Searcher>),
+    External(Box<UciClient>),
+}
+
+/// What an engine did with its turn
+enum Reply {
+    Move(Move),
+    /// it had no legal move, or claimed it had none
+    NoMove,
+    /// its clock ran out
+    Timeout,
+    /// an external engine stopped answering
+    Crashed,
+}
+
+impl Engine {
+    fn name(&self) -> String {
+        match self {
+            Engine::BuiltIn(name, _) => name.clone(),
+            Engine::External(client) => client.name().unwrap_or("unknown").to_string(),
+        }
+    }
+
+    /// Asks for a move in `board`, the engine to move having `clocks` of its own clock left
+    fn think(&mut self, board: &Board, clocks: &Clocks) -> Reply {
+        let start = Instant::now();
+        let remaining = clocks.remaining(board.get_turn());
+        match self {
+            Engine::BuiltIn(_, searcher) => {
+                let limits = SearchLimits::for_clock(remaining, clocks.increment, None);
+                let best = searcher.search(board, limits, |_| {});
+                match best {
+                    _ if start.elapsed() > remaining => Reply::Timeout,
+                    Some(mv) => Reply::Move(mv),
+                    None => Reply::NoMove,
+                }
+            }
+            Engine::External(client) => {
+                let go = client.go_clock(
+                    board,
+                    clocks.remaining(PieceColor::White),
+                    clocks.remaining(PieceColor::Black),
+                    clocks.increment,
+                );
+                if go.is_err() {
+                    return Reply::Crashed;
+                }
+                loop {
+                    match client.poll() {
+                        Some(UciEvent::BestMove(Some(mv))) => return Reply::Move(mv),
+                        Some(UciEvent::BestMove(None)) => return Reply::NoMove,
+                        Some(UciEvent::Exited) => return Reply::Crashed,
+                        None if start.elapsed() > remaining => {
+                            let _ = client.stop();
+                            return Reply::Timeout;
+                        }
+                        None => std::thread::sleep(POLL_INTERVAL),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Clocks of both sides during a game
+struct Clocks {
+    white: Duration,
+    black: Duration,
+    increment: Duration,
+}
+
+impl Clocks {
+    fn new(tc: TimeControl) -> Self {
+        Self {
+            white: tc.base,
+            black: tc.base,
+            increment: tc.increment,
+        }
+    }
+
+    fn remaining(&self, color: PieceColor) -> Duration {
+        match color {
+            PieceColor::White => self.white,
+            PieceColor::Black => self.black,
+        }
+    }
+
+    /// Takes the time `color` spent on its move off its clock and adds the increment
+    fn spend(&mut self, color: PieceColor, spent: Duration) {
+        let clock = match color {
+            PieceColor::White => &mut self.white,
+            PieceColor::Black => &mut self.black,
+        };
+        *clock = clock.saturating_sub(spent) + self.increment;
+    }
+}
+
+/// How a game ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win(PieceColor),
+    Draw,
+}
+
+impl Outcome {
+    /// The PGN result e.g. `1-0`
+    pub fn result(self) -> &'static str {
+        match self {
+            Outcome::Win(PieceColor::White) => "1-0",
+            Outcome::Win(PieceColor::Black) => "0-1",
+            Outcome::Draw => "1/2-1/2",
+        }
+    }
+}
+
+/// A finished game of the match
+#[derive(Debug, Clone)]
+pub struct GameRecord {
+    pub white: String,
+    pub black: String,
+    /// the game, from the starting position, its moves in the move history
+    pub board: Board,
+    pub outcome: Outcome,
+    /// why it ended e.g. `checkmate`
+    pub termination: String,
+}
+
+impl GameRecord {
+    /// Writes the game as PGN, the moves wrapped at 80 columns
+    pub fn to_pgn(&self, round: usize, tc: TimeControl) -> String {
+        let tags = [
+            ("Event", "ruche engine match".to_string()),
+            ("Site", "?".to_string()),
+            ("Date", pgn::today()),
+            ("Round", round.to_string()),
+            ("White", self.white.clone()),
+            ("Black", self.black.clone()),
+            ("Result", self.outcome.result().to_string()),
+            ("TimeControl", tc.to_string()),
+            ("Termination", self.termination.clone()),
+        ];
+        let mut start = Board::new();
+        start.load_position(START_FEN.to_string());
+        pgn::write_game(
+            &tags,
+            &start,
+            self.board.get_move_history(),
+            self.outcome.result(),
+        )
+    }
+}
+
+/// Plays a game from `opening`, returning how it ended
+fn play_game(
+    opening: &Board,
+    white: &mut Engine,
+    black: &mut Engine,
+    tc: TimeControl,
+) -> (Board, Outcome, String) {
+    let mut board = opening.clone();
+    let mut clocks = Clocks::new(tc);
+    let mut seen = HashMap::<u64, u32>::new();
+
+    loop {
+        let mover = board.get_turn();
+        let repetitions = seen.entry(board.zobrist_hash()).or_default();
+        *repetitions += 1;
+        if *repetitions >= 3 {
+            return (board, Outcome::Draw, "threefold repetition".to_string());
+        }
+        if board.legal_moves().next().is_none() {
+            return if board.is_in_check(mover) {
+                (
+                    board,
+                    Outcome::Win(mover.opposite()),
+                    "checkmate".to_string(),
+                )
+            } else {
+                (board, Outcome::Draw, "stalemate".to_string())
+            };
+        }
+        if board.halfmove_clock() >= 100 {
+            return (board, Outcome::Draw, "fifty-move rule".to_string());
+        }
+        if board.has_insufficient_material() {
+            return (board, Outcome::Draw, "insufficient material".to_string());
+        }
+        if board.get_move_history().len() >= MAX_PLIES {
+            return (board, Outcome::Draw, "move limit".to_string());
+        }
+
+        let engine = match mover {
+            PieceColor::White => &mut *white,
+            PieceColor::Black => &mut *black,
+        };
+        let start = Instant::now();
+        let reply = engine.think(&board, &clocks);
+        clocks.spend(mover, start.elapsed());
+        let loss = |reason: &str| (Outcome::Win(mover.opposite()), reason.to_string());
+        let (outcome, termination) = match reply {
+            Reply::Move(mv) if board.is_legal(&mv) && board.play(&mv).is_ok() => continue,
+            Reply::Move(mv) => loss(&format!("illegal move {}", mv)),
+            Reply::NoMove => loss("no move with legal moves left"),
+            Reply::Timeout => loss("time forfeit"),
+            Reply::Crashed => loss("engine crashed"),
+        };
+        return (board, outcome, termination);
+    }
+}
+
+/// Score of the first engine of a match
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MatchScore {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl MatchScore {
+    /// Points out of the games played, a draw counting half
+    pub fn points(&self) -> f32 {
+        self.wins as f32 + self.draws as f32 / 2.0
+    }
+}
+
+/// Plays `games` games between `first` and `second`, the first engine taking white in the odd
+/// ones, each pair of games starting from the same random opening. `on_game` gets each game as
+/// it ends
+pub fn run_match<F>(
+    first: &Player,
+    second: &Player,
+    games: usize,
+    tc: TimeControl,
+    mut on_game: F,
+) -> Result<MatchScore, MatchError>
+where
+    F: FnMut(usize, &GameRecord) -> Result<(), MatchError>,
+{
+    let mut first = first.start()?;
+    let mut second = second.start()?;
+    let mut score = MatchScore::default();
+    let mut opening = Board::new();
+
+    for round in 1..=games {
+        if round % 2 == 1 {
+            let seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos() as u64;
+            opening = engine::random_opening(seed);
+        }
+        let first_white = round % 2 == 1;
+        let (white, black) = if first_white {
+            (&mut first, &mut second)
+        } else {
+            (&mut second, &mut first)
+        };
+        let (board, outcome, termination) = play_game(&opening, white, black, tc);
+        let record = GameRecord {
+            white: white.name(),
+            black: black.name(),
+            board,
+            outcome,
+            termination,
+        };
+
+        let first_color = if first_white {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        };
+        match outcome {
+            Outcome::Draw => score.draws += 1,
+            Outcome::Win(color) if color == first_color => score.wins += 1,
+            Outcome::Win(_) => score.losses += 1,
+        }
+        on_game(round, &record)?;
+    }
+    Ok(score)
+}
@@ -0,0 +1,18 @@
+use crate::board::PieceColor;
+
+/// Events emitted by [crate::game::Game] whenever the board changes, so widgets (and anything
+/// else interested) can react without polling the board every frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardEvent {
+    /// A move has been applied to the board
+    MoveMade {
+        from: usize,
+        to: usize,
+        /// a piece was captured
+        capture: bool,
+        /// the move gives check
+        check: bool,
+    },
+    /// The side to move has changed
+    TurnChanged(PieceColor),
+}
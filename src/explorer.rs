@@ -0,0 +1,136 @@
+//! Opening explorer over a collection of games in PGN. The positions reached in the opening of
+//! each game are indexed by their Zobrist hash with the moves played from them and how the
+//! games went on to end, so a line reached by transposition counts all of its games
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::board::{Board, Move};
+use crate::pgn;
+use crate::san;
+
+/// Database read when `RUCHE_DATABASE` isn't set
+const DEFAULT_DATABASE: &str = "games.pgn";
+/// Plies indexed from the start of each game, past the opening few games share a position
+const INDEXED_PLIES: usize = 40;
+
+/// Returns the path of the game database, `RUCHE_DATABASE` or `games.pgn` in the working
+/// directory
+pub fn database_path() -> PathBuf {
+    std::env::var("RUCHE_DATABASE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(DEFAULT_DATABASE))
+}
+
+/// How the games of a move ended
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Results {
+    pub white_wins: u32,
+    pub draws: u32,
+    pub black_wins: u32,
+}
+
+impl Results {
+    /// Counts a game ending in `result`, games that aren't over aren't counted
+    fn add(&mut self, result: &str) {
+        match result {
+            "1-0" => self.white_wins += 1,
+            "0-1" => self.black_wins += 1,
+            "1/2-1/2" => self.draws += 1,
+            _ => {}
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.white_wins + self.draws + self.black_wins
+    }
+
+    /// Shares of white wins, draws and black wins, in percent
+    pub fn percentages(&self) -> (u32, u32, u32) {
+        let games = self.games().max(1);
+        (
+            self.white_wins * 100 / games,
+            self.draws * 100 / games,
+            self.black_wins * 100 / games,
+        )
+    }
+}
+
+/// A move played in the database from a position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplorerMove {
+    pub mv: Move,
+    pub results: Results,
+}
+
+/// The moves played from each position of the openings of the database
+#[derive(Debug, Default)]
+pub struct Explorer {
+    positions: HashMap<u64, Vec<ExplorerMove>>,
+    /// games indexed, those with an illegal move being indexed up to it
+    games: usize,
+}
+
+impl Explorer {
+    /// Reads and indexes the database at `path`
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        Ok(Self::index(&std::fs::read_to_string(path)?))
+    }
+
+    /// Indexes the games of a PGN collection. Unfinished games are left out as they say
+    /// nothing of how a move fares
+    pub fn index(text: &str) -> Self {
+        let mut explorer = Self::default();
+        for game in pgn::parse_games(text) {
+            if game.result == "*" || game.result.is_empty() {
+                continue;
+            }
+            let Ok(mut board) = game.start() else {
+                continue;
+            };
+            explorer.games += 1;
+            for text in game.moves.iter().take(INDEXED_PLIES) {
+                let Ok(mv) = san::parse_san(&board, text) else {
+                    break;
+                };
+                let moves = explorer.positions.entry(board.zobrist_hash()).or_default();
+                match moves.iter_mut().find(|m| m.mv == mv) {
+                    Some(known) => known.results.add(&game.result),
+                    None => {
+                        let mut results = Results::default();
+                        results.add(&game.result);
+                        moves.push(ExplorerMove {
+                            mv: mv.clone(),
+                            results,
+                        });
+                    }
+                }
+                if board.play(&mv).is_err() {
+                    break;
+                }
+            }
+        }
+        explorer
+    }
+
+    /// Number of games indexed
+    pub fn games(&self) -> usize {
+        self.games
+    }
+
+    /// Returns the moves played from the position on `board`, most played first. Moves that
+    /// aren't legal on `board` (e.g. a key collision) are skipped
+    pub fn probe(&self, board: &Board) -> Vec<ExplorerMove> {
+        let Some(moves) = self.positions.get(&board.zobrist_hash()) else {
+            return Vec::new();
+        };
+        let legal = board.legal_moves().collect::<Vec<_>>();
+        let mut moves = moves
+            .iter()
+            .filter(|m| legal.contains(&m.mv))
+            .cloned()
+            .collect::<Vec<_>>();
+        moves.sort_by_key(|m| std::cmp::Reverse(m.results.games()));
+        moves
+    }
+}
@@ -0,0 +1,116 @@
+//! Pictures of the board written to disk: a PNG of the position on screen or an animated GIF
+//! of the whole game, rendered offscreen at the size the board has on screen
+
+use std::ffi::CString;
+use std::fmt::Display;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use raylib::core::color::Color;
+use raylib::core::drawing::{RaylibDraw, RaylibTextureMode, RaylibTextureModeExt};
+use raylib::core::texture::{Image, RaylibRenderTexture2D, RaylibTexture2D};
+use raylib::core::{RaylibHandle, RaylibThread};
+
+use crate::widgets::BoardRect;
+
+/// What the board is saved as
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// the position on screen
+    Png,
+    /// every position of the game, one after the other
+    Gif,
+}
+
+impl ExportFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            ExportFormat::Png => "PNG",
+            ExportFormat::Gif => "GIF",
+        }
+    }
+
+    /// File in the working directory offered until the user types another path
+    pub fn default_path(self) -> String {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let extension = match self {
+            ExportFormat::Png => "png",
+            ExportFormat::Gif => "gif",
+        };
+        format!("ruche-{}.{}", secs, extension)
+    }
+}
+
+/// Why the board couldn't be saved
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    /// raylib failed to create the framebuffer, read it back or write the image
+    Render(String),
+}
+
+impl Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Io(e) => write!(f, "{}", e),
+            ExportError::Render(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(value: std::io::Error) -> Self {
+        ExportError::Io(value)
+    }
+}
+
+/// Draws with `draw` into an offscreen framebuffer the size of the window and reads back the
+/// part covered by the board
+pub fn render<F>(
+    rl: &mut RaylibHandle,
+    thread: &RaylibThread,
+    rect: BoardRect,
+    draw: F,
+) -> Result<Image, ExportError>
+where
+    F: FnOnce(&mut RaylibTextureMode<&mut RaylibHandle>),
+{
+    let (width, height) = (rl.get_screen_width(), rl.get_screen_height());
+    let mut target = rl
+        .load_render_texture(thread, width as u32, height as u32)
+        .map_err(ExportError::Render)?;
+    {
+        let mut handle = &mut *rl;
+        let mut d = handle.begin_texture_mode(thread, &mut target);
+        d.clear_background(Color::WHITE);
+        draw(&mut d);
+    }
+    let mut image = target
+        .texture()
+        .get_texture_data()
+        .map_err(ExportError::Render)?;
+    // framebuffers are stored bottom row first
+    image.flip_vertical();
+    image.crop(raylib::ffi::Rectangle {
+        x: rect.x as f32,
+        y: rect.y as f32,
+        width: rect.size as f32,
+        height: rect.size as f32,
+    });
+    Ok(image)
+}
+
+/// Writes `image` to `path` as a PNG
+pub fn write_png(image: &Image, path: &Path) -> Result<(), ExportError> {
+    let name = CString::new(path.to_string_lossy().as_bytes())
+        .map_err(|e| ExportError::Render(e.to_string()))?;
+    // SAFETY: ExportImage only reads the image and the file name, both alive for the call
+    if unsafe { raylib::ffi::ExportImage(**image, name.as_ptr()) } {
+        Ok(())
+    } else {
+        Err(ExportError::Render(format!("failed to write {:?}", path)))
+    }
+}
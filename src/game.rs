@@ -1,5 +1,122 @@
-use crate::board::{self, Move, MoveError, Piece, PieceColor, PieceType};
-use std::{collections::HashMap, path::Path};
+use crate::audit::{AuditEvent, ClockAdjustment, ClockAuditLog};
+use crate::autosave::{Autosave, AutosaveConfig};
+use crate::board::{
+    self, Board, BoardBuilder, Move, Odds, Piece, PieceColor, PieceType, Variant, START_FEN,
+};
+use crate::book::{self, Book, BookMove};
+use crate::clock::{self, Clock, LOW_TIME};
+use crate::conditional::ConditionTree;
+use crate::config::{AnimationSpeed, Config};
+use crate::correspondence;
+use crate::eco::EcoTable;
+use crate::engine::{self, Difficulty, EngineHandle, EngineMessage, Params, SearchLimits};
+use crate::events::BoardEvent;
+use crate::explorer::{self, Explorer};
+use crate::export::{self, ExportError, ExportFormat};
+use crate::gamepad::Gamepad;
+use crate::gif::GifEncoder;
+use crate::input::InputMap;
+use crate::lifecycle::{self, GameState, Lifecycle, OfferStatus, TimeControl};
+use crate::narrator::Narrator;
+use crate::net::{self, Link, LinkEvent, NetMessage};
+use crate::packs::PackManager;
+use crate::pgn;
+use crate::pieces::{self, PieceSets, PIECES_DIR};
+use crate::profile::{Paths, Profile, ProfileError};
+use crate::puzzles::{self, Attempt, Puzzle, PuzzleTrainer, PUZZLES_FILE};
+use crate::repertoire::{DrillMove, Repertoire, RepertoireDrill};
+use crate::review::{MoveQuality, Review};
+use crate::san;
+use crate::saved_game::SavedGame;
+use crate::sound::{SoundKind, SoundPlayer};
+use crate::stats;
+use crate::theme::{IndicatorSize, Theme, THEMES_DIR};
+use crate::touch::Touch;
+use crate::uci_client::{self, UciClient, UciEvent};
+use crate::widgets::{
+    annotation_color, draw_arrow, draw_book_moves, draw_captures, draw_clock, draw_conditions,
+    draw_coordinates, draw_passed_pawns, draw_pocket, pocket_slot_at, AnalysisPanel, Annotations,
+    BackupPicker, BoardRect, EngineStats, EvalBar, EvalGraph, ExplorerPanel, MoveInput, MoveList,
+    Notice, TurnIndicator, ANALYSIS_LINES, ANALYSIS_PANEL_WIDTH, CAPTURES_HEIGHT, EVAL_BAR_WIDTH,
+    EVAL_GRAPH_HEIGHT, EXPLORER_PANEL_WIDTH, MOVE_LIST_WIDTH,
+};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Time the engine can think per move, in seconds, cycled through from the keyboard
+const THINK_TIMES: [f32; 5] = [0.5, 1.0, 2.0, 5.0, 10.0];
+/// Time the evaluation bar analyses each position for
+const ANALYSIS_TIME: Duration = Duration::from_secs(3);
+/// Time the engine looks for a hint
+const HINT_TIME: Duration = Duration::from_secs(1);
+/// Pieces a pawn can promote to, in the order the promotion picker shows them
+const PROMOTION_PIECES: [PieceType; 4] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+];
+/// Where each piece of the promotion picker sits from the promotion square when it is a cross,
+/// the queen above
+const RADIAL_PROMOTION: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+/// Time each position of an exported GIF is shown, in hundredths of a second
+const GIF_FRAME_DELAY: u16 = 100;
+/// Time the final position of an exported GIF stays before it loops
+const GIF_LAST_FRAME_DELAY: u16 = 300;
+/// Pause before the opponent's move of a puzzle or a drill is played, to see the move it answers
+const REPLY_DELAY: Duration = Duration::from_millis(500);
+/// Smallest squares the board shrinks to, in pixels
+pub const MIN_CELL_SIZE: i32 = 24;
+
+/// What the opening book is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookMode {
+    Off,
+    /// the book moves of the current position are shown on the board
+    ShowMoves,
+    /// the engine plays from the book while the position is in it
+    EnginePlays,
+}
+
+/// Pieces drawn on the board, fewer of them for visualization training. Moves are still
+/// accepted, typed in the move box or played blind with the mouse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PieceVisibility {
+    #[default]
+    All,
+    /// only the pieces of the side the user plays
+    Own,
+    /// an empty board, blindfold play
+    Hidden,
+}
+
+/// Line of conditional moves being recorded on a copy of the position
+#[derive(Debug, Clone)]
+struct ConditionEditor {
+    /// the real position, put back once the line is recorded
+    board: Board,
+    /// turn number of the opponent move the line starts with
+    turn: u32,
+    line: Vec<Move>,
+}
+
+/// Search of the engine on the position after the reply it expects, run while the user thinks.
+/// If the user plays that reply the search carries on as the search of the engine's move,
+/// otherwise it is dropped
+struct Ponder {
+    handle: EngineHandle,
+    /// the reply the search assumes
+    expected: Move,
+    started: Instant,
+    /// best line so far, the engine ponders on its second move in turn
+    pv: Vec<Move>,
+    /// the search's move, if it ended before the user moved
+    best: Option<Option<Move>>,
+}
 
 #[derive(Debug, Clone, Default)]
 pub struct Vector2 {
@@ -13,456 +130,4335 @@ impl PartialEq for Vector2 {
     }
 }
 
-trait ToVector2 {
-    fn to_vec2(&self) -> Vector2;
-}
-
-impl ToVector2 for usize {
-    fn to_vec2(&self) -> Vector2 {
-        let x: f32 = (*self as f32 % 8.0).floor();
-        let y: f32 = (*self as f32 / 8.0).floor();
-
-        Vector2 { x, y }
-    }
-}
-
 pub struct Game {
     _size: u32,
     x_offset: u32,
     y_offset: u32,
     cell_size: u32,
     pub board: board::Board,
+    /// position the move list starts from, earlier positions are replayed from it
+    start_board: Board,
+    /// earlier position shown instead of the current one, with the plies played to reach it
+    viewing: Option<(usize, Board)>,
+    /// moves taken back, the next one to replay last
+    redo: Vec<Move>,
 
     cursor: Vector2,
+    /// position of the mouse on screen
+    mouse: Vector2,
     pub selected: Option<Vector2>,
+    /// squares the selected piece can move to, each with whether the move captures
+    targets: Vec<(usize, bool)>,
+    /// piece of the pocket picked to drop in crazyhouse, its drops shown as the targets
+    pocket_selected: Option<PieceType>,
+    /// the selected piece follows the mouse until the button is released
+    dragging: bool,
+    /// square of the keyboard cursor while the board is played from the keyboard, in board
+    /// coordinates. Moving the mouse hands the board back to it
+    key_cursor: Option<(usize, usize)>,
+    /// piece of the promotion picker chosen from the keyboard
+    key_promotion: usize,
+    /// arrows and circles drawn on each position of the game, by Zobrist hash
+    annotations: HashMap<u64, Annotations>,
+    /// square the right button was pressed on, where the arrow being drawn starts
+    annotation_from: Option<usize>,
     image_map: HashMap<Piece, raylib::core::texture::Texture2D>,
 
     pub pawn_promotion: bool,
     can_promote_to: Vec<Move>,
-    pawn_promotion_img_map: HashMap<Piece, raylib::core::texture::Texture2D>,
 
-    pawn_promotion_from_to: (usize, usize),
+    pub audit_log: ClockAuditLog,
+
+    /// events produced since the last call to [Game::update]
+    events: Vec<BoardEvent>,
+    turn_indicator: TurnIndicator,
+
+    /// analysis setting where either side's pieces can be moved regardless of turn
+    pub free_move: bool,
+
+    autosave: Autosave,
+    /// open when the user is picking a backup to restore
+    pub backup_picker: Option<BackupPicker>,
+    /// keys and mouse buttons of the actions
+    pub input: InputMap,
+    /// the finger on a touchscreen
+    pub touch: Touch,
+    /// the stick of a controller
+    pub gamepad: Gamepad,
+
+    /// side the user plays, facing the engine or the network peer
+    seat: PieceColor,
+    /// turns the board to the user's side whenever they take a seat
+    pub auto_flip: bool,
+    /// turns the board to the side to move after each move when both sides are played here
+    hotseat_flip: bool,
+    /// files and ranks written along the edges of the board
+    show_coordinates: bool,
+    /// promotes to a queen without opening the picker
+    auto_queen: bool,
+    /// opens the promotion picker even with auto-queen on, for an underpromotion
+    pub force_promotion_picker: bool,
+    /// holds the moves picked on the board until they are confirmed
+    confirm_moves: bool,
+    /// move picked and waiting for a second click or Enter
+    pending_move: Option<Move>,
+    /// the engines stand still while the pause menu is shown
+    paused: bool,
+    /// the clock was running when the game was paused, and runs again on resume
+    clock_paused: bool,
+    animation_speed: AnimationSpeed,
+    /// last move while its piece slides to its square, and when it was played
+    animation: Option<(Move, Instant)>,
+    /// side whose pieces start at the bottom of the screen
+    bottom: PieceColor,
+    pieces_shown: PieceVisibility,
+    /// size of the marks drawn on the squares
+    indicator_size: IndicatorSize,
+
+    /// the side played by the internal engine, if any
+    engine_color: Option<PieceColor>,
+    /// search currently running for the engine's move
+    engine: Option<EngineHandle>,
+    /// best line of that search, its second move is pondered on
+    engine_pv: Vec<Move>,
+    /// when a search carried on from pondering has to answer
+    engine_deadline: Option<Instant>,
+    /// thinks on the user's time
+    pondering: bool,
+    ponder: Option<Ponder>,
+    pub engine_stats: EngineStats,
+    /// time budget of each engine move
+    think_time: Duration,
+    /// how well the internal engine plays
+    difficulty: Difficulty,
+    /// threads each search of the engine runs on
+    threads: usize,
+    /// search and evaluation constants of the engine
+    engine_params: Params,
+    pub eval_bar: EvalBar,
+    pub analysis_panel: AnalysisPanel,
+    move_list: MoveList,
+    /// names the opening of the position on screen in the move list
+    openings: EcoTable,
+    /// grades of the moves of the game that just ended
+    review: Option<Review>,
+    /// chances of either side over the reviewed game, under the move list
+    eval_graph: EvalGraph,
+    /// search feeding the evaluation bar and the analysis panel, with the position it analyses
+    analysis: Option<(EngineHandle, Board)>,
+    /// search looking for a hint, and the move it suggested
+    hint_search: Option<EngineHandle>,
+    hint: Option<Move>,
+    /// the side played by an external UCI engine, if any
+    external_color: Option<PieceColor>,
+    external: Option<UciClient>,
+
+    book: Option<Book>,
+    book_mode: BookMode,
+    /// book moves of the current position
+    book_moves: Vec<BookMove>,
+
+    /// games of the database, indexed on a thread the first time the explorer is shown
+    explorer: Option<Explorer>,
+    explorer_loading: Option<Receiver<std::io::Result<Explorer>>>,
+    explorer_panel: ExplorerPanel,
+
+    /// marks the passed pawns on the board
+    pub show_passed_pawns: bool,
+
+    /// the side played by the network peer, if any
+    remote_color: Option<PieceColor>,
+    /// turn number of the position the board was loaded from
+    turn_base: u32,
+    /// messages waiting to be sent to the network peer
+    outbox: Vec<NetMessage>,
+    /// connection to the network peer
+    link: Option<Link>,
+    /// replies played automatically to the network peer's moves
+    conditions: ConditionTree,
+    /// open while the user records a line of conditional moves
+    condition_editor: Option<ConditionEditor>,
+
+    lifecycle: Lifecycle,
+    /// names typed for the players of white and black at this board, empty when not given
+    player_names: (String, String),
+    /// clocks of a timed game
+    clock: Option<Clock>,
+    /// how the game ended, until the game over screen takes it
+    game_over: Option<GameState>,
+    /// open while a move is typed instead of played with the mouse
+    pub move_input: Option<MoveInput>,
+    /// position the game started from, rematches start from it again
+    start_fen: String,
+    /// rules of the game, kept for its rematches and copies as only crazyhouse shows in a FEN
+    variant: Variant,
+    notice: Notice,
+    /// puzzles being solved, the board holding the current one
+    puzzles: Option<PuzzleTrainer>,
+    /// when the opponent's next move of the puzzle is played
+    puzzle_reply_at: Option<Instant>,
+    /// the moves played on the board are added to it while the repertoire is recorded
+    repertoire: Option<Repertoire>,
+    /// opening lines of the repertoire being drilled, the board holding the current one
+    drill: Option<RepertoireDrill>,
+    /// when the opponent's next move of the drill is played
+    drill_reply_at: Option<Instant>,
+    /// picture of the board waiting for the next frame to be rendered offscreen
+    export: Option<(ExportFormat, PathBuf)>,
+    /// file of the games directory the game on the board was picked up from, written back
+    /// when the game is put aside and removed once it is over
+    ongoing_path: Option<PathBuf>,
+
+    packs: PackManager,
+    theme: Theme,
+    /// piece sets found in [PIECES_DIR]
+    piece_sets: PieceSets,
+    /// name of the set the piece images are read from
+    piece_set: String,
+    sound: SoundPlayer,
+    /// whether the low time warning was played for white and black, once each per game
+    low_time_played: (bool, bool),
+    /// writes what happens on the board as text, for screen readers
+    narrator: Option<Narrator>,
+    /// where the settings, statistics, repertoire, backups and games put aside are kept
+    paths: Paths,
+    /// profile whose settings and games are used, none for the shared ones
+    profile: Option<Profile>,
 }
 
 impl Game {
-    pub fn new(_size: u32, x: u32, y: u32) -> Self {
+    /// A game with the settings and files of `profile`, or the shared ones for none
+    pub fn new(_size: u32, x: u32, y: u32, profile: Option<&Profile>) -> Self {
+        let paths = Paths::of(profile);
+        let mut config = Config::load_or_default(&paths.config);
+        // a piece set that was removed falls back to another one
+        let piece_sets = PieceSets::discover(PIECES_DIR);
+        if let Some(set) = piece_sets.get_or_default(&config.piece_set) {
+            if set.name != config.piece_set {
+                tracing::warn!(
+                    "Piece set {:?} not found, using {:?}",
+                    config.piece_set,
+                    set.name
+                );
+                config.piece_set = set.name.clone();
+            }
+        }
+        let packs = PackManager::new(THEMES_DIR);
+        let theme = Theme::load(&packs, &config.theme, config.palette);
+        let mut sound = SoundPlayer::new();
+        sound.load(&theme.sounds);
+        sound.enabled = config.sounds;
+        let mut engine_stats = EngineStats::default();
+        engine_stats.set_think_time(config.think_time);
+        engine_stats.set_difficulty(config.difficulty);
+
         Self {
             _size,
             x_offset: x,
             y_offset: y,
             cell_size: _size / 8,
             board: board::Board::new(),
+            start_board: board::Board::new(),
+            viewing: None,
+            redo: vec![],
 
             cursor: Vector2 { x: 0.0, y: 0.0 },
+            mouse: Vector2 { x: 0.0, y: 0.0 },
             selected: None,
+            targets: vec![],
+            pocket_selected: None,
+            key_cursor: None,
+            key_promotion: 0,
+            annotations: HashMap::new(),
+            annotation_from: None,
+            dragging: false,
             image_map: HashMap::new(),
 
             pawn_promotion: false,
             can_promote_to: vec![],
-            pawn_promotion_img_map: HashMap::new(),
-            pawn_promotion_from_to: (0, 0),
+
+            audit_log: ClockAuditLog::new(),
+
+            events: Vec::new(),
+            turn_indicator: TurnIndicator::new(PieceColor::White),
+
+            free_move: false,
+
+            autosave: Autosave::new(AutosaveConfig {
+                dir: paths.autosave_dir.clone(),
+                ..AutosaveConfig::from_env()
+            }),
+            backup_picker: None,
+            input: config.input,
+            touch: Touch::default(),
+            gamepad: Gamepad::default(),
+
+            seat: PieceColor::White,
+            auto_flip: true,
+            hotseat_flip: config.hotseat_flip,
+            show_coordinates: config.coordinates,
+            auto_queen: config.auto_queen,
+            confirm_moves: config.confirm_moves,
+            pending_move: None,
+            paused: false,
+            clock_paused: false,
+            force_promotion_picker: false,
+            animation_speed: config.animation,
+            animation: None,
+            bottom: PieceColor::White,
+            pieces_shown: PieceVisibility::default(),
+            indicator_size: config.indicator_size,
+
+            engine_color: None,
+            engine: None,
+            engine_pv: vec![],
+            engine_deadline: None,
+            pondering: config.ponder,
+            ponder: None,
+            engine_stats,
+            think_time: config.think_time,
+            difficulty: config.difficulty,
+            threads: config.threads.min(engine::MAX_THREADS),
+            engine_params: Params::load_or_default(&engine::params_path()),
+            eval_bar: EvalBar::default(),
+            analysis_panel: AnalysisPanel::default(),
+            move_list: MoveList::default(),
+            openings: EcoTable::bundled(),
+            review: None,
+            eval_graph: EvalGraph::default(),
+            analysis: None,
+            hint_search: None,
+            hint: None,
+            external_color: None,
+            external: None,
+
+            book: Self::load_book(),
+            book_mode: BookMode::Off,
+            book_moves: Vec::new(),
+
+            explorer: None,
+            explorer_loading: None,
+            explorer_panel: ExplorerPanel::default(),
+
+            show_passed_pawns: false,
+
+            remote_color: None,
+            turn_base: 0,
+            outbox: Vec::new(),
+            link: None,
+            conditions: ConditionTree::default(),
+            condition_editor: None,
+
+            lifecycle: Lifecycle::default(),
+            player_names: (String::new(), String::new()),
+            clock: None,
+            game_over: None,
+            move_input: None,
+            start_fen: String::new(),
+            variant: Variant::Standard,
+            notice: Notice::default(),
+            puzzles: None,
+            puzzle_reply_at: None,
+            repertoire: None,
+            drill: None,
+            drill_reply_at: None,
+            export: None,
+            ongoing_path: None,
+
+            packs,
+            theme,
+            piece_sets,
+            piece_set: config.piece_set,
+            sound,
+            low_time_played: (false, false),
+            narrator: None,
+            paths,
+            profile: profile.cloned(),
         }
     }
 
-    pub fn debug(&self) {
-        self.board.print_debug();
+    /// Writes the selections, moves, checks and results to `narrator` from now on
+    pub fn set_narrator(&mut self, narrator: Narrator) {
+        self.narrator = Some(narrator);
     }
 
-    pub fn draw_piece<T>(&self, d: &mut T, i: usize, piece: Piece)
-    where
-        T: raylib::core::drawing::RaylibDraw,
-    {
-        if piece.get_type() == PieceType::None {
-            return;
+    /// The settings as they are now, as saved to the config file
+    fn config(&self) -> Config {
+        Config {
+            theme: self.theme.name.clone(),
+            palette: self.theme.palette,
+            indicator_size: self.indicator_size,
+            piece_set: self.piece_set.clone(),
+            sounds: self.sound.enabled,
+            coordinates: self.show_coordinates,
+            auto_queen: self.auto_queen,
+            confirm_moves: self.confirm_moves,
+            animation: self.animation_speed,
+            difficulty: self.difficulty,
+            think_time: self.think_time,
+            threads: self.threads,
+            ponder: self.pondering,
+            hotseat_flip: self.hotseat_flip,
+            input: self.input.clone(),
         }
+    }
 
-        let texture = self.image_map.get(&piece).unwrap();
-        let x = i % 8;
-        let y = i / 8;
-
-        d.draw_texture(
-            texture,
-            (x as u32 * self.cell_size + self.x_offset) as i32,
-            (y as u32 * self.cell_size + self.y_offset) as i32,
-            raylib::core::color::Color::WHITE,
-        );
+    fn save_config(&self) {
+        if let Err(e) = self.config().save(&self.paths.config) {
+            tracing::warn!("Failed to save settings to {:?}: {}", self.paths.config, e);
+        }
     }
 
-    fn draw_piece_for_promotion<T>(&self, d: &mut T, x: i32, piece: Piece)
-    where
-        T: raylib::core::drawing::RaylibDraw,
-    {
-        let texture = self.pawn_promotion_img_map.get(&piece).unwrap();
-        d.draw_texture(
-            texture,
-            x,
-            self.y_offset as i32,
-            raylib::core::color::Color::WHITE,
-        );
+    /// Switches to the theme called `name`, reloading its sounds
+    pub fn set_theme(&mut self, name: &str) {
+        self.theme = Theme::load(&self.packs, name, self.theme.palette);
+        self.sound.load(&self.theme.sounds);
+        tracing::info!("Theme: {}", self.theme.name);
     }
 
-    pub fn unset_selected(&mut self) {
-        self.selected = None;
+    /// Switches to the next theme found on disk
+    pub fn next_theme(&mut self) {
+        let name = Theme::next_name(&self.packs, &self.theme.name);
+        self.set_theme(&name);
+        self.save_config();
     }
 
-    pub fn make_move(&mut self) {
-        if self.selected.is_none() {
+    pub fn debug(&self) {
+        tracing::info!("\n{}", self.board);
+        tracing::debug!("{}", self.board.debug_string());
+    }
+
+    /// Dispatches the events produced since the last frame to the widgets
+    pub fn update(&mut self) {
+        let board_changed = !self.events.is_empty();
+        for event in self.events.drain(..) {
+            self.turn_indicator.on_event(&event);
+            self.sound.on_event(&event);
+        }
+        if board_changed {
+            self.check_game_end();
+            self.probe_book();
+            self.on_display_changed();
+        }
+        self.poll_analysis();
+        self.poll_explorer();
+        self.poll_hint();
+        self.check_flag();
+        self.check_low_time();
+        self.poll_puzzle();
+        self.poll_drill();
+        self.poll_network();
+        if self.review.as_mut().is_some_and(|review| review.poll()) {
+            self.show_review();
+        }
+
+        // the board holds a made up line while conditions are recorded
+        if self.condition_editor.is_some() {
             return;
         }
 
-        let selected = self.selected.clone().unwrap();
-        let from = self
-            .board
-            .get_square(selected.x as usize, selected.y as usize);
-        let to = self
-            .board
-            .get_square(self.cursor.x as usize, self.cursor.y as usize);
+        self.autosave.tick(|| self.board.to_fen());
+        if self.paused {
+            return;
+        }
 
-        //TODO: chanege None to pawn promotion
-        match self.board.make_move(from, to, None) {
-            Ok(_) => {
-                self.board.toggle_turn();
-                self.board.generate_moves_current_position();
-                self.unset_selected();
-                self.pawn_promotion = false;
-            }
-            Err(e) => {
-                if let MoveError::MultipleLeagalMove(moves) = e {
-                    self.pawn_promotion = true;
-                    self.can_promote_to.clear();
-                    self.can_promote_to.extend(moves);
-                    self.pawn_promotion_from_to = (from, to);
-                } else {
-                    tracing::debug!("Invalid Move");
+        self.poll_external();
+        if let Some(external) = &mut self.external {
+            let to_move = self.external_color == Some(self.board.get_turn());
+            if to_move && !external.is_searching() && self.lifecycle.is_in_progress() {
+                let go = match &self.clock {
+                    Some(clock) => external.go_clock(
+                        &self.board,
+                        clock.remaining(PieceColor::White),
+                        clock.remaining(PieceColor::Black),
+                        clock.increment(),
+                    ),
+                    None => external.go(&self.board, self.think_time),
+                };
+                if let Err(e) = go {
+                    self.notice.show(format!("External engine stopped: {}", e));
+                    self.external = None;
+                    self.external_color = None;
                 }
             }
         }
+
+        self.poll_ponder();
+        self.poll_engine();
+        if self.engine_deadline.is_some_and(|d| Instant::now() >= d) {
+            self.engine_deadline = None;
+            if let Some(engine) = &self.engine {
+                engine.stop();
+            }
+        }
+        if self.is_engine_turn() && self.engine.is_none() && self.lifecycle.is_in_progress() {
+            if self.ponder_hit() {
+                return;
+            }
+            if self.book_mode == BookMode::EnginePlays && self.play_book_move() {
+                return;
+            }
+            self.engine = Some(EngineHandle::spawn_opponent(
+                self.board.clone(),
+                Some(self.engine_move_time()),
+                self.difficulty,
+                self.threads,
+                self.engine_params.clone(),
+            ));
+        }
     }
 
-    pub fn draw_board<T>(&self, d: &mut T)
-    where
-        T: raylib::core::drawing::RaylibDraw,
-    {
-        let white = raylib::core::color::Color::from_hex("EBECD0").expect("Error parsing hex");
-        let black = raylib::core::color::Color::from_hex("739552").expect("Error parsing hex");
+    /// Shows or hides the evaluation bar, it only analyses while shown
+    pub fn toggle_eval_bar(&mut self) {
+        self.eval_bar.toggle();
+        self.restart_analysis();
+    }
 
-        let cursor_color =
-            raylib::core::color::Color::from_hex("ffee80").expect("Error parsing hex");
-        let selected_color =
-            raylib::core::color::Color::from_hex("8ab7ff").expect("Error parsing hex");
+    /// Shows or hides the analysis panel, the best lines are searched for as long as it is shown
+    pub fn toggle_analysis_panel(&mut self) {
+        self.analysis_panel.toggle();
+        self.restart_analysis();
+    }
 
-        let legal_color =
-            raylib::core::color::Color::from_hex("ff11ff").expect("Error parsing hex");
+    /// Shows or hides the opening explorer, reading the database the first time it is shown
+    pub fn toggle_explorer(&mut self) {
+        self.explorer_panel.toggle();
+        if self.explorer_panel.visible && self.explorer.is_none() && self.explorer_loading.is_none()
+        {
+            let path = explorer::database_path();
+            self.explorer_panel
+                .set_status(format!("indexing {}...", path.display()));
+            let (tx, rx) = mpsc::channel();
+            std::thread::spawn(move || {
+                let _ = tx.send(Explorer::open(&path));
+            });
+            self.explorer_loading = Some(rx);
+        }
+        self.probe_explorer();
+    }
 
-        let copy_arr = self.board.clone_board();
+    /// Takes the database once it is indexed
+    fn poll_explorer(&mut self) {
+        let Some(loading) = &self.explorer_loading else {
+            return;
+        };
+        let loaded = match loading.try_recv() {
+            Ok(loaded) => loaded,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err(std::io::ErrorKind::Interrupted.into()),
+        };
+        self.explorer_loading = None;
+        match loaded {
+            Ok(database) => {
+                tracing::info!("Indexed {} games for the explorer", database.games());
+                self.explorer_panel
+                    .set_status(format!("{} games", database.games()));
+                self.explorer = Some(database);
+                self.probe_explorer();
+            }
+            Err(e) => {
+                let path = explorer::database_path();
+                tracing::debug!("No game database at {:?}: {:?}", path, e);
+                self.explorer_panel.set_status("no database".to_string());
+                self.notice
+                    .show(format!("No game database found at {:?}", path));
+            }
+        }
+    }
 
-        for (idx, p) in copy_arr.iter().enumerate() {
-            let x = idx % 8;
-            let y = idx / 8;
-            let color = if (x + y) % 2 == 0 { white } else { black };
+    /// Lists the moves of the database played from the position on screen
+    fn probe_explorer(&mut self) {
+        if !self.explorer_panel.visible {
+            return;
+        }
+        let board = self.displayed_board().clone();
+        let moves = self
+            .explorer
+            .as_ref()
+            .map_or_else(Vec::new, |explorer| explorer.probe(&board));
+        self.explorer_panel.set_moves(&board, moves);
+    }
 
-            if self.cursor.x as usize == x as usize && self.cursor.y as usize == y as usize {
-                d.draw_rectangle(
-                    (self.x_offset + x as u32 * self.cell_size) as i32,
-                    (self.y_offset + y as u32 * self.cell_size) as i32,
-                    self.cell_size as i32,
-                    self.cell_size as i32,
-                    cursor_color,
-                );
-            } else if self.selected.is_some() {
-                let selected = self.selected.clone().unwrap();
-                if selected.x as usize == x as usize && selected.y as usize == y as usize {
-                    d.draw_rectangle(
-                        (self.x_offset + x as u32 * self.cell_size) as i32,
-                        (self.y_offset + y as u32 * self.cell_size) as i32,
-                        self.cell_size as i32,
-                        self.cell_size as i32,
-                        selected_color,
-                    );
-                } else {
-                    d.draw_rectangle(
-                        (self.x_offset + x as u32 * self.cell_size) as i32,
-                        (self.y_offset + y as u32 * self.cell_size) as i32,
-                        self.cell_size as i32,
-                        self.cell_size as i32,
-                        color,
-                    );
-                }
+    /// Width of the panels shown beside the board
+    fn panels_width(&self) -> i32 {
+        EVAL_BAR_WIDTH + self.side_panels_width() + MOVE_LIST_WIDTH
+    }
 
-                if let Some(selected) = &self.selected {
-                    let moves = self.board.get_moves();
-                    let moves = moves
-                        .iter()
-                        .filter(|x| {
-                            let init = x.from.to_vec2();
-                            return init.eq(&selected);
-                        })
-                        .collect::<Vec<_>>();
+    /// Width of the analysis panel and the explorer, those shown
+    fn side_panels_width(&self) -> i32 {
+        let analysis = if self.analysis_panel.visible {
+            ANALYSIS_PANEL_WIDTH
+        } else {
+            0
+        };
+        let explorer = if self.explorer_panel.visible {
+            EXPLORER_PANEL_WIDTH
+        } else {
+            0
+        };
+        analysis + explorer
+    }
 
-                    let moves = moves
-                        .iter()
-                        .map(|f| f.to.to_vec2())
-                        .find(|v| v.x.floor() as usize == x && v.y.floor() as usize == y);
-
-                    if let Some(found) = moves {
-                        d.draw_rectangle(
-                            (self.x_offset + found.x as u32 * self.cell_size) as i32,
-                            (self.y_offset + found.y as u32 * self.cell_size) as i32,
-                            self.cell_size as i32,
-                            self.cell_size as i32,
-                            legal_color,
-                        );
-                    }
-                }
-            } else {
-                d.draw_rectangle(
-                    (self.x_offset + x as u32 * self.cell_size) as i32,
-                    (self.y_offset + y as u32 * self.cell_size) as i32,
-                    self.cell_size as i32,
-                    self.cell_size as i32,
-                    color,
-                );
-            }
+    /// Left edge of the explorer, right of the analysis panel
+    fn explorer_x(&self) -> i32 {
+        let rect = self.board_rect();
+        let analysis = if self.analysis_panel.visible {
+            ANALYSIS_PANEL_WIDTH
+        } else {
+            0
+        };
+        rect.x + rect.size + EVAL_BAR_WIDTH + analysis
+    }
 
-            self.draw_piece(d, idx, (*p).into());
+    /// Picks the piece of the pocket under the mouse to drop, in crazyhouse on the user's turn.
+    /// Picking it again puts it back
+    pub fn click_pocket(&mut self) {
+        if !self.board.is_crazyhouse()
+            || self.viewing.is_some()
+            || self.is_engine_turn()
+            || self.is_remote_turn()
+            || !self.lifecycle.is_in_progress()
+        {
+            return;
         }
-
-        if self.pawn_promotion {
-            let y = self.y_offset;
-            let pr = raylib::core::color::Color::from_hex("11fff0").expect("Error parsing hex");
-
-            let piece_color = self.board.get_turn();
-            let promotion_piece = [
-                Piece {
-                    piece_type: PieceType::Bishop,
-                    piece_color,
-                },
-                Piece {
-                    piece_type: PieceType::Knight,
-                    piece_color,
-                },
-                Piece {
-                    piece_type: PieceType::Rook,
-                    piece_color,
-                },
-                Piece {
-                    piece_type: PieceType::Queen,
-                    piece_color,
-                },
-            ];
-
-            for (x, p) in promotion_piece.iter().enumerate() {
-                let x = self.x_offset + x as u32 * (self.cell_size * 2);
-
-                d.draw_rectangle(
-                    x as i32,
-                    y as i32,
-                    self.cell_size as i32 * 2,
-                    self.cell_size as i32 * 2,
-                    pr,
-                );
-                self.draw_piece_for_promotion(d, x as i32, *p);
-            }
+        let turn = self.board.get_turn();
+        let (x, y, height) = self.move_column();
+        let strip_y = if turn == self.bottom {
+            y + height
+        } else {
+            y - CAPTURES_HEIGHT
+        };
+        let (mx, my) = (self.mouse.x as i32, self.mouse.y as i32);
+        if !(x..x + MOVE_LIST_WIDTH).contains(&mx)
+            || !(strip_y..strip_y + CAPTURES_HEIGHT).contains(&my)
+        {
+            return;
         }
+        let picked = pocket_slot_at(x, mx).filter(|t| self.board.pocket(turn).count(*t) > 0);
+        let again = picked == self.pocket_selected;
+        self.unset_selected();
+        let Some(piece_type) = picked.filter(|_| !again) else {
+            return;
+        };
+        self.pocket_selected = Some(piece_type);
+        self.targets = self
+            .board
+            .legal_moves()
+            .filter(|m| m.dropped_piece() == Some(piece_type))
+            .map(|m| (m.to, false))
+            .collect();
     }
 
-    pub fn selected_pawn_promotion(&mut self, idx: usize) {
-        let promotion_piece = [
-            PieceType::Bishop,
-            PieceType::Knight,
-            PieceType::Rook,
-            PieceType::Queen,
-        ];
-        match self.board.make_move(
-            self.pawn_promotion_from_to.0,
-            self.pawn_promotion_from_to.1,
-            Some(promotion_piece[idx]),
-        ) {
-            Ok(_) => {
-                self.board.toggle_turn();
-                self.board.generate_moves_current_position();
-                self.unset_selected();
-                self.pawn_promotion = false;
-            }
-            Err(e) => {
-                if let MoveError::MultipleLeagalMove(moves) = e {
-                    unreachable!("Should not happen");
-                } else {
-                    tracing::debug!("Invalid Move");
-                }
-            }
+    /// Plays the move of the explorer under the mouse, if it is the user's turn in the current
+    /// position
+    pub fn click_explorer(&mut self) {
+        let Some(mo) = self.explorer_panel.move_at(
+            self.explorer_x(),
+            self.board_rect(),
+            self.mouse.x as i32,
+            self.mouse.y as i32,
+        ) else {
+            return;
         };
+        let refused = if self.viewing.is_some() {
+            Some("Go back to the current position to play")
+        } else if self.is_opponent_engine_turn() || self.is_remote_turn() {
+            Some("It's not your turn")
+        } else if !self.lifecycle.is_in_progress() {
+            Some("The game is over")
+        } else {
+            None
+        };
+        match refused {
+            Some(reason) => self.notice.show(reason.to_string()),
+            None => self.play_chosen(mo),
+        }
     }
 
-    pub fn follow_mouse(&mut self, d: &raylib::core::RaylibHandle) {
-        let mouse = d.get_mouse_position();
-        self.cursor.x = (mouse.x - self.x_offset as f32) / self.cell_size as f32;
-        self.cursor.y = (mouse.y - self.y_offset as f32) / self.cell_size as f32;
+    /// Width of the window fitting the board and the panels shown beside it
+    pub fn window_width(&self) -> i32 {
+        let rect = self.board_rect();
+        rect.x + rect.size + self.panels_width()
     }
 
-    pub fn select_piece(&mut self, d: &raylib::core::RaylibHandle) {
-        let x = self.cursor.x as usize;
-        let y = self.cursor.y as usize;
+    /// Moves the board and its panels `x` pixels from the left of the window
+    pub fn set_origin(&mut self, x: i32) {
+        self.x_offset = x.max(0) as u32;
+    }
 
-        let piece = self.board.get_piece_at_index(self.board.get_square(x, y));
+    /// Returns true if the board or the panels beside it cover `x`, from the left of the window
+    pub fn covers(&self, x: i32) -> bool {
+        (self.board_rect().x..self.window_width()).contains(&x)
+    }
 
-        if piece.get_type() == PieceType::None {
+    /// A game on another board for simul mode, from the position on screen with the same time
+    /// control
+    pub fn spawn_board(&self) -> Game {
+        let mut board = Game::new(
+            self._size,
+            self.x_offset,
+            self.y_offset,
+            self.profile.as_ref(),
+        );
+        board.start_timed_game(&self.fen(), self.lifecycle.time_control());
+        board.set_variant(self.variant);
+        board.load_images();
+        board
+    }
+
+    /// Fits the board to a window of `width` by `height` pixels, the panels beside it keeping
+    /// their width, and rasterizes the pieces again when the squares change size
+    pub fn resize(&mut self, width: i32, height: i32) {
+        let room =
+            (width - self.x_offset as i32 - self.panels_width()).min(height - self.y_offset as i32);
+        let cell_size = (room / 8).max(MIN_CELL_SIZE) as u32;
+        if cell_size == self.cell_size {
             return;
         }
+        self.cell_size = cell_size;
+        self._size = cell_size * 8;
+        self.load_images();
+    }
 
-        if self.selected.is_some() {
-            self.selected = None;
-        }
+    /// Restarts the analysis and drops the hint when another position is shown
+    fn on_display_changed(&mut self) {
+        self.move_list
+            .set_moves(&self.start_board, self.board.get_move_history());
+        let opening = self
+            .openings
+            .classify(&self.start_board, self.displayed_board().get_move_history());
+        self.move_list.set_opening(opening.cloned());
+        self.probe_explorer();
+        self.show_review();
+        self.restart_analysis();
+        self.hint_search = None;
+        self.hint = None;
+    }
 
-        if piece.get_color() == self.board.get_turn() {
-            self.selected = Some(Vector2 {
-                x: self.cursor.x,
-                y: self.cursor.y,
-            });
+    /// Starts analysing the position on screen for the evaluation bar and the analysis panel,
+    /// dropping the search of the previous one. The panel's lines are searched until the position
+    /// changes, the bar alone only gets a short search
+    fn restart_analysis(&mut self) {
+        self.eval_bar.clear();
+        self.analysis_panel.clear();
+        let limits = if self.analysis_panel.visible {
+            SearchLimits::infinite().with_multipv(ANALYSIS_LINES)
         } else {
-            tracing::info!("Wrong turn: {:?} ", self.board.get_turn());
+            SearchLimits::movetime(ANALYSIS_TIME)
         }
+        .with_threads(self.threads);
+        let wanted = self.eval_bar.visible || self.analysis_panel.visible;
+        self.analysis = wanted.then(|| {
+            let board = self.displayed_board().clone();
+            self.eval_bar.on_static_eval(engine::evaluate(&board));
+            let handle = EngineHandle::spawn(board.clone(), limits, self.engine_params.clone());
+            (handle, board)
+        });
     }
 
-    fn load_images_for_pawn_promotion(&mut self) {
-        let pieces = [
-            Piece {
-                piece_type: PieceType::Rook,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Knight,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Bishop,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Queen,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Rook,
-                piece_color: PieceColor::Black,
-            },
-            Piece {
-                piece_type: PieceType::Knight,
-                piece_color: PieceColor::Black,
-            },
-            Piece {
-                piece_type: PieceType::Bishop,
-                piece_color: PieceColor::Black,
-            },
-            Piece {
-                piece_type: PieceType::Queen,
-                piece_color: PieceColor::Black,
-            },
-        ];
+    /// Asks the engine for a move for the side to move, shown as an arrow until the position
+    /// changes. Asking again hides it
+    pub fn toggle_hint(&mut self) {
+        if self.hint.take().is_some() || self.hint_search.take().is_some() {
+            return;
+        }
+        self.hint_search = Some(EngineHandle::spawn(
+            self.displayed_board().clone(),
+            SearchLimits::movetime(HINT_TIME).with_threads(self.threads),
+            self.engine_params.clone(),
+        ));
+    }
 
-        for piece in pieces.iter() {
-            self.pawn_promotion_img_map
-                .insert(*piece, self.get_texture(piece, self.cell_size as i32 * 2));
+    fn poll_hint(&mut self) {
+        let Some(search) = &self.hint_search else {
+            return;
+        };
+        while let Some(msg) = search.poll() {
+            if let EngineMessage::BestMove(best) = msg {
+                if best.is_none() {
+                    self.notice.show("No legal move to hint".to_string());
+                }
+                self.hint = best;
+                self.hint_search = None;
+                return;
+            }
         }
     }
 
-    pub fn load_images(&mut self) {
-        self.load_images_for_pawn_promotion();
-        let pieces = [
-            Piece {
-                piece_type: PieceType::Pawn,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Rook,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Knight,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Bishop,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Queen,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::King,
-                piece_color: PieceColor::White,
-            },
-            Piece {
-                piece_type: PieceType::Pawn,
-                piece_color: PieceColor::Black,
-            },
-            Piece {
-                piece_type: PieceType::Rook,
-                piece_color: PieceColor::Black,
-            },
-            Piece {
-                piece_type: PieceType::Knight,
-                piece_color: PieceColor::Black,
-            },
-            Piece {
-                piece_type: PieceType::Bishop,
-                piece_color: PieceColor::Black,
-            },
-            Piece {
-                piece_type: PieceType::Queen,
-                piece_color: PieceColor::Black,
-            },
-            Piece {
-                piece_type: PieceType::King,
-                piece_color: PieceColor::Black,
-            },
-        ];
+    /// Position on screen, an earlier one while stepping through the history
+    fn displayed_board(&self) -> &Board {
+        match &self.viewing {
+            Some((_, board)) => board,
+            None => &self.board,
+        }
+    }
 
-        for piece in pieces.iter() {
-            self.image_map
-                .insert(*piece, self.get_texture(piece, self.cell_size as i32));
+    /// Number of moves played in the game
+    pub fn game_length(&self) -> usize {
+        self.board.get_move_history().len()
+    }
+
+    /// Steps through the game like [Game::step_history], the piece of a move played forward
+    /// sliding to its square
+    pub fn replay_step(&mut self, delta: isize) {
+        let before = self.shown_ply();
+        self.step_history(delta);
+        let ply = self.shown_ply();
+        if ply == before + 1 && self.animation_speed != AnimationSpeed::Off {
+            let mo = self.board.get_move_history()[ply - 1].clone();
+            self.animation = Some((mo, Instant::now()));
         }
     }
 
-    fn get_texture(&self, piece: &Piece, size: i32) -> raylib::core::texture::Texture2D {
-        let mut buffer = String::from("./resource/output/");
-        match piece.get_color() {
-            PieceColor::White => buffer.push('w'),
-            PieceColor::Black => buffer.push('b'),
+    /// Replaces the game with the first one written in `text`, as PGN
+    pub fn load_pgn(&mut self, text: &str) -> Result<(), pgn::PgnError> {
+        let game = pgn::parse_games(text)
+            .into_iter()
+            .next()
+            .ok_or(pgn::PgnError::NoGame)?;
+        self.load_pgn_game(&game)
+    }
+
+    /// Replaces the game with `game`, e.g. one of a database
+    pub fn load_pgn_game(&mut self, game: &pgn::PgnGame) -> Result<(), pgn::PgnError> {
+        let start = game.start()?;
+        let board = game.replay()?;
+        self.start_timed_game(&start.to_fen(), None);
+        self.board = board;
+        self.turn_to_mover();
+        Ok(())
+    }
+
+    /// The game in progress as it is to be picked up again, none for analysis, puzzles, network
+    /// games and games without a move played
+    fn unfinished_game(&self) -> Option<SavedGame> {
+        if !self.lifecycle.is_in_progress()
+            || self.board.get_move_history().is_empty()
+            || self.free_move
+            || self.puzzles.is_some()
+            || self.drill.is_some()
+            || self.remote_color.is_some()
+        {
+            return None;
+        }
+        let clock = self.clock.as_ref().map(|clock| {
+            (
+                clock.remaining(PieceColor::White),
+                clock.remaining(PieceColor::Black),
+            )
+        });
+        Some(SavedGame {
+            start: self.start_board.clone(),
+            board: self.board.clone(),
+            names: self.player_names.clone(),
+            time_control: self.lifecycle.time_control(),
+            clock,
+            engine_color: self.engine_color,
+        })
+    }
+
+    /// Saves the game in progress for Continue to pick up at the next start, on quit. A game
+    /// picked up from the games directory goes back there instead
+    pub fn save_unfinished(&self) {
+        let Some(saved) = self.unfinished_game() else {
+            return;
+        };
+        let path = self
+            .ongoing_path
+            .clone()
+            .unwrap_or_else(|| self.autosave.resume_path());
+        match saved.write(&path) {
+            Ok(()) => tracing::info!("Saved the unfinished game to {:?}", path),
+            Err(e) => tracing::error!("Failed to save the unfinished game to {:?}: {}", path, e),
         }
+    }
 
-        match piece.get_type() {
-            PieceType::Pawn => buffer.push('P'),
-            PieceType::Rook => buffer.push('R'),
-            PieceType::Knight => buffer.push('N'),
-            PieceType::Bishop => buffer.push('B'),
-            PieceType::Queen => buffer.push('Q'),
-            PieceType::King => buffer.push('K'),
-            PieceType::None => panic!("Invalid piece type"),
+    /// Picks up the game left unfinished at the last quit, unless a game is already under way
+    /// on the board
+    pub fn resume_saved_game(&mut self) {
+        if !self.board.get_move_history().is_empty() {
+            return;
         }
-        buffer.push_str(".svg.png");
-        // check if the file exists
+        let path = self.autosave.resume_path();
+        let saved = match SavedGame::take(&path) {
+            Ok(Some(saved)) => saved,
+            Ok(None) => return,
+            Err(e) => {
+                self.notice
+                    .show(format!("Failed to resume the saved game: {}", e));
+                return;
+            }
+        };
+        self.resume(saved);
+        self.notice.show("Saved game resumed".to_string());
+    }
 
-        if !Path::new(&buffer).exists() {
-            tracing::error!("File does not exist: {:?}", buffer);
-            panic!("File does not exist: {:?}", buffer);
+    /// Puts the game in progress aside in the games directory, to be picked up again with
+    /// [Game::pick_up]. It stays on the board, moves played on it going to the same file.
+    /// Returns the file, none for a game that isn't worth keeping, see [Game::unfinished_game]
+    pub fn put_aside(&mut self) -> Option<PathBuf> {
+        let saved = self.unfinished_game()?;
+        let path = self
+            .ongoing_path
+            .get_or_insert_with(|| correspondence::new_path(&self.paths.games_dir))
+            .clone();
+        if let Err(e) = saved.write(&path) {
+            self.notice
+                .show(format!("Failed to put the game aside: {}", e));
+            return None;
         }
+        tracing::info!("Put the game aside in {:?}", path);
+        Some(path)
+    }
 
-        let mut image = raylib::core::texture::Image::load_image(&buffer)
-            .map_err(|err| {
-                tracing::error!("Error loading image: {:?}", err);
-            })
-            .expect("Error loading image");
+    /// Whether the game on the board was put aside at `path`
+    pub fn is_put_aside_at(&self, path: &std::path::Path) -> bool {
+        self.ongoing_path.as_deref() == Some(path)
+    }
 
-        image.resize(size, size);
-        //
-        // SAFETY: LoadTextureFromImage is a safe function
-        unsafe {
-            let texture = raylib::core::texture::Texture2D::from_raw(
-                raylib::ffi::LoadTextureFromImage(*image),
-            );
-            return texture;
+    /// Removes the game put aside at `path`. The game on the board stays, no longer kept
+    pub fn drop_put_aside(&mut self, path: &std::path::Path) {
+        if let Err(e) = std::fs::remove_file(path) {
+            self.notice
+                .show(format!("Failed to remove the game {:?}: {}", path, e));
+            return;
+        }
+        if self.is_put_aside_at(path) {
+            self.ongoing_path = None;
+        }
+    }
+
+    /// Picks up the game put aside at `path`
+    pub fn pick_up(&mut self, path: &std::path::Path) {
+        match correspondence::read(path) {
+            Ok(saved) => {
+                self.resume(saved);
+                self.ongoing_path = Some(path.to_path_buf());
+            }
+            Err(e) => self
+                .notice
+                .show(format!("Failed to read the game {:?}: {}", path, e)),
+        }
+    }
+
+    /// Sets the board, the clocks and the opponent as they were saved
+    fn resume(&mut self, saved: SavedGame) {
+        self.stop_opponents();
+        self.set_free_move(false);
+        self.start_timed_game(&saved.start.to_fen(), saved.time_control);
+        self.board = saved.board;
+        self.player_names = saved.names;
+        if let (Some(time_control), Some((white, black))) = (saved.time_control, saved.clock) {
+            let mut clock = Clock::resumed(time_control, white, black);
+            clock.start(self.board.get_turn());
+            self.clock = Some(clock);
+        }
+        self.engine_color = saved.engine_color;
+        self.update_seat();
+        self.turn_to_mover();
+        self.events
+            .push(BoardEvent::TurnChanged(self.board.get_turn()));
+    }
+
+    /// Goes back to the start of the game to play it through again, without the engines.
+    /// Returns false for a game still running on the clock
+    pub fn start_replay(&mut self) -> bool {
+        if self.lifecycle.is_timed_and_live() {
+            self.notice
+                .show("The game can be replayed once it is over".to_string());
+            return false;
+        }
+        self.stop_opponents();
+        self.set_free_move(false);
+        self.show_ply(0);
+        true
+    }
+
+    /// Returns true if the mouse is over the board
+    pub fn is_cursor_on_board(&self) -> bool {
+        (0.0..8.0).contains(&self.cursor.x) && (0.0..8.0).contains(&self.cursor.y)
+    }
+
+    /// Shows the position `delta` plies later in the game, earlier when negative, stopping at
+    /// the first and the current position. Not available while a timed game is being played
+    pub fn step_history(&mut self, delta: isize) {
+        let ply = self.shown_ply().saturating_add_signed(delta);
+        self.show_ply(ply);
+    }
+
+    /// Number of moves played to reach the position on screen
+    pub fn shown_ply(&self) -> usize {
+        self.viewing
+            .as_ref()
+            .map_or(self.board.get_move_history().len(), |(ply, _)| *ply)
+    }
+
+    /// Shows the position after the first `ply` moves of the game, the current one for the
+    /// last move. Not available while a timed game is being played
+    pub fn show_ply(&mut self, ply: usize) {
+        if self.lifecycle.is_timed_and_live() {
+            return;
+        }
+        let history = self.board.get_move_history();
+        let ply = ply.min(history.len());
+        if ply == self.shown_ply() {
+            return;
+        }
+
+        if ply == history.len() {
+            self.viewing = None;
+        } else {
+            let mut board = self.start_board.clone();
+            for mo in &history[..ply] {
+                if board.play(mo).is_err() {
+                    tracing::error!("Failed to replay {} from the start of the game", mo);
+                    return;
+                }
+            }
+            self.viewing = Some((ply, board));
+        }
+        self.unset_selected();
+        self.on_display_changed();
+    }
+
+    /// Left edge, top and height of the move list, right of the other panels and between the
+    /// captured pieces of the side at the top and of the side at the bottom, the evaluation
+    /// graph taking the bottom of that room when there is one
+    fn move_list_area(&self) -> (i32, i32, i32) {
+        let (x, y, height) = self.move_column();
+        if self.eval_graph.is_empty() {
+            (x, y, height)
+        } else {
+            (x, y, height - EVAL_GRAPH_HEIGHT)
+        }
+    }
+
+    /// Top left corner of the evaluation graph, under the move list
+    fn eval_graph_origin(&self) -> (i32, i32) {
+        let (x, y, height) = self.move_list_area();
+        (x, y + height)
+    }
+
+    /// Left edge, top and height of the room between the captured pieces
+    fn move_column(&self) -> (i32, i32, i32) {
+        let rect = self.board_rect();
+        (
+            rect.x + rect.size + EVAL_BAR_WIDTH + self.side_panels_width(),
+            rect.y + CAPTURES_HEIGHT,
+            rect.size - 2 * CAPTURES_HEIGHT,
+        )
+    }
+
+    /// Shows the position after the move of the move list, or at the point of the evaluation
+    /// graph, under the mouse
+    pub fn click_move_list(&mut self) {
+        let ply = self.move_list.ply_at(
+            self.move_list_area(),
+            self.shown_ply(),
+            self.mouse.x as i32,
+            self.mouse.y as i32,
+        );
+        let ply = ply.or_else(|| {
+            self.eval_graph.ply_at(
+                self.eval_graph_origin(),
+                self.mouse.x as i32,
+                self.mouse.y as i32,
+            )
+        });
+        if let Some(ply) = ply {
+            self.show_ply(ply);
+        }
+    }
+
+    /// Passes the reports of the analysis to the evaluation bar and the analysis panel
+    fn poll_analysis(&mut self) {
+        let Some((analysis, board)) = &self.analysis else {
+            return;
+        };
+        while let Some(msg) = analysis.poll() {
+            match msg {
+                EngineMessage::Info(info) => {
+                    if info.multipv <= 1 {
+                        self.eval_bar.on_info(&info, board.get_turn());
+                    }
+                    self.analysis_panel.on_info(&info, board);
+                }
+                EngineMessage::BestMove(_) => {
+                    self.analysis = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Gives the engine the next longer think time, wrapping around to the shortest
+    pub fn next_think_time(&mut self) {
+        let next = THINK_TIMES
+            .iter()
+            .map(|secs| Duration::from_secs_f32(*secs))
+            .find(|t| *t > self.think_time)
+            .unwrap_or(Duration::from_secs_f32(THINK_TIMES[0]));
+        self.think_time = next;
+        self.engine_stats.set_think_time(next);
+        tracing::info!("Engine think time: {:?}", next);
+        self.save_config();
+    }
+
+    /// Names and values of the rows of the settings screen, in the order [Game::change_setting]
+    /// takes them
+    pub fn setting_rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Difficulty", self.difficulty.name().to_string()),
+            (
+                "Think time",
+                format!("{:.1}s", self.think_time.as_secs_f32()),
+            ),
+            ("Threads", self.threads.to_string()),
+            (
+                "Ponder",
+                if self.pondering { "On" } else { "Off" }.to_string(),
+            ),
+            (
+                "Hotseat flip",
+                if self.hotseat_flip { "On" } else { "Off" }.to_string(),
+            ),
+            (
+                "Coordinates",
+                if self.show_coordinates { "On" } else { "Off" }.to_string(),
+            ),
+            ("Theme", self.theme.name.clone()),
+            ("Board colors", self.theme.palette.name.to_string()),
+            ("Piece set", self.piece_set.clone()),
+            (
+                "Sounds",
+                if self.sound.enabled { "On" } else { "Off" }.to_string(),
+            ),
+            (
+                "Auto-queen",
+                if self.auto_queen {
+                    "On, ctrl to pick"
+                } else {
+                    "Off"
+                }
+                .to_string(),
+            ),
+            ("Animation", self.animation_speed.name().to_string()),
+            ("Indicator size", self.indicator_size.name().to_string()),
+            (
+                "Confirm moves",
+                if self.confirm_moves { "On" } else { "Off" }.to_string(),
+            ),
+        ]
+    }
+
+    /// Moves the setting in `row` of the settings screen `delta` steps, up for a positive one
+    pub fn change_setting(&mut self, row: usize, delta: isize) {
+        match row {
+            0 => {
+                self.difficulty = self.difficulty.step(delta);
+                self.engine_stats.set_difficulty(self.difficulty);
+                tracing::info!("Engine difficulty: {:?}", self.difficulty);
+            }
+            1 => {
+                let times = THINK_TIMES.map(Duration::from_secs_f32);
+                let idx = times
+                    .iter()
+                    .position(|t| *t >= self.think_time)
+                    .unwrap_or(times.len() - 1) as isize;
+                let next = times[(idx + delta).clamp(0, times.len() as isize - 1) as usize];
+                self.think_time = next;
+                self.engine_stats.set_think_time(next);
+                tracing::info!("Engine think time: {:?}", next);
+            }
+            2 => {
+                // more threads than cores only slows each of them down
+                let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+                let max = cores.min(engine::MAX_THREADS) as isize;
+                self.threads = (self.threads as isize + delta).clamp(1, max) as usize;
+                tracing::info!("Engine threads: {}", self.threads);
+                self.restart_analysis();
+            }
+            3 => {
+                self.pondering = !self.pondering;
+                if !self.pondering {
+                    self.ponder = None;
+                }
+                tracing::info!("Pondering: {}", self.pondering);
+            }
+            4 => {
+                self.hotseat_flip = !self.hotseat_flip;
+                self.turn_to_mover();
+                tracing::info!("Hotseat flip: {}", self.hotseat_flip);
+            }
+            5 => self.show_coordinates = !self.show_coordinates,
+            6 => {
+                let name = Theme::next_name(&self.packs, &self.theme.name);
+                self.set_theme(&name);
+            }
+            7 => self.theme.palette = self.theme.palette.step(delta),
+            8 => {
+                if let Some(name) = self.piece_sets.step(&self.piece_set, delta) {
+                    self.piece_set = name.to_string();
+                    self.load_images();
+                    tracing::info!("Piece set: {}", self.piece_set);
+                }
+            }
+            9 => self.sound.enabled = !self.sound.enabled,
+            10 => self.auto_queen = !self.auto_queen,
+            11 => {
+                self.animation_speed = self.animation_speed.step(delta);
+                self.animation = None;
+            }
+            12 => self.indicator_size = self.indicator_size.step(delta),
+            _ => {
+                self.confirm_moves = !self.confirm_moves;
+                self.pending_move = None;
+            }
+        }
+        self.save_config();
+    }
+
+    fn load_book() -> Option<Book> {
+        let path = book::book_path();
+        match Book::open(&path) {
+            Ok(book) => {
+                tracing::info!("Loaded opening book {:?}", path);
+                Some(book)
+            }
+            Err(e) => {
+                tracing::debug!("No opening book at {:?}: {:?}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Switches between not using the book, showing the book moves and letting the engine play
+    /// from the book
+    pub fn next_book_mode(&mut self) {
+        if self.book.is_none() {
+            self.notice
+                .show(format!("No opening book found at {:?}", book::book_path()));
+            return;
+        }
+        self.book_mode = match self.book_mode {
+            BookMode::Off => BookMode::ShowMoves,
+            BookMode::ShowMoves => BookMode::EnginePlays,
+            BookMode::EnginePlays => BookMode::Off,
+        };
+        tracing::info!("Book: {:?}", self.book_mode);
+        self.probe_book();
+    }
+
+    fn probe_book(&mut self) {
+        self.book_moves = match (&self.book, self.book_mode) {
+            (Some(book), BookMode::ShowMoves | BookMode::EnginePlays) => book.probe(&self.board),
+            _ => Vec::new(),
+        };
+    }
+
+    /// Plays a book move for the engine, returns false once out of book
+    fn play_book_move(&mut self) -> bool {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos() as u64;
+        let Some(book_move) = book::pick(&self.book_moves, seed) else {
+            return false;
+        };
+        tracing::info!("Book move: {}", book_move.mv);
+        self.apply_move(book_move.mv.clone());
+        true
+    }
+
+    /// Returns true if the internal engine is to move
+    pub fn is_engine_turn(&self) -> bool {
+        self.engine_color == Some(self.board.get_turn())
+    }
+
+    /// Drops the search of the engine's move and the one pondering on the user's time
+    fn stop_engine(&mut self) {
+        self.engine = None;
+        self.engine_deadline = None;
+        self.ponder = None;
+    }
+
+    /// Stops the internal and the external engine, both sides being played here
+    fn stop_opponents(&mut self) {
+        self.engine_color = None;
+        self.stop_engine();
+        self.external = None;
+        self.external_color = None;
+    }
+
+    /// Lets the engine play the side not to move, or stops it if it was already playing
+    pub fn toggle_engine(&mut self) {
+        self.stop_engine();
+        self.engine_color = match self.engine_color {
+            Some(_) => None,
+            None => Some(self.board.get_turn().opposite()),
+        };
+        // one engine per side
+        if self.engine_color.is_some() && self.engine_color == self.external_color {
+            self.external = None;
+            self.external_color = None;
+        }
+        self.update_seat();
+        tracing::info!("Engine plays: {:?}", self.engine_color);
+    }
+
+    /// Handles the messages of the running search, playing its move once it is done
+    fn poll_engine(&mut self) {
+        let Some(engine) = &self.engine else {
+            return;
+        };
+        let messages = std::iter::from_fn(|| engine.poll()).collect::<Vec<_>>();
+
+        for msg in messages {
+            match msg {
+                EngineMessage::Info(info) => {
+                    self.engine_pv = info.pv.clone();
+                    self.engine_stats.on_info(info);
+                }
+                EngineMessage::BestMove(best) => {
+                    self.engine = None;
+                    self.engine_deadline = None;
+                    let pv = std::mem::take(&mut self.engine_pv);
+                    match best {
+                        Some(mo) => {
+                            self.apply_move(mo.clone());
+                            self.start_pondering(&mo, &pv);
+                        }
+                        None => tracing::info!("Engine has no legal move"),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts thinking on the user's time about the reply to `mo` the engine expects, the
+    /// second move of `pv`, the line the engine played `mo` from
+    fn start_pondering(&mut self, mo: &Move, pv: &[Move]) {
+        if !self.pondering || !self.lifecycle.is_in_progress() || pv.first() != Some(mo) {
+            return;
+        }
+        let Some(expected) = pv.get(1) else {
+            return;
+        };
+        let mut board = self.board.clone();
+        if !board.is_legal(expected) || board.play(expected).is_err() {
+            return;
+        }
+        tracing::debug!("Pondering on {}", expected);
+        self.ponder = Some(Ponder {
+            handle: EngineHandle::spawn_opponent(
+                board,
+                None,
+                self.difficulty,
+                self.threads,
+                self.engine_params.clone(),
+            ),
+            expected: expected.clone(),
+            started: Instant::now(),
+            pv: vec![],
+            best: None,
+        });
+    }
+
+    fn poll_ponder(&mut self) {
+        let Some(ponder) = &mut self.ponder else {
+            return;
+        };
+        while let Some(msg) = ponder.handle.poll() {
+            match msg {
+                EngineMessage::Info(info) => {
+                    ponder.pv = info.pv.clone();
+                    self.engine_stats.on_info(info);
+                }
+                EngineMessage::BestMove(best) => ponder.best = Some(best),
+            }
+        }
+    }
+
+    /// Carries on with the pondering search if the user played the reply it expected, returns
+    /// false if there was none to carry on with. A search that already ran for the think time
+    /// answers at once
+    fn ponder_hit(&mut self) -> bool {
+        let Some(ponder) = self.ponder.take() else {
+            return false;
+        };
+        if self.board.get_move_history().last() != Some(&ponder.expected) {
+            tracing::debug!("Ponder miss, expected {}", ponder.expected);
+            return false;
+        }
+        tracing::debug!("Ponder hit on {}", ponder.expected);
+
+        match ponder.best {
+            Some(Some(mo)) => {
+                self.apply_move(mo.clone());
+                self.start_pondering(&mo, &ponder.pv);
+            }
+            Some(None) => tracing::info!("Engine has no legal move"),
+            None => {
+                self.engine = Some(ponder.handle);
+                self.engine_pv = ponder.pv;
+                let deadline = ponder.started + self.engine_move_time();
+                self.engine_deadline = Some(deadline.max(Instant::now()));
+            }
+        }
+        true
+    }
+
+    /// Starts the external engine from [uci_client::engine_path] to play the side not to move,
+    /// or stops it if it was already playing
+    pub fn toggle_external_engine(&mut self) {
+        if self.external.take().is_some() {
+            self.external_color = None;
+            tracing::info!("External engine stopped");
+            return;
+        }
+
+        let path = uci_client::engine_path();
+        match UciClient::spawn(&path) {
+            Ok(client) => {
+                let color = self.board.get_turn().opposite();
+                // one engine per side
+                if self.engine_color == Some(color) {
+                    self.engine_color = None;
+                    self.stop_engine();
+                }
+                self.external = Some(client);
+                self.external_color = Some(color);
+                self.update_seat();
+                tracing::info!("External engine {:?} plays: {:?}", path, color);
+            }
+            Err(e) => self
+                .notice
+                .show(format!("Failed to start engine {:?}: {}", path, e)),
+        }
+    }
+
+    /// Handles the output of the external engine, playing its move once it answers
+    fn poll_external(&mut self) {
+        let Some(external) = &mut self.external else {
+            return;
+        };
+        let Some(event) = external.poll() else {
+            return;
+        };
+        match event {
+            UciEvent::BestMove(Some(mo)) => {
+                let name = external.name().unwrap_or("External engine");
+                tracing::info!("{} plays {}", name, mo);
+                self.apply_move(mo);
+            }
+            UciEvent::BestMove(None) => tracing::info!("External engine has no legal move"),
+            UciEvent::Exited => {
+                self.notice.show("External engine exited".to_string());
+                self.external = None;
+                self.external_color = None;
+            }
+        }
+    }
+
+    /// Replaces the current game with the position from `fen`
+    pub fn load_fen(&mut self, fen: &str) {
+        let mut board = board::Board::new();
+        board.load_position(fen.to_string());
+        if board.variant() == Variant::Standard {
+            board.set_variant(self.variant);
+        }
+        self.set_board(board);
+    }
+
+    /// Replaces the current game with `board`
+    fn set_board(&mut self, board: board::Board) {
+        self.review = None;
+        self.pending_move = None;
+        self.start_board = board.clone();
+        self.viewing = None;
+        self.board = board;
+        self.redo.clear();
+        self.annotations.clear();
+        self.game_over = None;
+        self.turn_base = 0;
+        self.conditions.clear();
+        self.condition_editor = None;
+        self.stop_engine();
+        if let Some(external) = &mut self.external {
+            let _ = external.stop();
+        }
+        self.unset_selected();
+        self.pawn_promotion = false;
+        self.events
+            .push(BoardEvent::TurnChanged(self.board.get_turn()));
+    }
+
+    pub fn toggle_backup_picker(&mut self) {
+        self.backup_picker = match self.backup_picker {
+            Some(_) => None,
+            None => Some(BackupPicker::new(self.autosave.backups())),
+        };
+    }
+
+    /// Restores the backup selected in the backup picker and closes it
+    pub fn restore_selected_backup(&mut self) {
+        let Some(picker) = self.backup_picker.take() else {
+            return;
+        };
+        let Some(backup) = picker.selected() else {
+            return;
+        };
+
+        match Autosave::restore(&backup.path) {
+            Ok(session) => {
+                let fen = session.lines().next().unwrap_or_default();
+                tracing::info!("Restoring {:?}: {}", backup.path, fen);
+                self.start_game(fen);
+            }
+            Err(e) => tracing::error!("Failed to restore {:?}: {:?}", backup.path, e),
+        }
+    }
+
+    pub fn set_free_move(&mut self, free_move: bool) {
+        // the puzzles and the drill are over once pieces move freely
+        if free_move {
+            self.puzzles = None;
+            self.drill = None;
+        }
+        if self.free_move != free_move {
+            self.free_move = free_move;
+            tracing::info!("Free move: {}", self.free_move);
+        }
+    }
+
+    /// Analysis: toggles one of the castling rights of `color`
+    pub fn toggle_castling_right(&mut self, color: PieceColor, king_side: bool) {
+        let mut rights = self.board.castling_rights();
+        let right = match (color, king_side) {
+            (PieceColor::White, true) => &mut rights.white_king_side,
+            (PieceColor::White, false) => &mut rights.white_queen_side,
+            (PieceColor::Black, true) => &mut rights.black_king_side,
+            (PieceColor::Black, false) => &mut rights.black_queen_side,
+        };
+        *right = !*right;
+        self.edit_position(BoardBuilder::from_board(&self.board).castling(rights));
+    }
+
+    /// Analysis: makes the square under the cursor the en passant square, or clears it if it
+    /// already was
+    pub fn toggle_en_passant_at_cursor(&mut self) {
+        let square = self
+            .board
+            .get_square(self.cursor.x as usize, self.cursor.y as usize);
+        let en_passant = if self.board.en_passant_square() == Some(square) {
+            None
+        } else {
+            Some(square)
+        };
+        self.edit_position(BoardBuilder::from_board(&self.board).en_passant(en_passant));
+    }
+
+    /// Replaces the position with the one from `builder` if it is valid
+    fn edit_position(&mut self, builder: BoardBuilder) {
+        match builder.build() {
+            Ok(board) => {
+                self.set_board(board);
+                tracing::info!("Position edited: {}", self.board.to_fen());
+            }
+            Err(e) => self.notice.show(e.to_string()),
+        }
+    }
+
+    /// Sets the side to move, used by free move mode to move either side's pieces
+    fn set_side_to_move(&mut self, color: PieceColor) {
+        if self.board.get_turn() != color {
+            self.board.make_null_move();
+            self.events.push(BoardEvent::TurnChanged(color));
+        }
+    }
+
+    /// Screen space occupied by the board
+    pub fn board_rect(&self) -> BoardRect {
+        BoardRect {
+            x: self.x_offset as i32,
+            y: self.y_offset as i32,
+            size: (self.cell_size * 8) as i32,
+            bottom: self.bottom,
+        }
+    }
+
+    /// Applies `mo` to the board and does the bookkeeping: records it, passes the turn and
+    /// regenerates the moves for the new position
+    fn apply_move(&mut self, mo: Move) {
+        let capture = self.board.is_capture(&mo);
+        // the opponent's moves of a drill come from the repertoire already
+        let recorded =
+            (self.repertoire.is_some() && self.drill.is_none() && self.condition_editor.is_none())
+                .then(|| self.board.clone());
+        let san = self
+            .narrator
+            .as_ref()
+            .map(|_| san::to_san(&self.board, &mo));
+        let played = match mo.dropped_piece() {
+            Some(piece_type) => self.board.make_drop(piece_type, mo.to),
+            None => self.board.make_move(mo.from, mo.to, mo.promotion_piece()),
+        };
+        if played.is_err() {
+            tracing::debug!("Invalid Move: {}", mo);
+            return;
+        }
+        if let Some(before) = recorded {
+            self.record_repertoire_move(&before, &mo);
+        }
+
+        match &mut self.condition_editor {
+            Some(editor) => editor.line.push(mo.clone()),
+            None => self.audit_log.record(AuditEvent::MoveReceived {
+                ply: self.board.get_move_history().len(),
+                color: self.board.get_turn(),
+                from: mo.from,
+                to: mo.to,
+            }),
+        }
+        // the peer learns of the moves played here, the host's being accepted as they are
+        if self.condition_editor.is_none() && self.is_local_move_over_network() {
+            let turn = self.turn_number() - 1;
+            match self.link.as_ref().map(Link::is_host) {
+                Some(true) => self.outbox.push(NetMessage::MoveAccepted {
+                    turn,
+                    mv: mo.clone(),
+                }),
+                _ => self.outbox.push(NetMessage::MoveRequest {
+                    turn,
+                    mv: mo.clone(),
+                }),
+            }
+        }
+        // the moves taken back stay to be replayed only while the same line is played again
+        if self.redo.last() == Some(&mo) {
+            self.redo.pop();
+        } else {
+            self.redo.clear();
+        }
+
+        let mover = self.board.get_turn();
+        // playing on instead of answering turns the opponent's draw offer down
+        if self.lifecycle.draw_offer() == Some(mover.opposite()) {
+            let _ = self.lifecycle.decline_draw();
+        }
+        // and a takeback request lapses once another move is on the board
+        let _ = self.lifecycle.answer_takeback();
+        if let (Some(clock), None) = (&mut self.clock, &self.condition_editor) {
+            if self.lifecycle.is_in_progress() {
+                clock.press(mover);
+                if !clock.increment().is_zero() {
+                    self.audit_log.record(AuditEvent::ClockAdjusted {
+                        color: mover,
+                        delta_ms: clock.increment().as_millis() as i64,
+                        reason: ClockAdjustment::Increment,
+                    });
+                }
+            }
+        }
+
+        self.board.toggle_turn();
+        self.board.generate_moves_current_position();
+        #[cfg(debug_assertions)]
+        board::watchdog(&self.board, &mo);
+        self.viewing = None;
+        self.unset_selected();
+        self.pawn_promotion = false;
+        self.turn_to_mover();
+        self.animation =
+            (self.animation_speed != AnimationSpeed::Off).then(|| (mo.clone(), Instant::now()));
+
+        let turn = self.board.get_turn();
+        let check = self.board.is_in_check(turn);
+        if check && self.board.variant() == Variant::ThreeCheck {
+            let checks = self.board.checks_given(mover);
+            self.notice
+                .show(format!("{:?} gave check {} of 3", mover, checks));
+        }
+        if let (Some(narrator), Some(san)) = (&mut self.narrator, san) {
+            let ply = self.board.get_move_history().len();
+            narrator.moved(ply, mover, &san, mo.from, mo.to);
+            if check {
+                narrator.check(turn, self.board.legal_moves().next().is_none());
+            }
+        }
+
+        self.events.push(BoardEvent::MoveMade {
+            from: mo.from,
+            to: mo.to,
+            capture,
+            check,
+        });
+        self.events
+            .push(BoardEvent::TurnChanged(self.board.get_turn()));
+    }
+
+    /// Returns true if the engine or the external engine is to move
+    fn is_opponent_engine_turn(&self) -> bool {
+        let turn = Some(self.board.get_turn());
+        self.engine_color == turn || self.external_color == turn
+    }
+
+    /// Returns true if moves can be taken back and replayed, which they can't in a network game,
+    /// while a timed game is being played nor while conditions are recorded
+    fn can_take_back(&mut self) -> bool {
+        if self.remote_color.is_some() {
+            self.notice
+                .show("Moves can't be taken back in a network game".to_string());
+            return false;
+        }
+        if self.lifecycle.is_timed_and_live() {
+            self.notice
+                .show("Moves can't be taken back while the clock is running".to_string());
+            return false;
+        }
+        if self.puzzles.is_some() {
+            self.notice
+                .show("Moves can't be taken back in a puzzle".to_string());
+            return false;
+        }
+        if self.drill.is_some() {
+            self.notice
+                .show("Moves can't be taken back in a drill".to_string());
+            return false;
+        }
+        self.condition_editor.is_none()
+    }
+
+    /// Returns true if taking a move back needs the opponent's consent: while a game between
+    /// two players, at this board or over the network, is being played
+    fn takes_back_by_request(&self) -> bool {
+        self.engine_color.is_none()
+            && self.external_color.is_none()
+            && !self.free_move
+            && self.puzzles.is_none()
+            && self.drill.is_none()
+            && self.condition_editor.is_none()
+            && self.lifecycle.is_in_progress()
+    }
+
+    /// Takes back the last move, and the engine's reply before it so that the user is to move
+    /// again. The moves taken back can be replayed with [Game::redo_move] until another move is
+    /// played. Between two players the opponent is asked first, see [Game::request_takeback]
+    pub fn take_back(&mut self) {
+        if self.takes_back_by_request() {
+            self.request_takeback();
+            return;
+        }
+        if !self.can_take_back() {
+            return;
+        }
+        let taken = self.redo.len();
+        while let Some(mo) = self.board.unmake_move() {
+            self.redo.push(mo);
+            if !self.is_opponent_engine_turn() {
+                break;
+            }
+        }
+        if self.redo.len() == taken {
+            self.notice.show("No move to take back".to_string());
+            return;
+        }
+        // a game ended on the board carries on from the position before
+        if self.lifecycle.resume().is_ok() {
+            self.game_over = None;
+        }
+
+        self.stop_engine();
+        if let Some(external) = &mut self.external {
+            let _ = external.stop();
+        }
+        self.viewing = None;
+        self.unset_selected();
+        self.pawn_promotion = false;
+        self.turn_to_mover();
+        self.events
+            .push(BoardEvent::TurnChanged(self.board.get_turn()));
+    }
+
+    /// Asks the opponent to let the last mover take their move back. Over the network the
+    /// request is always the user's own, even when they are to move
+    pub fn request_takeback(&mut self) {
+        if self.board.get_move_history().is_empty() {
+            self.notice.show("No move to take back".to_string());
+            return;
+        }
+        let by = match self.remote_color {
+            Some(_) => self.local_color(),
+            None => self.board.get_turn().opposite(),
+        };
+        if let Err(e) = self.lifecycle.request_takeback(by) {
+            self.notice.show(e.to_string());
+            return;
+        }
+        if self.remote_color.is_some() {
+            self.outbox.push(NetMessage::TakebackRequest {
+                turn: self.turn_number(),
+            });
+            self.notice.show("Takeback requested".to_string());
+        }
+    }
+
+    /// Lets the opponent take their move back
+    pub fn accept_takeback(&mut self) {
+        let Some(by) = self.lifecycle.takeback_request() else {
+            return;
+        };
+        if self.remote_color.is_some() && by == self.local_color() {
+            return;
+        }
+        let _ = self.lifecycle.answer_takeback();
+        if self.remote_color.is_some() {
+            self.outbox.push(NetMessage::TakebackAccept {
+                turn: self.turn_number(),
+            });
+        }
+        self.undo_for(by);
+        self.notice.show("Takeback accepted".to_string());
+    }
+
+    /// Turns down the opponent's takeback request
+    pub fn decline_takeback(&mut self) {
+        let Some(by) = self.lifecycle.takeback_request() else {
+            return;
+        };
+        if self.remote_color.is_some() && by == self.local_color() {
+            return;
+        }
+        let _ = self.lifecycle.answer_takeback();
+        if self.remote_color.is_some() {
+            self.outbox.push(NetMessage::TakebackDecline);
+        }
+        self.notice.show("Takeback declined".to_string());
+    }
+
+    /// Unmakes the moves since `by` last moved, so that they are to move again, and restarts
+    /// the clock of the side now to move. Unlike [Game::take_back] these moves can't be replayed
+    fn undo_for(&mut self, by: PieceColor) {
+        let plies = if self.board.get_turn() == by { 2 } else { 1 };
+        for _ in 0..plies {
+            if self.board.unmake_move().is_none() {
+                break;
+            }
+        }
+        self.redo.clear();
+        // conditional moves are tied to turn numbers that will be played again
+        self.conditions.clear();
+        if let Some(clock) = &mut self.clock {
+            if self.lifecycle.is_in_progress() {
+                clock.start(self.board.get_turn());
+            }
+        }
+        self.viewing = None;
+        self.unset_selected();
+        self.pawn_promotion = false;
+        self.pending_move = None;
+        self.turn_to_mover();
+        self.events
+            .push(BoardEvent::TurnChanged(self.board.get_turn()));
+    }
+
+    /// Replays the last move taken back, and the engine's reply after it
+    pub fn redo_move(&mut self) {
+        if !self.can_take_back() {
+            return;
+        }
+        let Some(mo) = self.redo.last().cloned() else {
+            self.notice.show("No move to replay".to_string());
+            return;
+        };
+        self.stop_engine();
+        self.apply_move(mo);
+        if self.is_opponent_engine_turn() {
+            if let Some(reply) = self.redo.last().cloned() {
+                self.apply_move(reply);
+            }
+        }
+    }
+
+    /// Turn number used by the network protocol, the number of plies played so far
+    pub fn turn_number(&self) -> u32 {
+        self.turn_base + self.board.get_move_history().len() as u32
+    }
+
+    /// Returns true if the network peer is to move
+    pub fn is_remote_turn(&self) -> bool {
+        self.remote_color == Some(self.board.get_turn())
+    }
+
+    /// Returns true if the move just made on the board was played by the user of a network game
+    fn is_local_move_over_network(&self) -> bool {
+        self.remote_color
+            .is_some_and(|remote| remote != self.board.get_turn())
+    }
+
+    /// Host side: arbitrates a move sent by the peer, plays it if accepted and returns the reply
+    pub fn receive_move(&mut self, turn: u32, mv: Move) -> NetMessage {
+        self.close_condition_editor();
+        let Some(color) = self.remote_color else {
+            return NetMessage::MoveRejected {
+                turn,
+                reason: net::RejectReason::NotYourTurn,
+            };
+        };
+        if !self.lifecycle.is_in_progress() {
+            return NetMessage::MoveRejected {
+                turn,
+                reason: net::RejectReason::GameOver,
+            };
+        }
+        match net::arbitrate(&self.board, self.turn_number(), color, turn, &mv) {
+            Ok(()) => {
+                self.apply_move(mv.clone());
+                self.play_conditional_reply(turn, &mv);
+                NetMessage::MoveAccepted { turn, mv }
+            }
+            Err(reason) => {
+                tracing::info!("Rejected {} on turn {}: {:?}", mv, turn, reason);
+                NetMessage::MoveRejected { turn, reason }
+            }
+        }
+    }
+
+    /// Client side: handles a message from the host
+    pub fn on_host_message(&mut self, msg: NetMessage) {
+        self.close_condition_editor();
+        match msg {
+            NetMessage::MoveAccepted { turn, mv } => {
+                // our own moves are already on the board
+                if turn == self.turn_number() {
+                    self.apply_move(mv.clone());
+                    self.play_conditional_reply(turn, &mv);
+                }
+            }
+            NetMessage::MoveRejected { reason, .. } => {
+                self.unset_selected();
+                self.pawn_promotion = false;
+                self.notice.show(reason.to_string());
+            }
+            NetMessage::Resync { turn, fen } => {
+                // nothing was missed while the connection was down
+                if turn == self.turn_number() && fen == self.board.to_fen() {
+                    return;
+                }
+                self.load_fen(&fen);
+                self.turn_base = turn;
+                self.notice
+                    .show("Position resynced with the host".to_string());
+                tracing::info!("Resynced to turn {}", turn);
+            }
+            NetMessage::NewGame {
+                color,
+                fen,
+                time_control,
+            } => {
+                self.begin_network_game(color.opposite(), &fen, time_control);
+                self.notice
+                    .show(format!("Network game, you play {:?}", color));
+            }
+            NetMessage::Clock { white, black } => {
+                let Some(time_control) = self.lifecycle.time_control() else {
+                    return;
+                };
+                if !self.lifecycle.is_in_progress() {
+                    return;
+                }
+                let mut clock = Clock::resumed(time_control, white, black);
+                clock.start(self.board.get_turn());
+                // the peer's reading replaces ours, the difference being the lag made up for
+                if let Some(old) = &self.clock {
+                    for color in [PieceColor::White, PieceColor::Black] {
+                        let delta = clock.remaining(color).as_millis() as i64
+                            - old.remaining(color).as_millis() as i64;
+                        self.audit_log.record(AuditEvent::ClockAdjusted {
+                            color,
+                            delta_ms: delta,
+                            reason: ClockAdjustment::LagCompensation,
+                        });
+                    }
+                }
+                self.clock = Some(clock);
+            }
+            NetMessage::Abort { .. }
+            | NetMessage::Resign { .. }
+            | NetMessage::RematchOffer
+            | NetMessage::RematchDecline
+            | NetMessage::TakebackRequest { .. }
+            | NetMessage::TakebackAccept { .. }
+            | NetMessage::TakebackDecline
+            | NetMessage::DrawOffer { .. }
+            | NetMessage::DrawDecline => {
+                self.on_lifecycle_message(msg);
+            }
+            NetMessage::MoveRequest { .. } | NetMessage::Ping => {
+                tracing::warn!("Unexpected message from host: {:?}", msg);
+            }
+        }
+    }
+
+    /// Handles an abort, resignation, rematch, draw or takeback message sent by the network
+    /// peer, on either side
+    pub fn on_lifecycle_message(&mut self, msg: NetMessage) {
+        let Some(remote) = self.remote_color else {
+            return;
+        };
+        match msg {
+            NetMessage::Abort { .. } => match self.lifecycle.abort(remote, self.turn_number()) {
+                Ok(()) => self
+                    .notice
+                    .show("Your opponent aborted the game".to_string()),
+                Err(e) => tracing::warn!("Ignoring abort from peer: {}", e),
+            },
+            NetMessage::Resign { .. } => match self.lifecycle.resign(remote) {
+                Ok(()) => self.on_game_over(),
+                Err(e) => tracing::warn!("Ignoring resignation from peer: {}", e),
+            },
+            NetMessage::RematchOffer => match self.lifecycle.offer_rematch(remote) {
+                Ok(OfferStatus::Offered) => self
+                    .notice
+                    .show("Rematch offered, press R to accept or N to decline".to_string()),
+                Ok(OfferStatus::Accepted) => self.start_rematch(),
+                Err(e) => tracing::warn!("Ignoring rematch offer from peer: {}", e),
+            },
+            NetMessage::RematchDecline => {
+                if self.lifecycle.decline_rematch().is_ok() {
+                    self.notice.show("Rematch declined".to_string());
+                }
+            }
+            // a request or an answer sent before a move crossed it no longer applies
+            NetMessage::TakebackRequest { turn } if turn == self.turn_number() => {
+                match self.lifecycle.request_takeback(remote) {
+                    Ok(()) => self.notice.show(
+                        "Your opponent asks to take back their move, press Y to accept or N to decline"
+                            .to_string(),
+                    ),
+                    Err(e) => tracing::warn!("Ignoring takeback request from peer: {}", e),
+                }
+            }
+            NetMessage::TakebackAccept { turn }
+                if turn == self.turn_number()
+                    && self.lifecycle.takeback_request() == Some(remote.opposite()) =>
+            {
+                let _ = self.lifecycle.answer_takeback();
+                self.undo_for(remote.opposite());
+                self.notice.show("Takeback accepted".to_string());
+            }
+            NetMessage::TakebackDecline => {
+                if self.lifecycle.takeback_request() == Some(remote.opposite()) {
+                    let _ = self.lifecycle.answer_takeback();
+                    self.notice.show("Takeback declined".to_string());
+                }
+            }
+            NetMessage::DrawOffer { turn } if turn == self.turn_number() => {
+                match self.lifecycle.offer_draw(remote) {
+                    Ok(OfferStatus::Offered) => self.notice.show(
+                        "Your opponent offers a draw, press Shift+D to accept or N to decline"
+                            .to_string(),
+                    ),
+                    Ok(OfferStatus::Accepted) => self.on_game_over(),
+                    Err(e) => tracing::warn!("Ignoring draw offer from peer: {}", e),
+                }
+            }
+            NetMessage::DrawDecline => {
+                if self.lifecycle.draw_offer() == Some(remote.opposite()) {
+                    let _ = self.lifecycle.decline_draw();
+                    self.notice.show("Draw declined".to_string());
+                }
+            }
+            _ => tracing::warn!("Unexpected lifecycle message: {:?}", msg),
+        }
+    }
+
+    /// Plays the conditional reply to `mv`, the peer's move on `turn`, if one was set
+    fn play_conditional_reply(&mut self, turn: u32, mv: &Move) {
+        if self.conditions.is_empty() {
+            return;
+        }
+        match self.conditions.take_reply(turn, mv) {
+            Some(reply) if self.board.is_legal(&reply) => {
+                tracing::info!("Conditional reply {} to {}", reply, mv);
+                self.apply_move(reply);
+            }
+            Some(reply) => {
+                self.conditions.clear();
+                tracing::warn!(
+                    "Conditional reply {} isn't legal, conditions cleared",
+                    reply
+                );
+            }
+            None => self
+                .notice
+                .show("Your opponent left your conditional moves, they were cleared".to_string()),
+        }
+    }
+
+    /// Starts recording a line of conditional moves, or saves the line being recorded. The
+    /// line is played on a copy of the position: the opponent's move, the reply, and so on
+    pub fn toggle_condition_editor(&mut self) {
+        if self.condition_editor.is_some() {
+            self.close_condition_editor();
+            return;
+        }
+        if !self.is_remote_turn() || !self.lifecycle.is_in_progress() {
+            self.notice.show(
+                "Conditional moves can only be set while waiting for your opponent".to_string(),
+            );
+            return;
+        }
+        self.condition_editor = Some(ConditionEditor {
+            board: self.board.clone(),
+            turn: self.turn_number(),
+            line: vec![],
+        });
+        self.unset_selected();
+    }
+
+    /// Saves the line being recorded, if any, and puts the real position back
+    fn close_condition_editor(&mut self) {
+        let Some(editor) = self.condition_editor.take() else {
+            return;
+        };
+        self.board = editor.board;
+        self.unset_selected();
+        self.pawn_promotion = false;
+        self.events
+            .push(BoardEvent::TurnChanged(self.board.get_turn()));
+        if editor.line.len() >= 2 {
+            self.conditions.add(editor.turn, &editor.line);
+            tracing::info!("Conditional moves: {:?}", self.condition_lines());
+        }
+    }
+
+    /// Removes the last line of conditional moves
+    pub fn remove_last_condition(&mut self) {
+        self.conditions.remove_last();
+    }
+
+    pub fn clear_conditions(&mut self) {
+        self.conditions.clear();
+    }
+
+    /// The conditional moves as text, one line per branch, e.g. `if e5: Nf3, if Nc6: Bb5`
+    fn condition_lines(&self) -> Vec<String> {
+        let start = match &self.condition_editor {
+            Some(editor) => &editor.board,
+            None => &self.board,
+        };
+        self.conditions
+            .lines()
+            .iter()
+            .map(|line| {
+                let mut board = start.clone();
+                let mut sans = vec![];
+                for mv in line {
+                    sans.push(san::to_san(&board, mv));
+                    if board.play(mv).is_err() {
+                        break;
+                    }
+                }
+                sans.chunks(2)
+                    .map(|pair| format!("if {}: {}", pair[0], pair.get(1).map_or("?", |r| r)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect()
+    }
+
+    /// Side played by the user at this screen, the side to move when both sides are played here
+    fn local_color(&self) -> PieceColor {
+        match self
+            .remote_color
+            .or(self.engine_color)
+            .or(self.external_color)
+        {
+            Some(_) => self.seat,
+            None => self.board.get_turn(),
+        }
+    }
+
+    /// Seats the user opposite the network peer or the engine, turning the board to their side
+    /// when auto flip is on
+    fn update_seat(&mut self) {
+        if let Some(opponent) = self
+            .remote_color
+            .or(self.engine_color)
+            .or(self.external_color)
+        {
+            self.seat = opponent.opposite();
+        }
+        if self.auto_flip {
+            self.bottom = self.seat;
+        }
+    }
+
+    /// Turns the board to the side to move when both sides are played here and hotseat flip is
+    /// on, so each player sees their pieces at the bottom
+    fn turn_to_mover(&mut self) {
+        let hotseat = self
+            .remote_color
+            .or(self.engine_color)
+            .or(self.external_color)
+            .is_none();
+        let training = self.puzzles.is_some() || self.drill.is_some();
+        if self.hotseat_flip && hotseat && !self.free_move && !training {
+            self.bottom = self.board.get_turn();
+        }
+    }
+
+    /// Turns the board around, auto flip stays off until it is turned back on
+    pub fn flip_board(&mut self) {
+        self.auto_flip = false;
+        self.bottom = self.bottom.opposite();
+    }
+
+    /// Turns auto flip on or off, turning the board to the user's side when it comes on
+    pub fn toggle_auto_flip(&mut self) {
+        self.auto_flip = !self.auto_flip;
+        self.update_seat();
+        tracing::info!("Auto flip: {}", self.auto_flip);
+    }
+
+    /// FEN of the position on screen
+    pub fn fen(&self) -> String {
+        self.displayed_board().to_fen()
+    }
+
+    /// Starts a new game from `fen` as typed or pasted by the user, showing what is wrong with
+    /// it instead when it can't be loaded
+    pub fn paste_fen(&mut self, fen: &str) {
+        if self.remote_color.is_some() {
+            self.notice
+                .show("The position can't be changed in a network game".to_string());
+            return;
+        }
+        match Board::parse_fen(fen.trim()) {
+            Ok(board) => {
+                self.start_game(&board.to_fen());
+                self.notice.show("Position loaded".to_string());
+            }
+            Err(e) => self.notice.show(format!("Invalid FEN: {}", e)),
+        }
+    }
+
+    /// Starts a new game from `fen`
+    pub fn start_game(&mut self, fen: &str) {
+        self.start_timed_game(fen, None);
+    }
+
+    /// Starts a new game from `fen` played on a clock, or untimed without a time control
+    fn start_timed_game(&mut self, fen: &str, time_control: Option<TimeControl>) {
+        // a game started any other way ends the puzzles and the drill
+        self.puzzles = None;
+        self.puzzle_reply_at = None;
+        self.drill = None;
+        self.drill_reply_at = None;
+        // and the game picked up from the games directory is left there as it was
+        self.ongoing_path = None;
+        // and the network game
+        self.link = None;
+        self.remote_color = None;
+        self.outbox.clear();
+        self.start_fen = fen.to_string();
+        self.variant = Variant::Standard;
+        self.lifecycle = Lifecycle::new(time_control);
+        self.load_fen(fen);
+        self.reset_clock();
+    }
+
+    /// Sets the clocks to the time control of the game, the time of the side to move running
+    fn reset_clock(&mut self) {
+        self.clock = self.lifecycle.time_control().map(Clock::new);
+        self.low_time_played = (false, false);
+        if let Some(clock) = &mut self.clock {
+            clock.start(self.board.get_turn());
+        }
+    }
+
+    /// Starts a new game of `variant` from its starting position, black giving `odds` unless the
+    /// variant has a position of its own, on a clock with `time_control` or untimed without one
+    pub fn start_new_game(
+        &mut self,
+        time_control: Option<TimeControl>,
+        variant: Variant,
+        odds: Odds,
+    ) {
+        let fen = match variant.start_fen() {
+            START_FEN => odds.fen(),
+            fen => fen,
+        };
+        self.start_timed_game(fen, time_control);
+        self.set_variant(variant);
+        let variant = match (variant, odds) {
+            (Variant::Standard, Odds::None) => String::new(),
+            (variant, Odds::None) => format!("{}, ", variant),
+            (Variant::Standard, odds) => format!("{} odds, ", odds),
+            (variant, odds) => format!("{}, {} odds, ", variant, odds),
+        };
+        let text = match time_control {
+            Some(time_control) => format!("New game, {}{}", variant, time_control),
+            None => format!("New game, {}untimed", variant),
+        };
+        self.notice.show(text);
+    }
+
+    /// Plays the game by the rules of `variant` from the position on the board
+    fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+        self.board.set_variant(variant);
+        self.start_board.set_variant(variant);
+    }
+
+    /// Solves the puzzles of `RUCHE_PUZZLES`, or of [PUZZLES_FILE], against the moves of their
+    /// solutions instead of an engine. Returns false when there are none to solve
+    pub fn start_puzzles(&mut self) -> bool {
+        if self.remote_color.is_some() {
+            self.notice
+                .show("Puzzles can't be solved during a network game".to_string());
+            return false;
+        }
+        let path = PathBuf::from(
+            std::env::var("RUCHE_PUZZLES").unwrap_or_else(|_| PUZZLES_FILE.to_string()),
+        );
+        let trainer = match puzzles::load(&path) {
+            Ok(puzzles) => PuzzleTrainer::new(puzzles),
+            Err(e) => {
+                self.notice
+                    .show(format!("Failed to read puzzles {:?}: {}", path, e));
+                return false;
+            }
+        };
+        let Some(trainer) = trainer else {
+            self.notice.show(format!("No puzzles in {:?}", path));
+            return false;
+        };
+        self.solve_puzzles(trainer);
+        true
+    }
+
+    /// Solves `puzzle` alone, e.g. one imported from lichess
+    pub fn start_puzzle(&mut self, puzzle: Puzzle) {
+        let trainer = PuzzleTrainer::new(vec![puzzle]).expect("one puzzle to solve");
+        self.solve_puzzles(trainer);
+    }
+
+    fn solve_puzzles(&mut self, trainer: PuzzleTrainer) {
+        self.stop_opponents();
+        self.set_free_move(false);
+        self.puzzles = Some(trainer);
+        self.load_puzzle();
+    }
+
+    pub fn is_solving_puzzles(&self) -> bool {
+        self.puzzles.is_some()
+    }
+
+    /// Sets up the current puzzle, its first move played by the opponent after a pause
+    fn load_puzzle(&mut self) {
+        let Some(trainer) = self.puzzles.take() else {
+            return;
+        };
+        let puzzle = trainer.puzzle();
+        self.start_timed_game(&puzzle.fen.clone(), None);
+        self.bottom = puzzle.solver();
+        self.seat = puzzle.solver();
+        self.puzzles = Some(trainer);
+        self.puzzle_reply_at = Some(Instant::now() + REPLY_DELAY);
+    }
+
+    /// Moves on to the next puzzle
+    pub fn next_puzzle(&mut self) {
+        if let Some(trainer) = &mut self.puzzles {
+            trainer.next();
+            self.load_puzzle();
+        }
+    }
+
+    /// Checks the move the user played against the solution, taking it back when it isn't the
+    /// one, and plays the opponent's moves once their pause is over
+    fn poll_puzzle(&mut self) {
+        let Some(trainer) = &mut self.puzzles else {
+            return;
+        };
+        let history = self.board.get_move_history();
+        if history.len() > trainer.ply() {
+            let mv = history[history.len() - 1].clone();
+            let mates = self.board.legal_moves().next().is_none()
+                && self.board.is_in_check(self.board.get_turn());
+            match trainer.check(&mv, mates) {
+                Attempt::Correct => {
+                    self.puzzle_reply_at = Some(Instant::now() + REPLY_DELAY);
+                }
+                Attempt::Wrong => {
+                    self.board.unmake_move();
+                    if self.lifecycle.resume().is_ok() {
+                        self.game_over = None;
+                    }
+                    self.viewing = None;
+                    self.unset_selected();
+                    self.events
+                        .push(BoardEvent::TurnChanged(self.board.get_turn()));
+                    self.notice
+                        .show(format!("{} isn't the move, try again", mv));
+                }
+                Attempt::Solved => self
+                    .notice
+                    .show("Puzzle solved - N: next puzzle".to_string()),
+            }
+            return;
+        }
+
+        if self.puzzle_reply_at.is_some_and(|at| Instant::now() >= at) {
+            self.puzzle_reply_at = None;
+            if let Some(reply) = trainer.reply() {
+                self.apply_move(reply);
+            }
+        }
+    }
+
+    /// Starts or stops adding the moves played on the board to the repertoire of the profile,
+    /// or the shared one
+    pub fn toggle_repertoire_recording(&mut self) {
+        if self.repertoire.take().is_some() {
+            self.notice
+                .show("Stopped recording the repertoire".to_string());
+            return;
+        }
+        let path = self.paths.repertoire.clone();
+        let repertoire = match Repertoire::open(&path) {
+            Ok(repertoire) => repertoire,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Repertoire::default(),
+            Err(e) => {
+                self.notice
+                    .show(format!("Failed to read the repertoire {:?}: {}", path, e));
+                return;
+            }
+        };
+        self.repertoire = Some(repertoire);
+        self.notice
+            .show(format!("Recording the moves played into {:?}", path));
+    }
+
+    /// Adds `mo`, played from the position on `before`, to the repertoire being recorded
+    fn record_repertoire_move(&mut self, before: &Board, mo: &Move) {
+        let Some(repertoire) = &mut self.repertoire else {
+            return;
+        };
+        repertoire.record(before, mo);
+        let path = &self.paths.repertoire;
+        if let Err(e) = repertoire.save(path) {
+            self.notice
+                .show(format!("Failed to save the repertoire {:?}: {}", path, e));
+        }
+    }
+
+    /// Drills the repertoire with the user playing `color`, the opponent's moves drawn from
+    /// it. Returns false when there is none to drill
+    pub fn start_repertoire_drill(&mut self, color: PieceColor) -> bool {
+        if self.remote_color.is_some() {
+            self.notice
+                .show("The repertoire can't be drilled during a network game".to_string());
+            return false;
+        }
+        let path = self.paths.repertoire.clone();
+        let drill = match Repertoire::open(&path) {
+            Ok(repertoire) => RepertoireDrill::new(repertoire, color),
+            Err(e) => {
+                self.notice
+                    .show(format!("Failed to read the repertoire {:?}: {}", path, e));
+                return false;
+            }
+        };
+        let Some(drill) = drill else {
+            self.notice.show(format!("No moves in {:?}", path));
+            return false;
+        };
+        self.stop_opponents();
+        self.set_free_move(false);
+        self.drill = Some(drill);
+        self.load_drill_line();
+        true
+    }
+
+    pub fn is_drilling(&self) -> bool {
+        self.drill.is_some()
+    }
+
+    /// Sets up the start of a line, the opponent moving first after a pause
+    fn load_drill_line(&mut self) {
+        let Some(drill) = self.drill.take() else {
+            return;
+        };
+        self.start_timed_game(START_FEN, None);
+        self.bottom = drill.color;
+        self.seat = drill.color;
+        self.drill = Some(drill);
+        self.drill_reply_at = Some(Instant::now() + REPLY_DELAY);
+    }
+
+    /// Starts the drill over from the first move, playing the other side when `switch_sides`
+    pub fn next_drill_line(&mut self, switch_sides: bool) {
+        if let Some(drill) = &mut self.drill {
+            let color = if switch_sides {
+                drill.color.opposite()
+            } else {
+                drill.color
+            };
+            drill.restart(color);
+            self.load_drill_line();
+        }
+    }
+
+    /// Checks the move the user played against the repertoire, taking it back when it isn't
+    /// one of theirs, and plays the opponent's moves once their pause is over
+    fn poll_drill(&mut self) {
+        let Some(drill) = &mut self.drill else {
+            return;
+        };
+        if drill.is_finished() {
+            return;
+        }
+        let history = self.board.get_move_history();
+        if history.len() > drill.ply() {
+            let mv = history[history.len() - 1].clone();
+            let mut before = self.board.clone();
+            before.unmake_move();
+            match drill.check(&before, &mv) {
+                DrillMove::Known => {
+                    self.drill_reply_at = Some(Instant::now() + REPLY_DELAY);
+                }
+                DrillMove::Deviation(expected) => {
+                    self.board.unmake_move();
+                    if self.lifecycle.resume().is_ok() {
+                        self.game_over = None;
+                    }
+                    self.viewing = None;
+                    self.unset_selected();
+                    self.events
+                        .push(BoardEvent::TurnChanged(self.board.get_turn()));
+                    let expected = expected
+                        .iter()
+                        .map(|m| san::to_san(&before, m))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    self.notice.show(format!(
+                        "{} isn't in your repertoire, you play {}",
+                        san::to_san(&before, &mv),
+                        expected
+                    ));
+                }
+            }
+            return;
+        }
+
+        if drill.finish(&self.board) {
+            self.notice
+                .show("End of the line - N: next line, C: other side".to_string());
+            return;
+        }
+        if self.drill_reply_at.is_some_and(|at| Instant::now() >= at) {
+            self.drill_reply_at = None;
+            let seed = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .subsec_nanos() as u64;
+            if let Some(reply) = drill.reply(&self.board, seed) {
+                self.apply_move(reply);
+            }
+        }
+    }
+
+    /// Time the engine may think about its move, taken from its clock in a timed game
+    fn engine_move_time(&self) -> Duration {
+        match &self.clock {
+            Some(clock) => SearchLimits::for_clock(
+                clock.remaining(self.board.get_turn()),
+                clock.increment(),
+                None,
+            )
+            .movetime
+            .unwrap_or(self.think_time),
+            None => self.think_time,
+        }
+    }
+
+    /// Warns the side to move once when its clock drops under [LOW_TIME]
+    fn check_low_time(&mut self) {
+        let turn = self.board.get_turn();
+        let Some(clock) = self.clock.as_ref().filter(|c| c.is_running(turn)) else {
+            return;
+        };
+        let played = match turn {
+            PieceColor::White => &mut self.low_time_played.0,
+            PieceColor::Black => &mut self.low_time_played.1,
+        };
+        if !*played && clock.remaining(turn) < LOW_TIME {
+            *played = true;
+            self.sound.play(SoundKind::LowTime);
+        }
+    }
+
+    /// Ends the game when the clock of the side to move runs out
+    fn check_flag(&mut self) {
+        let Some(by) = self.clock.as_ref().and_then(Clock::flagged) else {
+            return;
+        };
+        if self.lifecycle.flag(by).is_err() {
+            return;
+        }
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+        self.audit_log.record(AuditEvent::Flagged {
+            color: by,
+            remaining_ms: 0,
+        });
+        self.on_game_over();
+    }
+
+    /// Ends the game if it is over on the board: checkmate, stalemate, the fifty-move rule,
+    /// insufficient material or a position seen for the third time
+    fn check_game_end(&mut self) {
+        if !self.lifecycle.is_in_progress() || self.condition_editor.is_some() || self.free_move {
+            return;
+        }
+        let Some(state) = lifecycle::board_state(&self.start_board, &self.board) else {
+            return;
+        };
+        if self.lifecycle.finish(state).is_ok() {
+            self.on_game_over();
+        }
+    }
+
+    /// Stops everything still running for the game that just ended and shows its result
+    fn on_game_over(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+        // a game picked up from the games directory is no longer ongoing
+        if let Some(path) = self.ongoing_path.take() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                tracing::warn!("Failed to remove the finished game {:?}: {}", path, e);
+            }
+        }
+        self.stop_engine();
+        if let Some(external) = &mut self.external {
+            let _ = external.stop();
+        }
+        self.unset_selected();
+        self.pawn_promotion = false;
+        self.pending_move = None;
+        let state = self.lifecycle.state();
+        tracing::info!("Game over: {} {}", state, state.result());
+        if let Some(narrator) = &mut self.narrator {
+            narrator.game_over(state);
+        }
+        self.game_over = Some(state);
+        self.record_stats(state);
+
+        let moves = self.board.get_move_history().to_vec();
+        let training = self.puzzles.is_some() || self.drill.is_some();
+        self.review = (!moves.is_empty() && !training).then(|| {
+            Review::spawn(
+                self.start_board.clone(),
+                moves,
+                self.threads,
+                self.engine_params.clone(),
+            )
+        });
+        self.show_review();
+    }
+
+    /// Adds the game that ended in `state` to the statistics, when the user played one side
+    /// against the engine, an external engine or a network player
+    fn record_stats(&self, state: GameState) {
+        let opponent = self
+            .remote_color
+            .or(self.engine_color)
+            .or(self.external_color);
+        let engines_only = self.engine_color.is_some()
+            && self.external_color.is_some()
+            && self.engine_color != self.external_color;
+        let training = self.puzzles.is_some() || self.drill.is_some();
+        let (Some(opponent), "1-0" | "0-1" | "1/2-1/2") = (opponent, state.result()) else {
+            return;
+        };
+        if engines_only || training || self.free_move {
+            return;
+        }
+        let user = opponent.opposite();
+        let outcome = match (state.result(), user) {
+            ("1/2-1/2", _) => stats::Outcome::Draw,
+            ("1-0", PieceColor::White) | ("0-1", PieceColor::Black) => stats::Outcome::Win,
+            _ => stats::Outcome::Loss,
+        };
+        let opening = self
+            .openings
+            .classify(&self.start_board, self.board.get_move_history())
+            .map(|opening| format!("{} {}", opening.eco, opening.name));
+        let built_in = self.engine_color == Some(opponent) && self.external_color.is_none();
+        let record = stats::GameRecord {
+            date: pgn::today(),
+            opponent: self.player_name(opponent),
+            color: user.into(),
+            outcome,
+            opening,
+            time_control: self.lifecycle.time_control().map(|tc| tc.to_string()),
+            level: built_in.then(|| self.difficulty.name().to_string()),
+        };
+        let path = &self.paths.stats;
+        match stats::Stats::append(path, record) {
+            Ok(()) => tracing::info!("Recorded the game in {:?}", path),
+            Err(e) => tracing::warn!("Failed to record the game in {:?}: {}", path, e),
+        }
+    }
+
+    /// Passes the grades found by the review to the move list, with its progress or the
+    /// accuracy of both players once it is done. Moves played after the review drop it
+    fn show_review(&mut self) {
+        let review = self
+            .review
+            .as_ref()
+            .filter(|r| r.is_of(&self.start_board, self.board.get_move_history()));
+        let Some(review) = review else {
+            self.move_list.set_review(vec![], None);
+            self.eval_graph.set_chances(vec![]);
+            return;
+        };
+        self.eval_graph.set_chances(review.win_chances());
+        let summary = if review.is_done() {
+            let accuracy = |color| {
+                review
+                    .accuracy(color)
+                    .map_or("-".to_string(), |a| format!("{:.0}%", a))
+            };
+            format!(
+                "W {}  B {}",
+                accuracy(PieceColor::White),
+                accuracy(PieceColor::Black)
+            )
+        } else {
+            let (done, total) = review.progress();
+            format!("Review {}/{}", done, total)
+        };
+        self.move_list.set_review(review.qualities(), Some(summary));
+    }
+
+    /// How the game ended, once, for the game over screen to show it
+    pub fn take_game_over(&mut self) -> Option<GameState> {
+        self.game_over.take()
+    }
+
+    /// Returns true if the internal engine plays one of the sides
+    pub fn is_engine_playing(&self) -> bool {
+        self.engine_color.is_some()
+    }
+
+    pub fn show_notice(&mut self, text: &str) {
+        self.notice.show(text.to_string());
+    }
+
+    /// Gives up the game on behalf of the user
+    pub fn resign(&mut self) {
+        let by = self.local_color();
+        if let Err(e) = self.lifecycle.resign(by) {
+            self.notice.show(e.to_string());
+            return;
+        }
+        if self.remote_color.is_some() {
+            self.outbox.push(NetMessage::Resign {
+                turn: self.turn_number(),
+            });
+        }
+        self.on_game_over();
+    }
+
+    /// Starts a fresh game from the standard position, with the time control of the last one
+    pub fn new_game(&mut self) {
+        let time_control = self.lifecycle.time_control();
+        self.start_timed_game(START_FEN, time_control);
+        self.notice.show("New game".to_string());
+    }
+
+    /// The game so far as PGN
+    pub fn to_pgn(&self) -> String {
+        let name = |color| self.player_name(color);
+        let state = self.lifecycle.state();
+        let mut tags = vec![
+            ("Event", "ruche game".to_string()),
+            ("Site", "?".to_string()),
+            ("Date", pgn::today()),
+            ("Round", "-".to_string()),
+            ("White", name(PieceColor::White)),
+            ("Black", name(PieceColor::Black)),
+            ("Result", state.result().to_string()),
+        ];
+        if let Some(tc) = self.lifecycle.time_control() {
+            let time_control = format!("{}+{}", tc.base.as_secs(), tc.increment.as_secs());
+            tags.push(("TimeControl", time_control));
+        }
+        pgn::write_game(
+            &tags,
+            &self.start_board,
+            self.board.get_move_history(),
+            state.result(),
+        )
+    }
+
+    /// Name of the player of `color`: the engine's, the one typed at the game setup or a
+    /// placeholder
+    pub fn player_name(&self, color: PieceColor) -> String {
+        let typed = match color {
+            PieceColor::White => &self.player_names.0,
+            PieceColor::Black => &self.player_names.1,
+        };
+        if self.engine_color == Some(color) {
+            "ruche".to_string()
+        } else if self.external_color == Some(color) {
+            self.external
+                .as_ref()
+                .and_then(|e| e.name())
+                .unwrap_or("External engine")
+                .to_string()
+        } else if self.remote_color == Some(color) {
+            "Opponent".to_string()
+        } else if !typed.is_empty() {
+            typed.clone()
+        } else if let Some(profile) = self.profile.as_ref().filter(|_| {
+            // the user's side against an opponent that isn't at this board
+            self.remote_color
+                .or(self.engine_color)
+                .or(self.external_color)
+                .is_some()
+        }) {
+            profile.name.clone()
+        } else {
+            "Player".to_string()
+        }
+    }
+
+    /// Name of the profile in use, none for the shared settings and games
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_ref().map(|profile| profile.name.as_str())
+    }
+
+    /// Where the settings, statistics, repertoire, backups and games put aside of the profile
+    /// in use are kept
+    pub fn paths(&self) -> &Paths {
+        &self.paths
+    }
+
+    /// Switches to the settings, statistics and games of `profile`, or the shared ones for none.
+    /// The game in progress is kept for Continue in the profile it was played in, and a new
+    /// one is set up with the settings of the other
+    pub fn switch_profile(&mut self, profile: Option<&Profile>) {
+        self.save_unfinished();
+        self.stop_opponents();
+        if let Some(profile) = profile {
+            tracing::info!("Profile {:?} in {:?}", profile.name, profile.dir);
+        }
+        let narrator = self.narrator.take();
+        *self = Game::new(self._size, self.x_offset, self.y_offset, profile);
+        self.narrator = narrator;
+        self.load_images();
+        self.start_game(START_FEN);
+    }
+
+    /// Makes a profile called `name` starting from the settings in use, and switches to it
+    pub fn create_profile(&mut self, name: &str) -> Result<(), ProfileError> {
+        let profile = Profile::create(name)?;
+        self.config().save(&profile.paths().config)?;
+        self.switch_profile(Some(&profile));
+        Ok(())
+    }
+
+    /// Stops the engines and the clock until [Game::unpause]. The clock of a network game keeps
+    /// running, as the peer's can't be stopped
+    pub fn pause(&mut self) {
+        if std::mem::replace(&mut self.paused, true) {
+            return;
+        }
+        self.stop_engine();
+        if let Some(external) = &mut self.external {
+            let _ = external.stop();
+        }
+        let turn = self.board.get_turn();
+        if let (Some(clock), None) = (&mut self.clock, self.remote_color) {
+            self.clock_paused = clock.is_running(turn);
+            clock.stop();
+        }
+    }
+
+    /// Lets the engines think and the clock run again
+    pub fn unpause(&mut self) {
+        if !std::mem::take(&mut self.paused) {
+            return;
+        }
+        if let Some(clock) = self.clock.as_mut().filter(|_| self.clock_paused) {
+            clock.start(self.board.get_turn());
+        }
+        self.clock_paused = false;
+    }
+
+    /// Writes the game so far as PGN to a new file in the working directory
+    pub fn save_game(&mut self) {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = PathBuf::from(format!("ruche-{}.pgn", secs));
+        match std::fs::write(&path, self.to_pgn()) {
+            Ok(()) => self
+                .notice
+                .show(format!("Game saved to {}", path.display())),
+            Err(e) => self.notice.show(format!("Failed to save the game: {}", e)),
+        }
+    }
+
+    /// Title of the window, so that the game can be followed with the window in the
+    /// background: the side to move or the result, the opponent and the time of the side to
+    /// move once it runs low
+    pub fn window_title(&self) -> String {
+        let mut title = "ruche — ".to_string();
+        if self.paused {
+            title.push_str("Paused");
+        } else if self.puzzles.is_some() {
+            title.push_str("Puzzles");
+        } else if self.drill.is_some() {
+            title.push_str("Repertoire drill");
+        } else if self.free_move {
+            title.push_str("Analysis");
+        } else if self.lifecycle.is_in_progress() {
+            let turn = self.board.get_turn();
+            title.push_str(&format!("{:?} to move", turn));
+            if let Some(clock) = self.clock.as_ref().filter(|c| c.is_running(turn)) {
+                let left = clock.remaining(turn);
+                if left < LOW_TIME {
+                    title.push_str(&format!(" ({})", clock::format_time(left)));
+                }
+            }
+        } else {
+            title.push_str(&self.lifecycle.state().to_string());
+        }
+
+        let opponent = self
+            .engine_color
+            .or(self.external_color)
+            .or(self.remote_color);
+        if let Some(color) = opponent {
+            title.push_str(&format!(" — vs {}", self.player_name(color)));
+            if self.link.is_some() && !self.is_peer_connected() {
+                title.push_str(" (connection lost)");
+            }
+        } else if !self.player_names.0.is_empty() || !self.player_names.1.is_empty() {
+            title.push_str(&format!(
+                " — {} vs {}",
+                self.player_name(PieceColor::White),
+                self.player_name(PieceColor::Black)
+            ));
+        }
+        title
+    }
+
+    /// Names typed for the players of white and black, empty when not given
+    pub fn player_names(&self) -> &(String, String) {
+        &self.player_names
+    }
+
+    /// Names the players of white and black, an empty name leaving the placeholder
+    pub fn set_player_names(&mut self, white: &str, black: &str) {
+        self.player_names = (white.trim().to_string(), black.trim().to_string());
+    }
+
+    /// Offers a draw to the other player, at this board or over the network, or accepts theirs
+    pub fn offer_draw(&mut self) {
+        if self.engine_color.is_some() || self.external_color.is_some() {
+            self.notice
+                .show("Draws can only be offered to another player".to_string());
+            return;
+        }
+        let by = self.local_color();
+        let offer = self.lifecycle.offer_draw(by);
+        // offering back is how the peer learns we accepted
+        if offer.is_ok() && self.remote_color.is_some() {
+            self.outbox.push(NetMessage::DrawOffer {
+                turn: self.turn_number(),
+            });
+        }
+        match offer {
+            Ok(OfferStatus::Accepted) => self.on_game_over(),
+            Ok(OfferStatus::Offered) if self.remote_color.is_some() => {
+                self.notice.show("Draw offered".to_string());
+            }
+            Ok(OfferStatus::Offered) => {
+                self.notice
+                    .show(format!("{} offers a draw", self.player_name(by)));
+            }
+            Err(e) => self.notice.show(e.to_string()),
+        }
+    }
+
+    /// Turns down the other player's draw offer
+    pub fn decline_draw(&mut self) {
+        let theirs = self.lifecycle.draw_offer() != Some(self.local_color());
+        if theirs && self.lifecycle.decline_draw().is_ok() {
+            if self.remote_color.is_some() {
+                self.outbox.push(NetMessage::DrawDecline);
+            }
+            self.notice.show("Draw declined".to_string());
+        }
+    }
+
+    /// Calls the game off, only possible before both sides have moved
+    pub fn abort(&mut self) {
+        let by = self.local_color();
+        if let Err(e) = self.lifecycle.abort(by, self.turn_number()) {
+            self.notice.show(e.to_string());
+            return;
+        }
+        self.stop_engine();
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+        self.unset_selected();
+        self.pawn_promotion = false;
+        if self.remote_color.is_some() {
+            self.outbox.push(NetMessage::Abort {
+                turn: self.turn_number(),
+            });
+        }
+        self.notice.show("Game aborted".to_string());
+    }
+
+    /// Offers a rematch once the game is over, or accepts the opponent's offer. The engine
+    /// always accepts
+    pub fn offer_rematch(&mut self) {
+        let by = self.local_color();
+        match self.lifecycle.offer_rematch(by) {
+            Ok(OfferStatus::Accepted) => {
+                // offering back is how the peer learns we accepted
+                if self.remote_color.is_some() {
+                    self.outbox.push(NetMessage::RematchOffer);
+                }
+                self.start_rematch();
+            }
+            Ok(OfferStatus::Offered) if self.remote_color.is_some() => {
+                self.outbox.push(NetMessage::RematchOffer);
+                self.notice.show("Rematch offered".to_string());
+            }
+            Ok(OfferStatus::Offered) => self.start_rematch(),
+            Err(e) => self.notice.show(e.to_string()),
+        }
+    }
+
+    /// Turns down the opponent's rematch offer
+    pub fn decline_rematch(&mut self) {
+        let theirs = self.lifecycle.rematch_offer() != Some(self.local_color());
+        if theirs && self.lifecycle.decline_rematch().is_ok() && self.remote_color.is_some() {
+            self.outbox.push(NetMessage::RematchDecline);
+        }
+    }
+
+    /// Starts the rematch from the same position and time control, with colors swapped
+    fn start_rematch(&mut self) {
+        self.lifecycle = self.lifecycle.rematch();
+        self.engine_color = self.engine_color.map(PieceColor::opposite);
+        self.remote_color = self.remote_color.map(PieceColor::opposite);
+        self.external_color = self.external_color.map(PieceColor::opposite);
+        // the players swap colors too
+        let (white, black) = std::mem::take(&mut self.player_names);
+        self.player_names = (black, white);
+        self.update_seat();
+        let fen = self.start_fen.clone();
+        self.load_fen(&fen);
+        self.reset_clock();
+        self.notice
+            .show(format!("Rematch: you play {:?}", self.local_color()));
+    }
+
+    /// Takes the messages waiting to be sent to the network peer
+    pub fn drain_outbox(&mut self) -> Vec<NetMessage> {
+        std::mem::take(&mut self.outbox)
+    }
+
+    /// Plays a game over `link` against the peer playing `remote`, from `fen` with
+    /// `time_control`. The host tells the client about the game with [NetMessage::NewGame]
+    pub fn start_network_game(
+        &mut self,
+        link: Link,
+        remote: PieceColor,
+        fen: &str,
+        time_control: Option<TimeControl>,
+    ) {
+        if link.is_host() {
+            self.outbox.push(NetMessage::NewGame {
+                color: remote,
+                fen: fen.to_string(),
+                time_control,
+            });
+        }
+        self.begin_network_game(remote, fen, time_control);
+        self.link = Some(link);
+        self.notice
+            .show(format!("Network game, you play {:?}", remote.opposite()));
+    }
+
+    /// Starts a game against the network peer, keeping the connection
+    fn begin_network_game(
+        &mut self,
+        remote: PieceColor,
+        fen: &str,
+        time_control: Option<TimeControl>,
+    ) {
+        let link = self.link.take();
+        self.stop_opponents();
+        self.free_move = false;
+        self.player_names = Default::default();
+        self.start_timed_game(fen, time_control);
+        self.remote_color = Some(remote);
+        self.link = link;
+        self.update_seat();
+    }
+
+    /// Returns true while a network game is played and the peer is connected
+    pub fn is_peer_connected(&self) -> bool {
+        self.link.as_ref().is_some_and(Link::is_connected)
+    }
+
+    /// Time left to either side as the host sends it
+    fn clock_message(&self) -> Option<NetMessage> {
+        let clock = self.clock.as_ref()?;
+        Some(NetMessage::Clock {
+            white: clock.remaining(PieceColor::White),
+            black: clock.remaining(PieceColor::Black),
+        })
+    }
+
+    /// Handles what the network peer sent since the last frame and sends it the messages
+    /// waiting in the outbox
+    fn poll_network(&mut self) {
+        let Some(link) = &mut self.link else {
+            return;
+        };
+        let is_host = link.is_host();
+        let events = std::iter::from_fn(|| link.poll()).collect::<Vec<_>>();
+        for event in events {
+            match event {
+                LinkEvent::Connected if is_host => {
+                    // the client may have missed moves while it was away
+                    let resync = NetMessage::Resync {
+                        turn: self.turn_number(),
+                        fen: self.board.to_fen(),
+                    };
+                    self.send(resync);
+                    if let Some(clock) = self.clock_message() {
+                        self.send(clock);
+                    }
+                    self.notice.show("Your opponent is back".to_string());
+                }
+                LinkEvent::Connected => {
+                    self.notice.show("Reconnected to the host".to_string());
+                }
+                LinkEvent::Disconnected if is_host => self
+                    .notice
+                    .show("Connection lost, waiting for your opponent".to_string()),
+                LinkEvent::Disconnected => self
+                    .notice
+                    .show("Connection lost, reconnecting".to_string()),
+                LinkEvent::Message(NetMessage::MoveRequest { turn, mv }) if is_host => {
+                    let reply = self.receive_move(turn, mv);
+                    let accepted = matches!(reply, NetMessage::MoveAccepted { .. });
+                    self.send(reply);
+                    if let Some(clock) = self.clock_message().filter(|_| accepted) {
+                        self.send(clock);
+                    }
+                }
+                LinkEvent::Message(msg) if is_host => self.on_lifecycle_message(msg),
+                LinkEvent::Message(msg) => self.on_host_message(msg),
+            }
+        }
+        for msg in self.drain_outbox() {
+            self.send(msg);
+        }
+    }
+
+    /// Sends `msg` to the network peer right away, ahead of the outbox
+    fn send(&mut self, msg: NetMessage) {
+        if let Some(link) = &mut self.link {
+            link.send(&msg);
+        }
+    }
+
+    /// Writes the clock audit log to the current directory
+    pub fn export_audit_log(&self) {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = PathBuf::from(format!("ruche-audit-{}.log", secs));
+        match self.audit_log.export(&path) {
+            Ok(_) => tracing::info!("Audit log written to {:?}", path),
+            Err(e) => tracing::error!("Failed to write audit log: {:?}", e),
+        }
+    }
+
+    /// Saves the board to `path` once the frame is drawn, see [Game::run_export]
+    pub fn request_export(&mut self, format: ExportFormat, path: PathBuf) {
+        if format == ExportFormat::Gif && self.lifecycle.is_timed_and_live() {
+            self.notice
+                .show("The game can be exported once it is over".to_string());
+            return;
+        }
+        self.export = Some((format, path));
+    }
+
+    /// Writes the picture asked for with [Game::request_export], if any. Called outside of the
+    /// drawing of a frame, the board being drawn again offscreen without the mouse cursor
+    pub fn run_export(
+        &mut self,
+        rl: &mut raylib::core::RaylibHandle,
+        thread: &raylib::core::RaylibThread,
+    ) {
+        let Some((format, path)) = self.export.take() else {
+            return;
+        };
+        let cursor = std::mem::replace(&mut self.cursor, Vector2 { x: -1.0, y: -1.0 });
+        let notice = std::mem::take(&mut self.notice);
+        let animation = self.animation.take();
+        let result = match format {
+            ExportFormat::Png => self
+                .render_board(rl, thread)
+                .and_then(|image| export::write_png(&image, &path)),
+            ExportFormat::Gif => self.export_gif(rl, thread, &path),
+        };
+        self.cursor = cursor;
+        self.notice = notice;
+        self.animation = animation;
+        match result {
+            Ok(()) => self.notice.show(format!("Saved {:?}", path)),
+            Err(e) => self
+                .notice
+                .show(format!("Failed to save {:?}: {}", path, e)),
+        }
+    }
+
+    fn render_board(
+        &self,
+        rl: &mut raylib::core::RaylibHandle,
+        thread: &raylib::core::RaylibThread,
+    ) -> Result<raylib::core::texture::Image, ExportError> {
+        export::render(rl, thread, self.board_rect(), |d| self.draw_board(d))
+    }
+
+    /// Writes every position of the game to `path` as the frames of a GIF, the position on
+    /// screen being shown again afterwards
+    fn export_gif(
+        &mut self,
+        rl: &mut raylib::core::RaylibHandle,
+        thread: &raylib::core::RaylibThread,
+        path: &std::path::Path,
+    ) -> Result<(), ExportError> {
+        let size = self.board_rect().size as u16;
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut gif = GifEncoder::new(file, size, size)?;
+        let shown = self.shown_ply();
+        let plies = self.game_length();
+        let mut result = Ok(());
+        for ply in 0..=plies {
+            self.show_ply(ply);
+            let delay = if ply == plies {
+                GIF_LAST_FRAME_DELAY
+            } else {
+                GIF_FRAME_DELAY
+            };
+            result = self
+                .render_board(rl, thread)
+                .and_then(|image| Ok(gif.add_frame(&image.get_image_data(), delay)?));
+            if result.is_err() {
+                break;
+            }
+        }
+        self.show_ply(shown);
+        result?;
+        Ok(gif.finish()?)
+    }
+
+    /// Shows all the pieces, then only the user's, then none
+    pub fn next_piece_visibility(&mut self) {
+        let (next, text) = match self.pieces_shown {
+            PieceVisibility::All => (PieceVisibility::Own, "Only your pieces are shown"),
+            PieceVisibility::Own => (
+                PieceVisibility::Hidden,
+                "Blindfold: pieces hidden, / to type a move",
+            ),
+            PieceVisibility::Hidden => (PieceVisibility::All, "All pieces are shown"),
+        };
+        self.pieces_shown = next;
+        self.notice.show(text.to_string());
+    }
+
+    fn is_piece_shown(&self, piece: Piece) -> bool {
+        match self.pieces_shown {
+            PieceVisibility::All => true,
+            PieceVisibility::Own => piece.get_color() == self.local_color(),
+            PieceVisibility::Hidden => false,
+        }
+    }
+
+    pub fn draw_piece<T>(&self, d: &mut T, i: usize, piece: Piece)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        if piece.get_type() == PieceType::None {
+            return;
+        }
+
+        let texture = self.image_map.get(&piece).unwrap();
+        let (x, y) = self.board_rect().square_origin(i);
+
+        d.draw_texture(texture, x, y, raylib::core::color::Color::WHITE);
+    }
+
+    pub fn unset_selected(&mut self) {
+        self.selected = None;
+        self.pocket_selected = None;
+        self.targets.clear();
+        self.dragging = false;
+    }
+
+    /// Left button pressed: moves the selected piece to the square under the cursor, or else
+    /// selects the piece there and starts dragging it
+    pub fn grab_piece(&mut self) {
+        // a second click on the square of the pending move plays it, anywhere else drops it
+        if let Some(pending) = self.pending_move.take() {
+            if self.cursor_square() == Some(pending.to) {
+                self.apply_move(pending);
+            }
+            return;
+        }
+        if let Some(piece_type) = self.pocket_selected {
+            let drop = self.cursor_square().and_then(|to| {
+                self.board
+                    .legal_moves()
+                    .find(|m| m.dropped_piece() == Some(piece_type) && m.to == to)
+            });
+            self.unset_selected();
+            if let Some(drop) = drop {
+                self.play_chosen(drop);
+                return;
+            }
+        }
+        if self.selected.is_some() {
+            self.make_move();
+            if self.selected.is_none() || self.pawn_promotion {
+                return;
+            }
+        }
+        self.select_piece();
+        self.dragging = self.selected.is_some();
+    }
+
+    /// Square under the cursor, if it is on the board
+    pub fn cursor_square(&self) -> Option<usize> {
+        self.is_cursor_on_board().then(|| {
+            self.board
+                .get_square(self.cursor.x as usize, self.cursor.y as usize)
+        })
+    }
+
+    /// Right button pressed: starts an arrow on the square under the cursor
+    pub fn start_annotation(&mut self) {
+        self.annotation_from = self.cursor_square();
+    }
+
+    /// Right button released: circles the square the button was pressed on, or draws an arrow
+    /// to the square under the cursor. Drawing either again removes it
+    pub fn finish_annotation(&mut self) {
+        let (Some(from), Some(to)) = (self.annotation_from.take(), self.cursor_square()) else {
+            return;
+        };
+        let hash = self.displayed_board().zobrist_hash();
+        let annotations = self.annotations.entry(hash).or_default();
+        if from == to {
+            annotations.toggle_square(from);
+        } else {
+            annotations.toggle_arrow(from, to);
+        }
+        if annotations.is_empty() {
+            self.annotations.remove(&hash);
+        }
+    }
+
+    /// Removes the arrows and circles of the position on screen
+    pub fn clear_annotations(&mut self) {
+        let hash = self.displayed_board().zobrist_hash();
+        self.annotations.remove(&hash);
+    }
+
+    /// Left button released: drops the dragged piece on the square under the cursor. An illegal
+    /// drop puts it back on its square, and a drop on its own square keeps it selected so it can
+    /// be moved with a second click
+    pub fn drop_piece(&mut self) {
+        if !std::mem::take(&mut self.dragging) {
+            return;
+        }
+        let Some(selected) = self.selected.clone() else {
+            return;
+        };
+        if !self.is_cursor_on_board()
+            || (selected.x as usize == self.cursor.x as usize
+                && selected.y as usize == self.cursor.y as usize)
+        {
+            return;
+        }
+        self.make_move();
+        // the dropped piece is already on its square
+        self.animation = None;
+        if !self.pawn_promotion {
+            self.unset_selected();
+        }
+    }
+
+    pub fn make_move(&mut self) {
+        if self.selected.is_none() {
+            return;
+        }
+
+        let selected = self.selected.clone().unwrap();
+        let from = self
+            .board
+            .get_square(selected.x as usize, selected.y as usize);
+        let to = self
+            .board
+            .get_square(self.cursor.x as usize, self.cursor.y as usize);
+
+        // in free move mode the side of the selected piece is the side to move
+        if self.free_move {
+            let color = self.board.get_piece_at_index(from).get_color();
+            self.set_side_to_move(color);
+        }
+
+        // a pawn reaching the last rank has one move per promotion piece, let the user pick
+        let moves = self
+            .board
+            .moves_from(from)
+            .filter(|m| m.to == to)
+            .collect::<Vec<_>>();
+        if self.auto_queen && !self.force_promotion_picker {
+            if let Some(queen) = moves
+                .iter()
+                .find(|m| m.promotion_piece() == Some(PieceType::Queen))
+            {
+                self.play_chosen(queen.clone());
+                return;
+            }
+        }
+        if moves.iter().any(|m| m.promotion_piece().is_some()) {
+            self.pawn_promotion = true;
+            self.key_promotion = 0;
+            self.can_promote_to = moves;
+            return;
+        }
+
+        match moves.into_iter().next() {
+            Some(mo) => self.play_chosen(mo),
+            None => tracing::debug!("Invalid Move"),
+        }
+    }
+
+    /// Plays a move picked by the user, or holds it until confirmed when moves need confirming.
+    /// Lines of conditional moves and free moves of the analysis board are played at once
+    fn play_chosen(&mut self, mo: Move) {
+        if !self.confirm_moves || self.free_move || self.condition_editor.is_some() {
+            self.apply_move(mo);
+            return;
+        }
+        self.unset_selected();
+        self.pawn_promotion = false;
+        self.pending_move = Some(mo);
+        self.notice
+            .show("Click the square again or press Enter to confirm".to_string());
+    }
+
+    /// Plays the move waiting for confirmation, returns false if there is none
+    pub fn confirm_pending_move(&mut self) -> bool {
+        match self.pending_move.take() {
+            Some(mo) => {
+                self.apply_move(mo);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drops the move waiting for confirmation, returns false if there is none
+    pub fn cancel_pending_move(&mut self) -> bool {
+        self.pending_move.take().is_some()
+    }
+
+    /// Square of the piece still sliding to it after the last move, and where it is drawn
+    fn sliding_piece(&self) -> Option<(usize, (i32, i32))> {
+        let (mo, played) = self.animation.as_ref()?;
+        if self.displayed_board().get_move_history().last() != Some(mo) {
+            return None;
+        }
+        let progress =
+            played.elapsed().as_secs_f32() / self.animation_speed.duration().as_secs_f32();
+        if progress >= 1.0 {
+            return None;
+        }
+        // eases out, fast at first and settling on the square
+        let t = 1.0 - (1.0 - progress).powi(2);
+        let rect = self.board_rect();
+        let (fx, fy) = rect.square_origin(mo.from);
+        let (tx, ty) = rect.square_origin(mo.to);
+        let lerp = |a: i32, b: i32| a + ((b - a) as f32 * t) as i32;
+        Some((mo.to, (lerp(fx, tx), lerp(fy, ty))))
+    }
+
+    /// The position on the board, to be edited
+    pub fn position_builder(&self) -> BoardBuilder {
+        BoardBuilder::from_board(&self.board)
+    }
+
+    pub fn piece_textures(&self) -> &HashMap<Piece, raylib::core::texture::Texture2D> {
+        &self.image_map
+    }
+
+    /// Draws the position on `board` small, in the squares of `rect`, with the colors and the
+    /// pieces of the board on screen
+    pub fn draw_thumbnail<T>(&self, d: &mut T, board: &Board, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let palette = self.theme.palette;
+        let cell = rect.size / 8;
+        let scale = cell as f32 / self.cell_size as f32;
+        for idx in 0..64 {
+            let (x, y) = rect.square_origin(idx);
+            let color = if (idx % 8 + idx / 8) % 2 == 0 {
+                palette.light
+            } else {
+                palette.dark
+            };
+            d.draw_rectangle(x, y, cell, cell, color);
+            let piece = board.get_piece_at_index(idx);
+            if let Some(texture) = self.image_map.get(&piece) {
+                let position = raylib::core::math::Vector2::new(x as f32, y as f32);
+                d.draw_texture_ex(
+                    texture,
+                    position,
+                    0.0,
+                    scale,
+                    raylib::core::color::Color::WHITE,
+                );
+            }
+        }
+    }
+
+    /// Draws the squares and the pieces of a position being edited, the en passant square
+    /// marked
+    pub fn draw_position<T>(&self, d: &mut T, builder: &BoardBuilder)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let palette = self.theme.palette;
+        let rect = self.board_rect();
+        let cell = self.cell_size as i32;
+        for (idx, piece) in builder.pieces().iter().enumerate() {
+            let (sx, sy) = rect.square_origin(idx);
+            let color = if self.cursor_square() == Some(idx) {
+                palette.cursor
+            } else if (idx % 8 + idx / 8) % 2 == 0 {
+                palette.light
+            } else {
+                palette.dark
+            };
+            d.draw_rectangle(sx, sy, cell, cell, color);
+            if builder.en_passant_square() == Some(idx) {
+                d.draw_rectangle(sx, sy, cell, cell, palette.last_move);
+            }
+            self.draw_piece(d, idx, *piece);
+        }
+        if self.show_coordinates {
+            draw_coordinates(d, rect, palette.light, palette.dark);
+        }
+    }
+
+    pub fn draw_board<T>(&self, d: &mut T)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let palette = self.theme.palette;
+        let (white, black) = (palette.light, palette.dark);
+        let cursor_color = palette.cursor;
+        let selected_color = palette.selection;
+        let legal_color = palette.legal_move;
+
+        let copy_arr = self.displayed_board().clone_board();
+        let rect = self.board_rect();
+        let sliding = self.sliding_piece();
+
+        // king of the side to move when it is in check
+        let shown = self.displayed_board();
+        let checked_king = shown
+            .is_in_check(shown.get_turn())
+            .then(|| shown.king_square(shown.get_turn()))
+            .flatten();
+        let check_color = palette.check;
+        let scale = self.indicator_size.scale();
+        // width of the outlines of palettes marking with shapes
+        let thickness = (self.cell_size as f32 * 0.06 * scale).max(2.0) as i32;
+        // squares of the move leading to the position on screen, and of the queued replies
+        let last_move = shown.get_move_history().last().map(|mo| [mo.from, mo.to]);
+        let premoves = match self.viewing {
+            Some(_) => vec![],
+            None => self
+                .conditions
+                .lines()
+                .iter()
+                .filter_map(|line| line.get(1))
+                .flat_map(|mo| [mo.from, mo.to])
+                .collect(),
+        };
+
+        for (idx, p) in copy_arr.iter().enumerate() {
+            let x = idx % 8;
+            let y = idx / 8;
+            let (sx, sy) = rect.square_origin(idx);
+            let color = if (x + y) % 2 == 0 { white } else { black };
+
+            if self.cursor.x as usize == x && self.cursor.y as usize == y {
+                d.draw_rectangle(
+                    sx,
+                    sy,
+                    self.cell_size as i32,
+                    self.cell_size as i32,
+                    cursor_color,
+                );
+            } else if self.selected.is_some() {
+                let selected = self.selected.clone().unwrap();
+                if selected.x as usize == x && selected.y as usize == y {
+                    d.draw_rectangle(
+                        sx,
+                        sy,
+                        self.cell_size as i32,
+                        self.cell_size as i32,
+                        selected_color,
+                    );
+                } else {
+                    d.draw_rectangle(sx, sy, self.cell_size as i32, self.cell_size as i32, color);
+                }
+            } else {
+                d.draw_rectangle(sx, sy, self.cell_size as i32, self.cell_size as i32, color);
+            }
+
+            let cell = self.cell_size as i32;
+            if last_move.is_some_and(|squares| squares.contains(&idx)) {
+                d.draw_rectangle(sx, sy, cell, cell, palette.last_move);
+                // a mark in each corner
+                if palette.shapes {
+                    let mark = 2 * thickness;
+                    for (cx, cy) in [
+                        (0, 0),
+                        (cell - mark, 0),
+                        (0, cell - mark),
+                        (cell - mark, cell - mark),
+                    ] {
+                        d.draw_rectangle(sx + cx, sy + cy, mark, mark, palette.last_move.fade(1.0));
+                    }
+                }
+            }
+            if premoves.contains(&idx) {
+                d.draw_rectangle(sx, sy, cell, cell, palette.premove);
+                // crossed out
+                if palette.shapes {
+                    let (x0, y0, x1, y1) =
+                        (sx as f32, sy as f32, (sx + cell) as f32, (sy + cell) as f32);
+                    let color = palette.premove.fade(1.0);
+                    d.draw_line_ex(
+                        raylib::core::math::Vector2::new(x0, y0),
+                        raylib::core::math::Vector2::new(x1, y1),
+                        thickness as f32,
+                        color,
+                    );
+                    d.draw_line_ex(
+                        raylib::core::math::Vector2::new(x1, y0),
+                        raylib::core::math::Vector2::new(x0, y1),
+                        thickness as f32,
+                        color,
+                    );
+                }
+            }
+            let selected = self
+                .selected
+                .as_ref()
+                .is_some_and(|s| s.x as usize == x && s.y as usize == y);
+            if selected && palette.shapes {
+                d.draw_rectangle_lines_ex(
+                    raylib::ffi::Rectangle {
+                        x: sx as f32,
+                        y: sy as f32,
+                        width: cell as f32,
+                        height: cell as f32,
+                    },
+                    thickness,
+                    selected_color,
+                );
+            }
+
+            if checked_king == Some(idx) {
+                let half = self.cell_size as f32 / 2.0;
+                d.draw_circle_gradient(
+                    sx + half as i32,
+                    sy + half as i32,
+                    half,
+                    check_color,
+                    check_color.fade(0.0),
+                );
+                // a ring around the king
+                if palette.shapes {
+                    let center =
+                        raylib::core::math::Vector2::new(sx as f32 + half, sy as f32 + half);
+                    d.draw_ring(
+                        center,
+                        half - thickness as f32,
+                        half,
+                        0.0,
+                        360.0,
+                        32,
+                        check_color,
+                    );
+                }
+            }
+
+            // the dragged piece is drawn under the mouse instead
+            let dragged = self.dragging
+                && self
+                    .selected
+                    .as_ref()
+                    .is_some_and(|s| s.x as usize == x && s.y as usize == y);
+            let piece = Piece::from(*p);
+            if !dragged && sliding.is_none_or(|(to, _)| to != idx) && self.is_piece_shown(piece) {
+                self.draw_piece(d, idx, piece);
+            }
+
+            // a dot on the squares the selected piece can move to, a ring around its captures
+            if let Some((_, capture)) = self.targets.iter().find(|(to, _)| *to == idx) {
+                let half = self.cell_size as f32 / 2.0;
+                let center = raylib::core::math::Vector2::new(sx as f32 + half, sy as f32 + half);
+                if *capture {
+                    let inner = half * (1.0 - 0.2 * scale);
+                    d.draw_ring(center, inner, half, 0.0, 360.0, 32, legal_color);
+                } else {
+                    d.draw_circle_v(center, half / 3.0 * scale, legal_color);
+                }
+            }
+
+            if self.key_cursor == Some((x, y)) && !self.pawn_promotion {
+                for inset in 0..3 {
+                    d.draw_rectangle_lines(
+                        sx + inset,
+                        sy + inset,
+                        cell - 2 * inset,
+                        cell - 2 * inset,
+                        raylib::core::color::Color::BLUE,
+                    );
+                }
+            }
+        }
+
+        // drawn over the squares it passes
+        if let Some((to, (x, y))) = sliding {
+            let piece = self.board.get_piece_at_index(to);
+            if let Some(texture) = self
+                .image_map
+                .get(&piece)
+                .filter(|_| self.is_piece_shown(piece))
+            {
+                d.draw_texture(texture, x, y, raylib::core::color::Color::WHITE);
+            }
+        }
+
+        if self.show_coordinates {
+            draw_coordinates(d, rect, white, black);
+        }
+
+        self.turn_indicator.draw(d, self.board_rect());
+
+        if self.book_mode == BookMode::ShowMoves {
+            draw_book_moves(d, self.board_rect(), &self.book_moves);
+        }
+
+        if self.show_passed_pawns {
+            draw_passed_pawns(d, self.board_rect(), engine::passed_pawns(&self.board));
+        }
+
+        if let Some(picker) = &self.backup_picker {
+            picker.draw(d, self.board_rect());
+        }
+
+        if let Some(input) = &self.move_input {
+            input.draw(d, self.board_rect());
+        }
+
+        if let Some(annotations) = self.annotations.get(&self.displayed_board().zobrist_hash()) {
+            annotations.draw(d, rect);
+        }
+        if let (Some(from), Some(to)) = (self.annotation_from, self.cursor_square()) {
+            draw_arrow(d, rect, from, to, annotation_color());
+        }
+
+        // grade of the move leading to the position on screen, in the corner of its square
+        let shown = self.shown_ply();
+        let quality = shown
+            .checked_sub(1)
+            .and_then(|ply| self.move_list.quality(ply))
+            .filter(|q| *q != MoveQuality::Good);
+        if let (Some(quality), Some(mo)) =
+            (quality, self.displayed_board().get_move_history().last())
+        {
+            let (sx, sy) = rect.square_origin(mo.to);
+            let radius = self.cell_size as f32 * 0.18;
+            let (cx, cy) = (
+                sx + self.cell_size as i32 - radius as i32,
+                sy + radius as i32,
+            );
+            d.draw_circle(cx, cy, radius, quality.color());
+            let size = (radius * 1.2) as i32;
+            let width = raylib::core::text::measure_text(quality.glyph(), size);
+            d.draw_text(
+                quality.glyph(),
+                cx - width / 2,
+                cy - size / 2,
+                size,
+                raylib::core::color::Color::WHITE,
+            );
+        }
+
+        if let Some(pending) = &self.pending_move {
+            let color = raylib::core::color::Color::from_hex("e68a00").expect("Error parsing hex");
+            draw_arrow(d, rect, pending.from, pending.to, color.fade(0.8));
+        }
+
+        if let Some(hint) = &self.hint {
+            let color = raylib::core::color::Color::from_hex("2f7fd6").expect("Error parsing hex");
+            draw_arrow(d, self.board_rect(), hint.from, hint.to, color.fade(0.8));
+        }
+
+        if let (true, Some(selected)) = (self.dragging, &self.selected) {
+            let idx = self
+                .board
+                .get_square(selected.x as usize, selected.y as usize);
+            let piece = self.board.get_piece_at_index(idx);
+            if let Some(texture) = self
+                .image_map
+                .get(&piece)
+                .filter(|_| self.is_piece_shown(piece))
+            {
+                let half = self.cell_size as f32 / 2.0;
+                d.draw_texture(
+                    texture,
+                    (self.mouse.x - half) as i32,
+                    (self.mouse.y - half) as i32,
+                    raylib::core::color::Color::WHITE,
+                );
+            }
+        }
+
+        self.eval_bar.draw(d, self.board_rect());
+        self.analysis_panel.draw(d, self.board_rect());
+        self.explorer_panel
+            .draw(d, self.explorer_x(), self.board_rect());
+        self.move_list
+            .draw(d, self.move_list_area(), self.shown_ply());
+        self.eval_graph
+            .draw(d, self.eval_graph_origin(), self.shown_ply());
+        let (x, y, height) = self.move_column();
+        let boards = (&self.start_board, self.displayed_board());
+        for (color, y) in [
+            (self.bottom.opposite(), y - CAPTURES_HEIGHT),
+            (self.bottom, y + height),
+        ] {
+            if boards.1.is_crazyhouse() {
+                let selected = self
+                    .pocket_selected
+                    .filter(|_| color == self.board.get_turn());
+                let pocket = (boards.1.pocket(color), color);
+                draw_pocket(d, x, y, MOVE_LIST_WIDTH, &self.image_map, pocket, selected);
+            } else {
+                draw_captures(d, x, y, MOVE_LIST_WIDTH, &self.image_map, boards, color);
+            }
+            if let Some(clock) = &self.clock {
+                let running = clock.is_running(color);
+                draw_clock(d, x + MOVE_LIST_WIDTH, y, clock.remaining(color), running);
+            }
+        }
+        self.engine_stats.draw(d, self.x_offset as i32 + 5, 25);
+        draw_conditions(
+            d,
+            self.board_rect(),
+            &self.condition_lines(),
+            self.condition_editor.is_some(),
+        );
+        self.notice.draw(d, self.board_rect());
+
+        if let Some(color) = self.lifecycle.draw_offer() {
+            let text = format!(
+                "{} offers a draw - Shift+D: accept, N: decline",
+                self.player_name(color)
+            );
+            let width = raylib::core::text::measure_text(&text, 16);
+            d.draw_text(
+                &text,
+                (self.x_offset + self.cell_size * 8) as i32 - width - 5,
+                5,
+                16,
+                raylib::core::color::Color::DARKBLUE,
+            );
+        }
+
+        if let Some(color) = self.lifecycle.takeback_request() {
+            let text = if self.remote_color == Some(color) || self.remote_color.is_none() {
+                format!(
+                    "{} asks to take back - Y: accept, N: decline",
+                    self.player_name(color)
+                )
+            } else {
+                "Waiting for the takeback to be answered".to_string()
+            };
+            let width = raylib::core::text::measure_text(&text, 16);
+            d.draw_text(
+                &text,
+                (self.x_offset + self.cell_size * 8) as i32 - width - 5,
+                25,
+                16,
+                raylib::core::color::Color::DARKBLUE,
+            );
+        }
+
+        if !self.lifecycle.is_in_progress() {
+            let text = format!("{} - R: rematch", self.lifecycle.state());
+            let width = raylib::core::text::measure_text(&text, 16);
+            d.draw_text(
+                &text,
+                (self.x_offset + self.cell_size * 8) as i32 - width - 5,
+                5,
+                16,
+                raylib::core::color::Color::MAROON,
+            );
+        }
+
+        if let Some((ply, _)) = &self.viewing {
+            let text = format!(
+                "Move {} of {} - scroll down to return",
+                ply,
+                self.board.get_move_history().len()
+            );
+            let width = raylib::core::text::measure_text(&text, 16);
+            d.draw_text(
+                &text,
+                (self.x_offset + self.cell_size * 8) as i32 - width - 5,
+                25,
+                16,
+                raylib::core::color::Color::DARKBLUE,
+            );
+        }
+
+        if let Some(trainer) = &self.puzzles {
+            let (current, total) = trainer.progress();
+            let to_play = match trainer.puzzle().solver() {
+                PieceColor::White => "White",
+                PieceColor::Black => "Black",
+            };
+            d.draw_text(
+                &format!(
+                    "Puzzle {} of {}: {} to play  solved {}  failed {}",
+                    current, total, to_play, trainer.solved, trainer.failed
+                ),
+                self.x_offset as i32 + 5,
+                5,
+                16,
+                raylib::core::color::Color::DARKGRAY,
+            );
+        }
+
+        if let Some(drill) = &self.drill {
+            let side = match drill.color {
+                PieceColor::White => "White",
+                PieceColor::Black => "Black",
+            };
+            d.draw_text(
+                &format!(
+                    "Repertoire drill: {}  lines {}  deviations {}",
+                    side, drill.lines, drill.deviations
+                ),
+                self.x_offset as i32 + 5,
+                5,
+                16,
+                raylib::core::color::Color::DARKGRAY,
+            );
+        }
+
+        if self.free_move {
+            let en_passant = self
+                .board
+                .en_passant_square()
+                .map(board::square_to_algebraic)
+                .unwrap_or("-".to_string());
+            d.draw_text(
+                &format!(
+                    "Analysis: free move  castling {}  ep {}",
+                    self.board.castling_rights(),
+                    en_passant
+                ),
+                self.x_offset as i32 + 5,
+                5,
+                16,
+                raylib::core::color::Color::DARKGRAY,
+            );
+        }
+
+        if self.pawn_promotion {
+            self.draw_promotion_picker(d);
+        }
+    }
+
+    /// Returns true if the promotion picker is a cross around the promotion square, each piece
+    /// chosen by pushing the cursor its way, as suits a controller
+    fn is_radial_promotion(&self) -> bool {
+        self.key_cursor.is_some() && self.gamepad.is_connected()
+    }
+
+    /// Squares of the promotion picker on screen, a column of the pieces the pawn can become
+    /// running from the promotion square toward the middle of the board, or a cross around it
+    /// for a controller
+    fn promotion_squares(&self) -> Vec<(PieceType, (i32, i32))> {
+        let Some(to) = self.can_promote_to.first().map(|m| m.to) else {
+            return vec![];
+        };
+        let rect = self.board_rect();
+        let cell = self.cell_size as i32;
+        let (x, y) = rect.square_origin(to);
+        if self.is_radial_promotion() {
+            // the cross is kept on the board, next to an edge square
+            let x = x.clamp(rect.x + cell, rect.x + rect.size - 2 * cell);
+            let y = y.clamp(rect.y + cell, rect.y + rect.size - 2 * cell);
+            return PROMOTION_PIECES
+                .iter()
+                .zip(RADIAL_PROMOTION)
+                .map(|(piece_type, (dx, dy))| (*piece_type, (x + dx * cell, y + dy * cell)))
+                .collect();
+        }
+        let step = if rect.screen_square(to).1 < 4 {
+            cell
+        } else {
+            -cell
+        };
+        PROMOTION_PIECES
+            .iter()
+            .enumerate()
+            .map(|(i, piece_type)| (*piece_type, (x, y + i as i32 * step)))
+            .collect()
+    }
+
+    fn draw_promotion_picker<T>(&self, d: &mut T)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let rect = self.board_rect();
+        let cell = self.cell_size as i32;
+        let background = raylib::core::color::Color::from_hex("f0f0f0").expect("Error parsing hex");
+        let hovered = raylib::core::color::Color::from_hex("ffee80").expect("Error parsing hex");
+
+        d.draw_rectangle(
+            rect.x,
+            rect.y,
+            rect.size,
+            rect.size,
+            raylib::core::color::Color::BLACK.fade(0.4),
+        );
+        let piece_color = self.board.get_turn();
+        for (piece_type, (x, y)) in self.promotion_squares() {
+            let under_mouse = match self.key_cursor {
+                Some(_) => PROMOTION_PIECES[self.key_promotion] == piece_type,
+                None => {
+                    (x..x + cell).contains(&(self.mouse.x as i32))
+                        && (y..y + cell).contains(&(self.mouse.y as i32))
+                }
+            };
+            let half = cell as f32 / 2.0;
+            d.draw_circle(
+                x + cell / 2,
+                y + cell / 2,
+                half,
+                if under_mouse { hovered } else { background },
+            );
+            if let Some(texture) = self.image_map.get(&Piece::new(piece_color, piece_type)) {
+                d.draw_texture(texture, x, y, raylib::core::color::Color::WHITE);
+            }
+        }
+    }
+
+    /// Promotes to the piece of the picker under the mouse, a click anywhere else takes the move
+    /// back
+    pub fn click_promotion(&mut self) {
+        let cell = self.cell_size as i32;
+        let (mx, my) = (self.mouse.x as i32, self.mouse.y as i32);
+        let clicked = self
+            .promotion_squares()
+            .into_iter()
+            .find(|(_, (x, y))| (*x..x + cell).contains(&mx) && (*y..y + cell).contains(&my));
+        let Some((piece_type, _)) = clicked else {
+            self.cancel_promotion();
+            return;
+        };
+        self.promote(piece_type);
+    }
+
+    /// Plays the promotion waiting in the picker, to `piece_type`
+    fn promote(&mut self, piece_type: PieceType) {
+        let Some(mo) = self
+            .can_promote_to
+            .iter()
+            .find(|m| m.promotion_piece() == Some(piece_type))
+            .cloned()
+        else {
+            return;
+        };
+
+        self.play_chosen(mo);
+    }
+
+    /// Closes the promotion picker, the pawn staying where it was
+    pub fn cancel_promotion(&mut self) {
+        self.pawn_promotion = false;
+        self.can_promote_to.clear();
+        self.unset_selected();
+    }
+
+    pub fn toggle_move_input(&mut self) {
+        self.move_input = match self.move_input {
+            Some(_) => None,
+            None => Some(MoveInput::default()),
+        };
+    }
+
+    /// Plays the move typed in the move input, or shows there why it can't be played. The input
+    /// stays open for the next move
+    pub fn submit_move_input(&mut self) {
+        let Some(text) = self.move_input.as_ref().map(|i| i.text().to_string()) else {
+            return;
+        };
+        if text.is_empty() {
+            return;
+        }
+        let editing = self.condition_editor.is_some();
+        let refused = if !editing && (self.is_opponent_engine_turn() || self.is_remote_turn()) {
+            Some("It's not your turn".to_string())
+        } else if !editing && !self.lifecycle.is_in_progress() {
+            Some("The game is over".to_string())
+        } else {
+            match san::parse_move(&self.board, &text) {
+                Ok(mo) => {
+                    self.play_chosen(mo);
+                    None
+                }
+                Err(e) => Some(e.to_string()),
+            }
+        };
+        if let Some(input) = &mut self.move_input {
+            match refused {
+                Some(error) => input.set_error(error),
+                None => input.clear(),
+            }
+        }
+    }
+
+    /// Returns true while the board is played from the keyboard
+    pub fn is_keyboard_mode(&self) -> bool {
+        self.key_cursor.is_some()
+    }
+
+    /// Starts or stops playing from the keyboard, the cursor starting on the king of the side
+    /// to move
+    pub fn toggle_keyboard_mode(&mut self) {
+        self.key_cursor = match self.key_cursor {
+            Some(_) => None,
+            None => {
+                let square = self.board.king_square(self.board.get_turn()).unwrap_or(27);
+                Some((square % 8, square / 8))
+            }
+        };
+        self.follow_key_cursor();
+    }
+
+    fn follow_key_cursor(&mut self) {
+        if let Some((x, y)) = self.key_cursor {
+            self.cursor = Vector2 {
+                x: x as f32 + 0.5,
+                y: y as f32 + 0.5,
+            };
+        }
+    }
+
+    /// Moves the keyboard cursor `dx` squares right and `dy` squares down on screen, or along
+    /// the promotion picker while it is open
+    pub fn move_key_cursor(&mut self, dx: i32, dy: i32) {
+        let Some((x, y)) = self.key_cursor else {
+            return;
+        };
+        if self.pawn_promotion && self.is_radial_promotion() {
+            if let Some(i) = RADIAL_PROMOTION.iter().position(|d| *d == (dx, dy)) {
+                self.key_promotion = i;
+            }
+            return;
+        }
+        if self.pawn_promotion {
+            // the picker runs down from a promotion square at the top of the screen
+            let down = self
+                .can_promote_to
+                .first()
+                .is_some_and(|m| self.board_rect().screen_square(m.to).1 < 4);
+            let step = if down { dy } else { -dy };
+            self.key_promotion = self
+                .key_promotion
+                .saturating_add_signed(step as isize)
+                .min(PROMOTION_PIECES.len() - 1);
+            return;
+        }
+        // board coordinates run right to left and bottom to top with white at the bottom
+        let (dx, dy) = match self.bottom {
+            PieceColor::White => (-dx, -dy),
+            PieceColor::Black => (dx, dy),
+        };
+        self.key_cursor = Some((
+            x.saturating_add_signed(dx as isize).min(7),
+            y.saturating_add_signed(dy as isize).min(7),
+        ));
+        self.follow_key_cursor();
+    }
+
+    /// Picking in keyboard mode: selects the piece under the cursor, moves the selected one to the
+    /// cursor, or promotes to the piece chosen in the picker
+    pub fn press_key_cursor(&mut self) {
+        if self.pawn_promotion {
+            self.promote(PROMOTION_PIECES[self.key_promotion]);
+            return;
+        }
+        self.grab_piece();
+        self.dragging = false;
+    }
+
+    /// Moves the cursor to the square under the mouse or the finger, in board coordinates. In
+    /// keyboard mode the cursor stays where the keys put it until the mouse moves
+    pub fn follow_mouse(&mut self, d: &raylib::core::RaylibHandle) {
+        let mouse = Touch::position(d).unwrap_or_else(|| d.get_mouse_position());
+        let moved = mouse.x != self.mouse.x || mouse.y != self.mouse.y;
+        self.mouse = Vector2 {
+            x: mouse.x,
+            y: mouse.y,
+        };
+        if self.key_cursor.is_some() {
+            if !moved {
+                return;
+            }
+            self.key_cursor = None;
+        }
+        let col = (mouse.x - self.x_offset as f32) / self.cell_size as f32;
+        let row = (mouse.y - self.y_offset as f32) / self.cell_size as f32;
+        (self.cursor.x, self.cursor.y) = match self.bottom {
+            PieceColor::White => (8.0 - col, 8.0 - row),
+            PieceColor::Black => (col, row),
+        };
+    }
+
+    pub fn select_piece(&mut self) {
+        // the pieces of an earlier position can't be moved, a click goes back to the game
+        if self.viewing.is_some() {
+            self.viewing = None;
+            self.on_display_changed();
+            return;
+        }
+
+        let editing = self.condition_editor.is_some();
+        if !editing
+            && (self.is_engine_turn() || self.is_remote_turn() || !self.lifecycle.is_in_progress())
+        {
+            return;
+        }
+
+        let x = self.cursor.x as usize;
+        let y = self.cursor.y as usize;
+
+        let piece = self.board.get_piece_at_index(self.board.get_square(x, y));
+
+        if piece.get_type() == PieceType::None {
+            return;
+        }
+
+        self.unset_selected();
+
+        if self.free_move || piece.get_color() == self.board.get_turn() {
+            self.selected = Some(Vector2 {
+                x: self.cursor.x,
+                y: self.cursor.y,
+            });
+            self.targets = self
+                .board
+                .moves_from(self.board.get_square(x, y))
+                .map(|m| (m.to, self.board.is_capture(&m)))
+                .collect();
+            if let Some(narrator) = &mut self.narrator {
+                let targets = self.targets.iter().map(|(to, _)| *to).collect::<Vec<_>>();
+                narrator.selected(self.board.get_square(x, y), piece, &targets);
+            }
+        } else {
+            tracing::info!("Wrong turn: {:?} ", self.board.get_turn());
+        }
+    }
+
+    /// Loads the images of the piece set at the size of a square, replacing the ones shown
+    pub fn load_images(&mut self) {
+        for piece in pieces::all_pieces() {
+            self.image_map
+                .insert(piece, self.get_texture(&piece, self.cell_size as i32));
+        }
+    }
+
+    /// Texture of `piece` from the piece set in use, or from the set built into the binary when
+    /// none was found on disk
+    fn get_texture(&self, piece: &Piece, size: i32) -> raylib::core::texture::Texture2D {
+        let image = match self.piece_sets.get(&self.piece_set) {
+            Some(set) => {
+                raylib::core::texture::Image::load_image(&set.path(piece).to_string_lossy())
+            }
+            None => match pieces::embedded_image(piece) {
+                Some(png) => raylib::core::texture::Image::load_image_from_mem(
+                    ".png",
+                    &png.to_vec(),
+                    png.len() as i32,
+                ),
+                None => {
+                    tracing::error!("No piece set found in {:?}", PIECES_DIR);
+                    panic!("No piece set found in {:?}", PIECES_DIR);
+                }
+            },
+        };
+        let mut image = image
+            .map_err(|err| {
+                tracing::error!("Error loading image: {:?}", err);
+            })
+            .expect("Error loading image");
+
+        image.resize(size, size);
+        //
+        // SAFETY: LoadTextureFromImage is a safe function
+        unsafe {
+            raylib::core::texture::Texture2D::from_raw(raylib::ffi::LoadTextureFromImage(*image))
         }
     }
 }
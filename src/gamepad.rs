@@ -0,0 +1,71 @@
+//! The left stick of a controller moving the square cursor like the d-pad, one square when it
+//! is pushed then repeating while it is held. The buttons are bound like keys, see
+//! [crate::input]
+
+use std::time::{Duration, Instant};
+
+use raylib::consts::GamepadAxis;
+use raylib::core::RaylibHandle;
+
+/// The controller whose stick is read
+const GAMEPAD: i32 = 0;
+/// How far the stick is pushed before it counts, out of 1
+const DEAD_ZONE: f32 = 0.5;
+/// How long the stick is held before the cursor starts repeating
+const REPEAT_DELAY: Duration = Duration::from_millis(300);
+/// How often the cursor moves once repeating
+const REPEAT_INTERVAL: Duration = Duration::from_millis(120);
+
+/// Follows the left stick of the first controller from frame to frame
+#[derive(Debug, Default)]
+pub struct Gamepad {
+    /// direction the stick is held in and when the cursor moves next
+    held: Option<((i32, i32), Instant)>,
+    connected: bool,
+}
+
+impl Gamepad {
+    /// Returns true if a controller was plugged in at the last poll
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Direction the cursor moves this frame, `(dx, dy)` on screen, none while the stick rests
+    /// or between repeats
+    pub fn poll(&mut self, rl: &RaylibHandle) -> Option<(i32, i32)> {
+        self.connected = rl.is_gamepad_available(GAMEPAD);
+        if !self.connected {
+            self.held = None;
+            return None;
+        }
+        let x = rl.get_gamepad_axis_movement(GAMEPAD, GamepadAxis::GAMEPAD_AXIS_LEFT_X);
+        let y = rl.get_gamepad_axis_movement(GAMEPAD, GamepadAxis::GAMEPAD_AXIS_LEFT_Y);
+        // the axis pushed further wins, diagonals would skip squares
+        let direction = if x.abs().max(y.abs()) < DEAD_ZONE {
+            None
+        } else if x.abs() > y.abs() {
+            Some((x.signum() as i32, 0))
+        } else {
+            Some((0, y.signum() as i32))
+        };
+
+        let now = Instant::now();
+        match (direction, self.held) {
+            (None, _) => {
+                self.held = None;
+                None
+            }
+            (Some(direction), Some((held, next))) if held == direction => {
+                if now < next {
+                    return None;
+                }
+                self.held = Some((direction, now + REPEAT_INTERVAL));
+                Some(direction)
+            }
+            (Some(direction), _) => {
+                self.held = Some((direction, now + REPEAT_DELAY));
+                Some(direction)
+            }
+        }
+    }
+}
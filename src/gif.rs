@@ -0,0 +1,155 @@
+//! Animated GIF writer for exporting games, the frames being mapped to a fixed palette of 252
+//! colors: 6 levels of red and blue, 7 of green
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use raylib::core::color::Color;
+
+/// Levels of red, green and blue in the palette
+const LEVELS: [u32; 3] = [6, 7, 6];
+/// Codes are at most 12 bits, the table is cleared once it holds this many
+const MAX_CODES: u16 = 4096;
+
+/// Index of the palette color closest to `color`
+fn palette_index(color: &Color) -> u8 {
+    let level = |value: u8, levels: u32| (value as u32 * (levels - 1) + 127) / 255;
+    let r = level(color.r, LEVELS[0]);
+    let g = level(color.g, LEVELS[1]);
+    let b = level(color.b, LEVELS[2]);
+    ((r * LEVELS[1] + g) * LEVELS[2] + b) as u8
+}
+
+/// The 256 colors of the global color table, the ones past the cube left black
+fn palette() -> Vec<u8> {
+    let value = |level: u32, levels: u32| (level * 255 / (levels - 1)) as u8;
+    let mut colors = Vec::with_capacity(256 * 3);
+    for r in 0..LEVELS[0] {
+        for g in 0..LEVELS[1] {
+            for b in 0..LEVELS[2] {
+                colors.extend([
+                    value(r, LEVELS[0]),
+                    value(g, LEVELS[1]),
+                    value(b, LEVELS[2]),
+                ]);
+            }
+        }
+    }
+    colors.resize(256 * 3, 0);
+    colors
+}
+
+/// Codes of variable width packed from the least significant bit
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u32) {
+        self.acc |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
+
+/// LZW compression of palette indices with 8 bit symbols, as GIF image data expects
+fn compress(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    let mut out = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut size = 9;
+    let mut next = END + 1;
+    out.write(CLEAR, size);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        out.write(END, size);
+        return out.finish();
+    };
+    let mut prefix = first as u16;
+    for &symbol in rest {
+        if let Some(&code) = table.get(&(prefix, symbol)) {
+            prefix = code;
+            continue;
+        }
+        out.write(prefix, size);
+        if next == MAX_CODES {
+            out.write(CLEAR, size);
+            table.clear();
+            size = 9;
+            next = END + 1;
+        } else {
+            table.insert((prefix, symbol), next);
+            next += 1;
+            if next > 1 << size && size < 12 {
+                size += 1;
+            }
+        }
+        prefix = symbol as u16;
+    }
+    out.write(prefix, size);
+    out.write(END, size);
+    out.finish()
+}
+
+/// Writes the frames of an endlessly looping animation as they come
+pub struct GifEncoder<W: Write> {
+    out: W,
+    width: u16,
+    height: u16,
+}
+
+impl<W: Write> GifEncoder<W> {
+    /// Writes the header of a `width` by `height` animation
+    pub fn new(mut out: W, width: u16, height: u16) -> std::io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // global color table of 256 colors, 8 bits per primary
+        out.write_all(&[0xf7, 0, 0])?;
+        out.write_all(&palette())?;
+        // loops forever
+        out.write_all(&[0x21, 0xff, 0x0b])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+        Ok(Self { out, width, height })
+    }
+
+    /// Adds a frame shown for `delay` hundredths of a second, `pixels` given row by row
+    pub fn add_frame(&mut self, pixels: &[Color], delay: u16) -> std::io::Result<()> {
+        let [lo, hi] = delay.to_le_bytes();
+        self.out
+            .write_all(&[0x21, 0xf9, 0x04, 0x00, lo, hi, 0x00, 0x00])?;
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&self.width.to_le_bytes())?;
+        self.out.write_all(&self.height.to_le_bytes())?;
+        self.out.write_all(&[0x00, 0x08])?;
+
+        let indices = pixels.iter().map(palette_index).collect::<Vec<_>>();
+        for block in compress(&indices).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0x00])
+    }
+
+    /// Ends the file
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()
+    }
+}
@@ -0,0 +1,243 @@
+//! Games and puzzles fetched from lichess and chess.com by their URL or ID:
+//!
+//! - a lichess game, `https://lichess.org/q7ZvsdUF` or `q7ZvsdUF`, read as PGN
+//! - a lichess puzzle, `https://lichess.org/training/K69di` or `K69di`, read from the puzzle
+//!   API into a [Puzzle]
+//! - the chess.com daily puzzle, `https://www.chess.com/daily-chess-puzzle`, read as PGN
+//!
+//! chess.com has no public API returning a single game, those are downloaded as PGN from the
+//! site and replayed from the clipboard instead
+
+use std::fmt::Display;
+
+use serde::Deserialize;
+
+use crate::pgn::{self, PgnError};
+use crate::puzzles::{Puzzle, PuzzleError};
+
+/// Length of a lichess game ID, the URL of a player's view adding four more characters
+const LICHESS_GAME_ID_LEN: usize = 8;
+/// Length of a lichess puzzle ID
+const LICHESS_PUZZLE_ID_LEN: usize = 5;
+
+/// Where a game or puzzle is fetched from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    LichessGame(String),
+    LichessPuzzle(String),
+    ChessComDailyPuzzle,
+}
+
+/// What was fetched
+#[derive(Debug, Clone)]
+pub enum Imported {
+    /// a game as PGN, replayed on the board
+    Game(String),
+    Puzzle(Puzzle),
+}
+
+/// Why a game or puzzle couldn't be imported
+#[derive(Debug)]
+pub enum ImportError {
+    /// The text is neither a URL nor an ID known here
+    UnknownUrl(String),
+    /// The site serves this but not in a way that can be fetched
+    Unsupported(&'static str),
+    /// The request failed or the site answered with an error
+    Http(String),
+    /// The answer isn't what the site is known to send
+    InvalidResponse(String),
+    Pgn(PgnError),
+    Puzzle(PuzzleError),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::UnknownUrl(url) => write!(f, "not a lichess or chess.com URL: {}", url),
+            ImportError::Unsupported(what) => write!(f, "{}", what),
+            ImportError::Http(e) => write!(f, "request failed: {}", e),
+            ImportError::InvalidResponse(e) => write!(f, "unexpected answer: {}", e),
+            ImportError::Pgn(e) => write!(f, "{}", e),
+            ImportError::Puzzle(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<PgnError> for ImportError {
+    fn from(e: PgnError) -> Self {
+        ImportError::Pgn(e)
+    }
+}
+
+impl From<PuzzleError> for ImportError {
+    fn from(e: PuzzleError) -> Self {
+        ImportError::Puzzle(e)
+    }
+}
+
+fn is_id(text: &str, len: usize) -> bool {
+    text.len() == len && text.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Reads where to fetch from out of a URL or a bare lichess ID
+pub fn parse_source(text: &str) -> Result<Source, ImportError> {
+    let text = text.trim();
+    if is_id(text, LICHESS_GAME_ID_LEN) || is_id(text, LICHESS_GAME_ID_LEN + 4) {
+        return Ok(Source::LichessGame(text[..LICHESS_GAME_ID_LEN].to_string()));
+    }
+    if is_id(text, LICHESS_PUZZLE_ID_LEN) {
+        return Ok(Source::LichessPuzzle(text.to_string()));
+    }
+
+    let unknown = || ImportError::UnknownUrl(text.to_string());
+    let rest = text
+        .strip_prefix("https://")
+        .or_else(|| text.strip_prefix("http://"))
+        .unwrap_or(text);
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let segments = path
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    match host.strip_prefix("www.").unwrap_or(host) {
+        "lichess.org" => match segments.as_slice() {
+            ["training", id, ..] if is_id(id, LICHESS_PUZZLE_ID_LEN) => {
+                Ok(Source::LichessPuzzle(id.to_string()))
+            }
+            [id, ..] if is_id(id, LICHESS_GAME_ID_LEN) || is_id(id, LICHESS_GAME_ID_LEN + 4) => {
+                Ok(Source::LichessGame(id[..LICHESS_GAME_ID_LEN].to_string()))
+            }
+            _ => Err(unknown()),
+        },
+        "chess.com" => match segments.as_slice() {
+            ["daily-chess-puzzle", ..] => Ok(Source::ChessComDailyPuzzle),
+            ["game", ..] | ["analysis", "game", ..] => Err(ImportError::Unsupported(
+                "chess.com games can't be fetched, download their PGN and paste it in the replay",
+            )),
+            _ => Err(unknown()),
+        },
+        _ => Err(unknown()),
+    }
+}
+
+/// The part of the lichess puzzle API answer that is read
+#[derive(Deserialize)]
+struct LichessPuzzle {
+    game: LichessPuzzleGame,
+    puzzle: LichessPuzzleInfo,
+}
+
+#[derive(Deserialize)]
+struct LichessPuzzleGame {
+    id: String,
+    /// moves of the game in SAN, up to and including the opponent's move setting the puzzle
+    pgn: String,
+}
+
+#[derive(Deserialize)]
+struct LichessPuzzleInfo {
+    id: String,
+    rating: Option<u32>,
+    /// moves of the solution in coordinates
+    solution: Vec<String>,
+    #[serde(default)]
+    themes: Vec<String>,
+}
+
+/// The part of the chess.com daily puzzle answer that is read
+#[derive(Deserialize)]
+struct ChessComPuzzle {
+    /// the position in a `FEN` tag and the solution
+    pgn: String,
+}
+
+/// Turns an answer of the lichess puzzle API into a puzzle: the game is played up to the move
+/// before the puzzle, which becomes the opponent's setup move
+fn lichess_puzzle(json: &str) -> Result<Puzzle, ImportError> {
+    let answer: LichessPuzzle =
+        serde_json::from_str(json).map_err(|e| ImportError::InvalidResponse(e.to_string()))?;
+    let mut game = pgn::parse_games(&answer.game.pgn)
+        .into_iter()
+        .next()
+        .ok_or(PgnError::NoGame)?;
+    let setup = game.moves.pop().ok_or(PgnError::NoGame)?;
+    let board = game.replay()?;
+    let setup = crate::san::parse_san(&board, &setup)
+        .map_err(|e| PgnError::Move(game.moves.len() + 1, e))?;
+
+    let moves = std::iter::once(setup.to_string())
+        .chain(answer.puzzle.solution)
+        .collect::<Vec<_>>()
+        .join(" ");
+    // the fields of the lichess puzzle CSV, read back like a line of the database
+    let line = format!(
+        "{},{},{},{},,,,{},https://lichess.org/{},",
+        answer.puzzle.id,
+        board.to_fen(),
+        moves,
+        answer
+            .puzzle
+            .rating
+            .map(|r| r.to_string())
+            .unwrap_or_default(),
+        answer.puzzle.themes.join(" "),
+        answer.game.id,
+    );
+    Ok(Puzzle::from_csv(&line)?)
+}
+
+/// Fetches the game or puzzle at `source`, blocking until the site answers
+pub fn fetch(source: &Source) -> Result<Imported, ImportError> {
+    match source {
+        Source::LichessGame(id) => {
+            let url = format!("https://lichess.org/game/export/{}", id);
+            let text = http_get(&url, "application/x-chess-pgn")?;
+            // read here so a page that isn't a game fails now rather than in the replay
+            pgn::parse_games(&text)
+                .first()
+                .ok_or(PgnError::NoGame)?
+                .replay()?;
+            Ok(Imported::Game(text))
+        }
+        Source::LichessPuzzle(id) => {
+            let url = format!("https://lichess.org/api/puzzle/{}", id);
+            let json = http_get(&url, "application/json")?;
+            Ok(Imported::Puzzle(lichess_puzzle(&json)?))
+        }
+        Source::ChessComDailyPuzzle => {
+            let json = http_get("https://api.chess.com/pub/puzzle", "application/json")?;
+            let answer: ChessComPuzzle = serde_json::from_str(&json)
+                .map_err(|e| ImportError::InvalidResponse(e.to_string()))?;
+            pgn::parse_games(&answer.pgn)
+                .first()
+                .ok_or(PgnError::NoGame)?
+                .replay()?;
+            Ok(Imported::Game(answer.pgn))
+        }
+    }
+}
+
+/// Body of the answer to a GET of `url`
+#[cfg(feature = "import")]
+fn http_get(url: &str, accept: &str) -> Result<String, ImportError> {
+    tracing::info!("GET {}", url);
+    let http = |e: ureq::Error| ImportError::Http(e.to_string());
+    ureq::get(url)
+        .header("Accept", accept)
+        .header("User-Agent", concat!("ruche/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(http)?
+        .body_mut()
+        .read_to_string()
+        .map_err(http)
+}
+
+/// Builds without the `import` feature can't make requests
+#[cfg(not(feature = "import"))]
+fn http_get(_url: &str, _accept: &str) -> Result<String, ImportError> {
+    Err(ImportError::Unsupported(
+        "importing by URL isn't supported in this build",
+    ))
+}
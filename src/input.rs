@@ -0,0 +1,725 @@
+//! Input of the board, each action bound to one or more keys, mouse buttons or buttons of the
+//! first controller, held with Shift or Ctrl. Any of them can be rebound in the config file,
+//! several bindings being separated by commas and an empty string leaving the action unbound:
+//!
+//! ```text
+//! key.flip_board = "Shift+F"
+//! key.move_input = "/, Shift+;"
+//! key.annotate = "MouseRight, Shift+MouseLeft"
+//! key.pick = "Enter, PadA, PadX"
+//! key.hint = ""
+//! ```
+//!
+//! The keys of the other screens, typing in text boxes and the arrows of the menus, are fixed
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use raylib::consts::{GamepadButton, KeyboardKey, MouseButton};
+use raylib::core::RaylibHandle;
+
+/// Something done on the board with a key or a mouse button
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// picks pieces up and puts them down, clicks the move list
+    Select,
+    /// draws arrows and circles squares
+    Annotate,
+    /// closes what is open, else goes back to the main menu
+    Menu,
+    /// moves of the square cursor in keyboard mode, or choices of the promotion picker
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    /// picks up and puts down the piece under the square cursor
+    Pick,
+    /// drops the piece picked with the cursor, closes the promotion picker
+    Cancel,
+    /// steps through the moves played
+    HistoryBack,
+    HistoryForward,
+    AnalysisPanel,
+    Explorer,
+    Fullscreen,
+    /// another board in simul mode
+    AddBoard,
+    CopyFen,
+    PasteFen,
+    ExportPng,
+    ExportGif,
+    MoveInput,
+    OfferDraw,
+    Promotion,
+    ConfirmMove,
+    Debug,
+    WhiteKingside,
+    WhiteQueenside,
+    BlackKingside,
+    BlackQueenside,
+    EnPassant,
+    Engine,
+    ExternalEngine,
+    EngineStats,
+    FlipBoard,
+    AutoFlip,
+    KeyboardMode,
+    Hint,
+    PieceVisibility,
+    EvalBar,
+    PassedPawns,
+    ThinkTime,
+    ConditionEditor,
+    RemoveCondition,
+    ClearConditions,
+    NextTheme,
+    AuditLog,
+    BookMode,
+    RecordRepertoire,
+    Resign,
+    Abort,
+    Rematch,
+    BackupPicker,
+    Analysis,
+    NewGame,
+    GameSetup,
+    GameSwitcher,
+    Decline,
+    AcceptTakeback,
+    Settings,
+    TakeBack,
+    Redo,
+    Help,
+}
+
+/// Every action with its name in the config file, what it does and its default keys, in the
+/// order the help lists them
+const ACTIONS: &[(Action, &str, &str, &str)] = &[
+    (Action::Help, "help", "Show this help", "Shift+/"),
+    (
+        Action::Select,
+        "select",
+        "Move pieces, pick a move",
+        "MouseLeft",
+    ),
+    (
+        Action::Annotate,
+        "annotate",
+        "Draw arrows and circles",
+        "MouseRight",
+    ),
+    (
+        Action::Menu,
+        "menu",
+        "Close, or go back to the menu",
+        "Escape, PadStart",
+    ),
+    (
+        Action::CursorLeft,
+        "cursor_left",
+        "Keyboard mode: cursor left",
+        "Left, H, PadLeft",
+    ),
+    (
+        Action::CursorRight,
+        "cursor_right",
+        "Keyboard mode: cursor right",
+        "Right, L, PadRight",
+    ),
+    (
+        Action::CursorUp,
+        "cursor_up",
+        "Keyboard mode: cursor up",
+        "Up, K, PadUp",
+    ),
+    (
+        Action::CursorDown,
+        "cursor_down",
+        "Keyboard mode: cursor down",
+        "Down, J, PadDown",
+    ),
+    (
+        Action::Pick,
+        "pick",
+        "Keyboard mode: pick up, put down",
+        "Enter, PadA",
+    ),
+    (
+        Action::Cancel,
+        "cancel",
+        "Keyboard mode: drop the piece",
+        "Escape, PadB",
+    ),
+    (
+        Action::HistoryBack,
+        "history_back",
+        "Show the previous position",
+        "PadLB",
+    ),
+    (
+        Action::HistoryForward,
+        "history_forward",
+        "Show the next position",
+        "PadRB",
+    ),
+    (
+        Action::AnalysisPanel,
+        "analysis_panel",
+        "Show the analysis panel",
+        "Z",
+    ),
+    (
+        Action::Explorer,
+        "explorer",
+        "Show the opening explorer",
+        "Shift+O",
+    ),
+    (Action::Fullscreen, "fullscreen", "Toggle fullscreen", "F11"),
+    (
+        Action::AddBoard,
+        "add_board",
+        "Simul: add a board",
+        "Ctrl+B",
+    ),
+    (Action::MoveInput, "move_input", "Type a move", "/, Shift+;"),
+    (
+        Action::ConfirmMove,
+        "confirm_move",
+        "Confirm the pending move",
+        "Enter",
+    ),
+    (Action::TakeBack, "take_back", "Take back a move", "Left"),
+    (Action::Redo, "redo", "Replay a move taken back", "Right"),
+    (
+        Action::KeyboardMode,
+        "keyboard_mode",
+        "Play from the keyboard",
+        "Tab",
+    ),
+    (
+        Action::Promotion,
+        "promotion",
+        "Show the promotion picker",
+        "D",
+    ),
+    (
+        Action::FlipBoard,
+        "flip_board",
+        "Turn the board around",
+        "I",
+    ),
+    (
+        Action::AutoFlip,
+        "auto_flip",
+        "Turn auto flip on or off",
+        "Shift+I",
+    ),
+    (Action::Engine, "engine", "Play against the engine", "E"),
+    (
+        Action::ExternalEngine,
+        "external_engine",
+        "Play against the external engine",
+        "U",
+    ),
+    (
+        Action::ThinkTime,
+        "think_time",
+        "Change the engine's think time",
+        "M",
+    ),
+    (
+        Action::EngineStats,
+        "engine_stats",
+        "Show the engine's statistics",
+        "S",
+    ),
+    (Action::Hint, "hint", "Show a hint", "H"),
+    (Action::EvalBar, "eval_bar", "Show the evaluation bar", "V"),
+    (
+        Action::PassedPawns,
+        "passed_pawns",
+        "Mark the passed pawns",
+        "F",
+    ),
+    (
+        Action::PieceVisibility,
+        "piece_visibility",
+        "Hide pieces for blindfold play",
+        "W",
+    ),
+    (
+        Action::BookMode,
+        "book_mode",
+        "Change the opening book mode",
+        "O",
+    ),
+    (
+        Action::RecordRepertoire,
+        "record_repertoire",
+        "Record the moves into the repertoire",
+        "Shift+R",
+    ),
+    (
+        Action::NextTheme,
+        "next_theme",
+        "Switch to the next theme",
+        "T",
+    ),
+    (
+        Action::OfferDraw,
+        "offer_draw",
+        "Offer or accept a draw",
+        "Shift+D",
+    ),
+    (
+        Action::AcceptTakeback,
+        "accept_takeback",
+        "Accept a takeback",
+        "Y",
+    ),
+    (
+        Action::Decline,
+        "decline",
+        "Decline a rematch, draw or takeback",
+        "N",
+    ),
+    (Action::Abort, "abort", "Abort the game", "X"),
+    (Action::Resign, "resign", "Resign", "Shift+X"),
+    (Action::Rematch, "rematch", "Offer or accept a rematch", "R"),
+    (Action::NewGame, "new_game", "Start a new game", "Ctrl+N"),
+    (
+        Action::GameSetup,
+        "game_setup",
+        "Set up a new game",
+        "Shift+N",
+    ),
+    (
+        Action::GameSwitcher,
+        "game_switcher",
+        "Put the game aside and switch to another",
+        "Ctrl+Tab",
+    ),
+    (
+        Action::Analysis,
+        "analysis",
+        "Switch between play and analysis",
+        "A",
+    ),
+    (
+        Action::WhiteKingside,
+        "white_kingside",
+        "Analysis: white's short castling",
+        "K",
+    ),
+    (
+        Action::WhiteQueenside,
+        "white_queenside",
+        "Analysis: white's long castling",
+        "Q",
+    ),
+    (
+        Action::BlackKingside,
+        "black_kingside",
+        "Analysis: black's short castling",
+        "Shift+K",
+    ),
+    (
+        Action::BlackQueenside,
+        "black_queenside",
+        "Analysis: black's long castling",
+        "Shift+Q",
+    ),
+    (
+        Action::EnPassant,
+        "en_passant",
+        "Analysis: en passant square at cursor",
+        "P",
+    ),
+    (
+        Action::ConditionEditor,
+        "condition_editor",
+        "Record conditional moves",
+        "C",
+    ),
+    (
+        Action::RemoveCondition,
+        "remove_condition",
+        "Remove the last condition",
+        "Backspace",
+    ),
+    (
+        Action::ClearConditions,
+        "clear_conditions",
+        "Clear the conditions",
+        "Delete",
+    ),
+    (Action::CopyFen, "copy_fen", "Copy the FEN", "Ctrl+C"),
+    (
+        Action::PasteFen,
+        "paste_fen",
+        "Load the FEN in the clipboard",
+        "Ctrl+V",
+    ),
+    (
+        Action::ExportPng,
+        "export_png",
+        "Save the board as a PNG",
+        "Ctrl+S",
+    ),
+    (
+        Action::ExportGif,
+        "export_gif",
+        "Save the game as a GIF",
+        "Ctrl+G",
+    ),
+    (
+        Action::BackupPicker,
+        "backup_picker",
+        "Restore a saved game",
+        "B",
+    ),
+    (Action::AuditLog, "audit_log", "Export the audit log", "L"),
+    (Action::Settings, "settings", "Open the settings", "G"),
+    (
+        Action::Debug,
+        "debug",
+        "Print the position to the log",
+        "Enter",
+    ),
+];
+
+impl Action {
+    fn entry(self) -> &'static (Action, &'static str, &'static str, &'static str) {
+        ACTIONS
+            .iter()
+            .find(|(action, ..)| *action == self)
+            .expect("Every action is listed")
+    }
+
+    /// Name of the action in the config file
+    pub fn name(self) -> &'static str {
+        self.entry().1
+    }
+
+    pub fn description(self) -> &'static str {
+        self.entry().2
+    }
+
+    pub fn named(name: &str) -> Option<Self> {
+        ACTIONS
+            .iter()
+            .find(|(_, n, ..)| *n == name)
+            .map(|(action, ..)| *action)
+    }
+}
+
+/// Names of the keys that can be bound
+const KEY_NAMES: &[(KeyboardKey, &str)] = &[
+    (KeyboardKey::KEY_A, "A"),
+    (KeyboardKey::KEY_B, "B"),
+    (KeyboardKey::KEY_C, "C"),
+    (KeyboardKey::KEY_D, "D"),
+    (KeyboardKey::KEY_E, "E"),
+    (KeyboardKey::KEY_F, "F"),
+    (KeyboardKey::KEY_G, "G"),
+    (KeyboardKey::KEY_H, "H"),
+    (KeyboardKey::KEY_I, "I"),
+    (KeyboardKey::KEY_J, "J"),
+    (KeyboardKey::KEY_K, "K"),
+    (KeyboardKey::KEY_L, "L"),
+    (KeyboardKey::KEY_M, "M"),
+    (KeyboardKey::KEY_N, "N"),
+    (KeyboardKey::KEY_O, "O"),
+    (KeyboardKey::KEY_P, "P"),
+    (KeyboardKey::KEY_Q, "Q"),
+    (KeyboardKey::KEY_R, "R"),
+    (KeyboardKey::KEY_S, "S"),
+    (KeyboardKey::KEY_T, "T"),
+    (KeyboardKey::KEY_U, "U"),
+    (KeyboardKey::KEY_V, "V"),
+    (KeyboardKey::KEY_W, "W"),
+    (KeyboardKey::KEY_X, "X"),
+    (KeyboardKey::KEY_Y, "Y"),
+    (KeyboardKey::KEY_Z, "Z"),
+    (KeyboardKey::KEY_ZERO, "0"),
+    (KeyboardKey::KEY_ONE, "1"),
+    (KeyboardKey::KEY_TWO, "2"),
+    (KeyboardKey::KEY_THREE, "3"),
+    (KeyboardKey::KEY_FOUR, "4"),
+    (KeyboardKey::KEY_FIVE, "5"),
+    (KeyboardKey::KEY_SIX, "6"),
+    (KeyboardKey::KEY_SEVEN, "7"),
+    (KeyboardKey::KEY_EIGHT, "8"),
+    (KeyboardKey::KEY_NINE, "9"),
+    (KeyboardKey::KEY_SLASH, "/"),
+    (KeyboardKey::KEY_SEMICOLON, ";"),
+    (KeyboardKey::KEY_PERIOD, "."),
+    (KeyboardKey::KEY_MINUS, "-"),
+    (KeyboardKey::KEY_EQUAL, "="),
+    (KeyboardKey::KEY_APOSTROPHE, "'"),
+    (KeyboardKey::KEY_LEFT_BRACKET, "["),
+    (KeyboardKey::KEY_RIGHT_BRACKET, "]"),
+    (KeyboardKey::KEY_BACKSLASH, "\\"),
+    (KeyboardKey::KEY_GRAVE, "`"),
+    (KeyboardKey::KEY_COMMA, "Comma"),
+    (KeyboardKey::KEY_SPACE, "Space"),
+    (KeyboardKey::KEY_ENTER, "Enter"),
+    (KeyboardKey::KEY_ESCAPE, "Escape"),
+    (KeyboardKey::KEY_TAB, "Tab"),
+    (KeyboardKey::KEY_BACKSPACE, "Backspace"),
+    (KeyboardKey::KEY_DELETE, "Delete"),
+    (KeyboardKey::KEY_INSERT, "Insert"),
+    (KeyboardKey::KEY_HOME, "Home"),
+    (KeyboardKey::KEY_END, "End"),
+    (KeyboardKey::KEY_PAGE_UP, "PageUp"),
+    (KeyboardKey::KEY_PAGE_DOWN, "PageDown"),
+    (KeyboardKey::KEY_LEFT, "Left"),
+    (KeyboardKey::KEY_RIGHT, "Right"),
+    (KeyboardKey::KEY_UP, "Up"),
+    (KeyboardKey::KEY_DOWN, "Down"),
+    (KeyboardKey::KEY_F1, "F1"),
+    (KeyboardKey::KEY_F2, "F2"),
+    (KeyboardKey::KEY_F3, "F3"),
+    (KeyboardKey::KEY_F4, "F4"),
+    (KeyboardKey::KEY_F5, "F5"),
+    (KeyboardKey::KEY_F6, "F6"),
+    (KeyboardKey::KEY_F7, "F7"),
+    (KeyboardKey::KEY_F8, "F8"),
+    (KeyboardKey::KEY_F9, "F9"),
+    (KeyboardKey::KEY_F10, "F10"),
+    (KeyboardKey::KEY_F11, "F11"),
+    (KeyboardKey::KEY_F12, "F12"),
+];
+
+/// Names of the mouse buttons that can be bound
+const BUTTON_NAMES: &[(MouseButton, &str)] = &[
+    (MouseButton::MOUSE_LEFT_BUTTON, "MouseLeft"),
+    (MouseButton::MOUSE_RIGHT_BUTTON, "MouseRight"),
+    (MouseButton::MOUSE_MIDDLE_BUTTON, "MouseMiddle"),
+];
+
+/// Names of the buttons of the controller that can be bound, the face buttons after their place
+/// on an Xbox controller
+const PAD_NAMES: &[(GamepadButton, &str)] = &[
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP, "PadUp"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT, "PadRight"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN, "PadDown"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT, "PadLeft"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN, "PadA"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT, "PadB"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT, "PadX"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP, "PadY"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1, "PadLB"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1, "PadRB"),
+    (GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2, "PadLT"),
+    (GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2, "PadRT"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT, "PadSelect"),
+    (GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT, "PadStart"),
+];
+
+/// The controller whose buttons are read
+const GAMEPAD: i32 = 0;
+
+/// Returns true if any button of the controller went down this frame
+pub fn pad_pressed(rl: &RaylibHandle) -> bool {
+    rl.is_gamepad_available(GAMEPAD)
+        && PAD_NAMES
+            .iter()
+            .any(|(button, _)| rl.is_gamepad_button_pressed(GAMEPAD, *button))
+}
+
+/// Characters typed this frame, in order. Taking the handle keeps it to the thread with the
+/// window
+pub fn typed_chars(_rl: &RaylibHandle) -> impl Iterator<Item = char> {
+    std::iter::from_fn(|| {
+        // SAFETY: GetCharPressed only pops raylib's queue of the characters typed this frame
+        char::from_u32(unsafe { raylib::ffi::GetCharPressed() } as u32).filter(|c| *c != '\0')
+    })
+}
+
+/// What is pressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Trigger {
+    Key(KeyboardKey),
+    Mouse(MouseButton),
+    Pad(GamepadButton),
+}
+
+impl Trigger {
+    fn named(name: &str) -> Option<Self> {
+        let key = KEY_NAMES
+            .iter()
+            .find(|(_, n)| n.eq_ignore_ascii_case(name))
+            .map(|(key, _)| Trigger::Key(*key));
+        let mouse = || {
+            BUTTON_NAMES
+                .iter()
+                .find(|(_, n)| n.eq_ignore_ascii_case(name))
+                .map(|(button, _)| Trigger::Mouse(*button))
+        };
+        let pad = || {
+            PAD_NAMES
+                .iter()
+                .find(|(_, n)| n.eq_ignore_ascii_case(name))
+                .map(|(button, _)| Trigger::Pad(*button))
+        };
+        key.or_else(mouse).or_else(pad)
+    }
+
+    fn name(self) -> &'static str {
+        let name = match self {
+            Trigger::Key(key) => KEY_NAMES.iter().find(|(k, _)| *k == key).map(|(_, n)| n),
+            Trigger::Mouse(button) => BUTTON_NAMES
+                .iter()
+                .find(|(b, _)| *b == button)
+                .map(|(_, n)| n),
+            Trigger::Pad(button) => PAD_NAMES.iter().find(|(b, _)| *b == button).map(|(_, n)| n),
+        };
+        name.copied().unwrap_or("?")
+    }
+}
+
+/// A key or a button and the modifiers held with it, `Shift+D` for instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    trigger: Trigger,
+    shift: bool,
+    ctrl: bool,
+}
+
+impl Binding {
+    /// Parses `Ctrl+Shift+K` or `Shift+MouseLeft`, case insensitively
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+        let mut binding = Self {
+            trigger: Trigger::named(parts.pop()?)?,
+            shift: false,
+            ctrl: false,
+        };
+        for modifier in parts {
+            match modifier.to_ascii_lowercase().as_str() {
+                "shift" => binding.shift = true,
+                "ctrl" => binding.ctrl = true,
+                _ => return None,
+            }
+        }
+        Some(binding)
+    }
+
+    /// Returns true if the key or button went down this frame with these modifiers held. Keys
+    /// need exactly these, buttons at least these so that Ctrl can be held while dragging a
+    /// piece
+    pub fn is_pressed(&self, rl: &RaylibHandle) -> bool {
+        let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        match self.trigger {
+            Trigger::Key(key) => rl.is_key_pressed(key) && shift == self.shift && ctrl == self.ctrl,
+            Trigger::Mouse(button) => {
+                rl.is_mouse_button_pressed(button) && (shift || !self.shift) && (ctrl || !self.ctrl)
+            }
+            Trigger::Pad(button) => {
+                rl.is_gamepad_available(GAMEPAD)
+                    && rl.is_gamepad_button_pressed(GAMEPAD, button)
+                    && (shift || !self.shift)
+                    && (ctrl || !self.ctrl)
+            }
+        }
+    }
+
+    /// Returns true if the key or button went up this frame, whatever is held with it
+    pub fn is_released(&self, rl: &RaylibHandle) -> bool {
+        match self.trigger {
+            Trigger::Key(key) => rl.is_key_released(key),
+            Trigger::Mouse(button) => rl.is_mouse_button_released(button),
+            Trigger::Pad(button) => {
+                rl.is_gamepad_available(GAMEPAD) && rl.is_gamepad_button_released(GAMEPAD, button)
+            }
+        }
+    }
+}
+
+impl Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", self.trigger.name())
+    }
+}
+
+/// Parses a comma separated list of bindings, none for an empty one
+pub fn parse_bindings(text: &str) -> Option<Vec<Binding>> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(Binding::parse)
+        .collect()
+}
+
+/// Writes bindings back as [parse_bindings] reads them
+pub fn format_bindings(bindings: &[Binding]) -> String {
+    bindings
+        .iter()
+        .map(Binding::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The keys and buttons bound to each action
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputMap {
+    bindings: HashMap<Action, Vec<Binding>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .map(|(action, _, _, keys)| {
+                let keys = parse_bindings(keys).expect("Default bindings are valid");
+                (*action, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl InputMap {
+    /// Returns true if a key or button bound to `action` was pressed this frame
+    pub fn pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.bindings(action).iter().any(|b| b.is_pressed(rl))
+    }
+
+    /// Returns true if a key or button bound to `action` was released this frame, for the
+    /// actions held down like dragging a piece
+    pub fn released(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.bindings(action).iter().any(|b| b.is_released(rl))
+    }
+
+    pub fn bindings(&self, action: Action) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn bind(&mut self, action: Action, bindings: Vec<Binding>) {
+        self.bindings.insert(action, bindings);
+    }
+
+    /// Every action with its bindings, in the order of the help
+    pub fn entries(&self) -> impl Iterator<Item = (Action, &[Binding])> {
+        ACTIONS
+            .iter()
+            .map(|(action, ..)| (*action, self.bindings(*action)))
+    }
+
+    /// The actions bound otherwise than by default
+    pub fn remapped(&self) -> impl Iterator<Item = (Action, &[Binding])> {
+        let defaults = Self::default();
+        self.entries()
+            .filter(move |(action, keys)| defaults.bindings(*action) != *keys)
+    }
+}
@@ -0,0 +1,372 @@
+use std::time::Duration;
+
+use crate::board::{Board, PieceColor, VariantEnd};
+
+/// Number of plies after which a game can no longer be aborted, i.e. once both sides have moved
+const ABORT_PLIES: u32 = 2;
+
+/// Time control of a game, kept when a rematch is started
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeControl {
+    pub base: Duration,
+    /// added after each move
+    pub increment: Duration,
+    /// time each move may take before the clock starts counting down
+    pub delay: Duration,
+}
+
+impl TimeControl {
+    /// A time control of `base` seconds with `increment` and `delay` seconds per move
+    pub const fn new(base: u64, increment: u64, delay: u64) -> Self {
+        Self {
+            base: Duration::from_secs(base),
+            increment: Duration::from_secs(increment),
+            delay: Duration::from_secs(delay),
+        }
+    }
+}
+
+/// State of a game from start to finish
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+    /// Moves are being played
+    InProgress,
+    /// The game was called off by `by` before both sides moved, it doesn't count
+    Aborted { by: PieceColor },
+    /// The clock of `by` ran out, it lost the game
+    Flagged { by: PieceColor },
+    /// `winner` mated the other side
+    Checkmate { winner: PieceColor },
+    /// `winner` won by a rule of the variant played, blowing up the other side's king in atomic
+    /// chess for one
+    VariantEnd { winner: PieceColor, end: VariantEnd },
+    /// `by` gave up the game
+    Resigned { by: PieceColor },
+    /// Neither side won
+    Drawn(DrawReason),
+}
+
+/// Why a game ended in a draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    Stalemate,
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    InsufficientMaterial,
+    /// both players agreed to a draw
+    Agreement,
+}
+
+impl GameState {
+    /// The result as PGN writes it e.g. `1-0`, `*` while the game is played or once aborted
+    pub fn result(self) -> &'static str {
+        let winner = match self {
+            GameState::InProgress | GameState::Aborted { .. } => return "*",
+            GameState::Drawn(_) => return "1/2-1/2",
+            GameState::Checkmate { winner } | GameState::VariantEnd { winner, .. } => winner,
+            GameState::Flagged { by } | GameState::Resigned { by } => by.opposite(),
+        };
+        match winner {
+            PieceColor::White => "1-0",
+            PieceColor::Black => "0-1",
+        }
+    }
+}
+
+/// How the game ended e.g. `White wins by checkmate`
+impl std::fmt::Display for GameState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameState::InProgress => write!(f, "Game in progress"),
+            GameState::Aborted { by } => write!(f, "Aborted by {:?}", by),
+            GameState::Flagged { by } => write!(f, "{:?} wins on time", by.opposite()),
+            GameState::Checkmate { winner } => write!(f, "{:?} wins by checkmate", winner),
+            GameState::VariantEnd { winner, end } => write!(f, "{:?} wins {}", winner, end),
+            GameState::Resigned { by } => write!(f, "{:?} resigned", by),
+            GameState::Drawn(DrawReason::Stalemate) => write!(f, "Draw by stalemate"),
+            GameState::Drawn(DrawReason::ThreefoldRepetition) => {
+                write!(f, "Draw by threefold repetition")
+            }
+            GameState::Drawn(DrawReason::FiftyMoveRule) => write!(f, "Draw by the fifty-move rule"),
+            GameState::Drawn(DrawReason::InsufficientMaterial) => {
+                write!(f, "Draw by insufficient material")
+            }
+            GameState::Drawn(DrawReason::Agreement) => write!(f, "Draw by agreement"),
+        }
+    }
+}
+
+/// How the game reached on `board` from `start` ended on the board, if it did: a rule of its
+/// variant, checkmate, stalemate, the fifty-move rule, insufficient material or a position seen
+/// for the third time
+pub fn board_state(start: &Board, board: &Board) -> Option<GameState> {
+    let turn = board.get_turn();
+    let state = if let Some((winner, end)) = board.result() {
+        GameState::VariantEnd { winner, end }
+    } else if board.legal_moves().next().is_none() {
+        if board.is_in_check(turn) {
+            GameState::Checkmate {
+                winner: turn.opposite(),
+            }
+        } else {
+            GameState::Drawn(DrawReason::Stalemate)
+        }
+    } else if board.halfmove_clock() >= 100 {
+        GameState::Drawn(DrawReason::FiftyMoveRule)
+    } else if board.has_insufficient_material() {
+        GameState::Drawn(DrawReason::InsufficientMaterial)
+    } else if repetitions(start, board) >= 3 {
+        GameState::Drawn(DrawReason::ThreefoldRepetition)
+    } else {
+        return None;
+    };
+    Some(state)
+}
+
+/// Number of times the position of `board` was reached in the game played from `start`,
+/// counting this one
+fn repetitions(start: &Board, board: &Board) -> usize {
+    let hash = board.zobrist_hash();
+    let mut replay = start.clone();
+    let mut seen = (replay.zobrist_hash() == hash) as usize;
+    for mo in board.get_move_history() {
+        if replay.play(mo).is_err() {
+            break;
+        }
+        seen += (replay.zobrist_hash() == hash) as usize;
+    }
+    seen
+}
+
+/// Why a lifecycle transition was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleError {
+    /// Both sides have moved, the game can only be resigned now
+    TooLateToAbort,
+    /// The game is still being played
+    GameInProgress,
+    /// The game is already over
+    GameOver,
+    /// There is no rematch offer to answer
+    NoRematchOffer,
+    /// There is no draw offer to answer
+    NoDrawOffer,
+    /// There is no takeback request to answer
+    NoTakebackRequest,
+}
+
+/// Answer to a rematch or draw offer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfferStatus {
+    /// Waiting for the opponent to answer
+    Offered,
+    /// Both sides agree, to a rematch which should be started or to a draw
+    Accepted,
+}
+
+/// Drives a game through its states and the offers made once it is over
+#[derive(Debug, Clone)]
+pub struct Lifecycle {
+    state: GameState,
+    time_control: Option<TimeControl>,
+    /// side that offered a rematch, if any
+    rematch_offer: Option<PieceColor>,
+    /// side that offered a draw, if any
+    draw_offer: Option<PieceColor>,
+    /// side that asked to take its last move back, if any
+    takeback_request: Option<PieceColor>,
+}
+
+impl Lifecycle {
+    pub fn new(time_control: Option<TimeControl>) -> Self {
+        Self {
+            state: GameState::InProgress,
+            time_control,
+            rematch_offer: None,
+            draw_offer: None,
+            takeback_request: None,
+        }
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn is_in_progress(&self) -> bool {
+        self.state == GameState::InProgress
+    }
+
+    pub fn time_control(&self) -> Option<TimeControl> {
+        self.time_control
+    }
+
+    /// Returns true while a game with a clock is being played
+    pub fn is_timed_and_live(&self) -> bool {
+        self.time_control.is_some() && self.is_in_progress()
+    }
+
+    /// Returns true if a game with `plies` plies played can still be aborted
+    pub fn can_abort(&self, plies: u32) -> bool {
+        self.is_in_progress() && plies < ABORT_PLIES
+    }
+
+    /// Calls the game off, only allowed before both sides have moved
+    pub fn abort(&mut self, by: PieceColor, plies: u32) -> Result<(), LifecycleError> {
+        if !self.is_in_progress() {
+            return Err(LifecycleError::GameOver);
+        }
+        if !self.can_abort(plies) {
+            return Err(LifecycleError::TooLateToAbort);
+        }
+        self.state = GameState::Aborted { by };
+        Ok(())
+    }
+
+    /// Ends the game on time, `by` being the side whose clock ran out
+    pub fn flag(&mut self, by: PieceColor) -> Result<(), LifecycleError> {
+        if !self.is_in_progress() {
+            return Err(LifecycleError::GameOver);
+        }
+        self.state = GameState::Flagged { by };
+        Ok(())
+    }
+
+    /// Ends the game on the board, by checkmate or a draw
+    pub fn finish(&mut self, state: GameState) -> Result<(), LifecycleError> {
+        if !self.is_in_progress() {
+            return Err(LifecycleError::GameOver);
+        }
+        self.state = state;
+        Ok(())
+    }
+
+    /// Carries a game ended on the board on after its last move is taken back
+    pub fn resume(&mut self) -> Result<(), LifecycleError> {
+        match self.state {
+            GameState::Drawn(DrawReason::Agreement) => Err(LifecycleError::GameOver),
+            GameState::Checkmate { .. } | GameState::VariantEnd { .. } | GameState::Drawn(_) => {
+                self.state = GameState::InProgress;
+                self.rematch_offer = None;
+                Ok(())
+            }
+            GameState::InProgress => Err(LifecycleError::GameInProgress),
+            _ => Err(LifecycleError::GameOver),
+        }
+    }
+
+    /// Gives the game up on behalf of `by`
+    pub fn resign(&mut self, by: PieceColor) -> Result<(), LifecycleError> {
+        if !self.is_in_progress() {
+            return Err(LifecycleError::GameOver);
+        }
+        self.state = GameState::Resigned { by };
+        Ok(())
+    }
+
+    /// Offers a draw on behalf of `by`, which ends the game right away if the opponent already
+    /// offered one
+    pub fn offer_draw(&mut self, by: PieceColor) -> Result<OfferStatus, LifecycleError> {
+        if !self.is_in_progress() {
+            return Err(LifecycleError::GameOver);
+        }
+        match self.draw_offer {
+            Some(color) if color != by => {
+                self.draw_offer = None;
+                self.state = GameState::Drawn(DrawReason::Agreement);
+                Ok(OfferStatus::Accepted)
+            }
+            _ => {
+                self.draw_offer = Some(by);
+                Ok(OfferStatus::Offered)
+            }
+        }
+    }
+
+    /// Returns the side waiting for an answer to its draw offer
+    pub fn draw_offer(&self) -> Option<PieceColor> {
+        self.draw_offer
+    }
+
+    /// Turns down the pending draw offer
+    pub fn decline_draw(&mut self) -> Result<(), LifecycleError> {
+        self.draw_offer
+            .take()
+            .map(|_| ())
+            .ok_or(LifecycleError::NoDrawOffer)
+    }
+
+    /// Asks the opponent of `by` to let it take its last move back
+    pub fn request_takeback(&mut self, by: PieceColor) -> Result<(), LifecycleError> {
+        if !self.is_in_progress() {
+            return Err(LifecycleError::GameOver);
+        }
+        self.takeback_request = Some(by);
+        Ok(())
+    }
+
+    /// Returns the side waiting for an answer to its takeback request
+    pub fn takeback_request(&self) -> Option<PieceColor> {
+        self.takeback_request
+    }
+
+    /// Answers the pending takeback request, returning the side that made it
+    pub fn answer_takeback(&mut self) -> Result<PieceColor, LifecycleError> {
+        self.takeback_request
+            .take()
+            .ok_or(LifecycleError::NoTakebackRequest)
+    }
+
+    /// Offers a rematch on behalf of `by`, which is accepted right away if the opponent already
+    /// offered one
+    pub fn offer_rematch(&mut self, by: PieceColor) -> Result<OfferStatus, LifecycleError> {
+        if self.is_in_progress() {
+            return Err(LifecycleError::GameInProgress);
+        }
+        match self.rematch_offer {
+            Some(color) if color != by => Ok(OfferStatus::Accepted),
+            _ => {
+                self.rematch_offer = Some(by);
+                Ok(OfferStatus::Offered)
+            }
+        }
+    }
+
+    /// Returns the side waiting for an answer to its rematch offer
+    pub fn rematch_offer(&self) -> Option<PieceColor> {
+        self.rematch_offer
+    }
+
+    /// Turns down the pending rematch offer
+    pub fn decline_rematch(&mut self) -> Result<(), LifecycleError> {
+        self.rematch_offer
+            .take()
+            .map(|_| ())
+            .ok_or(LifecycleError::NoRematchOffer)
+    }
+
+    /// Lifecycle of the rematch, played with the same time control
+    pub fn rematch(&self) -> Lifecycle {
+        Lifecycle::new(self.time_control)
+    }
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+/// Message shown to the player whose action was refused
+impl std::fmt::Display for LifecycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LifecycleError::TooLateToAbort => write!(f, "Too late to abort, both sides have moved"),
+            LifecycleError::GameInProgress => write!(f, "The game is still in progress"),
+            LifecycleError::GameOver => write!(f, "The game is already over"),
+            LifecycleError::NoRematchOffer => write!(f, "There is no rematch offer"),
+            LifecycleError::NoDrawOffer => write!(f, "There is no draw offer"),
+            LifecycleError::NoTakebackRequest => write!(f, "There is no takeback request"),
+        }
+    }
+}
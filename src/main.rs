@@ -1,75 +1,488 @@
 #![deny(missing_docs)]
 #![allow(missing_docs)]
 
-use std::ops::Not;
+use std::io::Write;
+use std::path::Path;
 
 use raylib::prelude::*;
 use tracing::Level;
 
-mod board;
+use ruche_core::{board, engine, epd, pgn, san, zobrist};
+
+mod audit;
+mod autosave;
+mod book;
+mod cli;
+mod clock;
+mod conditional;
+mod config;
+mod correspondence;
+mod eco;
+mod engine_match;
+mod events;
+mod explorer;
+mod export;
 mod game;
+mod gamepad;
+mod gif;
+mod import;
+mod input;
+mod lifecycle;
+mod narrator;
+mod net;
+mod packs;
+mod pieces;
+mod profile;
+mod puzzles;
+mod repertoire;
+mod review;
+mod saved_game;
+mod scene;
+mod serve;
+mod sound;
+mod stats;
+mod suite;
+mod theme;
+mod touch;
+mod uci;
+mod uci_client;
+mod widgets;
+
+/// Returns the value following `flag` on the command line
+fn arg_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let idx = args.iter().position(|a| a == flag)?;
+    args.get(idx + 1).map(|v| v.as_str())
+}
+
+/// Runs an EPD suite without opening a window:
+/// `ruche --epd wac.epd [--depth 4] [--movetime 1000]`, the move time being in milliseconds
+fn run_epd_suite(path: &str, args: &[String]) {
+    tracing_subscriber::fmt().with_max_level(Level::WARN).init();
+
+    let mut limits = engine::SearchLimits::depth(4);
+    if let Some(ms) = arg_value(args, "--movetime").and_then(|t| t.parse().ok()) {
+        limits = engine::SearchLimits::movetime(std::time::Duration::from_millis(ms));
+    }
+    if let Some(depth) = arg_value(args, "--depth").and_then(|d| d.parse().ok()) {
+        limits.depth = depth;
+    }
+
+    let params = engine::Params::load_or_default(&engine::params_path());
+    match epd::run_suite(Path::new(path), limits, &params) {
+        Ok(results) if results.iter().all(|r| r.passed) => {}
+        Ok(_) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path, e);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Runs a JSON position suite without opening a window:
+/// `ruche --suite edge_cases.json [--report report.json] [--depth 4] [--movetime 1000]`, the
+/// report going to stdout unless a file is given
+fn run_position_suite(path: &str, args: &[String]) {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(Level::WARN)
+        .init();
+
+    let mut limits = engine::SearchLimits::depth(4);
+    if let Some(ms) = arg_value(args, "--movetime").and_then(|t| t.parse().ok()) {
+        limits = engine::SearchLimits::movetime(std::time::Duration::from_millis(ms));
+    }
+    if let Some(depth) = arg_value(args, "--depth").and_then(|d| d.parse().ok()) {
+        limits.depth = depth;
+    }
+
+    let params = engine::Params::load_or_default(&engine::params_path());
+    let report = match suite::run_suite(Path::new(path), limits, &params) {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", path, e);
+            std::process::exit(2);
+        }
+    };
+    let json = serde_json::to_string_pretty(&report).expect("the report is plain data");
+    match arg_value(args, "--report") {
+        Some(out) => {
+            if let Err(e) = std::fs::write(out, json) {
+                eprintln!("Failed to write {}: {}", out, e);
+                std::process::exit(2);
+            }
+        }
+        None => println!("{}", json),
+    }
+    if report.passed != report.total {
+        std::process::exit(1);
+    }
+}
+
+/// Tunes the engine parameters with self-play without opening a window:
+/// `ruche --tune [--iterations 100] [--pairs 2]`, writing them to the parameters file
+fn run_tuner(args: &[String]) {
+    tracing_subscriber::fmt().with_max_level(Level::WARN).init();
+
+    let mut config = engine::SpsaConfig::default();
+    if let Some(iterations) = arg_value(args, "--iterations").and_then(|i| i.parse().ok()) {
+        config.iterations = iterations;
+    }
+    if let Some(pairs) = arg_value(args, "--pairs").and_then(|p| p.parse().ok()) {
+        config.game_pairs = pairs;
+    }
+
+    let path = engine::params_path();
+    let params = engine::Params::load_or_default(&path);
+    if let Err(e) = engine::tune(params, config, &path) {
+        eprintln!("Failed to write {}: {}", path.display(), e);
+        std::process::exit(2);
+    }
+}
+
+/// Plays two engines against each other without opening a window:
+/// `ruche --match [--first ruche] [--second ruche:other.txt] [--games 2] [--tc 10+0.1]
+/// [--pgn games.pgn]`, either engine being `ruche`, ruche with another parameters file or the
+/// path of a UCI engine. The games go to stdout as PGN unless a file is given
+fn run_engine_match(args: &[String]) {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(Level::WARN)
+        .init();
+
+    let first = engine_match::Player::parse(arg_value(args, "--first").unwrap_or("ruche"));
+    let second = engine_match::Player::parse(arg_value(args, "--second").unwrap_or("ruche"));
+    let games = arg_value(args, "--games")
+        .and_then(|g| g.parse().ok())
+        .unwrap_or(2);
+    let tc = match engine_match::TimeControl::parse(arg_value(args, "--tc").unwrap_or("10+0.1")) {
+        Ok(tc) => tc,
+        Err(e) => {
+            eprintln!("Invalid time control: {}", e);
+            std::process::exit(2);
+        }
+    };
+
+    let mut pgn = match arg_value(args, "--pgn") {
+        Some(out) => match std::fs::File::create(out) {
+            Ok(file) => Box::new(file) as Box<dyn std::io::Write>,
+            Err(e) => {
+                eprintln!("Failed to create {}: {}", out, e);
+                std::process::exit(2);
+            }
+        },
+        None => Box::new(std::io::stdout()),
+    };
+    let result = engine_match::run_match(&first, &second, games, tc, |round, game| {
+        eprintln!(
+            "Game {}: {} - {} {} ({})",
+            round,
+            game.white,
+            game.black,
+            game.outcome.result(),
+            game.termination
+        );
+        pgn.write_all(game.to_pgn(round, tc).as_bytes())?;
+        Ok(())
+    });
+    match result {
+        Ok(score) => eprintln!(
+            "Score of the first engine: {} wins, {} losses, {} draws, {}/{}",
+            score.wins,
+            score.losses,
+            score.draws,
+            score.points(),
+            games
+        ),
+        Err(e) => {
+            eprintln!("Match failed: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Generates mate puzzles without opening a window:
+/// `ruche --puzzles [--from games.pgn] [--games 20] [--max-mate 3] [--movetime 200]
+/// [--out puzzles.csv]`, scanning the games of the PGN file or self-play games when none is
+/// given. The puzzles go to stdout as CSV unless a file is given
+fn run_puzzle_generator(args: &[String]) {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(Level::WARN)
+        .init();
+
+    let mut config = puzzles::PuzzleConfig::default();
+    if let Some(moves) = arg_value(args, "--max-mate").and_then(|m| m.parse().ok()) {
+        config.max_mate = moves;
+    }
+    if let Some(ms) = arg_value(args, "--movetime").and_then(|t| t.parse().ok()) {
+        config.movetime = std::time::Duration::from_millis(ms);
+    }
+
+    let mut out = match arg_value(args, "--out") {
+        Some(out) => match std::fs::File::create(out) {
+            Ok(file) => Box::new(file) as Box<dyn std::io::Write>,
+            Err(e) => {
+                eprintln!("Failed to create {}: {}", out, e);
+                std::process::exit(2);
+            }
+        },
+        None => Box::new(std::io::stdout()),
+    };
+    let mut found = 0;
+    let mut on_puzzle = |puzzle: &puzzles::Puzzle| {
+        if found == 0 {
+            writeln!(out, "{}", puzzles::CSV_HEADER)?;
+        }
+        found += 1;
+        eprintln!("mate in {}: {}", puzzle.mate_in, puzzle.fen);
+        writeln!(out, "{}", puzzle.to_csv())
+    };
+
+    let params = engine::Params::load_or_default(&engine::params_path());
+    let result = match arg_value(args, "--from") {
+        Some(path) => puzzles::from_pgn(Path::new(path), config, &params, &mut on_puzzle),
+        None => {
+            let games = arg_value(args, "--games")
+                .and_then(|g| g.parse().ok())
+                .unwrap_or(20);
+            puzzles::from_self_play(games, config, &params, &mut on_puzzle)
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to generate puzzles: {}", e);
+        std::process::exit(2);
+    }
+    eprintln!("{} puzzles found", found);
+}
+
+/// Plays in the terminal without opening a window, see [cli]
+fn run_cli(args: &[String]) {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(Level::WARN)
+        .init();
+
+    let mut options = cli::CliOptions::default();
+    if let Some(fen) = arg_value(args, "--fen") {
+        options.fen = fen.to_string();
+    }
+    options.engine = match arg_value(args, "--engine") {
+        None => None,
+        Some("white") => Some(board::PieceColor::White),
+        Some("black") => Some(board::PieceColor::Black),
+        Some(other) => {
+            eprintln!("--engine takes white or black, not {:?}", other);
+            std::process::exit(2);
+        }
+    };
+    options.unicode = args.iter().any(|a| a == "--unicode");
+    if let Some(ms) = arg_value(args, "--movetime").and_then(|t| t.parse().ok()) {
+        options.movetime = std::time::Duration::from_millis(ms);
+    }
+    if let Err(e) = cli::run(options) {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+}
+
+/// Hosts games over WebSocket without opening a window:
+/// `ruche --serve [--port 8080] [--movetime 1000]`, the engine's move time being in milliseconds
+fn run_serve(args: &[String]) {
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_max_level(Level::INFO)
+        .init();
+
+    let port = match arg_value(args, "--port") {
+        None => serve::DEFAULT_PORT,
+        Some(port) => port.parse().unwrap_or_else(|_| {
+            eprintln!("--port takes a port number, not {:?}", port);
+            std::process::exit(2);
+        }),
+    };
+    let movetime = arg_value(args, "--movetime")
+        .and_then(|t| t.parse().ok())
+        .map_or(
+            std::time::Duration::from_secs(1),
+            std::time::Duration::from_millis,
+        );
+    if let Err(e) = serve::run(port, movetime) {
+        eprintln!("Failed to serve on port {}: {}", port, e);
+        std::process::exit(2);
+    }
+}
 
 fn main() {
-    let (mut rl, thread) = raylib::init().size(500, 600).build();
+    let args = std::env::args().collect::<Vec<_>>();
+    if args.iter().any(|a| a == "--cli") {
+        run_cli(&args);
+        return;
+    }
+    if args.iter().any(|a| a == "--serve") {
+        run_serve(&args);
+        return;
+    }
+    if let Some(path) = arg_value(&args, "--epd") {
+        run_epd_suite(path, &args);
+        return;
+    }
+    if let Some(path) = arg_value(&args, "--suite") {
+        run_position_suite(path, &args);
+        return;
+    }
+    if args.iter().any(|a| a == "--uci") {
+        // stdout belongs to the protocol
+        tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
+            .with_max_level(Level::WARN)
+            .init();
+        uci::run();
+        return;
+    }
+    if args.iter().any(|a| a == "--tune") {
+        run_tuner(&args);
+        return;
+    }
+    if args.iter().any(|a| a == "--puzzles") {
+        run_puzzle_generator(&args);
+        return;
+    }
+    if args.iter().any(|a| a == "--match") {
+        run_engine_match(&args);
+        return;
+    }
+
+    let (mut rl, thread) = raylib::init()
+        .size(
+            500 + widgets::EVAL_BAR_WIDTH + widgets::MOVE_LIST_WIDTH,
+            600,
+        )
+        .resizable()
+        .title("ruche")
+        .build();
     rl.set_target_fps(60);
+    // Escape closes menus and dialogs, Quit in the main menu closes the window
+    rl.set_exit_key(None);
+    rl.set_window_min_size(
+        8 * game::MIN_CELL_SIZE + widgets::EVAL_BAR_WIDTH + widgets::MOVE_LIST_WIDTH,
+        100 + 8 * game::MIN_CELL_SIZE,
+    );
 
-    let (level, span) = if std::option_env!("LOGGER").is_some() {
+    let (level, _span) = if std::option_env!("LOGGER").is_some() {
         (Level::INFO, tracing::info_span!("Main"))
     } else {
         (Level::TRACE, tracing::trace_span!("Main"))
     };
-    tracing_subscriber::fmt().with_max_level(level).init();
+    // `--narrate` writes the game to stdout for screen readers, `--narrate-to` to a file or pipe
+    let narrator = match arg_value(&args, "--narrate-to") {
+        Some(path) => match narrator::Narrator::to_path(Path::new(path)) {
+            Ok(narrator) => Some(narrator),
+            Err(e) => {
+                eprintln!("Failed to open {}: {}", path, e);
+                std::process::exit(2);
+            }
+        },
+        None => args
+            .iter()
+            .any(|a| a == "--narrate")
+            .then(narrator::Narrator::stdout),
+    };
+    if narrator.is_some() {
+        // stdout may belong to the narration
+        tracing_subscriber::fmt()
+            .with_writer(std::io::stderr)
+            .with_max_level(level)
+            .init();
+    } else {
+        tracing_subscriber::fmt().with_max_level(level).init();
+    }
 
-    let mut game = game::Game::new(500, 0, 100);
+    // `--profile NAME` or `RUCHE_PROFILE` start with a player's settings and games, without
+    // asking who is playing
+    let chosen = arg_value(&args, "--profile")
+        .map(str::to_string)
+        .or_else(profile::from_env);
+    let opened = match &chosen {
+        Some(name) => match profile::Profile::open(name) {
+            Some(profile) => {
+                tracing::info!("Profile {:?} in {:?}", profile.name, profile.dir);
+                Some(profile)
+            }
+            None => {
+                eprintln!(
+                    "No profile called {:?} in {:?}",
+                    name,
+                    profile::profiles_dir()
+                );
+                std::process::exit(2);
+            }
+        },
+        None => None,
+    };
+
+    let mut game = game::Game::new(500, 0, 100, opened.as_ref());
+    if let Some(narrator) = narrator {
+        game.set_narrator(narrator);
+    }
     game.load_images();
-    game.board
-        .load_position("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1".to_string());
+    game.start_game("r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1");
 
+    let mut scene = scene::Scene::MainMenu(widgets::MainMenu::default());
+    if chosen.is_none() && !profile::list().is_empty() {
+        scene = scene::Scene::Profiles(scene::ProfilePicker::new(&game, Box::new(scene)));
+    }
+    let mut title = String::new();
     while !rl.window_should_close() {
-        let mut d = rl.begin_drawing(&thread);
-        d.clear_background(Color::WHITE);
-        game.draw_board(&mut d);
-
-        //TODO: use keyboard select the piece otherwise it'll be long kek
-        if d.is_key_pressed(raylib::ffi::KeyboardKey::KEY_D) {
-            game.pawn_promotion = game.pawn_promotion.not();
+        // the window grows to fit the analysis panel and the explorer beside the board
+        let analysis_panel = game.input.pressed(&rl, input::Action::AnalysisPanel);
+        let explorer = game.input.pressed(&rl, input::Action::Explorer);
+        if analysis_panel {
+            game.toggle_analysis_panel();
         }
-
-        if d.is_key_pressed(raylib::ffi::KeyboardKey::KEY_ENTER) {
-            game.debug();
+        if explorer {
+            game.toggle_explorer();
+        }
+        if analysis_panel || explorer {
+            let height = rl.get_screen_height();
+            rl.set_window_size(game.window_width(), height);
+            game.resize(game.window_width(), height);
         }
 
-        if !game.pawn_promotion {
-            if d.is_mouse_button_pressed(raylib::ffi::MouseButton::MOUSE_LEFT_BUTTON) {
-                if game.selected.is_some() {
-                    game.make_move();
-                } else {
-                    game.select_piece(&d);
-                }
-            }
+        let fullscreen = game.input.pressed(&rl, input::Action::Fullscreen);
+        if fullscreen {
+            rl.toggle_fullscreen();
+        }
+        // the board fills the window, whatever its size
+        if rl.is_window_resized() || fullscreen {
+            game.resize(rl.get_screen_width(), rl.get_screen_height());
+        }
 
-            if d.is_key_pressed(raylib::ffi::KeyboardKey::KEY_ESCAPE) {
-                game.unset_selected();
+        // the game stands still while the window is in the background
+        if !rl.is_window_focused() {
+            if let Some(paused) = scene.pause(&mut game) {
+                scene = paused;
             }
+        }
 
-            if d.is_mouse_button_pressed(raylib::ffi::MouseButton::MOUSE_RIGHT_BUTTON) {
-                game.unset_selected();
-            }
-        } else {
-            if d.is_key_pressed(raylib::ffi::KeyboardKey::KEY_ONE) {
-                game.selected_pawn_promotion(0);
-            }
-            if d.is_key_pressed(raylib::ffi::KeyboardKey::KEY_TWO) {
-                game.selected_pawn_promotion(1);
-            }
-            if d.is_key_pressed(raylib::ffi::KeyboardKey::KEY_THREE) {
-                game.selected_pawn_promotion(2);
-            }
-            if d.is_key_pressed(raylib::ffi::KeyboardKey::KEY_FOUR) {
-                game.selected_pawn_promotion(3);
-            }
+        game.follow_mouse(&rl);
+        match scene.update(&mut rl, &mut game) {
+            scene::Transition::Stay => {}
+            scene::Transition::Switch(next) => scene = next,
+            scene::Transition::Quit => break,
+        }
+        game.update();
+        // follows the game while the window is in the background
+        let next_title = game.window_title();
+        if next_title != title {
+            rl.set_window_title(&thread, &next_title);
+            title = next_title;
         }
 
-        game.follow_mouse(&d);
+        let mut d = rl.begin_drawing(&thread);
+        d.clear_background(Color::WHITE);
+        scene.draw(&mut d, &game);
+        drop(d);
+        game.run_export(&mut rl, &thread);
     }
+    game.save_unfinished();
 }
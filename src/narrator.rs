@@ -0,0 +1,107 @@
+//! Text output of what happens on the board, for screen readers and scripts following the game.
+//! Each event is one line made of its name followed by `key=value` fields, values holding
+//! spaces being quoted:
+//!
+//! ```text
+//! select square=e2 piece=pawn color=white targets=e3,e4
+//! move ply=1 color=white san=e4 from=e2 to=e4
+//! check color=black
+//! checkmate winner=white
+//! gameover result=1-0 reason="White wins by checkmate"
+//! ```
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::board::{square_to_algebraic, Piece, PieceColor};
+use crate::lifecycle::GameState;
+
+fn color_name(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "white",
+        PieceColor::Black => "black",
+    }
+}
+
+/// Writes the events of the board, one line each, flushed as they happen
+pub struct Narrator {
+    out: Box<dyn Write>,
+}
+
+impl Narrator {
+    /// Narrates to stdout
+    pub fn stdout() -> Self {
+        Self {
+            out: Box::new(std::io::stdout()),
+        }
+    }
+
+    /// Narrates to the file or named pipe at `path`, truncating a file
+    pub fn to_path(path: &Path) -> std::io::Result<Self> {
+        Ok(Self {
+            out: Box::new(BufWriter::new(File::create(path)?)),
+        })
+    }
+
+    /// Writes one event, giving up on the output for the rest of the game when it fails, as
+    /// happens when the reader of a pipe goes away
+    fn line(&mut self, line: String) {
+        let written = writeln!(self.out, "{}", line).and_then(|_| self.out.flush());
+        if let Err(e) = written {
+            tracing::warn!("Failed to narrate the game, narration stopped: {}", e);
+            self.out = Box::new(std::io::sink());
+        }
+    }
+
+    /// A piece was selected, with the squares it can move to
+    pub fn selected(&mut self, square: usize, piece: Piece, targets: &[usize]) {
+        let targets = targets
+            .iter()
+            .map(|&t| square_to_algebraic(t))
+            .collect::<Vec<_>>();
+        let targets = if targets.is_empty() {
+            "-".to_string()
+        } else {
+            targets.join(",")
+        };
+        let piece_type = format!("{:?}", piece.get_type()).to_lowercase();
+        self.line(format!(
+            "select square={} piece={} color={} targets={}",
+            square_to_algebraic(square),
+            piece_type,
+            color_name(piece.get_color()),
+            targets
+        ));
+    }
+
+    /// A move was played by `color`, the `ply`th of the game
+    pub fn moved(&mut self, ply: usize, color: PieceColor, san: &str, from: usize, to: usize) {
+        self.line(format!(
+            "move ply={} color={} san={} from={} to={}",
+            ply,
+            color_name(color),
+            san,
+            square_to_algebraic(from),
+            square_to_algebraic(to)
+        ));
+    }
+
+    /// The king of `color` is in check, `mated` when it can't get out of it
+    pub fn check(&mut self, color: PieceColor, mated: bool) {
+        if mated {
+            self.line(format!("checkmate winner={}", color_name(color.opposite())));
+        } else {
+            self.line(format!("check color={}", color_name(color)));
+        }
+    }
+
+    /// The game ended
+    pub fn game_over(&mut self, state: GameState) {
+        self.line(format!(
+            "gameover result={} reason={:?}",
+            state.result(),
+            state.to_string()
+        ));
+    }
+}
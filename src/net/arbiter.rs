@@ -0,0 +1,28 @@
+use crate::board::{Board, Move, PieceColor};
+
+use super::RejectReason;
+
+/// Decides on the host whether `color` may play `mv` on turn `turn`, the host's board being on
+/// turn `expected`.
+///
+/// The host is authoritative. When both sides believe it's their turn (e.g. after a resync) the
+/// first move to reach the host advances the turn, so the other one arrives with a stale turn
+/// number and is rejected
+pub fn arbitrate(
+    board: &Board,
+    expected: u32,
+    color: PieceColor,
+    turn: u32,
+    mv: &Move,
+) -> Result<(), RejectReason> {
+    if turn != expected {
+        return Err(RejectReason::StaleTurn { expected });
+    }
+    if board.get_turn() != color {
+        return Err(RejectReason::NotYourTurn);
+    }
+    if !board.moves_from(mv.from).any(|m| m == *mv) {
+        return Err(RejectReason::IllegalMove);
+    }
+    Ok(())
+}
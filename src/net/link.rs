@@ -0,0 +1,263 @@
+use std::io;
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use super::protocol::{read_frame, write_frame};
+use super::NetMessage;
+
+/// Port a game is hosted on unless another is typed
+pub const DEFAULT_PORT: u16 = 7878;
+/// Time a client waits before connecting again after failing to or losing the host
+const RETRY_INTERVAL: Duration = Duration::from_secs(2);
+/// Time after which a [NetMessage::Ping] is sent if nothing else was
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+/// Time without hearing from the peer after which the connection counts as lost
+const PEER_TIMEOUT: Duration = Duration::from_secs(8);
+/// Time a client waits for the host to answer a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// What happened on the link since the last poll
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkEvent {
+    /// The peer connected, for the first time or again after the connection was lost
+    Connected,
+    Message(NetMessage),
+    /// The peer went away, the host waits for it to come back and the client tries again
+    Disconnected,
+}
+
+/// Which end of the connection this is
+enum Role {
+    /// waits for the client on a port
+    Host(TcpListener),
+    /// connects to the host at an address, again whenever the connection is lost
+    Client {
+        address: String,
+        /// connection being opened on a thread, so resolving and connecting never block
+        connecting: Option<Receiver<io::Result<TcpStream>>>,
+        retry_at: Instant,
+    },
+}
+
+/// The connection between the host and the client, read on a thread of its own so polling
+/// never blocks the render loop
+struct Connection {
+    stream: TcpStream,
+    /// messages read, `None` once the connection is closed
+    incoming: Receiver<Option<NetMessage>>,
+    last_heard: Instant,
+    last_sent: Instant,
+}
+
+/// One end of a network game
+pub struct Link {
+    role: Role,
+    connection: Option<Connection>,
+}
+
+impl Link {
+    /// Listens for a client on `port` of every interface
+    pub fn host(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        tracing::info!("Hosting on port {}", port);
+        Ok(Self {
+            role: Role::Host(listener),
+            connection: None,
+        })
+    }
+
+    /// Connects to the host at `address`, `host:port`, in the background
+    pub fn join(address: &str) -> Self {
+        Self {
+            role: Role::Client {
+                address: address.to_string(),
+                connecting: None,
+                retry_at: Instant::now(),
+            },
+            connection: None,
+        }
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self.role, Role::Host(_))
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection.is_some()
+    }
+
+    /// Reads what happened since the last call, none when there is nothing new
+    pub fn poll(&mut self) -> Option<LinkEvent> {
+        let now = Instant::now();
+        let Some(connection) = &mut self.connection else {
+            let stream = self.accept_or_connect(now)?;
+            return match Connection::open(stream) {
+                Ok(connection) => {
+                    self.connection = Some(connection);
+                    Some(LinkEvent::Connected)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to set the connection up: {}", e);
+                    None
+                }
+            };
+        };
+
+        if now - connection.last_sent >= PING_INTERVAL {
+            self.send(&NetMessage::Ping);
+        }
+        loop {
+            let Some(connection) = &mut self.connection else {
+                return Some(LinkEvent::Disconnected);
+            };
+            match connection.incoming.try_recv() {
+                Ok(Some(NetMessage::Ping)) => connection.last_heard = now,
+                Ok(Some(msg)) => {
+                    connection.last_heard = now;
+                    return Some(LinkEvent::Message(msg));
+                }
+                Ok(None) | Err(TryRecvError::Disconnected) => {
+                    self.close();
+                    return Some(LinkEvent::Disconnected);
+                }
+                Err(TryRecvError::Empty) if now - connection.last_heard >= PEER_TIMEOUT => {
+                    tracing::warn!("Nothing heard from the peer for {:?}", PEER_TIMEOUT);
+                    self.close();
+                    return Some(LinkEvent::Disconnected);
+                }
+                Err(TryRecvError::Empty) => return None,
+            }
+        }
+    }
+
+    /// Sends `msg` to the peer. It is dropped while disconnected, the host resyncs the client
+    /// once it is back
+    pub fn send(&mut self, msg: &NetMessage) {
+        let Some(connection) = &mut self.connection else {
+            tracing::debug!("Not connected, dropping {:?}", msg);
+            return;
+        };
+        tracing::debug!("net > {:?}", msg);
+        connection.last_sent = Instant::now();
+        if let Err(e) = write_frame(&mut connection.stream, msg) {
+            tracing::warn!("Failed to send {:?}: {}", msg, e);
+            // the reading thread sees the connection close and the next poll reports it
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// Forgets the connection, a client connecting again after a while
+    fn close(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            let _ = connection.stream.shutdown(Shutdown::Both);
+        }
+        if let Role::Client { retry_at, .. } = &mut self.role {
+            *retry_at = Instant::now() + RETRY_INTERVAL;
+        }
+    }
+
+    /// The stream of a peer that just connected, if any
+    fn accept_or_connect(&mut self, now: Instant) -> Option<TcpStream> {
+        match &mut self.role {
+            Role::Host(listener) => match listener.accept() {
+                Ok((stream, peer)) => {
+                    tracing::info!("{} connected", peer);
+                    Some(stream)
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => None,
+                Err(e) => {
+                    tracing::warn!("Failed to accept a connection: {}", e);
+                    None
+                }
+            },
+            Role::Client {
+                address,
+                connecting,
+                retry_at,
+            } => {
+                let Some(pending) = connecting else {
+                    if now >= *retry_at {
+                        *connecting = Some(connect(address.clone()));
+                    }
+                    return None;
+                };
+                match pending.try_recv() {
+                    Ok(Ok(stream)) => {
+                        *connecting = None;
+                        tracing::info!("Connected to {}", address);
+                        Some(stream)
+                    }
+                    Ok(Err(e)) => {
+                        tracing::info!("Failed to connect to {}: {}", address, e);
+                        *connecting = None;
+                        *retry_at = now + RETRY_INTERVAL;
+                        None
+                    }
+                    Err(TryRecvError::Empty) => None,
+                    Err(TryRecvError::Disconnected) => {
+                        *connecting = None;
+                        None
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Connects to `address` on a thread, the stream arriving on the returned channel
+fn connect(address: String) -> Receiver<io::Result<TcpStream>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let stream = address.to_socket_addrs().and_then(|addrs| {
+            let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no address found");
+            for addr in addrs {
+                match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                    Ok(stream) => return Ok(stream),
+                    Err(e) => last_error = e,
+                }
+            }
+            Err(last_error)
+        });
+        let _ = tx.send(stream);
+    });
+    rx
+}
+
+impl Connection {
+    /// Starts reading the messages of `stream` on a thread
+    fn open(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(false)?;
+        stream.set_nodelay(true)?;
+        let mut reader = stream.try_clone()?;
+        let (tx, incoming) = mpsc::channel();
+        std::thread::spawn(move || loop {
+            match read_frame(&mut reader) {
+                Ok(msg) => {
+                    if tx.send(Some(msg)).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    tracing::info!("Connection closed: {}", e);
+                    let _ = tx.send(None);
+                    break;
+                }
+            }
+        });
+        let now = Instant::now();
+        Ok(Self {
+            stream,
+            incoming,
+            last_heard: now,
+            last_sent: now,
+        })
+    }
+}
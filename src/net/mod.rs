@@ -0,0 +1,10 @@
+//! Network play over TCP, the host holds the authoritative game and arbitrates the moves sent
+//! by the client
+
+mod arbiter;
+mod link;
+mod protocol;
+
+pub use arbiter::arbitrate;
+pub use link::{Link, LinkEvent, DEFAULT_PORT};
+pub use protocol::{NetMessage, RejectReason};
@@ -0,0 +1,432 @@
+//! Messages exchanged between the host and a client and how they travel over TCP.
+//!
+//! Each message is a frame: its length as a big endian `u32`, then a tag byte naming the
+//! message followed by its fields. Numbers are big endian, strings are UTF-8 behind a `u16`
+//! length and a move is four bytes: the squares it leaves and lands on, its kind and the piece
+//! a pawn promotes to, 0 for none
+
+use std::fmt::Display;
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+use crate::board::{Coordinate, Move, MoveType, PieceColor, PieceType};
+use crate::lifecycle::TimeControl;
+
+/// Longest frame accepted, anything longer is a peer speaking another protocol
+const MAX_FRAME_LEN: u32 = 4096;
+
+/// Messages exchanged between the host and a client.
+///
+/// Every move carries the turn number it was played on, the number of plies played before it,
+/// so the host can tell a move made on an outdated position apart from a valid one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetMessage {
+    /// A client asks to play `mv` on turn `turn`
+    MoveRequest { turn: u32, mv: Move },
+    /// The host accepted `mv`, played on turn `turn`
+    MoveAccepted { turn: u32, mv: Move },
+    /// The host refused a move, the client should undo it and wait for the host
+    MoveRejected { turn: u32, reason: RejectReason },
+    /// The authoritative position, sent by the host after a reconnect
+    Resync { turn: u32, fen: String },
+    /// The sender called the game off on turn `turn`
+    Abort { turn: u32 },
+    /// The sender gave up the game on turn `turn`
+    Resign { turn: u32 },
+    /// The sender wants a rematch, sent back by the opponent to accept it
+    RematchOffer,
+    /// The sender turned down the rematch offer
+    RematchDecline,
+    /// The sender asks to take its last move back, the position being on turn `turn`
+    TakebackRequest { turn: u32 },
+    /// The sender lets the opponent take its last move back from turn `turn`
+    TakebackAccept { turn: u32 },
+    /// The sender turned down the takeback request
+    TakebackDecline,
+    /// The host starts a game from `fen`, the client playing `color`
+    NewGame {
+        color: PieceColor,
+        fen: String,
+        time_control: Option<TimeControl>,
+    },
+    /// Time left to either side, sent by the host after each move as its clock is the one
+    /// that counts
+    Clock { white: Duration, black: Duration },
+    /// The sender offers a draw on turn `turn`, sent back by the opponent to accept it
+    DrawOffer { turn: u32 },
+    /// The sender turned down the draw offer
+    DrawDecline,
+    /// Sent when nothing else was for a while, so either side notices a peer gone silent
+    Ping,
+}
+
+/// Why the host refused a move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectReason {
+    /// The sender isn't the side to move
+    NotYourTurn,
+    /// The move was made on an outdated position, the host is on turn `expected`
+    StaleTurn { expected: u32 },
+    /// The move isn't legal in the host's position
+    IllegalMove,
+    /// The game is over, no more moves can be played
+    GameOver,
+}
+
+/// Message shown to the player whose move was refused
+impl Display for RejectReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RejectReason::NotYourTurn => write!(f, "Move rejected: it's not your turn"),
+            RejectReason::StaleTurn { expected } => {
+                write!(f, "Move rejected: the game is already on turn {}", expected)
+            }
+            RejectReason::IllegalMove => write!(f, "Move rejected: illegal move"),
+            RejectReason::GameOver => write!(f, "Move rejected: the game is over"),
+        }
+    }
+}
+
+/// Why a frame couldn't be read as a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The frame ended in the middle of a field
+    Truncated,
+    /// No message has this tag
+    UnknownTag(u8),
+    /// A field holds a value it can't take
+    InvalidField(&'static str),
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DecodeError::Truncated => write!(f, "truncated message"),
+            DecodeError::UnknownTag(tag) => write!(f, "unknown message tag {}", tag),
+            DecodeError::InvalidField(field) => write!(f, "invalid {}", field),
+        }
+    }
+}
+
+impl From<DecodeError> for io::Error {
+    fn from(e: DecodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+    }
+}
+
+/// Appends the fields of a message to its frame
+#[derive(Default)]
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn duration(&mut self, value: Duration) {
+        self.0
+            .extend_from_slice(&(value.as_millis() as u64).to_be_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.0
+            .extend_from_slice(&(value.len() as u16).to_be_bytes());
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn color(&mut self, color: PieceColor) {
+        self.u8(match color {
+            PieceColor::White => 0,
+            PieceColor::Black => 1,
+        });
+    }
+
+    fn mv(&mut self, mv: &Move) {
+        let (kind, promotion) = match mv.move_type {
+            MoveType::None => (0, None),
+            MoveType::PawnPush { promotion_piece } => (1, promotion_piece),
+            MoveType::PawnDoublePush => (2, None),
+            MoveType::PawnCapture { promotion_piece } => (3, promotion_piece),
+            MoveType::PawnEnPassant(_) => (4, None),
+            MoveType::QueenMove => (5, None),
+            MoveType::RookMove => (6, None),
+            MoveType::BishopMove => (7, None),
+            MoveType::KnightMove => (8, None),
+            MoveType::KingMove => (9, None),
+            MoveType::CastelKingSide => (10, None),
+            MoveType::CastelQueenSide => (11, None),
+            // the piece dropped goes where the promotion piece does
+            MoveType::Drop(piece) => (12, Some(piece)),
+        };
+        self.u8(mv.from as u8);
+        self.u8(mv.to as u8);
+        self.u8(kind);
+        self.u8(promotion.map_or(0, |piece| piece as u8));
+    }
+}
+
+/// Reads the fields of a message back from its frame
+struct Decoder<'a>(&'a [u8]);
+
+impl Decoder<'_> {
+    fn bytes(&mut self, len: usize) -> Result<&[u8], DecodeError> {
+        if self.0.len() < len {
+            return Err(DecodeError::Truncated);
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let bytes = self.bytes(4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().expect("4 bytes")))
+    }
+
+    fn duration(&mut self) -> Result<Duration, DecodeError> {
+        let bytes = self.bytes(8)?;
+        let millis = u64::from_be_bytes(bytes.try_into().expect("8 bytes"));
+        Ok(Duration::from_millis(millis))
+    }
+
+    fn str(&mut self) -> Result<String, DecodeError> {
+        let len = u16::from_be_bytes(self.bytes(2)?.try_into().expect("2 bytes"));
+        let bytes = self.bytes(len as usize)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| DecodeError::InvalidField("string"))
+    }
+
+    fn color(&mut self) -> Result<PieceColor, DecodeError> {
+        match self.u8()? {
+            0 => Ok(PieceColor::White),
+            1 => Ok(PieceColor::Black),
+            _ => Err(DecodeError::InvalidField("color")),
+        }
+    }
+
+    fn mv(&mut self) -> Result<Move, DecodeError> {
+        let (from, to) = (self.u8()? as usize, self.u8()? as usize);
+        if from > 63 || to > 63 {
+            return Err(DecodeError::InvalidField("square"));
+        }
+        let kind = self.u8()?;
+        let promotion_piece = match self.u8()? {
+            0 => None,
+            // only a drop carries a pawn
+            1 => Some(PieceType::Pawn),
+            2 => Some(PieceType::Knight),
+            3 => Some(PieceType::Bishop),
+            4 => Some(PieceType::Rook),
+            5 => Some(PieceType::Queen),
+            _ => return Err(DecodeError::InvalidField("promotion piece")),
+        };
+        let move_type = match kind {
+            0 => MoveType::None,
+            1 => MoveType::PawnPush { promotion_piece },
+            2 => MoveType::PawnDoublePush,
+            3 => MoveType::PawnCapture { promotion_piece },
+            // the pawn taken stands beside the one leaving, on the file it lands on
+            4 => MoveType::PawnEnPassant(Coordinate::from(from / 8 * 8 + to % 8)),
+            5 => MoveType::QueenMove,
+            6 => MoveType::RookMove,
+            7 => MoveType::BishopMove,
+            8 => MoveType::KnightMove,
+            9 => MoveType::KingMove,
+            10 => MoveType::CastelKingSide,
+            11 => MoveType::CastelQueenSide,
+            12 => {
+                MoveType::Drop(promotion_piece.ok_or(DecodeError::InvalidField("dropped piece"))?)
+            }
+            _ => return Err(DecodeError::InvalidField("move kind")),
+        };
+        Ok(Move {
+            from,
+            to,
+            move_type,
+        })
+    }
+}
+
+impl NetMessage {
+    /// The tag byte and fields of the message, without the length of the frame
+    pub fn encode(&self) -> Vec<u8> {
+        let mut e = Encoder::default();
+        match self {
+            NetMessage::MoveRequest { turn, mv } => {
+                e.u8(1);
+                e.u32(*turn);
+                e.mv(mv);
+            }
+            NetMessage::MoveAccepted { turn, mv } => {
+                e.u8(2);
+                e.u32(*turn);
+                e.mv(mv);
+            }
+            NetMessage::MoveRejected { turn, reason } => {
+                e.u8(3);
+                e.u32(*turn);
+                match reason {
+                    RejectReason::NotYourTurn => e.u8(0),
+                    RejectReason::StaleTurn { expected } => {
+                        e.u8(1);
+                        e.u32(*expected);
+                    }
+                    RejectReason::IllegalMove => e.u8(2),
+                    RejectReason::GameOver => e.u8(3),
+                }
+            }
+            NetMessage::Resync { turn, fen } => {
+                e.u8(4);
+                e.u32(*turn);
+                e.str(fen);
+            }
+            NetMessage::Abort { turn } => {
+                e.u8(5);
+                e.u32(*turn);
+            }
+            NetMessage::Resign { turn } => {
+                e.u8(6);
+                e.u32(*turn);
+            }
+            NetMessage::RematchOffer => e.u8(7),
+            NetMessage::RematchDecline => e.u8(8),
+            NetMessage::TakebackRequest { turn } => {
+                e.u8(9);
+                e.u32(*turn);
+            }
+            NetMessage::TakebackAccept { turn } => {
+                e.u8(10);
+                e.u32(*turn);
+            }
+            NetMessage::TakebackDecline => e.u8(11),
+            NetMessage::NewGame {
+                color,
+                fen,
+                time_control,
+            } => {
+                e.u8(12);
+                e.color(*color);
+                e.str(fen);
+                match time_control {
+                    Some(tc) => {
+                        e.u8(1);
+                        e.u32(tc.base.as_secs() as u32);
+                        e.u32(tc.increment.as_secs() as u32);
+                        e.u32(tc.delay.as_secs() as u32);
+                    }
+                    None => e.u8(0),
+                }
+            }
+            NetMessage::Clock { white, black } => {
+                e.u8(13);
+                e.duration(*white);
+                e.duration(*black);
+            }
+            NetMessage::DrawOffer { turn } => {
+                e.u8(14);
+                e.u32(*turn);
+            }
+            NetMessage::DrawDecline => e.u8(15),
+            NetMessage::Ping => e.u8(16),
+        }
+        e.0
+    }
+
+    /// Reads back a message written by [NetMessage::encode]
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut d = Decoder(bytes);
+        let msg = match d.u8()? {
+            1 => NetMessage::MoveRequest {
+                turn: d.u32()?,
+                mv: d.mv()?,
+            },
+            2 => NetMessage::MoveAccepted {
+                turn: d.u32()?,
+                mv: d.mv()?,
+            },
+            3 => {
+                let turn = d.u32()?;
+                let reason = match d.u8()? {
+                    0 => RejectReason::NotYourTurn,
+                    1 => RejectReason::StaleTurn { expected: d.u32()? },
+                    2 => RejectReason::IllegalMove,
+                    3 => RejectReason::GameOver,
+                    _ => return Err(DecodeError::InvalidField("reject reason")),
+                };
+                NetMessage::MoveRejected { turn, reason }
+            }
+            4 => NetMessage::Resync {
+                turn: d.u32()?,
+                fen: d.str()?,
+            },
+            5 => NetMessage::Abort { turn: d.u32()? },
+            6 => NetMessage::Resign { turn: d.u32()? },
+            7 => NetMessage::RematchOffer,
+            8 => NetMessage::RematchDecline,
+            9 => NetMessage::TakebackRequest { turn: d.u32()? },
+            10 => NetMessage::TakebackAccept { turn: d.u32()? },
+            11 => NetMessage::TakebackDecline,
+            12 => {
+                let color = d.color()?;
+                let fen = d.str()?;
+                let time_control = match d.u8()? {
+                    0 => None,
+                    1 => Some(TimeControl::new(
+                        d.u32()? as u64,
+                        d.u32()? as u64,
+                        d.u32()? as u64,
+                    )),
+                    _ => return Err(DecodeError::InvalidField("time control")),
+                };
+                NetMessage::NewGame {
+                    color,
+                    fen,
+                    time_control,
+                }
+            }
+            13 => NetMessage::Clock {
+                white: d.duration()?,
+                black: d.duration()?,
+            },
+            14 => NetMessage::DrawOffer { turn: d.u32()? },
+            15 => NetMessage::DrawDecline,
+            16 => NetMessage::Ping,
+            tag => return Err(DecodeError::UnknownTag(tag)),
+        };
+        if !d.0.is_empty() {
+            return Err(DecodeError::InvalidField("message length"));
+        }
+        Ok(msg)
+    }
+}
+
+/// Writes `msg` to `w` as a frame
+pub fn write_frame(w: &mut impl Write, msg: &NetMessage) -> io::Result<()> {
+    let payload = msg.encode();
+    w.write_all(&(payload.len() as u32).to_be_bytes())?;
+    w.write_all(&payload)?;
+    w.flush()
+}
+
+/// Reads the next frame from `r`, blocking until it arrives
+pub fn read_frame(r: &mut impl Read) -> io::Result<NetMessage> {
+    let mut len = [0; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len == 0 || len > MAX_FRAME_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes", len),
+        ));
+    }
+    let mut payload = vec![0; len as usize];
+    r.read_exact(&mut payload)?;
+    Ok(NetMessage::decode(&payload)?)
+}
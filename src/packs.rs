@@ -0,0 +1,42 @@
+use std::path::{Path, PathBuf};
+
+/// Name of the pack every lookup falls back to
+pub const DEFAULT_PACK: &str = "default";
+
+/// Resolves asset files bundled by themes, each theme being a directory under `root`.
+/// Files missing from a theme are looked up in the [DEFAULT_PACK] instead
+#[derive(Debug, Clone)]
+pub struct PackManager {
+    root: PathBuf,
+}
+
+impl PackManager {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Returns the path of `file` in the `theme` pack, or in the default pack if the theme
+    /// doesn't provide it, `None` if neither does
+    pub fn resolve(&self, theme: &str, file: &str) -> Option<PathBuf> {
+        [theme, DEFAULT_PACK]
+            .iter()
+            .map(|pack| self.root.join(pack).join(file))
+            .find(|path| path.is_file())
+    }
+
+    /// Returns the names of the packs found on disk, sorted
+    pub fn packs(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return vec![];
+        };
+        let mut packs = entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        packs.sort();
+        packs
+    }
+}
@@ -0,0 +1,138 @@
+//! Piece sets found on disk. The lila piece sets are unzipped into `resource/<set>/` as SVGs
+//! and rendered to PNGs next to them by `extra/convert.py`, a set being usable once all twelve
+//! images are there. The default set is also built into the binary, for installs without the
+//! resource directory
+
+use std::path::{Path, PathBuf};
+
+use crate::board::{Piece, PieceColor, PieceType};
+
+/// Directory the piece sets live in, one subdirectory of images per set
+pub const PIECES_DIR: &str = "./resource";
+/// Piece set used when the config doesn't name one
+pub const DEFAULT_PIECE_SET: &str = "alpha";
+
+/// File name endings of the rendered images, tried in order: `wP.svg.png` as written by
+/// `convert.py` or a plain `wP.png`
+const EXTENSIONS: [&str; 2] = [".svg.png", ".png"];
+
+include!(concat!(env!("OUT_DIR"), "/embedded_pieces.rs"));
+
+/// The image of `piece` in the default set built into the binary, as PNG
+pub fn embedded_image(piece: &Piece) -> Option<&'static [u8]> {
+    let stem = stem(piece);
+    EMBEDDED_PIECES
+        .iter()
+        .find(|(name, _)| *name == stem)
+        .map(|(_, png)| *png)
+}
+
+/// Name of the image of `piece` without its ending, `wP` for the white pawn
+fn stem(piece: &Piece) -> String {
+    let color = match piece.get_color() {
+        PieceColor::White => 'w',
+        PieceColor::Black => 'b',
+    };
+    let kind = match piece.get_type() {
+        PieceType::Pawn => 'P',
+        PieceType::Rook => 'R',
+        PieceType::Knight => 'N',
+        PieceType::Bishop => 'B',
+        PieceType::Queen => 'Q',
+        PieceType::King => 'K',
+        PieceType::None => panic!("Invalid piece type"),
+    };
+    format!("{}{}", color, kind)
+}
+
+/// Every piece with an image, white first
+pub fn all_pieces() -> impl Iterator<Item = Piece> {
+    [PieceColor::White, PieceColor::Black]
+        .into_iter()
+        .flat_map(|color| {
+            [
+                PieceType::Pawn,
+                PieceType::Rook,
+                PieceType::Knight,
+                PieceType::Bishop,
+                PieceType::Queen,
+                PieceType::King,
+            ]
+            .map(|kind| Piece::new(color, kind))
+        })
+}
+
+/// A directory of piece images
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceSet {
+    pub name: String,
+    dir: PathBuf,
+    /// ending shared by the file names of its images
+    extension: &'static str,
+}
+
+impl PieceSet {
+    /// The set in `dir`, if it has an image of every piece under one of the known names
+    fn open(dir: &Path) -> Option<Self> {
+        let name = dir.file_name()?.to_str()?.to_string();
+        let extension = EXTENSIONS.into_iter().find(|ext| {
+            all_pieces().all(|piece| dir.join(format!("{}{}", stem(&piece), ext)).is_file())
+        })?;
+        Some(Self {
+            name,
+            dir: dir.to_path_buf(),
+            extension,
+        })
+    }
+
+    /// Path of the image of `piece`
+    pub fn path(&self, piece: &Piece) -> PathBuf {
+        self.dir.join(format!("{}{}", stem(piece), self.extension))
+    }
+}
+
+/// The piece sets under a directory, sorted by name
+#[derive(Debug, Clone, Default)]
+pub struct PieceSets {
+    sets: Vec<PieceSet>,
+}
+
+impl PieceSets {
+    /// Looks for the sets in the subdirectories of `root`
+    pub fn discover<P: AsRef<Path>>(root: P) -> Self {
+        let mut sets = std::fs::read_dir(root)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| PieceSet::open(&entry.path()))
+            .collect::<Vec<_>>();
+        sets.sort_by(|a, b| a.name.cmp(&b.name));
+        tracing::info!("Found {} piece sets", sets.len());
+        Self { sets }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&PieceSet> {
+        self.sets.iter().find(|set| set.name == name)
+    }
+
+    /// The set called `name`, else the default one, else any
+    pub fn get_or_default(&self, name: &str) -> Option<&PieceSet> {
+        self.get(name)
+            .or_else(|| self.get(DEFAULT_PIECE_SET))
+            .or_else(|| self.sets.first())
+    }
+
+    /// Name of the set `delta` places after `current`, before it for a negative `delta`,
+    /// wrapping around
+    pub fn step(&self, current: &str, delta: isize) -> Option<&str> {
+        if self.sets.is_empty() {
+            return None;
+        }
+        let len = self.sets.len() as isize;
+        let idx = match self.sets.iter().position(|set| set.name == current) {
+            Some(idx) => (idx as isize + delta).rem_euclid(len),
+            None => 0,
+        };
+        Some(&self.sets[idx as usize].name)
+    }
+}
@@ -0,0 +1,167 @@
+//! Local player profiles, so people sharing a machine each keep their own settings, statistics,
+//! repertoire, backups and games put aside. A profile is a directory of the profiles directory
+//! named after the player, holding the files ruche otherwise keeps in the shared places:
+//!
+//! ```text
+//! profiles/alice/config.toml
+//! profiles/alice/stats.json
+//! profiles/alice/repertoire.txt
+//! profiles/alice/autosave/
+//! profiles/alice/games/
+//! ```
+//!
+//! Without a profile each of those files is found through its environment variable,
+//! `RUCHE_CONFIG` and the like. A profile is resolved into the [Paths] of its directory instead,
+//! which the game hands to whatever reads or writes the files
+
+use std::path::{Path, PathBuf};
+
+use crate::autosave::AutosaveConfig;
+use crate::{config, correspondence, repertoire, stats};
+
+/// Environment variable naming the profile to start with
+const PROFILE_VAR: &str = "RUCHE_PROFILE";
+/// Longest profile name
+pub const MAX_NAME_LEN: usize = 24;
+
+/// Where the settings, statistics, repertoire, backups and games put aside are kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    pub config: PathBuf,
+    pub stats: PathBuf,
+    pub repertoire: PathBuf,
+    pub autosave_dir: PathBuf,
+    pub games_dir: PathBuf,
+}
+
+impl Paths {
+    /// The shared files, used without a profile
+    pub fn shared() -> Self {
+        Self {
+            config: config::config_path(),
+            stats: stats::stats_path(),
+            repertoire: repertoire::repertoire_path(),
+            autosave_dir: AutosaveConfig::from_env().dir,
+            games_dir: correspondence::games_dir(),
+        }
+    }
+
+    /// The files of `profile`, or the shared ones for none
+    pub fn of(profile: Option<&Profile>) -> Self {
+        profile.map_or_else(Self::shared, Profile::paths)
+    }
+}
+
+/// Returns the directory of the profiles, `RUCHE_PROFILES_DIR` or `profiles` next to the
+/// shared config file
+pub fn profiles_dir() -> PathBuf {
+    match std::env::var("RUCHE_PROFILES_DIR") {
+        Ok(dir) => PathBuf::from(dir),
+        Err(_) => config::config_path()
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join("profiles"),
+    }
+}
+
+/// Why a profile couldn't be created
+#[derive(Debug)]
+pub enum ProfileError {
+    /// The name is empty, too long or has characters other than letters, digits, spaces, `-`
+    /// and `_`
+    InvalidName,
+    Exists,
+    Io(std::io::Error),
+}
+
+impl std::fmt::Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileError::InvalidName => write!(
+                f,
+                "a name is up to {} letters, digits, spaces, - and _",
+                MAX_NAME_LEN
+            ),
+            ProfileError::Exists => write!(f, "the profile already exists"),
+            ProfileError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for ProfileError {
+    fn from(value: std::io::Error) -> Self {
+        ProfileError::Io(value)
+    }
+}
+
+/// A player's profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub name: String,
+    pub dir: PathBuf,
+}
+
+impl Profile {
+    /// The profile called `name`, if its directory exists
+    pub fn open(name: &str) -> Option<Self> {
+        let dir = profiles_dir().join(name);
+        dir.is_dir().then(|| Self {
+            name: name.to_string(),
+            dir,
+        })
+    }
+
+    /// Makes the directory of a new profile called `name`
+    pub fn create(name: &str) -> Result<Self, ProfileError> {
+        let name = name.trim();
+        let valid = name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'));
+        if name.is_empty() || name.chars().count() > MAX_NAME_LEN || !valid {
+            return Err(ProfileError::InvalidName);
+        }
+        let dir = profiles_dir().join(name);
+        if dir.exists() {
+            return Err(ProfileError::Exists);
+        }
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            name: name.to_string(),
+            dir,
+        })
+    }
+
+    /// The files of the profile, all in its directory
+    pub fn paths(&self) -> Paths {
+        Paths {
+            config: self.dir.join("config.toml"),
+            stats: self.dir.join("stats.json"),
+            repertoire: self.dir.join("repertoire.txt"),
+            autosave_dir: self.dir.join("autosave"),
+            games_dir: self.dir.join("games"),
+        }
+    }
+}
+
+/// Name of the profile `RUCHE_PROFILE` starts with, none for the shared files
+pub fn from_env() -> Option<String> {
+    std::env::var(PROFILE_VAR)
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+/// The profiles, sorted by name
+pub fn list() -> Vec<Profile> {
+    let Ok(entries) = std::fs::read_dir(profiles_dir()) else {
+        return Vec::new();
+    };
+    let mut profiles = entries
+        .filter_map(|entry| {
+            let dir = entry.ok()?.path();
+            let name = dir.file_name()?.to_str()?.to_string();
+            dir.is_dir().then_some(Profile { name, dir })
+        })
+        .collect::<Vec<_>>();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
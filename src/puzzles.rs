@@ -0,0 +1,429 @@
+//! Mate puzzles found by the engine, in games read from PGN or played by ruche against itself
+//! with a noisy evaluation so that it blunders into mates
+//!
+//! Puzzles are written in the lichess puzzle CSV format: the position before the opponent's
+//! last move, then that move and the solution in coordinates, the player solving for the side
+//! that mates. Columns the generator knows nothing about, such as the rating, are left empty
+//!
+//! ```text
+//! PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags
+//! 5b3e17a0c2d4f981,6k1/5ppp/8/8/8/8/5PPP/3R2K1 b - - 0 1,g8h8 d1d8,,,,,mate mateIn1,,
+//! ```
+//!
+//! The same files, or the lichess puzzle database itself, are read back to be solved on the
+//! board with a [PuzzleTrainer]
+
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::board::{Board, Move, PieceColor};
+use crate::engine::{self, Params, SearchInfo, SearchLimits, Searcher};
+use crate::pgn;
+
+pub const CSV_HEADER: &str =
+    "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags";
+/// Puzzles solved on the board when `RUCHE_PUZZLES` doesn't name another file
+pub const PUZZLES_FILE: &str = "puzzles.csv";
+
+/// Noise of the evaluation in self-play games, enough for the weaker side to walk into mates
+const SELF_PLAY_NOISE: i32 = 150;
+/// Depth of the moves of self-play games
+const SELF_PLAY_DEPTH: u32 = 2;
+/// Self-play games still going after this many plies are abandoned
+const MAX_PLIES: usize = 200;
+
+/// How hard the generator looks for mates
+#[derive(Debug, Clone, Copy)]
+pub struct PuzzleConfig {
+    /// longest mate kept, in moves of the side that mates
+    pub max_mate: i32,
+    /// time spent looking for a mate in each position
+    pub movetime: Duration,
+}
+
+impl Default for PuzzleConfig {
+    fn default() -> Self {
+        Self {
+            max_mate: 3,
+            movetime: Duration::from_millis(200),
+        }
+    }
+}
+
+/// A forced mate, from the position before the opponent's last move
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub id: String,
+    pub fen: String,
+    /// the opponent's move leading to the puzzle, then the solution ending in mate
+    pub moves: Vec<Move>,
+    /// length of the mate, 0 for a puzzle read from a file whose themes don't tell
+    pub mate_in: i32,
+    /// where the game was played, when the PGN tells
+    pub game_url: Option<String>,
+}
+
+impl Puzzle {
+    /// The puzzle as a line of the lichess puzzle CSV, see [CSV_HEADER]
+    pub fn to_csv(&self) -> String {
+        let moves = self
+            .moves
+            .iter()
+            .map(|m| m.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        format!(
+            "{},{},{},,,,,mate mateIn{},{},",
+            self.id,
+            self.fen,
+            moves,
+            self.mate_in,
+            self.game_url.as_deref().unwrap_or("")
+        )
+    }
+
+    /// Reads a line of the lichess puzzle CSV, checking that its moves can be played from its
+    /// position
+    pub fn from_csv(line: &str) -> Result<Self, PuzzleError> {
+        let fields = line.trim_end().split(',').collect::<Vec<_>>();
+        let field = |i: usize, name: &'static str| {
+            fields
+                .get(i)
+                .copied()
+                .filter(|f| !f.is_empty())
+                .ok_or(PuzzleError::MissingField(name))
+        };
+        let id = field(0, "PuzzleId")?;
+        let fen = field(1, "FEN")?;
+
+        let mut board = Board::new();
+        board.load_position(fen.to_string());
+        let mut moves = vec![];
+        for text in field(2, "Moves")?.split_whitespace() {
+            let mv = board
+                .legal_moves()
+                .find(|m| m.to_string() == text)
+                .ok_or_else(|| PuzzleError::IllegalMove(text.to_string()))?;
+            board
+                .play(&mv)
+                .map_err(|_| PuzzleError::IllegalMove(text.to_string()))?;
+            moves.push(mv);
+        }
+        // the setup move and at least one move of the solution
+        if moves.len() < 2 {
+            return Err(PuzzleError::MissingField("Moves"));
+        }
+
+        let mate_in = fields
+            .get(7)
+            .into_iter()
+            .flat_map(|themes| themes.split_whitespace())
+            .find_map(|theme| theme.strip_prefix("mateIn")?.parse().ok())
+            .unwrap_or(0);
+        Ok(Self {
+            id: id.to_string(),
+            fen: fen.to_string(),
+            moves,
+            mate_in,
+            game_url: fields
+                .get(8)
+                .filter(|url| !url.is_empty())
+                .map(|url| url.to_string()),
+        })
+    }
+
+    /// Side the puzzle is solved for, the one not to move in its position
+    pub fn solver(&self) -> PieceColor {
+        let black_to_move = self.fen.split_whitespace().nth(1) == Some("b");
+        if black_to_move {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        }
+    }
+}
+
+/// Why a line of a puzzle file couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PuzzleError {
+    MissingField(&'static str),
+    /// A move that can't be played where it comes in the line
+    IllegalMove(String),
+}
+
+impl Display for PuzzleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PuzzleError::MissingField(name) => write!(f, "missing {}", name),
+            PuzzleError::IllegalMove(mv) => write!(f, "{} isn't legal", mv),
+        }
+    }
+}
+
+/// Reads the puzzles of the CSV file at `path`, the header and the lines that can't be read
+/// being skipped with a warning
+pub fn load(path: &Path) -> std::io::Result<Vec<Puzzle>> {
+    let contents = std::fs::read_to_string(path)?;
+    let puzzles = contents
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.starts_with("PuzzleId,"))
+        .filter_map(|(i, line)| match Puzzle::from_csv(line) {
+            Ok(puzzle) => Some(puzzle),
+            Err(e) => {
+                tracing::warn!("{:?} line {}: {}", path, i + 1, e);
+                None
+            }
+        })
+        .collect();
+    Ok(puzzles)
+}
+
+/// What a move played against a puzzle did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attempt {
+    /// the move of the solution, the opponent replies next
+    Correct,
+    Wrong,
+    /// the last move of the solution
+    Solved,
+}
+
+/// Puzzles solved one after the other, keeping the score. The opponent's moves, the setup move
+/// first, are handed out by [PuzzleTrainer::reply] and the user's checked by
+/// [PuzzleTrainer::check]
+#[derive(Debug, Clone)]
+pub struct PuzzleTrainer {
+    puzzles: Vec<Puzzle>,
+    current: usize,
+    /// moves of the current puzzle played so far
+    ply: usize,
+    /// a wrong move was played on the current puzzle, solving it no longer counts
+    missed: bool,
+    pub solved: u32,
+    pub failed: u32,
+}
+
+impl PuzzleTrainer {
+    /// Trains on `puzzles`, `None` when there are none
+    pub fn new(puzzles: Vec<Puzzle>) -> Option<Self> {
+        (!puzzles.is_empty()).then_some(Self {
+            puzzles,
+            current: 0,
+            ply: 0,
+            missed: false,
+            solved: 0,
+            failed: 0,
+        })
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzles[self.current]
+    }
+
+    /// Number of the current puzzle, from 1, and how many there are
+    pub fn progress(&self) -> (usize, usize) {
+        (self.current + 1, self.puzzles.len())
+    }
+
+    /// Moves of the current puzzle played so far
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.ply == self.puzzle().moves.len()
+    }
+
+    /// The opponent's next move, when it is their turn, counted as played
+    pub fn reply(&mut self) -> Option<Move> {
+        if !self.ply.is_multiple_of(2) || self.is_solved() {
+            return None;
+        }
+        self.ply += 1;
+        Some(self.puzzle().moves[self.ply - 1].clone())
+    }
+
+    /// Checks the user's move `mv`. Any move that mates ends the puzzle, even one other than
+    /// the solution's, and a puzzle missed once counts as failed even when solved afterwards
+    pub fn check(&mut self, mv: &Move, mates: bool) -> Attempt {
+        let moves = &self.puzzles[self.current].moves;
+        let last = self.ply + 1 == moves.len();
+        if moves.get(self.ply) != Some(mv) && !(last && mates) {
+            if !std::mem::replace(&mut self.missed, true) {
+                self.failed += 1;
+            }
+            return Attempt::Wrong;
+        }
+
+        self.ply += 1;
+        if !self.is_solved() {
+            return Attempt::Correct;
+        }
+        if !self.missed {
+            self.solved += 1;
+        }
+        Attempt::Solved
+    }
+
+    /// Moves on to the next puzzle, wrapping around. Skipping a puzzle before solving it counts
+    /// as failing it
+    pub fn next(&mut self) {
+        if !self.is_solved() && !self.missed {
+            self.failed += 1;
+        }
+        self.current = (self.current + 1) % self.puzzles.len();
+        self.ply = 0;
+        self.missed = false;
+    }
+}
+
+/// Looks for a mate of the side to move in at most `max_mate` moves with a single first move
+/// leading to it, returning the mating line and the number of moves
+fn find_mate(
+    searcher: &mut Searcher,
+    board: &Board,
+    config: PuzzleConfig,
+) -> Option<(Vec<Move>, i32)> {
+    let mut limits = SearchLimits::depth(2 * config.max_mate as u32 + 1).with_multipv(2);
+    limits.movetime = Some(config.movetime);
+
+    let mut lines: Vec<SearchInfo> = vec![];
+    searcher.search(board, limits, |info| {
+        if lines.first().is_some_and(|l| l.depth != info.depth) {
+            lines.clear();
+        }
+        lines.push(info.clone());
+    });
+
+    let best = lines.iter().find(|l| l.multipv == 1)?;
+    let mate_in = best
+        .mate_in()
+        .filter(|n| (1..=config.max_mate).contains(n))?;
+    // a second move mating as fast makes a poor puzzle, any of them being accepted or not
+    let second = lines.iter().find(|l| l.multipv == 2);
+    if second
+        .and_then(|l| l.mate_in())
+        .is_some_and(|n| n > 0 && n <= config.max_mate)
+    {
+        return None;
+    }
+
+    let plies = 2 * mate_in as usize - 1;
+    if best.pv.len() < plies {
+        return None;
+    }
+    let solution = best.pv[..plies].to_vec();
+    let mut end = board.clone();
+    for mv in solution.iter() {
+        end.play(mv).ok()?;
+    }
+    let mated = end.legal_moves().next().is_none() && end.is_in_check(end.get_turn());
+    mated.then_some((solution, mate_in))
+}
+
+/// Finds the first forced mate of a game from `start` through `moves`, the puzzle starting on
+/// the move before it
+pub fn scan_game(
+    searcher: &mut Searcher,
+    start: &Board,
+    moves: &[Move],
+    config: PuzzleConfig,
+) -> Option<Puzzle> {
+    let mut before = start.clone();
+    for setup in moves {
+        let mut after = before.clone();
+        after.play(setup).ok()?;
+        if let Some((solution, mate_in)) = find_mate(searcher, &after, config) {
+            return Some(Puzzle {
+                id: format!("{:016x}", after.zobrist_hash()),
+                fen: before.to_fen(),
+                moves: std::iter::once(setup.clone()).chain(solution).collect(),
+                mate_in,
+                game_url: None,
+            });
+        }
+        before = after;
+    }
+    None
+}
+
+/// Finds a puzzle in each game of the PGN file at `path`, games that can't be replayed being
+/// skipped with a warning
+pub fn from_pgn<F>(
+    path: &Path,
+    config: PuzzleConfig,
+    params: &Params,
+    mut on_puzzle: F,
+) -> std::io::Result<()>
+where
+    F: FnMut(&Puzzle) -> std::io::Result<()>,
+{
+    let contents = std::fs::read_to_string(path)?;
+    let mut searcher = Searcher::new(Arc::new(AtomicBool::new(false)), params.clone());
+    for (i, game) in pgn::parse_games(&contents).iter().enumerate() {
+        let (start, board) = match game.start().and_then(|s| Ok((s, game.replay()?))) {
+            Ok(boards) => boards,
+            Err(e) => {
+                eprintln!("game {}: skipped, {}", i + 1, e);
+                continue;
+            }
+        };
+        if let Some(mut puzzle) = scan_game(&mut searcher, &start, board.get_move_history(), config)
+        {
+            puzzle.game_url = game
+                .tag("Site")
+                .filter(|site| site.starts_with("http"))
+                .map(str::to_string);
+            on_puzzle(&puzzle)?;
+        }
+    }
+    Ok(())
+}
+
+/// Plays `games` games of ruche against itself and finds a puzzle in each where there is one
+pub fn from_self_play<F>(
+    games: usize,
+    config: PuzzleConfig,
+    params: &Params,
+    mut on_puzzle: F,
+) -> std::io::Result<()>
+where
+    F: FnMut(&Puzzle) -> std::io::Result<()>,
+{
+    let stop = Arc::new(AtomicBool::new(false));
+    let mut searcher = Searcher::new(stop.clone(), params.clone());
+    let mut player = Searcher::new(stop, params.clone());
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64;
+
+    for game in 0..games {
+        let game_seed = seed
+            .wrapping_add(game as u64)
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        player.set_eval_noise(SELF_PLAY_NOISE, game_seed);
+        let start = engine::random_opening(game_seed);
+        let mut board = start.clone();
+        while board.get_move_history().len() < MAX_PLIES {
+            let Some(mv) = player.search(&board, SearchLimits::depth(SELF_PLAY_DEPTH), |_| {})
+            else {
+                break;
+            };
+            if board.play(&mv).is_err() {
+                break;
+            }
+        }
+
+        let opening = start.get_move_history().len();
+        let moves = &board.get_move_history()[opening..];
+        if let Some(puzzle) = scan_game(&mut searcher, &start, moves, config) {
+            on_puzzle(&puzzle)?;
+        }
+    }
+    Ok(())
+}
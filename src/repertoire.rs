@@ -0,0 +1,220 @@
+//! Opening repertoire: the moves the user plays or expects in each position, recorded from the
+//! board, and a drill playing the opponent's side of it
+//!
+//! The repertoire is written one move per line: the position as the first four fields of its
+//! FEN, the move in coordinates and the number of times it was recorded, which weighs how often
+//! the drill plays it
+//!
+//! ```text
+//! rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 c7c5 3
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::board::{Board, Move, PieceColor};
+
+/// Repertoire read and written when `RUCHE_REPERTOIRE` doesn't name another file
+const REPERTOIRE_FILE: &str = "repertoire.txt";
+
+/// Returns the path of the repertoire, `RUCHE_REPERTOIRE` or `repertoire.txt` in the working
+/// directory
+pub fn repertoire_path() -> PathBuf {
+    std::env::var("RUCHE_REPERTOIRE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(REPERTOIRE_FILE))
+}
+
+/// Piece placement, side to move, castling rights and en passant square of `board`
+fn position_key(board: &Board) -> String {
+    board
+        .to_fen()
+        .split_whitespace()
+        .take(4)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Moves recorded by position, in coordinates with how many times each was recorded
+#[derive(Debug, Clone, Default)]
+pub struct Repertoire {
+    positions: HashMap<String, Vec<(String, u32)>>,
+}
+
+impl Repertoire {
+    /// Reads the repertoire at `path`, the lines that can't be read being skipped with a
+    /// warning
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let mut repertoire = Self::default();
+        for (i, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let [placement, turn, castling, en_passant, mv, count] = fields[..] else {
+                if !fields.is_empty() {
+                    tracing::warn!("{:?} line {}: expected 6 fields", path, i + 1);
+                }
+                continue;
+            };
+            let Ok(count) = count.parse() else {
+                tracing::warn!("{:?} line {}: invalid count {:?}", path, i + 1, count);
+                continue;
+            };
+            let key = [placement, turn, castling, en_passant].join(" ");
+            repertoire
+                .positions
+                .entry(key)
+                .or_default()
+                .push((mv.to_string(), count));
+        }
+        Ok(repertoire)
+    }
+
+    /// Writes the repertoire to `path`, the positions sorted so the file diffs well
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut positions = self.positions.iter().collect::<Vec<_>>();
+        positions.sort();
+        let mut text = String::new();
+        for (position, moves) in positions {
+            for (mv, count) in moves {
+                let _ = writeln!(text, "{} {} {}", position, mv, count);
+            }
+        }
+        std::fs::write(path, text)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Records `mv` as played from the position on `board`
+    pub fn record(&mut self, board: &Board, mv: &Move) {
+        let moves = self.positions.entry(position_key(board)).or_default();
+        let text = mv.to_string();
+        match moves.iter_mut().find(|(m, _)| *m == text) {
+            Some((_, count)) => *count += 1,
+            None => moves.push((text, 1)),
+        }
+    }
+
+    /// Moves recorded from the position on `board` with their counts, those that aren't legal
+    /// there being left out
+    pub fn moves(&self, board: &Board) -> Vec<(Move, u32)> {
+        let Some(recorded) = self.positions.get(&position_key(board)) else {
+            return Vec::new();
+        };
+        board
+            .legal_moves()
+            .filter_map(|mv| {
+                let text = mv.to_string();
+                let (_, count) = recorded.iter().find(|(m, _)| *m == text)?;
+                Some((mv, *count))
+            })
+            .collect()
+    }
+}
+
+/// Picks one of `moves` proportionally to its count, `seed` being any random number
+fn pick(moves: &[(Move, u32)], seed: u64) -> Option<&Move> {
+    let total = moves.iter().map(|(_, count)| *count as u64).sum::<u64>();
+    if total == 0 {
+        return moves.first().map(|(mv, _)| mv);
+    }
+    let mut target = seed % total;
+    moves
+        .iter()
+        .find(|(_, count)| {
+            if target < *count as u64 {
+                return true;
+            }
+            target -= *count as u64;
+            false
+        })
+        .map(|(mv, _)| mv)
+}
+
+/// What a move played in the drill did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DrillMove {
+    /// one of the user's moves of the repertoire, the opponent replies next
+    Known,
+    /// a move the repertoire doesn't have here, with those it has
+    Deviation(Vec<Move>),
+}
+
+/// The opening played from the start over and over, the opponent's moves drawn from the
+/// repertoire by how often they were recorded and the user's checked against it
+#[derive(Debug, Clone)]
+pub struct RepertoireDrill {
+    repertoire: Repertoire,
+    /// side the user plays
+    pub color: PieceColor,
+    /// moves of the current line played so far
+    ply: usize,
+    /// the repertoire has no move for the side to move of the current line
+    finished: bool,
+    /// lines played to the end of the repertoire, and moves off it
+    pub lines: u32,
+    pub deviations: u32,
+}
+
+impl RepertoireDrill {
+    /// Drills `repertoire` with the user playing `color`, `None` when it is empty
+    pub fn new(repertoire: Repertoire, color: PieceColor) -> Option<Self> {
+        (!repertoire.is_empty()).then_some(Self {
+            repertoire,
+            color,
+            ply: 0,
+            finished: false,
+            lines: 0,
+            deviations: 0,
+        })
+    }
+
+    /// Moves of the current line played so far
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    /// Starts over from the first move, with the user playing `color`
+    pub fn restart(&mut self, color: PieceColor) {
+        self.color = color;
+        self.ply = 0;
+        self.finished = false;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Ends the line once the repertoire has no move for the side to move on `board`, returns
+    /// true when it just ended
+    pub fn finish(&mut self, board: &Board) -> bool {
+        if self.finished || !self.repertoire.moves(board).is_empty() {
+            return false;
+        }
+        self.finished = true;
+        self.lines += 1;
+        true
+    }
+
+    /// The opponent's move on `board`, when it is their turn, counted as played
+    pub fn reply(&mut self, board: &Board, seed: u64) -> Option<Move> {
+        if board.get_turn() == self.color {
+            return None;
+        }
+        let mv = pick(&self.repertoire.moves(board), seed)?.clone();
+        self.ply += 1;
+        Some(mv)
+    }
+
+    /// Checks the user's move `mv` played from the position on `board`
+    pub fn check(&mut self, board: &Board, mv: &Move) -> DrillMove {
+        let moves = self.repertoire.moves(board);
+        if moves.iter().any(|(m, _)| m == mv) {
+            self.ply += 1;
+            return DrillMove::Known;
+        }
+        self.deviations += 1;
+        DrillMove::Deviation(moves.into_iter().map(|(m, _)| m).collect())
+    }
+}
@@ -0,0 +1,245 @@
+//! Review of a finished game: every position is searched in the background and each move is
+//! graded by how much of its player's winning chances it gave away
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::Duration;
+
+use raylib::core::color::Color;
+
+use crate::board::{Board, Move, PieceColor};
+use crate::engine::{Params, SearchLimits, Searcher};
+
+/// Time spent searching each position of the game
+const REVIEW_TIME: Duration = Duration::from_millis(200);
+/// Scores past this many centipawns are as good as won, mates included
+const SCORE_CAP: i32 = 1500;
+
+/// How good a move was, compared with the engine's choice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveQuality {
+    /// the move the engine would have played
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl MoveQuality {
+    /// Grades a move giving away `drop` points of its player's winning chances, in percent
+    fn from_drop(best: bool, drop: f32) -> Self {
+        if best {
+            MoveQuality::Best
+        } else if drop >= 30.0 {
+            MoveQuality::Blunder
+        } else if drop >= 20.0 {
+            MoveQuality::Mistake
+        } else if drop >= 10.0 {
+            MoveQuality::Inaccuracy
+        } else {
+            MoveQuality::Good
+        }
+    }
+
+    /// Annotation symbol written after the move, none for a good move
+    pub fn glyph(self) -> &'static str {
+        match self {
+            MoveQuality::Best => "!",
+            MoveQuality::Good => "",
+            MoveQuality::Inaccuracy => "?!",
+            MoveQuality::Mistake => "?",
+            MoveQuality::Blunder => "??",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            MoveQuality::Best => Color::from_hex("5cb85c").expect("Error parsing hex"),
+            MoveQuality::Good => Color::LIGHTGRAY,
+            MoveQuality::Inaccuracy => Color::from_hex("e6c229").expect("Error parsing hex"),
+            MoveQuality::Mistake => Color::from_hex("e69529").expect("Error parsing hex"),
+            MoveQuality::Blunder => Color::from_hex("d9432f").expect("Error parsing hex"),
+        }
+    }
+}
+
+/// Winning chances of white in percent for a score seen by white, as fitted on rated games
+fn win_percent(score: i32) -> f32 {
+    let cp = score.clamp(-SCORE_CAP, SCORE_CAP) as f32;
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.003_682_08 * cp).exp()) - 1.0)
+}
+
+/// Accuracy in percent of a move giving away `drop` points of winning chances
+fn move_accuracy(drop: f32) -> f32 {
+    (103.1668 * (-0.04354 * drop).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+/// What the search found in a position of the game
+#[derive(Debug, Clone)]
+struct Evaluation {
+    /// score seen by white
+    score: i32,
+    best: Option<Move>,
+}
+
+/// Evaluation of the position after `ply` moves of the game, sent by the review thread
+type ReviewMessage = (usize, i32, Option<Move>);
+
+/// The grades of the moves of a game, filled in as the positions get searched
+pub struct Review {
+    start: Board,
+    moves: Vec<Move>,
+    /// one per position, from the start position to the one after the last move
+    evaluations: Vec<Option<Evaluation>>,
+    rx: Receiver<ReviewMessage>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Review {
+    /// Starts searching each position of the game played from `start` through `moves`
+    pub fn spawn(start: Board, moves: Vec<Move>, threads: usize, params: Params) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let (board, history) = (start.clone(), moves.clone());
+
+        std::thread::spawn(move || {
+            let mut searcher = Searcher::new(thread_stop.clone(), params);
+            let mut board = board;
+            for ply in 0..=history.len() {
+                if thread_stop.load(Ordering::Relaxed) {
+                    return;
+                }
+                let (score, best) = evaluate(&mut searcher, &board, threads);
+                let white_score = match board.get_turn() {
+                    PieceColor::White => score,
+                    PieceColor::Black => -score,
+                };
+                if tx.send((ply, white_score, best)).is_err() {
+                    return;
+                }
+                if history.get(ply).is_some_and(|mv| board.play(mv).is_err()) {
+                    return;
+                }
+            }
+        });
+
+        Self {
+            evaluations: vec![None; moves.len() + 1],
+            start,
+            moves,
+            rx,
+            stop,
+        }
+    }
+
+    /// Stores the evaluations found since the last call, returns true if there were any
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        loop {
+            match self.rx.try_recv() {
+                Ok((ply, score, best)) => {
+                    self.evaluations[ply] = Some(Evaluation { score, best });
+                    changed = true;
+                }
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => return changed,
+            }
+        }
+    }
+
+    /// Positions searched and positions in the game
+    pub fn progress(&self) -> (usize, usize) {
+        let done = self.evaluations.iter().filter(|e| e.is_some()).count();
+        (done, self.evaluations.len())
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.evaluations.iter().all(|e| e.is_some())
+    }
+
+    /// Returns true if this is the review of the game played from `start` through `moves`
+    pub fn is_of(&self, start: &Board, moves: &[Move]) -> bool {
+        self.start.zobrist_hash() == start.zobrist_hash() && self.moves == moves
+    }
+
+    /// Winning chances the move at `ply` gave away, from its player's point of view, and
+    /// whether it was the engine's choice
+    fn loss(&self, ply: usize) -> Option<(f32, bool)> {
+        let before = self.evaluations.get(ply)?.as_ref()?;
+        let after = self.evaluations.get(ply + 1)?.as_ref()?;
+        let drop = win_percent(before.score) - win_percent(after.score);
+        let drop = match self.mover(ply) {
+            PieceColor::White => drop,
+            PieceColor::Black => -drop,
+        };
+        Some((
+            drop.max(0.0),
+            before.best.as_ref() == Some(&self.moves[ply]),
+        ))
+    }
+
+    fn mover(&self, ply: usize) -> PieceColor {
+        if ply.is_multiple_of(2) {
+            self.start.get_turn()
+        } else {
+            self.start.get_turn().opposite()
+        }
+    }
+
+    /// White's winning chances between 0 and 1 in each position, none until it is searched
+    pub fn win_chances(&self) -> Vec<Option<f32>> {
+        self.evaluations
+            .iter()
+            .map(|e| e.as_ref().map(|e| win_percent(e.score) / 100.0))
+            .collect()
+    }
+
+    /// Grade of each move, none until both positions around it are searched
+    pub fn qualities(&self) -> Vec<Option<MoveQuality>> {
+        (0..self.moves.len())
+            .map(|ply| {
+                self.loss(ply)
+                    .map(|(drop, best)| MoveQuality::from_drop(best, drop))
+            })
+            .collect()
+    }
+
+    /// Average accuracy in percent of the moves of `color` searched so far
+    pub fn accuracy(&self, color: PieceColor) -> Option<f32> {
+        let accuracies = (0..self.moves.len())
+            .filter(|&ply| self.mover(ply) == color)
+            .filter_map(|ply| self.loss(ply))
+            .map(|(drop, _)| move_accuracy(drop))
+            .collect::<Vec<_>>();
+        (!accuracies.is_empty()).then(|| accuracies.iter().sum::<f32>() / accuracies.len() as f32)
+    }
+}
+
+impl Drop for Review {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Score of `board` from the side to move's point of view, and the best move. Positions
+/// without a legal move are scored without searching
+fn evaluate(searcher: &mut Searcher, board: &Board, threads: usize) -> (i32, Option<Move>) {
+    if board.legal_moves().next().is_none() {
+        let score = if board.is_in_check(board.get_turn()) {
+            -SCORE_CAP
+        } else {
+            0
+        };
+        return (score, None);
+    }
+    let mut score = 0;
+    let limits = SearchLimits::movetime(REVIEW_TIME).with_threads(threads);
+    let best = searcher.search(board, limits, |info| {
+        if info.multipv <= 1 {
+            score = info.score;
+        }
+    });
+    (score, best)
+}
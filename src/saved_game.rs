@@ -0,0 +1,167 @@
+//! The game left unfinished when the window closed, offered back by Continue in the main menu.
+//! It is written as PGN, with the state a PGN doesn't hold in tags of its own:
+//!
+//! ```text
+//! [RucheTimeControl "300 3 0"]
+//! [RucheClock "287.4 301.2"]
+//! [RucheEngine "black"]
+//! ```
+//!
+//! the base time, increment and delay of the time control, the seconds left to white and black
+//! and the side the engine plays
+
+use std::fmt::Display;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::board::{Board, PieceColor};
+use crate::lifecycle::TimeControl;
+use crate::pgn::{self, PgnError};
+
+/// Why a saved game couldn't be read back
+#[derive(Debug)]
+pub enum SavedGameError {
+    Io(std::io::Error),
+    Pgn(PgnError),
+    /// A tag of the state holding something else
+    InvalidTag(&'static str, String),
+}
+
+impl Display for SavedGameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SavedGameError::Io(e) => write!(f, "{}", e),
+            SavedGameError::Pgn(e) => write!(f, "{}", e),
+            SavedGameError::InvalidTag(tag, value) => write!(f, "invalid {} tag {:?}", tag, value),
+        }
+    }
+}
+
+impl From<std::io::Error> for SavedGameError {
+    fn from(e: std::io::Error) -> Self {
+        SavedGameError::Io(e)
+    }
+}
+
+impl From<PgnError> for SavedGameError {
+    fn from(e: PgnError) -> Self {
+        SavedGameError::Pgn(e)
+    }
+}
+
+/// Everything needed to pick a game up where it was left
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SavedGame {
+    /// position the game started from
+    pub start: Board,
+    /// the position reached, holding the moves played in its history
+    pub board: Board,
+    /// names typed at the game setup, white's then black's
+    pub names: (String, String),
+    pub time_control: Option<TimeControl>,
+    /// time left to white and black
+    pub clock: Option<(Duration, Duration)>,
+    /// side the engine plays
+    pub engine_color: Option<PieceColor>,
+}
+
+fn color_name(color: PieceColor) -> &'static str {
+    match color {
+        PieceColor::White => "white",
+        PieceColor::Black => "black",
+    }
+}
+
+impl SavedGame {
+    pub fn to_pgn(&self) -> String {
+        let mut tags = vec![
+            ("Event", "ruche game".to_string()),
+            ("Site", "?".to_string()),
+            ("Date", pgn::today()),
+            ("Round", "-".to_string()),
+            ("White", self.names.0.clone()),
+            ("Black", self.names.1.clone()),
+            ("Result", "*".to_string()),
+        ];
+        if let Some(tc) = self.time_control {
+            let time_control = format!(
+                "{} {} {}",
+                tc.base.as_secs(),
+                tc.increment.as_secs(),
+                tc.delay.as_secs()
+            );
+            tags.push(("RucheTimeControl", time_control));
+        }
+        if let Some((white, black)) = self.clock {
+            let clock = format!("{:.1} {:.1}", white.as_secs_f32(), black.as_secs_f32());
+            tags.push(("RucheClock", clock));
+        }
+        if let Some(color) = self.engine_color {
+            tags.push(("RucheEngine", color_name(color).to_string()));
+        }
+        pgn::write_game(&tags, &self.start, self.board.get_move_history(), "*")
+    }
+
+    /// Reads back a game written by [SavedGame::to_pgn]
+    pub fn parse(text: &str) -> Result<Self, SavedGameError> {
+        let game = pgn::parse_games(text)
+            .into_iter()
+            .next()
+            .ok_or(PgnError::NoGame)?;
+        let numbers = |tag: &'static str, count: usize| -> Result<Option<Vec<f32>>, _> {
+            let Some(value) = game.tag(tag) else {
+                return Ok(None);
+            };
+            let numbers = value
+                .split_whitespace()
+                .map(|n| n.parse::<f32>().ok().filter(|n| *n >= 0.0))
+                .collect::<Option<Vec<_>>>()
+                .filter(|numbers| numbers.len() == count);
+            numbers
+                .map(Some)
+                .ok_or_else(|| SavedGameError::InvalidTag(tag, value.to_string()))
+        };
+
+        let time_control = numbers("RucheTimeControl", 3)?
+            .map(|n| TimeControl::new(n[0] as u64, n[1] as u64, n[2] as u64));
+        let clock = numbers("RucheClock", 2)?
+            .map(|n| (Duration::from_secs_f32(n[0]), Duration::from_secs_f32(n[1])));
+        let engine_color = match game.tag("RucheEngine") {
+            None => None,
+            Some("white") => Some(PieceColor::White),
+            Some("black") => Some(PieceColor::Black),
+            Some(other) => {
+                return Err(SavedGameError::InvalidTag("RucheEngine", other.to_string()))
+            }
+        };
+        let name = |tag| game.tag(tag).unwrap_or_default().to_string();
+        Ok(Self {
+            start: game.start()?,
+            board: game.replay()?,
+            names: (name("White"), name("Black")),
+            time_control,
+            clock,
+            engine_color,
+        })
+    }
+
+    pub fn write(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, self.to_pgn())
+    }
+
+    /// Reads the game saved at `path` and removes the file, a game being resumed only once.
+    /// None when there is no saved game
+    pub fn take(path: &Path) -> Result<Option<Self>, SavedGameError> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        std::fs::remove_file(path)?;
+        Self::parse(&text).map(Some)
+    }
+}
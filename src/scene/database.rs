@@ -0,0 +1,266 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{Replay, Scene, Transition};
+use crate::explorer;
+use crate::game::Game;
+use crate::input;
+use crate::pgn::{self, PgnGame};
+use crate::widgets::BoardRect;
+
+/// Height of a row of the games list
+const ROW_HEIGHT: i32 = 22;
+/// Space above the first game, for the title and the fields
+const HEADER_HEIGHT: i32 = 130;
+/// Games skipped by Page Up and Page Down
+const PAGE: usize = 10;
+
+/// Text field of the browser, Tab moves between them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// the PGN file listed
+    File,
+    /// part of the name of either player
+    Player,
+    /// start of the ECO code, `B` or `B90` for instance
+    Eco,
+}
+
+/// Lists the games of a PGN file, by default the game database of the explorer, to open one in
+/// the replay or on the analysis board
+pub struct GameBrowser {
+    /// file typed, read again on Enter
+    path: String,
+    games: Vec<PgnGame>,
+    player: String,
+    eco: String,
+    field: Field,
+    /// indices in `games` of the games matching the filters
+    shown: Vec<usize>,
+    /// index in `shown` of the selected game
+    selected: usize,
+    /// why the file couldn't be read or the game opened
+    error: Option<String>,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl GameBrowser {
+    pub fn new(back: Box<Scene>) -> Self {
+        let mut browser = Self {
+            path: explorer::database_path().display().to_string(),
+            games: Vec::new(),
+            player: String::new(),
+            eco: String::new(),
+            field: Field::Player,
+            shown: Vec::new(),
+            selected: 0,
+            error: None,
+            back,
+        };
+        browser.load();
+        browser
+    }
+
+    /// Reads the games of the file typed
+    fn load(&mut self) {
+        self.error = None;
+        self.games = match std::fs::read_to_string(self.path.trim()) {
+            Ok(text) => pgn::parse_games(&text),
+            Err(e) => {
+                self.error = Some(format!("Failed to read {}: {}", self.path.trim(), e));
+                Vec::new()
+            }
+        };
+        self.filter();
+    }
+
+    /// Lists the games one of whose players has a name containing the player filter and whose
+    /// `ECO` tag starts with the ECO filter, ignoring case
+    fn filter(&mut self) {
+        let player = self.player.trim().to_lowercase();
+        let eco = self.eco.trim().to_lowercase();
+        let matches = |game: &PgnGame| {
+            let named = |tag| {
+                game.tag(tag)
+                    .is_some_and(|name| name.to_lowercase().contains(&player))
+            };
+            let eco_matches = game
+                .tag("ECO")
+                .is_some_and(|code| code.to_lowercase().starts_with(&eco));
+            (player.is_empty() || named("White") || named("Black"))
+                && (eco.is_empty() || eco_matches)
+        };
+        self.shown = (0..self.games.len())
+            .filter(|i| matches(&self.games[*i]))
+            .collect();
+        self.selected = 0;
+    }
+
+    fn field_text(&mut self) -> &mut String {
+        match self.field {
+            Field::File => &mut self.path,
+            Field::Player => &mut self.player,
+            Field::Eco => &mut self.eco,
+        }
+    }
+
+    /// Loads the selected game on the board, in the replay or on the analysis board
+    fn open(&mut self, game: &mut Game, analysis: bool) -> Transition {
+        let Some(selected) = self.shown.get(self.selected).map(|i| &self.games[*i]) else {
+            return Transition::Stay;
+        };
+        if let Err(e) = game.load_pgn_game(selected) {
+            self.error = Some(format!("Failed to read the game: {}", e));
+            return Transition::Stay;
+        }
+        if analysis {
+            game.set_free_move(true);
+            return Transition::Switch(Scene::Analysis);
+        }
+        if !game.start_replay() {
+            return Transition::Switch(Scene::board(game));
+        }
+        // Escape in the replay comes back to the list
+        let back = std::mem::replace(&mut *self.back, Scene::Playing);
+        let browser = Self {
+            path: std::mem::take(&mut self.path),
+            games: std::mem::take(&mut self.games),
+            player: std::mem::take(&mut self.player),
+            eco: std::mem::take(&mut self.eco),
+            field: self.field,
+            shown: std::mem::take(&mut self.shown),
+            selected: self.selected,
+            error: None,
+            back: Box::new(back),
+        };
+        let browser = Box::new(Scene::Database(Box::new(browser)));
+        Transition::Switch(Scene::Replay(Replay::new(browser)))
+    }
+
+    /// Typing edits the field, Tab moves to the next one and Up/Down or Page Up/Page Down
+    /// select a game. Enter reads the file again on the file field, else replays the selected
+    /// game, Shift+Enter opens it on the analysis board. Escape goes back
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            self.field = match self.field {
+                Field::File => Field::Player,
+                Field::Player => Field::Eco,
+                Field::Eco => Field::File,
+            };
+        }
+
+        let before = (self.player.clone(), self.eco.clone());
+        for c in input::typed_chars(rl) {
+            if !c.is_control() {
+                self.field_text().push(c);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            self.field_text().pop();
+        }
+        if (self.player.clone(), self.eco.clone()) != before {
+            self.filter();
+        }
+
+        let last = self.shown.len().saturating_sub(1);
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1).min(last);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_PAGE_DOWN) {
+            self.selected = (self.selected + PAGE).min(last);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_PAGE_UP) {
+            self.selected = self.selected.saturating_sub(PAGE);
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            if self.field == Field::File {
+                self.load();
+                return Transition::Stay;
+            }
+            let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+            return self.open(game, shift);
+        }
+        Transition::Stay
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.95));
+        let title = format!(
+            "Game database ({} of {})",
+            self.shown.len(),
+            self.games.len()
+        );
+        d.draw_text(&title, rect.x + 10, rect.y + 10, 20, Color::WHITE);
+
+        let fields = [
+            (Field::File, "File", &self.path),
+            (Field::Player, "Player", &self.player),
+            (Field::Eco, "ECO", &self.eco),
+        ];
+        for (i, (field, label, text)) in fields.into_iter().enumerate() {
+            let y = rect.y + 40 + i as i32 * 22;
+            let (color, cursor) = if field == self.field {
+                (highlight, "_")
+            } else {
+                (Color::LIGHTGRAY, "")
+            };
+            d.draw_text(label, rect.x + 10, y, 16, color);
+            d.draw_text(
+                &format!("{}{}", text, cursor),
+                rect.x + 70,
+                y,
+                16,
+                Color::WHITE,
+            );
+        }
+        let hint = match (&self.error, self.field) {
+            (Some(error), _) => error.as_str(),
+            (None, Field::File) => "Enter to read the file, Tab for the filters, Esc to go back",
+            (None, _) => "Enter to replay, Shift+Enter to analyse, Tab to change field",
+        };
+        let color = if self.error.is_some() {
+            Color::RED
+        } else {
+            Color::GRAY
+        };
+        d.draw_text(hint, rect.x + 10, rect.y + 108, 14, color);
+
+        let rows = ((rect.size - HEADER_HEIGHT) / ROW_HEIGHT).max(1) as usize;
+        let first = (self.selected + 1).saturating_sub(rows);
+        for (row, &i) in self.shown.iter().skip(first).take(rows).enumerate() {
+            let game = &self.games[i];
+            let y = rect.y + HEADER_HEIGHT + row as i32 * ROW_HEIGHT;
+            if first + row == self.selected {
+                d.draw_rectangle(rect.x + 5, y - 3, rect.size - 10, ROW_HEIGHT, highlight);
+            }
+            let tag = |name| game.tag(name).unwrap_or("?");
+            let players = format!("{} - {}", tag("White"), tag("Black"));
+            d.draw_text(&players, rect.x + 10, y, 16, Color::WHITE);
+            let details = format!("{}  {}  {}", game.result, tag("Date"), tag("ECO"));
+            let width = raylib::core::text::measure_text(&details, 16);
+            d.draw_text(
+                &details,
+                rect.x + rect.size - width - 10,
+                y,
+                16,
+                Color::LIGHTGRAY,
+            );
+        }
+    }
+}
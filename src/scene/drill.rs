@@ -0,0 +1,20 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::RaylibHandle;
+
+use super::{playing, Transition};
+use crate::game::Game;
+
+/// The board plays the current line of the repertoire, N starts the next one, C starts over
+/// playing the other side and Escape opens the main menu
+pub fn update(rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+    // a line can end in mate, the next one follows instead of the dialog
+    game.take_game_over();
+
+    if rl.is_key_pressed(KeyboardKey::KEY_N) {
+        game.next_drill_line(false);
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_C) {
+        game.next_drill_line(true);
+    }
+    playing::board_input(rl, game)
+}
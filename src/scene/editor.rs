@@ -0,0 +1,140 @@
+use raylib::consts::{KeyboardKey, MouseButton};
+use raylib::core::drawing::RaylibDraw;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::board::{Board, BoardBuilder, CastlingRights, Piece, START_FEN};
+use crate::game::Game;
+use crate::widgets::EditorPanel;
+
+/// Sets up a position piece by piece before playing or analyzing it
+pub struct Editor {
+    builder: BoardBuilder,
+    /// piece a click on the board puts down, none to erase
+    brush: Piece,
+    /// why the position can't be played, if it can't
+    problem: Option<String>,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl Editor {
+    /// Starts from the position on the board
+    pub fn new(game: &Game, back: Box<Scene>) -> Self {
+        let mut editor = Self {
+            builder: game.position_builder(),
+            brush: Piece::new_none(),
+            problem: None,
+            back,
+        };
+        editor.check();
+        editor
+    }
+
+    fn check(&mut self) {
+        self.problem = self.builder.clone().build().err().map(|e| e.to_string());
+    }
+
+    /// Applies `edit` to the position and checks it again
+    fn edit(&mut self, edit: impl FnOnce(BoardBuilder) -> BoardBuilder) {
+        self.builder = edit(self.builder.clone());
+        self.check();
+    }
+
+    /// A click on the panel picks the brush, on the board puts it down or takes back the piece
+    /// it already put there; a right click empties the square. W flips the side to move, 1-4
+    /// the castling rights, E the en passant square under the cursor, C clears the board and S
+    /// sets up the start position. Enter plays the position, A analyzes it
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+
+        let mouse = rl.get_mouse_position();
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+            if let Some(piece) =
+                EditorPanel::brush_at(game.board_rect(), mouse.x as i32, mouse.y as i32)
+            {
+                self.brush = piece;
+            } else if let Some(idx) = game.cursor_square() {
+                let piece = if self.builder.pieces()[idx] == self.brush {
+                    Piece::new_none()
+                } else {
+                    self.brush
+                };
+                self.edit(|b| b.piece(idx, piece));
+            }
+        }
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_RIGHT_BUTTON) {
+            if let Some(idx) = game.cursor_square() {
+                self.edit(|b| b.piece(idx, Piece::new_none()));
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_W) {
+            let turn = self.builder.turn().opposite();
+            self.edit(|b| b.side_to_move(turn));
+        }
+        let rights = self.builder.castling_rights();
+        let toggled = [
+            KeyboardKey::KEY_ONE,
+            KeyboardKey::KEY_TWO,
+            KeyboardKey::KEY_THREE,
+            KeyboardKey::KEY_FOUR,
+        ]
+        .into_iter()
+        .position(|key| rl.is_key_pressed(key))
+        .map(|right| CastlingRights {
+            white_king_side: rights.white_king_side ^ (right == 0),
+            white_queen_side: rights.white_queen_side ^ (right == 1),
+            black_king_side: rights.black_king_side ^ (right == 2),
+            black_queen_side: rights.black_queen_side ^ (right == 3),
+        });
+        if let Some(rights) = toggled {
+            self.edit(|b| b.castling(rights));
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_E) {
+            if let Some(idx) = game.cursor_square() {
+                let square = (self.builder.en_passant_square() != Some(idx)).then_some(idx);
+                self.edit(|b| b.en_passant(square));
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_C) {
+            self.edit(|_| BoardBuilder::new());
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_S) {
+            let mut board = Board::new();
+            board.load_position(START_FEN.to_string());
+            self.edit(|_| BoardBuilder::from_board(&board));
+        }
+
+        let analysis = rl.is_key_pressed(KeyboardKey::KEY_A);
+        if !analysis && !rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return Transition::Stay;
+        }
+        // the panel already says what keeps an invalid position from being played
+        match self.builder.clone().build() {
+            Ok(board) => {
+                game.set_free_move(analysis);
+                game.start_game(&board.to_fen());
+                Transition::Switch(Scene::board(game))
+            }
+            Err(_) => Transition::Stay,
+        }
+    }
+
+    pub fn draw<T>(&self, d: &mut T, game: &Game)
+    where
+        T: RaylibDraw,
+    {
+        game.draw_position(d, &self.builder);
+        EditorPanel::draw(
+            d,
+            game.board_rect(),
+            game.piece_textures(),
+            (&self.builder, self.brush),
+            self.problem.as_deref(),
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::PathBuf;
+
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::export::ExportFormat;
+use crate::game::Game;
+use crate::input;
+use crate::widgets::BoardRect;
+
+/// Height of the path box
+const PROMPT_HEIGHT: i32 = 52;
+
+/// Asks where to save a picture of the board, offering a file in the working directory
+pub struct ExportPrompt {
+    format: ExportFormat,
+    path: String,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl ExportPrompt {
+    pub fn new(format: ExportFormat, back: Box<Scene>) -> Self {
+        Self {
+            format,
+            path: format.default_path(),
+            back,
+        }
+    }
+
+    /// Typing edits the path, Enter saves and Escape goes back without saving
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        for c in input::typed_chars(rl) {
+            if !c.is_control() {
+                self.path.push(c);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            self.path.pop();
+        }
+
+        let save = rl.is_key_pressed(KeyboardKey::KEY_ENTER) && !self.path.trim().is_empty();
+        if !save && !rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            return Transition::Stay;
+        }
+        if save {
+            game.request_export(self.format, PathBuf::from(self.path.trim()));
+        }
+        let back = std::mem::replace(&mut *self.back, Scene::Playing);
+        Transition::Switch(back)
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let y = rect.y + rect.size - PROMPT_HEIGHT;
+        d.draw_rectangle(rect.x, y, rect.size, PROMPT_HEIGHT, background.fade(0.9));
+        let prompt = format!("Save {} to: {}_", self.format.name(), self.path);
+        d.draw_text(&prompt, rect.x + 10, y + 7, 18, Color::WHITE);
+        d.draw_text(
+            "Enter to save, Esc to cancel",
+            rect.x + 10,
+            y + 30,
+            16,
+            Color::GRAY,
+        );
+    }
+}
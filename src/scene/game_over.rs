@@ -0,0 +1,36 @@
+use raylib::consts::{KeyboardKey, MouseButton};
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::game::Game;
+use crate::widgets::{GameOverAction, GameOverDialog};
+
+/// A click on a button starts a new game, offers a rematch or copies the PGN, Escape closes the
+/// dialog to look at the final position
+pub fn update(dialog: &mut GameOverDialog, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+    if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+        return Transition::Switch(Scene::board(game));
+    }
+    if !rl.is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON) {
+        return Transition::Stay;
+    }
+    let mouse = rl.get_mouse_position();
+    match dialog.button_at(game.board_rect(), mouse.x as i32, mouse.y as i32) {
+        Some(GameOverAction::NewGame) => {
+            game.new_game();
+            Transition::Switch(Scene::Playing)
+        }
+        Some(GameOverAction::Rematch) => {
+            game.offer_rematch();
+            Transition::Switch(Scene::Playing)
+        }
+        Some(GameOverAction::CopyPgn) => {
+            match rl.set_clipboard_text(&game.to_pgn()) {
+                Ok(()) => game.show_notice("PGN copied"),
+                Err(e) => tracing::error!("Failed to copy the PGN: {}", e),
+            }
+            Transition::Stay
+        }
+        None => Transition::Stay,
+    }
+}
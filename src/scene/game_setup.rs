@@ -0,0 +1,192 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::board::{Odds, Variant, START_FEN};
+use crate::clock::PRESETS;
+use crate::game::Game;
+use crate::input;
+use crate::widgets::{BoardRect, ClockSetup};
+
+/// Longest name a player can type
+const MAX_NAME_LEN: usize = 24;
+
+/// Names of the two players at the board, typed once the time control is picked
+struct NameEntry {
+    /// white's and black's
+    names: [String; 2],
+    /// field being typed in, 0 for white
+    field: usize,
+}
+
+/// Picks the time control of a new game, and who plays it when started from the main menu
+pub struct GameSetup {
+    clock: ClockSetup,
+    /// plays the engine, or both sides here; `None` keeps the players of the last game
+    vs_engine: Option<bool>,
+    /// open after the time control of a game between two players here
+    names: Option<NameEntry>,
+    /// rules of the game, standard chess unless V picks another
+    variant: Variant,
+    /// handicap O picks, for the variants starting from the standard position
+    odds: Odds,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl GameSetup {
+    pub fn new(vs_engine: Option<bool>, back: Box<Scene>) -> Self {
+        Self {
+            clock: ClockSetup::default(),
+            vs_engine,
+            names: None,
+            variant: Variant::Standard,
+            odds: Odds::None,
+            back,
+        }
+    }
+
+    /// Typing edits the name of the selected player, Tab/Up/Down select the other one, Enter
+    /// goes on to black's name then starts the game and Escape goes back to the time controls
+    fn update_names(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        let Some(entry) = &mut self.names else {
+            return Transition::Stay;
+        };
+        for c in input::typed_chars(rl) {
+            let name = &mut entry.names[entry.field];
+            if !c.is_control() && name.chars().count() < MAX_NAME_LEN {
+                name.push(c);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            entry.names[entry.field].pop();
+        }
+        if [
+            KeyboardKey::KEY_TAB,
+            KeyboardKey::KEY_UP,
+            KeyboardKey::KEY_DOWN,
+        ]
+        .into_iter()
+        .any(|key| rl.is_key_pressed(key))
+        {
+            entry.field = 1 - entry.field;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.names = None;
+            return Transition::Stay;
+        }
+        if !rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return Transition::Stay;
+        }
+        if entry.field == 0 {
+            entry.field = 1;
+            return Transition::Stay;
+        }
+        game.set_player_names(&entry.names[0], &entry.names[1]);
+        self.start(game)
+    }
+
+    /// Starts the game with the time control picked
+    fn start(&mut self, game: &mut Game) -> Transition {
+        game.set_free_move(false);
+        game.start_new_game(self.clock.selected(), self.variant, self.odds);
+        if self
+            .vs_engine
+            .is_some_and(|v| v != game.is_engine_playing())
+        {
+            game.toggle_engine();
+        }
+        Transition::Switch(Scene::Playing)
+    }
+
+    /// Up/Down pick the time control, Left/Right the opponent, V the variant, O the odds and Enter
+    /// starts the game, after asking the names of two players at the board
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if self.names.is_some() {
+            return self.update_names(rl, game);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.clock.select_next();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.clock.select_previous();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) || rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            self.vs_engine = self.vs_engine.map(|v| !v);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_V) {
+            self.variant = self.variant.next();
+            if self.variant.start_fen() != START_FEN {
+                self.odds = Odds::None;
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_O) && self.variant.start_fen() == START_FEN {
+            self.odds = self.odds.next();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        if !rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return Transition::Stay;
+        }
+        if self.vs_engine == Some(false) {
+            let (white, black) = game.player_names();
+            self.names = Some(NameEntry {
+                names: [white.clone(), black.clone()],
+                field: 0,
+            });
+            return Transition::Stay;
+        }
+        self.start(game)
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        self.clock.draw(d, rect);
+        if let Some(entry) = &self.names {
+            let background = Color::from_hex("303030").expect("Error parsing hex");
+            let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+            d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background);
+            d.draw_text(
+                "Players (Tab to switch, Enter, Esc to go back)",
+                rect.x + 10,
+                rect.y + 10,
+                16,
+                Color::WHITE,
+            );
+            for (i, (side, name)) in ["White", "Black"].iter().zip(&entry.names).enumerate() {
+                let y = rect.y + 40 + i as i32 * 28;
+                if i == entry.field {
+                    d.draw_rectangle(rect.x + 5, y - 4, rect.size - 10, 26, highlight);
+                }
+                let cursor = if i == entry.field { "_" } else { "" };
+                let text = format!("{}: {}{}", side, name, cursor);
+                d.draw_text(&text, rect.x + 10, y, 18, Color::WHITE);
+            }
+            return;
+        }
+        let mut y = rect.y + 40 + PRESETS.len() as i32 * 28 + 12;
+        if let Some(vs_engine) = self.vs_engine {
+            let opponent = if vs_engine { "ruche" } else { "Two players" };
+            d.draw_text("Opponent", rect.x + 10, y, 18, Color::WHITE);
+            let value = format!("< {} >", opponent);
+            let width = raylib::core::text::measure_text(&value, 18);
+            d.draw_text(&value, rect.x + rect.size - 15 - width, y, 18, Color::WHITE);
+            y += 28;
+        }
+        let odds = self.odds.to_string();
+        for (label, value) in [
+            ("Variant (V)", self.variant.to_string()),
+            ("Odds (O)", odds),
+        ] {
+            d.draw_text(label, rect.x + 10, y, 18, Color::WHITE);
+            let width = raylib::core::text::measure_text(&value, 18);
+            d.draw_text(&value, rect.x + rect.size - 15 - width, y, 18, Color::WHITE);
+            y += 28;
+        }
+    }
+}
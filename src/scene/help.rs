@@ -0,0 +1,35 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::game::Game;
+use crate::input::Action;
+use crate::widgets::{draw_help, BoardRect};
+
+/// The keyboard shortcuts listed over the board
+pub struct HelpScreen {
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl HelpScreen {
+    pub fn new(back: Box<Scene>) -> Self {
+        Self { back }
+    }
+
+    /// The help key or Escape close the list
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) || game.input.pressed(rl, Action::Help) {
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        Transition::Stay
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect, game: &Game)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        draw_help(d, rect, &game.input);
+    }
+}
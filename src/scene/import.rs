@@ -0,0 +1,147 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{Replay, Scene, Transition};
+use crate::game::Game;
+use crate::import::{self, ImportError, Imported};
+use crate::input;
+use crate::widgets::BoardRect;
+
+/// Height of the URL box
+const PROMPT_HEIGHT: i32 = 76;
+
+/// Asks for the URL or ID of a lichess or chess.com game or puzzle, then opens it in the
+/// replay or the puzzle trainer
+pub struct ImportDialog {
+    url: String,
+    /// answer of the request on its way, fetched on a thread so the window keeps drawing
+    fetching: Option<Receiver<Result<Imported, ImportError>>>,
+    /// why the last import failed
+    error: Option<String>,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl ImportDialog {
+    pub fn new(back: Box<Scene>) -> Self {
+        Self {
+            url: String::new(),
+            fetching: None,
+            error: None,
+            back,
+        }
+    }
+
+    /// Starts fetching what the URL points to
+    fn fetch(&mut self) {
+        self.error = None;
+        let source = match import::parse_source(&self.url) {
+            Ok(source) => source,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return;
+            }
+        };
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            let _ = tx.send(import::fetch(&source));
+        });
+        self.fetching = Some(rx);
+    }
+
+    /// Opens what was fetched once it arrives
+    fn poll(&mut self, game: &mut Game) -> Transition {
+        let Some(fetching) = &self.fetching else {
+            return Transition::Stay;
+        };
+        let imported = match fetching.try_recv() {
+            Ok(imported) => imported,
+            Err(TryRecvError::Empty) => return Transition::Stay,
+            Err(TryRecvError::Disconnected) => Err(ImportError::Http("no answer".to_string())),
+        };
+        self.fetching = None;
+        match imported {
+            Ok(Imported::Game(pgn)) => {
+                if let Err(e) = game.load_pgn(&pgn) {
+                    self.error = Some(format!("Failed to read the game: {}", e));
+                    return Transition::Stay;
+                }
+                if !game.start_replay() {
+                    return Transition::Switch(Scene::board(game));
+                }
+                let back = std::mem::replace(&mut *self.back, Scene::Playing);
+                Transition::Switch(Scene::Replay(Replay::new(Box::new(back))))
+            }
+            Ok(Imported::Puzzle(puzzle)) => {
+                game.start_puzzle(puzzle);
+                Transition::Switch(Scene::Puzzles)
+            }
+            Err(e) => {
+                self.error = Some(e.to_string());
+                Transition::Stay
+            }
+        }
+    }
+
+    /// Typing or ctrl+V edits the URL, Enter fetches it and Escape goes back, leaving the
+    /// answer of a request already sent unread
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        if self.fetching.is_some() {
+            return self.poll(game);
+        }
+
+        for c in input::typed_chars(rl) {
+            if !c.is_control() {
+                self.url.push(c);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            self.url.pop();
+        }
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_V) {
+            if let Ok(text) = rl.get_clipboard_text() {
+                self.url.push_str(text.trim());
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) && !self.url.trim().is_empty() {
+            self.fetch();
+        }
+        Transition::Stay
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let y = rect.y + rect.size - PROMPT_HEIGHT;
+        d.draw_rectangle(rect.x, y, rect.size, PROMPT_HEIGHT, background.fade(0.9));
+        let prompt = format!("Import from URL: {}_", self.url);
+        d.draw_text(&prompt, rect.x + 10, y + 7, 18, Color::WHITE);
+        let (hint, color) = match (&self.fetching, &self.error) {
+            (Some(_), _) => ("Fetching...", Color::WHITE),
+            (None, Some(error)) => (error.as_str(), Color::RED),
+            (None, None) => (
+                "A lichess game or puzzle, or the chess.com daily puzzle",
+                Color::GRAY,
+            ),
+        };
+        d.draw_text(hint, rect.x + 10, y + 30, 16, color);
+        d.draw_text(
+            "Enter to import, ctrl+V to paste, Esc to cancel",
+            rect.x + 10,
+            y + 52,
+            16,
+            Color::GRAY,
+        );
+    }
+}
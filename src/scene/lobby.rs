@@ -0,0 +1,233 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::board::{PieceColor, START_FEN};
+use crate::clock::PRESETS;
+use crate::game::Game;
+use crate::input;
+use crate::net::{Link, LinkEvent, NetMessage, DEFAULT_PORT};
+use crate::widgets::BoardRect;
+
+/// Longest address or port that can be typed
+const MAX_ADDRESS_LEN: usize = 48;
+/// Rows of the lobby: hosting or joining, the port or address, the time control
+const ROWS: usize = 3;
+
+/// Sets up a game over the local network: one player hosts it on a port and plays white, the
+/// other joins with the host's address
+pub struct Lobby {
+    hosting: bool,
+    /// port typed to host on
+    port: String,
+    /// `host:port` typed to join
+    address: String,
+    /// index of the time control of a hosted game in [PRESETS]
+    time_control: usize,
+    /// row being changed
+    row: usize,
+    /// open while waiting for the opponent
+    link: Option<Link>,
+    /// why hosting failed
+    error: Option<String>,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl Lobby {
+    pub fn new(back: Box<Scene>) -> Self {
+        Self {
+            hosting: true,
+            port: DEFAULT_PORT.to_string(),
+            address: format!("localhost:{}", DEFAULT_PORT),
+            time_control: 0,
+            row: 0,
+            link: None,
+            error: None,
+            back,
+        }
+    }
+
+    /// Starts hosting or joining with what was typed
+    fn open(&mut self) {
+        self.error = None;
+        if !self.hosting {
+            self.link = Some(Link::join(self.address.trim()));
+            return;
+        }
+        let Ok(port) = self.port.trim().parse() else {
+            self.error = Some(format!("Invalid port {:?}", self.port));
+            return;
+        };
+        match Link::host(port) {
+            Ok(link) => self.link = Some(link),
+            Err(e) => self.error = Some(format!("Failed to host on port {}: {}", port, e)),
+        }
+    }
+
+    /// Waits for the opponent, the game starting once the client is connected and has heard
+    /// from the host
+    fn wait(&mut self, game: &mut Game) -> Transition {
+        let Some(link) = &mut self.link else {
+            return Transition::Stay;
+        };
+        while let Some(event) = link.poll() {
+            let (remote, fen, time_control) = match event {
+                LinkEvent::Connected if link.is_host() => (
+                    PieceColor::Black,
+                    START_FEN.to_string(),
+                    PRESETS[self.time_control],
+                ),
+                LinkEvent::Message(NetMessage::NewGame {
+                    color,
+                    fen,
+                    time_control,
+                }) => (color.opposite(), fen, time_control),
+                LinkEvent::Message(msg) => {
+                    tracing::warn!("Unexpected message in the lobby: {:?}", msg);
+                    continue;
+                }
+                LinkEvent::Connected | LinkEvent::Disconnected => continue,
+            };
+            let link = self.link.take().expect("polled above");
+            game.start_network_game(link, remote, &fen, time_control);
+            return Transition::Switch(Scene::Playing);
+        }
+        Transition::Stay
+    }
+
+    /// Up/Down select a row, Left/Right change it and typing edits the port or address. Enter
+    /// hosts or joins and Escape stops waiting, or goes back
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            if self.link.take().is_some() {
+                return Transition::Stay;
+            }
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        if self.link.is_some() {
+            return self.wait(game);
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            // the time control is the host's to pick
+            let rows = if self.hosting { ROWS } else { ROWS - 1 };
+            self.row = (self.row + 1).min(rows - 1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.row = self.row.saturating_sub(1);
+        }
+        let step = match (
+            rl.is_key_pressed(KeyboardKey::KEY_LEFT),
+            rl.is_key_pressed(KeyboardKey::KEY_RIGHT),
+        ) {
+            (true, false) => -1,
+            (false, true) => 1,
+            _ => 0,
+        };
+        match self.row {
+            0 if step != 0 => self.hosting = !self.hosting,
+            1 => {
+                let field = if self.hosting {
+                    &mut self.port
+                } else {
+                    &mut self.address
+                };
+                for c in input::typed_chars(rl) {
+                    let allowed = if self.hosting {
+                        c.is_ascii_digit()
+                    } else {
+                        c.is_ascii_graphic()
+                    };
+                    if allowed && field.len() < MAX_ADDRESS_LEN {
+                        field.push(c);
+                    }
+                }
+                if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                    field.pop();
+                }
+            }
+            2 => {
+                let count = PRESETS.len() as i32;
+                self.time_control = (self.time_control as i32 + step).rem_euclid(count) as usize;
+            }
+            _ => {}
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            self.open();
+        }
+        Transition::Stay
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background);
+        d.draw_text("Network game", rect.x + 10, rect.y + 10, 20, Color::WHITE);
+
+        if let Some(link) = &self.link {
+            let status = match (link.is_host(), link.is_connected()) {
+                (true, _) => format!("Waiting for an opponent on port {}", self.port.trim()),
+                (false, false) => format!("Connecting to {}", self.address.trim()),
+                (false, true) => "Connected, waiting for the host".to_string(),
+            };
+            d.draw_text(&status, rect.x + 10, rect.y + 50, 18, Color::WHITE);
+            d.draw_text(
+                "Esc to cancel",
+                rect.x + 10,
+                rect.y + 78,
+                16,
+                Color::LIGHTGRAY,
+            );
+            return;
+        }
+
+        let mode = if self.hosting {
+            "< Host a game >"
+        } else {
+            "< Join a game >"
+        };
+        let field = if self.hosting {
+            format!("Port: {}", self.port)
+        } else {
+            format!("Address: {}", self.address)
+        };
+        let time_control = match PRESETS[self.time_control] {
+            Some(time_control) => format!("Time control: < {} >", time_control),
+            None => "Time control: < Untimed >".to_string(),
+        };
+        let mut rows = vec![mode.to_string(), field];
+        if self.hosting {
+            rows.push(time_control);
+        }
+        for (i, text) in rows.iter().enumerate() {
+            let y = rect.y + 50 + i as i32 * 28;
+            if i == self.row {
+                d.draw_rectangle(rect.x + 5, y - 4, rect.size - 10, 26, highlight);
+            }
+            let cursor = if i == 1 && i == self.row { "_" } else { "" };
+            d.draw_text(
+                &format!("{}{}", text, cursor),
+                rect.x + 10,
+                y,
+                18,
+                Color::WHITE,
+            );
+        }
+        let y = rect.y + 50 + rows.len() as i32 * 28 + 12;
+        let hint = if self.hosting {
+            "Enter to host, you play white. Esc to go back"
+        } else {
+            "Enter to join. Esc to go back"
+        };
+        d.draw_text(hint, rect.x + 10, y, 16, Color::LIGHTGRAY);
+        if let Some(error) = &self.error {
+            d.draw_text(error, rect.x + 10, y + 26, 16, Color::RED);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use raylib::consts::{KeyboardKey, MouseButton};
+use raylib::core::RaylibHandle;
+
+use super::{
+    Editor, GameBrowser, GameSetup, GameSwitcher, ImportDialog, Lobby, ProfilePicker, Replay,
+    Scene, SettingsScreen, Simul, StatsScreen, Transition,
+};
+use crate::board::PieceColor;
+use crate::game::Game;
+use crate::widgets::{MainMenu, MenuItem};
+
+/// Up/Down or the mouse select an entry, Enter or a click opens it and Escape goes back to the
+/// game
+pub fn update(menu: &mut MainMenu, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+    let rect = game.board_rect();
+    let mouse = rl.get_mouse_position();
+    let (mouse_x, mouse_y) = (mouse.x as i32, mouse.y as i32);
+    menu.hover(rect, mouse_x, mouse_y);
+    if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+        menu.select_next();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+        menu.select_previous();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+        return Transition::Switch(Scene::board(game));
+    }
+
+    let clicked = rl
+        .is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON)
+        .then(|| menu.item_at(rect, mouse_x, mouse_y))
+        .flatten();
+    let chosen = if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+        Some(menu.selected())
+    } else {
+        clicked
+    };
+    let back = || Box::new(Scene::MainMenu(MainMenu::default()));
+    match chosen {
+        None => Transition::Stay,
+        Some(MenuItem::Continue) => {
+            game.resume_saved_game();
+            Transition::Switch(Scene::board(game))
+        }
+        Some(MenuItem::PlayEngine) => {
+            Transition::Switch(Scene::GameSetup(GameSetup::new(Some(true), back())))
+        }
+        Some(MenuItem::TwoPlayers) => {
+            Transition::Switch(Scene::GameSetup(GameSetup::new(Some(false), back())))
+        }
+        Some(MenuItem::Network) => Transition::Switch(Scene::Lobby(Box::new(Lobby::new(back())))),
+        Some(MenuItem::Puzzles) if game.start_puzzles() => Transition::Switch(Scene::Puzzles),
+        Some(MenuItem::Puzzles) => Transition::Stay,
+        Some(MenuItem::Repertoire) if game.start_repertoire_drill(PieceColor::White) => {
+            Transition::Switch(Scene::Drill)
+        }
+        Some(MenuItem::Repertoire) => Transition::Stay,
+        Some(MenuItem::Analysis) => {
+            game.set_free_move(true);
+            Transition::Switch(Scene::Analysis)
+        }
+        Some(MenuItem::Replay) if game.start_replay() => {
+            Transition::Switch(Scene::Replay(Replay::new(back())))
+        }
+        Some(MenuItem::Replay) => Transition::Stay,
+        Some(MenuItem::Import) => Transition::Switch(Scene::Import(ImportDialog::new(back()))),
+        Some(MenuItem::Database) => {
+            Transition::Switch(Scene::Database(Box::new(GameBrowser::new(back()))))
+        }
+        Some(MenuItem::Ongoing) => {
+            Transition::Switch(Scene::Switcher(Box::new(GameSwitcher::new(game, back()))))
+        }
+        Some(MenuItem::Statistics) => {
+            Transition::Switch(Scene::Stats(StatsScreen::new(&game.paths().stats, back())))
+        }
+        Some(MenuItem::Profiles) => {
+            Transition::Switch(Scene::Profiles(ProfilePicker::new(game, back())))
+        }
+        Some(MenuItem::Simul) => Transition::Switch(Scene::Simul(Simul::new(game))),
+        Some(MenuItem::Editor) => Transition::Switch(Scene::Editor(Editor::new(game, back()))),
+        Some(MenuItem::Settings) => {
+            Transition::Switch(Scene::Settings(SettingsScreen::new(back())))
+        }
+        Some(MenuItem::Quit) => Transition::Quit,
+    }
+}
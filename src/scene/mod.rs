@@ -0,0 +1,173 @@
+//! Screens the window shows, each handling its own input and drawing over the board. A new mode
+//! is a new scene rather than more key checks in the main loop
+
+mod database;
+mod drill;
+mod editor;
+mod export;
+mod game_over;
+mod game_setup;
+mod help;
+mod import;
+mod lobby;
+mod main_menu;
+mod pause;
+mod playing;
+mod profiles;
+mod puzzles;
+mod replay;
+mod settings;
+mod simul;
+mod stats;
+mod switcher;
+
+use raylib::core::drawing::RaylibDrawHandle;
+use raylib::core::RaylibHandle;
+
+use crate::game::Game;
+use crate::widgets::{GameOverDialog, MainMenu, PauseMenu};
+
+pub use database::GameBrowser;
+pub use editor::Editor;
+pub use export::ExportPrompt;
+pub use game_setup::GameSetup;
+pub use help::HelpScreen;
+pub use import::ImportDialog;
+pub use lobby::Lobby;
+pub use profiles::ProfilePicker;
+pub use replay::Replay;
+pub use settings::SettingsScreen;
+pub use simul::Simul;
+pub use stats::StatsScreen;
+pub use switcher::GameSwitcher;
+
+/// What the window shows
+pub enum Scene {
+    MainMenu(MainMenu),
+    /// choosing the opponent and time control of a new game
+    GameSetup(GameSetup),
+    /// hosting or joining a game over the network
+    Lobby(Box<Lobby>),
+    Playing,
+    /// the game standing still behind the pause menu
+    Paused(PauseMenu),
+    /// the board with either side's pieces free to move and the position editable
+    Analysis,
+    /// solving puzzles on the board
+    Puzzles,
+    /// playing the lines of the repertoire against its recorded replies
+    Drill,
+    /// the game played back move by move
+    Replay(Replay),
+    /// several boards side by side
+    Simul(Simul),
+    /// setting up a position piece by piece
+    Editor(Editor),
+    Settings(SettingsScreen),
+    /// the keyboard shortcuts
+    Help(HelpScreen),
+    /// asking where to save a picture of the board
+    Export(ExportPrompt),
+    /// asking for the URL of a game or puzzle to fetch
+    Import(ImportDialog),
+    /// the games of a PGN file, to pick one to replay or analyse
+    Database(Box<GameBrowser>),
+    /// the games put aside, to pick one up
+    Switcher(Box<GameSwitcher>),
+    /// results of the games recorded
+    Stats(StatsScreen),
+    /// choosing whose settings and games are used
+    Profiles(ProfilePicker),
+    GameOver(GameOverDialog),
+}
+
+/// What to do after a scene handled the input of a frame
+pub enum Transition {
+    Stay,
+    Switch(Scene),
+    Quit,
+}
+
+impl Scene {
+    /// The scene playing the game on the board, analysis when pieces move freely
+    pub fn board(game: &Game) -> Self {
+        if game.is_solving_puzzles() {
+            Scene::Puzzles
+        } else if game.is_drilling() {
+            Scene::Drill
+        } else if game.free_move {
+            Scene::Analysis
+        } else {
+            Scene::Playing
+        }
+    }
+
+    /// Pauses the game being played and returns the pause menu, none in the other scenes
+    pub fn pause(&self, game: &mut Game) -> Option<Scene> {
+        match self {
+            Scene::Playing => {
+                game.pause();
+                Some(Scene::Paused(PauseMenu::default()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Handles the input of a frame
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        match self {
+            Scene::MainMenu(menu) => main_menu::update(menu, rl, game),
+            Scene::GameSetup(setup) => setup.update(rl, game),
+            Scene::Lobby(lobby) => lobby.update(rl, game),
+            Scene::Playing => playing::update(rl, game, false),
+            Scene::Paused(menu) => pause::update(menu, rl, game),
+            Scene::Analysis => playing::update(rl, game, true),
+            Scene::Puzzles => puzzles::update(rl, game),
+            Scene::Drill => drill::update(rl, game),
+            Scene::Replay(replay) => replay.update(rl, game),
+            Scene::Simul(simul) => simul.update(rl, game),
+            Scene::Editor(editor) => editor.update(rl, game),
+            Scene::Settings(screen) => screen.update(rl, game),
+            Scene::Help(screen) => screen.update(rl, game),
+            Scene::Export(prompt) => prompt.update(rl, game),
+            Scene::Import(dialog) => dialog.update(rl, game),
+            Scene::Database(browser) => browser.update(rl, game),
+            Scene::Switcher(switcher) => switcher.update(rl, game),
+            Scene::Stats(screen) => screen.update(rl, game),
+            Scene::Profiles(picker) => picker.update(rl, game),
+            Scene::GameOver(dialog) => game_over::update(dialog, rl, game),
+        }
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle, game: &Game) {
+        // the editor draws the position it sets up instead of the game
+        if let Scene::Editor(editor) = self {
+            editor.draw(d, game);
+            return;
+        }
+        game.draw_board(d);
+        let rect = game.board_rect();
+        match self {
+            Scene::MainMenu(menu) => menu.draw(d, rect),
+            Scene::Paused(menu) => menu.draw(d, rect),
+            Scene::GameSetup(setup) => setup.draw(d, rect),
+            Scene::Lobby(lobby) => lobby.draw(d, rect),
+            Scene::Playing | Scene::Analysis | Scene::Puzzles | Scene::Drill | Scene::Editor(_) => {
+            }
+            Scene::Replay(replay) => replay.draw(d, rect, game),
+            Scene::Simul(simul) => simul.draw(d, game),
+            Scene::Settings(screen) => screen.draw(d, rect, game),
+            Scene::Help(screen) => screen.draw(d, rect, game),
+            Scene::Export(prompt) => prompt.draw(d, rect),
+            Scene::Import(dialog) => dialog.draw(d, rect),
+            Scene::Database(browser) => browser.draw(d, rect),
+            Scene::Switcher(switcher) => switcher.draw(d, rect, game),
+            Scene::Stats(screen) => screen.draw(d, rect),
+            Scene::Profiles(picker) => picker.draw(d, rect),
+            Scene::GameOver(dialog) => {
+                let mouse = d.get_mouse_position();
+                dialog.draw(d, rect, (mouse.x as i32, mouse.y as i32));
+            }
+        }
+    }
+}
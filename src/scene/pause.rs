@@ -0,0 +1,57 @@
+use raylib::consts::{KeyboardKey, MouseButton};
+use raylib::core::RaylibHandle;
+
+use super::{Scene, SettingsScreen, Transition};
+use crate::game::Game;
+use crate::widgets::{MainMenu, PauseItem, PauseMenu};
+
+/// Up/Down or the mouse select an entry, Enter or a click opens it and Escape resumes the game.
+/// The game stays paused only as long as this menu or the settings opened from it are shown
+pub fn update(menu: &mut PauseMenu, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+    let rect = game.board_rect();
+    let mouse = rl.get_mouse_position();
+    let (mouse_x, mouse_y) = (mouse.x as i32, mouse.y as i32);
+    menu.hover(rect, mouse_x, mouse_y);
+    if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+        menu.select_next();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+        menu.select_previous();
+    }
+
+    let clicked = rl
+        .is_mouse_button_pressed(MouseButton::MOUSE_LEFT_BUTTON)
+        .then(|| menu.item_at(rect, mouse_x, mouse_y))
+        .flatten();
+    let chosen = if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+        Some(PauseItem::Resume)
+    } else if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+        Some(menu.selected())
+    } else {
+        clicked
+    };
+    match chosen {
+        None => Transition::Stay,
+        Some(PauseItem::Resume) => {
+            game.unpause();
+            Transition::Switch(Scene::board(game))
+        }
+        Some(PauseItem::Settings) => Transition::Switch(Scene::Settings(SettingsScreen::new(
+            Box::new(Scene::Paused(PauseMenu::default())),
+        ))),
+        Some(PauseItem::SaveGame) => {
+            game.save_game();
+            Transition::Stay
+        }
+        Some(PauseItem::Resign) => {
+            game.unpause();
+            game.resign();
+            Transition::Switch(Scene::board(game))
+        }
+        Some(PauseItem::MainMenu) => {
+            game.unpause();
+            Transition::Switch(Scene::MainMenu(MainMenu::default()))
+        }
+        Some(PauseItem::Quit) => Transition::Quit,
+    }
+}
@@ -0,0 +1,403 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::RaylibHandle;
+
+use super::{ExportPrompt, GameSetup, GameSwitcher, HelpScreen, Scene, SettingsScreen, Transition};
+use crate::board::PieceColor;
+use crate::export::ExportFormat;
+use crate::game::Game;
+use crate::input::{self, Action};
+use crate::touch::{self, TouchEvent};
+use crate::widgets::{GameOverDialog, MainMenu};
+
+/// Characters typed in the move box this frame
+fn type_move(rl: &mut RaylibHandle, game: &mut Game) {
+    for c in input::typed_chars(rl) {
+        if let Some(input) = game.move_input.as_mut() {
+            input.push(c);
+        }
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+        if let Some(input) = game.move_input.as_mut() {
+            input.backspace();
+        }
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+        game.submit_move_input();
+    }
+    if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+        game.toggle_move_input();
+    }
+}
+
+/// Shortcuts of the game on the board, returning the scene a key opens. The keys come from
+/// [Game::input], see [crate::input]
+fn shortcuts(rl: &mut RaylibHandle, game: &mut Game, analysis: bool) -> Transition {
+    let input = game.input.clone();
+
+    // the clipboard needs the handle to itself
+    if input.pressed(rl, Action::CopyFen) {
+        match rl.set_clipboard_text(&game.fen()) {
+            Ok(()) => game.show_notice("FEN copied"),
+            Err(e) => game.show_notice(&format!("Failed to copy the FEN: {}", e)),
+        }
+    }
+    if input.pressed(rl, Action::PasteFen) {
+        match rl.get_clipboard_text() {
+            Ok(text) => game.paste_fen(&text),
+            Err(e) => game.show_notice(&format!("Failed to read the clipboard: {}", e)),
+        }
+    }
+
+    let pressed = |action| input.pressed(rl, action);
+    if pressed(Action::Help) {
+        return Transition::Switch(Scene::Help(HelpScreen::new(Box::new(Scene::board(game)))));
+    }
+
+    let export = if pressed(Action::ExportPng) {
+        Some(ExportFormat::Png)
+    } else if pressed(Action::ExportGif) {
+        Some(ExportFormat::Gif)
+    } else {
+        None
+    };
+    if let Some(format) = export {
+        return Transition::Switch(Scene::Export(ExportPrompt::new(
+            format,
+            Box::new(Scene::board(game)),
+        )));
+    }
+
+    if pressed(Action::MoveInput) {
+        game.toggle_move_input();
+    }
+
+    if pressed(Action::HistoryBack) {
+        game.step_history(-1);
+    }
+    if pressed(Action::HistoryForward) {
+        game.step_history(1);
+    }
+
+    if pressed(Action::OfferDraw) {
+        game.offer_draw();
+    }
+    if pressed(Action::Promotion) {
+        game.pawn_promotion = !game.pawn_promotion;
+    }
+
+    // Enter confirms a pending move, else prints the position
+    if game.backup_picker.is_none() && !game.is_keyboard_mode() {
+        let confirmed = pressed(Action::ConfirmMove) && game.confirm_pending_move();
+        if !confirmed && pressed(Action::Debug) {
+            game.debug();
+        }
+    }
+
+    // position setup in analysis: castling rights and the en passant square under the cursor
+    if analysis && !game.is_keyboard_mode() {
+        if pressed(Action::WhiteKingside) {
+            game.toggle_castling_right(PieceColor::White, true);
+        }
+        if pressed(Action::WhiteQueenside) {
+            game.toggle_castling_right(PieceColor::White, false);
+        }
+        if pressed(Action::BlackKingside) {
+            game.toggle_castling_right(PieceColor::Black, true);
+        }
+        if pressed(Action::BlackQueenside) {
+            game.toggle_castling_right(PieceColor::Black, false);
+        }
+        if pressed(Action::EnPassant) {
+            game.toggle_en_passant_at_cursor();
+        }
+    }
+
+    if pressed(Action::Engine) {
+        game.toggle_engine();
+    }
+
+    // plays against the engine from RUCHE_UCI_ENGINE, stockfish by default
+    if pressed(Action::ExternalEngine) {
+        game.toggle_external_engine();
+    }
+
+    if pressed(Action::EngineStats) {
+        game.engine_stats.toggle();
+    }
+
+    if pressed(Action::FlipBoard) {
+        game.flip_board();
+    }
+    if pressed(Action::AutoFlip) {
+        game.toggle_auto_flip();
+    }
+
+    // the cursor is moved with arrows, hjkl or a controller and Enter selects a piece and
+    // moves it, which takes hjkl from their other uses
+    if pressed(Action::KeyboardMode) {
+        game.toggle_keyboard_mode();
+    }
+
+    if pressed(Action::Hint) && !game.is_keyboard_mode() {
+        game.toggle_hint();
+    }
+
+    // hides the opponent's pieces, then all of them for blindfold play
+    if pressed(Action::PieceVisibility) {
+        game.next_piece_visibility();
+    }
+
+    if pressed(Action::EvalBar) {
+        game.toggle_eval_bar();
+    }
+
+    if pressed(Action::PassedPawns) {
+        game.show_passed_pawns = !game.show_passed_pawns;
+    }
+
+    if pressed(Action::ThinkTime) {
+        game.next_think_time();
+    }
+
+    if pressed(Action::ConditionEditor) {
+        game.toggle_condition_editor();
+    }
+
+    if pressed(Action::RemoveCondition) {
+        game.remove_last_condition();
+    }
+
+    if pressed(Action::ClearConditions) {
+        game.clear_conditions();
+    }
+
+    if pressed(Action::NextTheme) {
+        game.next_theme();
+    }
+
+    if pressed(Action::AuditLog) && !game.is_keyboard_mode() {
+        game.export_audit_log();
+    }
+
+    if pressed(Action::BookMode) {
+        game.next_book_mode();
+    }
+    if pressed(Action::RecordRepertoire) {
+        game.toggle_repertoire_recording();
+    }
+
+    // the game can only be aborted before both sides moved
+    if pressed(Action::Resign) {
+        game.resign();
+    }
+    if pressed(Action::Abort) {
+        game.abort();
+    }
+
+    if pressed(Action::Rematch) {
+        game.offer_rematch();
+    }
+
+    if pressed(Action::BackupPicker) {
+        game.toggle_backup_picker();
+    }
+
+    // sets the pieces free for analysis, or puts them back under the rules
+    if pressed(Action::Analysis) {
+        game.set_free_move(!analysis);
+        return Transition::Switch(Scene::board(game));
+    }
+
+    if pressed(Action::NewGame) {
+        game.new_game();
+    }
+    if pressed(Action::GameSetup) {
+        return Transition::Switch(Scene::GameSetup(GameSetup::new(
+            None,
+            Box::new(Scene::board(game)),
+        )));
+    }
+    if pressed(Action::GameSwitcher) {
+        let back = Box::new(Scene::board(game));
+        return Transition::Switch(Scene::Switcher(Box::new(GameSwitcher::new(game, back))));
+    }
+    if pressed(Action::Decline) {
+        game.decline_rematch();
+        game.decline_draw();
+        game.decline_takeback();
+    }
+
+    if pressed(Action::AcceptTakeback) {
+        game.accept_takeback();
+    }
+
+    if pressed(Action::Settings) {
+        return Transition::Switch(Scene::Settings(SettingsScreen::new(Box::new(
+            Scene::board(game),
+        ))));
+    }
+    Transition::Stay
+}
+
+/// The cursor actions and the left stick move the square cursor, picking picks up and puts
+/// down pieces. `stick` is where the stick pushes the cursor this frame
+fn keyboard_mode(rl: &mut RaylibHandle, game: &mut Game, stick: Option<(i32, i32)>) -> Transition {
+    let input = game.input.clone();
+    let pressed = |action| input.pressed(rl, action);
+    if pressed(Action::CursorLeft) {
+        game.move_key_cursor(-1, 0);
+    }
+    if pressed(Action::CursorRight) {
+        game.move_key_cursor(1, 0);
+    }
+    if pressed(Action::CursorUp) {
+        game.move_key_cursor(0, -1);
+    }
+    if pressed(Action::CursorDown) {
+        game.move_key_cursor(0, 1);
+    }
+    if let Some((dx, dy)) = stick {
+        game.move_key_cursor(dx, dy);
+    }
+    if pressed(Action::Pick) {
+        game.press_key_cursor();
+    }
+    let cancel = pressed(Action::Cancel);
+    if cancel {
+        if game.pawn_promotion {
+            game.cancel_promotion();
+        } else {
+            game.unset_selected();
+        }
+    }
+    // Escape only drops the piece here, the menu takes a key of its own like Start
+    if pressed(Action::Menu) && !cancel {
+        if let Some(menu) = Scene::board(game).pause(game) {
+            return Transition::Switch(menu);
+        }
+    }
+    Transition::Stay
+}
+
+/// Mouse and keys of the board itself, returning the pause menu or the main menu when the menu
+/// key has nothing else to close
+pub(super) fn board_input(rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+    let input = game.input.clone();
+    // a finger selects and moves like the left button, holding it still annotates like the
+    // right one
+    let touch = game.touch.poll(rl);
+    let tapped = touch::SEPARATE_TOUCH && touch == Some(TouchEvent::Press);
+    let lifted = touch::SEPARATE_TOUCH && touch == Some(TouchEvent::Release);
+    if game.pawn_promotion {
+        if input.pressed(rl, Action::Select) || tapped {
+            game.click_promotion();
+        }
+        if input.pressed(rl, Action::Cancel) || input.pressed(rl, Action::Annotate) {
+            game.cancel_promotion();
+        }
+        return Transition::Stay;
+    }
+
+    if input.pressed(rl, Action::Select) || tapped {
+        if game.is_cursor_on_board() {
+            game.clear_annotations();
+            game.grab_piece();
+        } else {
+            game.click_move_list();
+            game.click_explorer();
+            game.click_pocket();
+        }
+    }
+    if input.released(rl, Action::Select) || lifted {
+        game.drop_piece();
+    }
+
+    // takes back the last move, or asks the opponent to
+    if input.pressed(rl, Action::TakeBack) {
+        game.take_back();
+    }
+    if input.pressed(rl, Action::Redo) {
+        game.redo_move();
+    }
+
+    // dragging draws an arrow, a click circles a square. A hold on a piece being moved keeps
+    // moving it
+    let held = touch == Some(TouchEvent::HoldStart) && game.selected.is_none();
+    if input.pressed(rl, Action::Annotate) || held {
+        game.unset_selected();
+        game.start_annotation();
+    }
+    if input.released(rl, Action::Annotate) || touch == Some(TouchEvent::HoldEnd) {
+        game.finish_annotation();
+    }
+
+    if input.pressed(rl, Action::Menu) && !game.cancel_pending_move() {
+        // a game being played pauses, analysis and puzzles go back to the menu
+        if game.selected.is_none() {
+            let menu = Scene::board(game)
+                .pause(game)
+                .unwrap_or(Scene::MainMenu(MainMenu::default()));
+            return Transition::Switch(menu);
+        }
+        game.unset_selected();
+    }
+    Transition::Stay
+}
+
+/// Input of the game on the board, `analysis` when either side's pieces move freely
+pub fn update(rl: &mut RaylibHandle, game: &mut Game, analysis: bool) -> Transition {
+    if let Some(state) = game.take_game_over() {
+        let players = (
+            game.player_name(PieceColor::White),
+            game.player_name(PieceColor::Black),
+        );
+        return Transition::Switch(Scene::GameOver(GameOverDialog::new(state, players)));
+    }
+
+    // holding ctrl while moving a pawn to the last rank asks for the piece despite auto-queen
+    game.force_promotion_picker = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+        || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+
+    // scrolling over the board steps through the moves played, up goes back
+    let wheel = rl.get_mouse_wheel_move();
+    if wheel != 0.0 && game.is_cursor_on_board() {
+        game.step_history(if wheel > 0.0 { -1 } else { 1 });
+    }
+
+    // while a move is typed the keys spell it instead of being shortcuts
+    if game.move_input.is_some() {
+        type_move(rl, game);
+        return Transition::Stay;
+    }
+    if let Transition::Switch(next) = shortcuts(rl, game, analysis) {
+        return Transition::Switch(next);
+    }
+
+    // a controller plays in keyboard mode, its first press only bringing the cursor up
+    let stick = game.gamepad.poll(rl);
+    let pad_used = stick.is_some() || input::pad_pressed(rl);
+    if pad_used && !game.is_keyboard_mode() && game.backup_picker.is_none() {
+        game.toggle_keyboard_mode();
+        return Transition::Stay;
+    }
+
+    if let Some(picker) = game.backup_picker.as_mut() {
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            picker.select_next();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            picker.select_previous();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            game.restore_selected_backup();
+        }
+        if game.input.pressed(rl, Action::Menu) {
+            game.toggle_backup_picker();
+        }
+        Transition::Stay
+    } else if game.is_keyboard_mode() {
+        keyboard_mode(rl, game, stick)
+    } else {
+        board_input(rl, game)
+    }
+}
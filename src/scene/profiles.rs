@@ -0,0 +1,141 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::game::Game;
+use crate::input;
+use crate::profile::{self, Profile, MAX_NAME_LEN};
+use crate::widgets::{BoardRect, MainMenu};
+
+/// Height of a row of the list
+const ROW_HEIGHT: i32 = 28;
+
+/// Picks the player whose settings, statistics and games are used, shown at startup when there
+/// are profiles. The first row goes without a profile and the last one makes a new one
+pub struct ProfilePicker {
+    profiles: Vec<Profile>,
+    /// row selected, 0 for no profile and one past the profiles for a new one
+    selected: usize,
+    /// name of the new profile, typed once its row is picked
+    name: Option<String>,
+    /// why the profile couldn't be made
+    error: Option<String>,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl ProfilePicker {
+    /// Lists the profiles, the one in use selected
+    pub fn new(game: &Game, back: Box<Scene>) -> Self {
+        let profiles = profile::list();
+        let selected = game
+            .profile()
+            .and_then(|name| profiles.iter().position(|p| p.name == name))
+            .map_or(0, |i| i + 1);
+        Self {
+            profiles,
+            selected,
+            name: None,
+            error: None,
+            back,
+        }
+    }
+
+    /// Typing edits the name, Enter makes the profile and Escape stops typing
+    fn update_name(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        let Some(name) = &mut self.name else {
+            return Transition::Stay;
+        };
+        for c in input::typed_chars(rl) {
+            if !c.is_control() && name.chars().count() < MAX_NAME_LEN {
+                name.push(c);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            name.pop();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            self.name = None;
+            self.error = None;
+            return Transition::Stay;
+        }
+        if !rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return Transition::Stay;
+        }
+        match game.create_profile(name) {
+            Ok(()) => Transition::Switch(Scene::MainMenu(MainMenu::default())),
+            Err(e) => {
+                self.error = Some(format!("Failed to make the profile: {}", e));
+                Transition::Stay
+            }
+        }
+    }
+
+    /// Up/Down select a row, Enter switches to its profile or starts typing the name of a new
+    /// one, Escape goes back
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if self.name.is_some() {
+            return self.update_name(rl, game);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1).min(self.profiles.len() + 1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if !rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            return Transition::Stay;
+        }
+        if self.selected > self.profiles.len() {
+            self.name = Some(String::new());
+            return Transition::Stay;
+        }
+        let profile = self.selected.checked_sub(1).map(|i| &self.profiles[i]);
+        game.switch_profile(profile);
+        Transition::Switch(Scene::MainMenu(MainMenu::default()))
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.95));
+        d.draw_text(
+            "Who is playing?",
+            rect.x + 10,
+            rect.y + 10,
+            20,
+            Color::WHITE,
+        );
+
+        let new_row = match &self.name {
+            Some(name) => format!("New profile: {}_", name),
+            None => "New profile".to_string(),
+        };
+        let rows = std::iter::once("No profile".to_string())
+            .chain(self.profiles.iter().map(|p| p.name.clone()))
+            .chain([new_row]);
+        let mut y = rect.y + 50;
+        for (i, label) in rows.enumerate() {
+            if i == self.selected {
+                d.draw_rectangle(rect.x + 5, y - 4, rect.size - 10, ROW_HEIGHT - 2, highlight);
+            }
+            d.draw_text(&label, rect.x + 10, y, 20, Color::WHITE);
+            y += ROW_HEIGHT;
+        }
+
+        let (hint, color) = match (&self.error, &self.name) {
+            (Some(error), _) => (error.as_str(), Color::RED),
+            (None, Some(_)) => ("Enter to make the profile, Esc to cancel", Color::GRAY),
+            (None, None) => ("Enter to pick, Esc to go back", Color::GRAY),
+        };
+        d.draw_text(hint, rect.x + 10, y + 10, 14, color);
+    }
+}
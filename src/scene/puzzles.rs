@@ -0,0 +1,19 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::RaylibHandle;
+
+use super::{playing, Transition};
+use crate::game::Game;
+
+/// The board plays the current puzzle, N moves on to the next one and Escape opens the main
+/// menu
+pub fn update(rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+    // the mate ending a puzzle ends its game too, the next puzzle follows instead of the dialog
+    game.take_game_over();
+
+    game.force_promotion_picker = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+        || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+    if rl.is_key_pressed(KeyboardKey::KEY_N) {
+        game.next_puzzle();
+    }
+    playing::board_input(rl, game)
+}
@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::game::Game;
+use crate::widgets::BoardRect;
+
+/// Seconds between the moves played back, Up and Down pick one
+const DELAYS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+/// Delay the playback starts with
+const DEFAULT_DELAY: usize = 2;
+
+/// Plays the game on the board back move by move, or the game pasted as PGN
+pub struct Replay {
+    playing: bool,
+    /// index in [DELAYS] of the time between moves
+    delay: usize,
+    /// when the next move is played
+    next_at: Instant,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl Replay {
+    pub fn new(back: Box<Scene>) -> Self {
+        Self {
+            playing: true,
+            delay: DEFAULT_DELAY,
+            next_at: Instant::now(),
+            back,
+        }
+    }
+
+    fn delay(&self) -> Duration {
+        Duration::from_secs_f32(DELAYS[self.delay])
+    }
+
+    /// Space pauses and resumes, Left/Right step through the moves, Home/End jump to the start
+    /// and the end, Up/Down change the speed and ctrl+V replays the PGN in the clipboard
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        // the end of the game is on screen already, the dialog would only cover it
+        game.take_game_over();
+
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            game.show_ply(game.game_length());
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+
+        let ctrl = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        if ctrl && rl.is_key_pressed(KeyboardKey::KEY_V) {
+            let loaded = rl
+                .get_clipboard_text()
+                .map_err(|e| e.to_string())
+                .and_then(|text| game.load_pgn(&text).map_err(|e| e.to_string()));
+            match loaded {
+                Ok(()) => {
+                    game.start_replay();
+                    self.playing = true;
+                    self.next_at = Instant::now() + self.delay();
+                }
+                Err(e) => game.show_notice(&format!("Failed to read the PGN: {}", e)),
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            // playing again from the end starts over
+            if !self.playing && game.shown_ply() == game.game_length() {
+                game.show_ply(0);
+            }
+            self.playing = !self.playing;
+            self.next_at = Instant::now() + self.delay();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.delay = self.delay.saturating_sub(1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.delay = (self.delay + 1).min(DELAYS.len() - 1);
+        }
+
+        // stepping by hand pauses the playback
+        let step = [
+            (KeyboardKey::KEY_LEFT, -1),
+            (KeyboardKey::KEY_RIGHT, 1),
+            (KeyboardKey::KEY_HOME, -(game.game_length() as isize)),
+            (KeyboardKey::KEY_END, game.game_length() as isize),
+        ]
+        .into_iter()
+        .find(|(key, _)| rl.is_key_pressed(*key));
+        if let Some((_, delta)) = step {
+            self.playing = false;
+            game.replay_step(delta);
+        }
+
+        if self.playing && Instant::now() >= self.next_at {
+            if game.shown_ply() < game.game_length() {
+                game.replay_step(1);
+                self.next_at = Instant::now() + self.delay();
+            } else {
+                self.playing = false;
+            }
+        }
+        Transition::Stay
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect, game: &Game)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("262421").expect("Error parsing hex");
+        let top = rect.y + rect.size - 44;
+        d.draw_rectangle(rect.x, top, rect.size, 44, background.fade(0.85));
+
+        let state = if self.playing { "Playing" } else { "Paused" };
+        let status = format!(
+            "{}  move {} of {}  {}s per move",
+            state,
+            game.shown_ply(),
+            game.game_length(),
+            DELAYS[self.delay]
+        );
+        d.draw_text(&status, rect.x + 8, top + 6, 16, Color::WHITE);
+        d.draw_text(
+            "Space: pause  Left/Right: step  Home/End  Up/Down: speed  Ctrl+V: paste PGN",
+            rect.x + 8,
+            top + 26,
+            12,
+            Color::LIGHTGRAY,
+        );
+    }
+}
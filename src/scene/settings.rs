@@ -0,0 +1,50 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::game::Game;
+use crate::widgets::{BoardRect, Settings};
+
+/// The settings listed over the board, saved as they change
+pub struct SettingsScreen {
+    rows: Settings,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl SettingsScreen {
+    pub fn new(back: Box<Scene>) -> Self {
+        Self {
+            rows: Settings::default(),
+            back,
+        }
+    }
+
+    /// Up/Down select a setting, Left/Right change it and Escape or G close the screen
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.rows.select_next(game.setting_rows().len());
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.rows.select_previous();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            game.change_setting(self.rows.selected(), 1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            game.change_setting(self.rows.selected(), -1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) || rl.is_key_pressed(KeyboardKey::KEY_G) {
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        Transition::Stay
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect, game: &Game)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        self.rows.draw(d, rect, &game.setting_rows());
+    }
+}
@@ -0,0 +1,104 @@
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{playing, Transition};
+use crate::game::Game;
+use crate::input::Action;
+
+/// Most boards shown side by side
+const MAX_BOARDS: usize = 4;
+
+/// Boards played side by side, each its own game with its own clock and move list, for simuls
+/// or comparing lines. Input goes to the board under the cursor. The game of the first board
+/// is the one the other screens know: opening one from another board brings that board's game
+/// to the front, and the others are dropped with the simul
+pub struct Simul {
+    /// the boards after the first one, which is the game passed to the scenes
+    others: Vec<Game>,
+    /// board under the cursor, 0 for the first one
+    active: usize,
+}
+
+impl Simul {
+    /// Adds a second board to `game`, starting from the position on screen
+    pub fn new(game: &Game) -> Self {
+        Self {
+            others: vec![game.spawn_board()],
+            active: 0,
+        }
+    }
+
+    fn boards(&self) -> usize {
+        self.others.len() + 1
+    }
+
+    /// Shares the width of the window between the boards
+    fn layout(&mut self, rl: &RaylibHandle, game: &mut Game) {
+        let (width, height) = (rl.get_screen_width(), rl.get_screen_height());
+        let slice = width / self.boards() as i32;
+        let boards = std::iter::once(game).chain(self.others.iter_mut());
+        for (i, board) in boards.enumerate() {
+            board.set_origin(i as i32 * slice);
+            board.resize((i as i32 + 1) * slice, height);
+        }
+    }
+
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if game.input.pressed(rl, Action::AddBoard) && self.boards() < MAX_BOARDS {
+            self.others.push(game.spawn_board());
+        }
+        self.layout(rl, game);
+        // the first board follows the mouse and updates with the main loop
+        for board in &mut self.others {
+            board.follow_mouse(rl);
+            board.update();
+        }
+
+        // results are shown on their board, a dialog would act on the first one
+        let boards = std::iter::once(&mut *game).chain(self.others.iter_mut());
+        for board in boards {
+            if let Some(state) = board.take_game_over() {
+                board.show_notice(&state.to_string());
+            }
+        }
+
+        let mouse = rl.get_mouse_position();
+        let mouse_x = mouse.x as i32;
+        if game.covers(mouse_x) {
+            self.active = 0;
+        } else if let Some(i) = self.others.iter().position(|b| b.covers(mouse_x)) {
+            self.active = i + 1;
+        }
+
+        let transition = match self.active {
+            0 => playing::update(rl, game, false),
+            i => playing::update(rl, &mut self.others[i - 1], false),
+        };
+        let Transition::Switch(next) = transition else {
+            return transition;
+        };
+        if self.active > 0 {
+            std::mem::swap(game, &mut self.others[self.active - 1]);
+        }
+        game.set_origin(0);
+        game.resize(rl.get_screen_width(), rl.get_screen_height());
+        Transition::Switch(next)
+    }
+
+    /// Draws the boards after the first one, and outlines the one taking the input
+    pub fn draw<T>(&self, d: &mut T, game: &Game)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        for board in &self.others {
+            board.draw_board(d);
+        }
+        let active = match self.active {
+            0 => game,
+            i => &self.others[i - 1],
+        };
+        let rect = active.board_rect();
+        let outline = Color::from_hex("8ab7ff").expect("Error parsing hex");
+        d.draw_rectangle_lines(rect.x, rect.y, rect.size, rect.size, outline);
+    }
+}
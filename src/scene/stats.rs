@@ -0,0 +1,173 @@
+use std::path::Path;
+
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::math::Vector2;
+use raylib::core::RaylibHandle;
+
+use super::{Scene, Transition};
+use crate::game::Game;
+use crate::stats::{Stats, Tally};
+use crate::widgets::BoardRect;
+
+/// Openings listed, the most played
+const OPENINGS: usize = 6;
+/// Height of a line of text
+const LINE_HEIGHT: i32 = 22;
+
+/// Results of the games recorded in the statistics: overall, by color and by opening, with the
+/// informal rating against the engine levels drawn game by game
+pub struct StatsScreen {
+    stats: Stats,
+    rating: Vec<i32>,
+    /// why the statistics couldn't be read
+    error: Option<String>,
+    /// scene Escape goes back to
+    back: Box<Scene>,
+}
+
+impl StatsScreen {
+    /// Reads the statistics kept at `path`
+    pub fn new(path: &Path, back: Box<Scene>) -> Self {
+        let (stats, error) = match Stats::open(path) {
+            Ok(stats) => (stats, None),
+            Err(e) => (
+                Stats::default(),
+                Some(format!("Failed to read {}: {}", path.display(), e)),
+            ),
+        };
+        Self {
+            rating: stats.rating_trend(),
+            stats,
+            error,
+            back,
+        }
+    }
+
+    /// Escape goes back
+    pub fn update(&mut self, rl: &mut RaylibHandle, _game: &mut Game) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        Transition::Stay
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.95));
+        d.draw_text("Statistics", rect.x + 10, rect.y + 10, 20, Color::WHITE);
+        if let Some(error) = &self.error {
+            d.draw_text(error, rect.x + 10, rect.y + 40, 14, Color::RED);
+            return;
+        }
+        if self.stats.games.is_empty() {
+            d.draw_text(
+                "No games recorded yet, play the engine or a network game",
+                rect.x + 10,
+                rect.y + 40,
+                14,
+                Color::LIGHTGRAY,
+            );
+            return;
+        }
+
+        let line = |tally: &Tally| {
+            format!(
+                "{} games  +{} ={} -{}  {}%",
+                tally.games(),
+                tally.wins,
+                tally.draws,
+                tally.losses,
+                tally.score()
+            )
+        };
+        let (white, black) = self.stats.by_color();
+        let mut y = rect.y + 44;
+        for (label, tally) in [
+            ("All", self.stats.total()),
+            ("White", white),
+            ("Black", black),
+        ] {
+            d.draw_text(label, rect.x + 10, y, 16, Color::LIGHTGRAY);
+            d.draw_text(&line(&tally), rect.x + 70, y, 16, Color::WHITE);
+            y += LINE_HEIGHT;
+        }
+
+        y += LINE_HEIGHT / 2;
+        d.draw_text("Openings", rect.x + 10, y, 16, highlight);
+        y += LINE_HEIGHT;
+        for (name, tally) in self.stats.by_opening().iter().take(OPENINGS) {
+            let results = line(tally);
+            let width = raylib::core::text::measure_text(&results, 14);
+            d.draw_text(name, rect.x + 10, y, 14, Color::WHITE);
+            d.draw_text(
+                &results,
+                rect.x + rect.size - width - 10,
+                y,
+                14,
+                Color::LIGHTGRAY,
+            );
+            y += LINE_HEIGHT - 4;
+        }
+
+        y += LINE_HEIGHT / 2;
+        let Some(current) = self.rating.last() else {
+            d.draw_text(
+                "No games against the engine levels for a rating",
+                rect.x + 10,
+                y,
+                14,
+                Color::GRAY,
+            );
+            return;
+        };
+        let title = format!("Rating against the engine: {}", current);
+        d.draw_text(&title, rect.x + 10, y, 16, highlight);
+        y += LINE_HEIGHT + 4;
+        self.draw_trend(
+            d,
+            rect.x + 10,
+            y,
+            rect.size - 20,
+            rect.y + rect.size - y - 10,
+        );
+    }
+
+    /// The rating game by game as a line in the box from (`x`, `y`), with the lowest and the
+    /// highest on the left
+    fn draw_trend<T>(&self, d: &mut T, x: i32, y: i32, width: i32, height: i32)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        if height <= 0 {
+            return;
+        }
+        d.draw_rectangle_lines(x, y, width, height, Color::GRAY);
+        let low = self.rating.iter().copied().min().unwrap_or_default();
+        let high = self.rating.iter().copied().max().unwrap_or_default();
+        d.draw_text(&high.to_string(), x + 4, y + 4, 12, Color::GRAY);
+        d.draw_text(&low.to_string(), x + 4, y + height - 16, 12, Color::GRAY);
+        let range = (high - low).max(1) as f32;
+        let step = width as f32 / self.rating.len().max(2).saturating_sub(1) as f32;
+        let points = self
+            .rating
+            .iter()
+            .enumerate()
+            .map(|(i, rating)| {
+                let share = (rating - low) as f32 / range;
+                Vector2::new(
+                    x as f32 + i as f32 * step,
+                    (y + height) as f32 - share * (height - 8) as f32 - 4.0,
+                )
+            })
+            .collect::<Vec<_>>();
+        for pair in points.windows(2) {
+            d.draw_line_ex(pair[0], pair[1], 2.0, Color::WHITE);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use raylib::consts::KeyboardKey;
+use raylib::core::color::Color;
+use raylib::core::RaylibHandle;
+
+use super::{GameSetup, Scene, Transition};
+use crate::board::PieceColor;
+use crate::clock::format_time;
+use crate::correspondence::{self, OngoingGame};
+use crate::game::Game;
+use crate::widgets::BoardRect;
+
+/// Games side by side on a row
+const COLUMNS: i32 = 3;
+/// Space above the first row, for the title and the keys
+const HEADER_HEIGHT: i32 = 56;
+/// Space under each thumbnail, for the players, the turn and the clocks
+const CAPTION_HEIGHT: i32 = 44;
+
+/// The games put aside in the games directory, each with a thumbnail of its board, whose turn
+/// it is and the clocks, to pick one up. The game on the board is put aside on opening it
+pub struct GameSwitcher {
+    games: Vec<OngoingGame>,
+    selected: usize,
+    /// scene Escape goes back to, the game on the board carrying on
+    back: Box<Scene>,
+}
+
+impl GameSwitcher {
+    /// Puts the game on the board aside and lists the games, the one on the board selected
+    pub fn new(game: &mut Game, back: Box<Scene>) -> Self {
+        let current = game.put_aside();
+        game.pause();
+        let games = correspondence::list(&game.paths().games_dir);
+        let selected = current
+            .and_then(|path| games.iter().position(|g| g.path == path))
+            .unwrap_or(0);
+        Self {
+            games,
+            selected,
+            back,
+        }
+    }
+
+    /// Arrows select a game, Enter picks it up, Delete removes it and N sets up a new game next
+    /// to the others. Escape goes back to the game on the board
+    pub fn update(&mut self, rl: &mut RaylibHandle, game: &mut Game) -> Transition {
+        if rl.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            game.unpause();
+            let back = std::mem::replace(&mut *self.back, Scene::Playing);
+            return Transition::Switch(back);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_N) {
+            game.unpause();
+            return Transition::Switch(Scene::GameSetup(GameSetup::new(
+                None,
+                Box::new(Scene::board(game)),
+            )));
+        }
+
+        let last = self.games.len().saturating_sub(1);
+        if rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            self.selected = (self.selected + 1).min(last);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + COLUMNS as usize).min(last);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = self.selected.saturating_sub(COLUMNS as usize);
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_DELETE) && !self.games.is_empty() {
+            let removed = self.games.remove(self.selected);
+            game.drop_put_aside(&removed.path);
+            self.selected = self.selected.min(self.games.len().saturating_sub(1));
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            let Some(chosen) = self.games.get(self.selected) else {
+                return Transition::Stay;
+            };
+            game.unpause();
+            if !game.is_put_aside_at(&chosen.path) {
+                game.pick_up(&chosen.path);
+            }
+            return Transition::Switch(Scene::board(game));
+        }
+        Transition::Stay
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect, game: &Game)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.95));
+        let title = format!("Ongoing games ({})", self.games.len());
+        d.draw_text(&title, rect.x + 10, rect.y + 10, 20, Color::WHITE);
+        d.draw_text(
+            "Enter to pick up, Delete to remove, N for a new game, Esc to go back",
+            rect.x + 10,
+            rect.y + 34,
+            14,
+            Color::GRAY,
+        );
+        if self.games.is_empty() {
+            d.draw_text(
+                "No games put aside",
+                rect.x + 10,
+                rect.y + HEADER_HEIGHT,
+                16,
+                Color::LIGHTGRAY,
+            );
+            return;
+        }
+
+        let cell_width = (rect.size - 10) / COLUMNS;
+        // thumbnails are a whole number of pixels per square
+        let thumbnail = (cell_width - 20) / 8 * 8;
+        let cell_height = thumbnail + CAPTION_HEIGHT;
+        let rows = ((rect.size - HEADER_HEIGHT) / cell_height).max(1) as usize;
+        let selected_row = self.selected / COLUMNS as usize;
+        let first_row = (selected_row + 1).saturating_sub(rows);
+        let first = first_row * COLUMNS as usize;
+        let shown = rows * COLUMNS as usize;
+        for (i, ongoing) in self.games.iter().enumerate().skip(first).take(shown) {
+            let column = ((i - first) % COLUMNS as usize) as i32;
+            let row = ((i - first) / COLUMNS as usize) as i32;
+            let x = rect.x + 5 + column * cell_width;
+            let y = rect.y + HEADER_HEIGHT + row * cell_height;
+            let board_x = x + (cell_width - thumbnail) / 2;
+            if i == self.selected {
+                d.draw_rectangle(x, y - 4, cell_width, cell_height, highlight.fade(0.5));
+            }
+
+            let saved = &ongoing.game;
+            let bottom = saved
+                .engine_color
+                .map_or(PieceColor::White, |c| c.opposite());
+            let board_rect = BoardRect {
+                x: board_x,
+                y,
+                size: thumbnail,
+                bottom,
+            };
+            game.draw_thumbnail(d, &saved.board, board_rect);
+            if game.is_put_aside_at(&ongoing.path) {
+                d.draw_rectangle_lines(board_x, y, thumbnail, thumbnail, highlight);
+            }
+
+            let players = format!("{} - {}", saved.names.0, saved.names.1);
+            let caption_y = y + thumbnail + 4;
+            d.draw_text(&players, board_x, caption_y, 12, Color::WHITE);
+            let turn = match saved.board.get_turn() {
+                PieceColor::White => "White to move",
+                PieceColor::Black => "Black to move",
+            };
+            let status = match saved.clock {
+                Some((white, black)) => {
+                    format!("{}  {} / {}", turn, format_time(white), format_time(black))
+                }
+                None => turn.to_string(),
+            };
+            d.draw_text(&status, board_x, caption_y + 16, 12, Color::LIGHTGRAY);
+        }
+    }
+}
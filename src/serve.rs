@@ -0,0 +1,487 @@
+//! WebSocket server: `ruche --serve` hosts games that browsers or other ruche instances play
+//! over WebSocket, against each other or against the engine. Every message is a JSON object
+//! whose `type` names it. Clients send:
+//!
+//! ```text
+//! {"type": "join", "game": "club", "name": "Ann", "color": "white", "engine": true}
+//! {"type": "move", "move": "Nf3"}
+//! {"type": "sync"}
+//! {"type": "chat", "text": "good luck"}
+//! ```
+//!
+//! `join` enters the game of that name, creating it from the standard position if needed, and
+//! sits at `color`, or the first free seat without one, or watches when both are taken; with
+//! `engine` the engine takes the other seat. Moves are SAN or coordinates. The server answers:
+//!
+//! ```text
+//! {"type": "joined", "game": "club", "color": "white"}
+//! {"type": "state", "fen": "...", "moves": ["e2e4"], "turn": "black", "result": "*",
+//!  "status": "Game in progress", "white": "Ann", "black": "ruche"}
+//! {"type": "chat", "from": "Ann", "text": "good luck"}
+//! {"type": "error", "message": "It's not your turn"}
+//! ```
+//!
+//! and sends `state` to everyone in the game after each move and whenever someone comes or
+//! goes, `color` being null for a spectator
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tungstenite::{Message, WebSocket};
+
+use crate::board::{Board, PieceColor, START_FEN};
+use crate::engine::{self, Params, SearchLimits, Searcher};
+use crate::lifecycle::{self, GameState};
+use crate::san;
+
+/// Port the server listens on unless another is given
+pub const DEFAULT_PORT: u16 = 8080;
+/// Time a client's thread waits for its next message before sending what others broadcast
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Longest chat message passed on, in characters
+const MAX_CHAT_LEN: usize = 500;
+
+/// A side as the JSON messages write it, `"white"` or `"black"`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Side {
+    White,
+    Black,
+}
+
+impl From<Side> for PieceColor {
+    fn from(side: Side) -> Self {
+        match side {
+            Side::White => PieceColor::White,
+            Side::Black => PieceColor::Black,
+        }
+    }
+}
+
+impl From<PieceColor> for Side {
+    fn from(color: PieceColor) -> Self {
+        match color {
+            PieceColor::White => Side::White,
+            PieceColor::Black => Side::Black,
+        }
+    }
+}
+
+/// Messages sent by clients
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ClientMessage {
+    Join {
+        game: String,
+        #[serde(default)]
+        name: String,
+        #[serde(default)]
+        color: Option<Side>,
+        /// the engine takes the other seat
+        #[serde(default)]
+        engine: bool,
+    },
+    Move {
+        #[serde(rename = "move")]
+        mv: String,
+    },
+    /// asks for the state of the game again
+    Sync,
+    Chat {
+        text: String,
+    },
+}
+
+/// Messages sent to clients
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ServerMessage {
+    Joined {
+        game: String,
+        /// seat taken, none for a spectator
+        color: Option<Side>,
+    },
+    State {
+        fen: String,
+        /// moves played from the standard position, in coordinates
+        moves: Vec<String>,
+        turn: Side,
+        /// as PGN writes it, `*` while the game is played
+        result: &'static str,
+        status: String,
+        white: Option<String>,
+        black: Option<String>,
+    },
+    Chat {
+        from: String,
+        text: String,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Who sits at a side of the board
+#[derive(Debug, Clone)]
+enum Seat {
+    Client { id: usize, name: String },
+    Engine,
+}
+
+impl Seat {
+    fn name(&self) -> String {
+        match self {
+            Seat::Client { name, .. } => name.clone(),
+            Seat::Engine => "ruche".to_string(),
+        }
+    }
+}
+
+/// A game and the clients in it, players and spectators
+struct Room {
+    start: Board,
+    board: Board,
+    /// white's and black's seat
+    seats: [Option<Seat>; 2],
+    /// everyone in the room, by client id
+    clients: HashMap<usize, Sender<ServerMessage>>,
+    /// true while the engine looks for its move
+    searching: bool,
+}
+
+fn seat_index(color: PieceColor) -> usize {
+    match color {
+        PieceColor::White => 0,
+        PieceColor::Black => 1,
+    }
+}
+
+impl Room {
+    fn new() -> Self {
+        let mut board = Board::new();
+        board.load_position(START_FEN.to_string());
+        Self {
+            start: board.clone(),
+            board,
+            seats: [None, None],
+            clients: HashMap::new(),
+            searching: false,
+        }
+    }
+
+    /// Side `id` plays, none for a spectator
+    fn color_of(&self, id: usize) -> Option<PieceColor> {
+        [PieceColor::White, PieceColor::Black]
+            .into_iter()
+            .find(|color| match &self.seats[seat_index(*color)] {
+                Some(Seat::Client { id: seated, .. }) => *seated == id,
+                _ => false,
+            })
+    }
+
+    fn game_state(&self) -> GameState {
+        lifecycle::board_state(&self.start, &self.board).unwrap_or(GameState::InProgress)
+    }
+
+    fn state(&self) -> ServerMessage {
+        let state = self.game_state();
+        ServerMessage::State {
+            fen: self.board.to_fen(),
+            moves: self
+                .board
+                .get_move_history()
+                .iter()
+                .map(|mo| mo.to_string())
+                .collect(),
+            turn: self.board.get_turn().into(),
+            result: state.result(),
+            status: state.to_string(),
+            white: self.seats[0].as_ref().map(Seat::name),
+            black: self.seats[1].as_ref().map(Seat::name),
+        }
+    }
+
+    fn broadcast(&self, msg: ServerMessage) {
+        for client in self.clients.values() {
+            let _ = client.send(msg.clone());
+        }
+    }
+
+    /// Returns true if the engine is to move in a game still being played
+    fn is_engine_turn(&self) -> bool {
+        matches!(
+            self.seats[seat_index(self.board.get_turn())],
+            Some(Seat::Engine)
+        ) && self.game_state() == GameState::InProgress
+    }
+}
+
+/// The position the engine is asked to play in, and the game it is from
+struct EngineJob {
+    game: String,
+    board: Board,
+}
+
+/// Shared by the threads of the clients and the engine's
+struct Server {
+    rooms: Mutex<HashMap<String, Room>>,
+    engine: Mutex<Sender<EngineJob>>,
+    next_id: AtomicUsize,
+}
+
+impl Server {
+    /// Hands the position to the engine if it is to move in `game`
+    fn wake_engine(&self, game: &str, room: &mut Room) {
+        if room.searching || !room.is_engine_turn() {
+            return;
+        }
+        room.searching = true;
+        let job = EngineJob {
+            game: game.to_string(),
+            board: room.board.clone(),
+        };
+        let _ = self.engine.lock().expect("engine lock").send(job);
+    }
+}
+
+/// Plays the engine's moves one search at a time, whatever the game
+fn run_engine(server: Arc<Server>, jobs: Receiver<EngineJob>, movetime: Duration) {
+    let params = Params::load_or_default(&engine::params_path());
+    let mut searcher = Searcher::new(Arc::new(AtomicBool::new(false)), params);
+    for job in jobs {
+        let best = searcher.search(&job.board, SearchLimits::movetime(movetime), |_| {});
+        let mut rooms = server.rooms.lock().expect("rooms lock");
+        let Some(room) = rooms.get_mut(&job.game) else {
+            continue;
+        };
+        room.searching = false;
+        // the game moved on while the engine thought
+        let plies = room.board.get_move_history().len();
+        if plies != job.board.get_move_history().len() {
+            server.wake_engine(&job.game, room);
+            continue;
+        }
+        if let Some(mo) = best {
+            if room.board.play(&mo).is_ok() {
+                room.broadcast(room.state());
+            }
+        }
+    }
+}
+
+/// What the thread of a client keeps between messages
+struct Client {
+    id: usize,
+    /// game joined, if any
+    game: Option<String>,
+    outbox: Sender<ServerMessage>,
+}
+
+impl Client {
+    fn reply(&self, msg: ServerMessage) {
+        let _ = self.outbox.send(msg);
+    }
+
+    fn error(&self, message: &str) {
+        self.reply(ServerMessage::Error {
+            message: message.to_string(),
+        });
+    }
+
+    fn handle(&mut self, server: &Server, msg: ClientMessage) {
+        if let ClientMessage::Join {
+            game,
+            name,
+            color,
+            engine,
+        } = msg
+        {
+            self.join(server, game, name, color, engine);
+            return;
+        }
+        let Some(game) = &self.game else {
+            return self.error("Join a game first");
+        };
+        let mut rooms = server.rooms.lock().expect("rooms lock");
+        let room = rooms
+            .get_mut(game)
+            .expect("the room lives while its clients are in it");
+        match msg {
+            ClientMessage::Join { .. } => unreachable!("handled above"),
+            ClientMessage::Sync => self.reply(room.state()),
+            ClientMessage::Chat { text } => {
+                let from = match room.color_of(self.id) {
+                    Some(color) => room.seats[seat_index(color)]
+                        .as_ref()
+                        .map_or_else(String::new, Seat::name),
+                    None => "Spectator".to_string(),
+                };
+                let text = text.chars().take(MAX_CHAT_LEN).collect();
+                room.broadcast(ServerMessage::Chat { from, text });
+            }
+            ClientMessage::Move { mv } => {
+                if room.color_of(self.id) != Some(room.board.get_turn()) {
+                    return self.error("It's not your turn");
+                }
+                if room.game_state() != GameState::InProgress {
+                    return self.error("The game is over");
+                }
+                let mo = match san::parse_move(&room.board, &mv) {
+                    Ok(mo) => mo,
+                    Err(e) => return self.error(&e.to_string()),
+                };
+                if room.board.play(&mo).is_err() {
+                    return self.error(&format!("{} can't be played", mv));
+                }
+                room.broadcast(room.state());
+                server.wake_engine(game, room);
+            }
+        }
+    }
+
+    fn join(
+        &mut self,
+        server: &Server,
+        game: String,
+        name: String,
+        color: Option<Side>,
+        engine: bool,
+    ) {
+        if game.trim().is_empty() {
+            return self.error("The game needs a name");
+        }
+        self.leave(server);
+        let mut rooms = server.rooms.lock().expect("rooms lock");
+        let room = rooms.entry(game.clone()).or_insert_with(Room::new);
+        let free = |color: PieceColor| room.seats[seat_index(color)].is_none();
+        let seat = match color.map(PieceColor::from) {
+            Some(color) => Some(color).filter(|color| free(*color)),
+            None => [PieceColor::White, PieceColor::Black]
+                .into_iter()
+                .find(|color| free(*color)),
+        };
+        if let Some(color) = seat {
+            let name = match name.trim() {
+                "" => format!("{:?}", color),
+                name => name.to_string(),
+            };
+            room.seats[seat_index(color)] = Some(Seat::Client { id: self.id, name });
+            let other = seat_index(color.opposite());
+            if engine && room.seats[other].is_none() {
+                room.seats[other] = Some(Seat::Engine);
+            }
+        }
+        room.clients.insert(self.id, self.outbox.clone());
+        self.reply(ServerMessage::Joined {
+            game: game.clone(),
+            color: seat.map(Side::from),
+        });
+        room.broadcast(room.state());
+        server.wake_engine(&game, room);
+        self.game = Some(game);
+    }
+
+    /// Leaves the game joined, freeing the seat for someone else or for a reconnection
+    fn leave(&mut self, server: &Server) {
+        let Some(game) = self.game.take() else {
+            return;
+        };
+        let mut rooms = server.rooms.lock().expect("rooms lock");
+        let Some(room) = rooms.get_mut(&game) else {
+            return;
+        };
+        room.clients.remove(&self.id);
+        if let Some(color) = room.color_of(self.id) {
+            room.seats[seat_index(color)] = None;
+        }
+        if room.clients.is_empty() {
+            rooms.remove(&game);
+        } else {
+            room.broadcast(room.state());
+        }
+    }
+}
+
+/// Talks to one client until it goes away
+fn serve_client(server: &Server, stream: TcpStream) -> Result<(), Box<tungstenite::Error>> {
+    let mut ws: WebSocket<TcpStream> = tungstenite::accept(stream).map_err(|e| match e {
+        tungstenite::HandshakeError::Failure(e) => Box::new(e),
+        tungstenite::HandshakeError::Interrupted(_) => Box::new(tungstenite::Error::Io(
+            io::Error::new(io::ErrorKind::WouldBlock, "handshake"),
+        )),
+    })?;
+    // reads give up now and then so broadcasts go out while the client is quiet
+    ws.get_mut()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .map_err(|e| Box::new(e.into()))?;
+
+    let (outbox, broadcasts) = mpsc::channel();
+    let mut client = Client {
+        id: server.next_id.fetch_add(1, Ordering::Relaxed),
+        game: None,
+        outbox,
+    };
+    let result = 'serve: loop {
+        match ws.read() {
+            Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(msg) => client.handle(server, msg),
+                Err(e) => client.error(&format!("Invalid message: {}", e)),
+            },
+            Ok(Message::Close(_)) => break Ok(()),
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(tungstenite::Error::ConnectionClosed) => break Ok(()),
+            Err(e) => break Err(Box::new(e)),
+        }
+        for msg in broadcasts.try_iter() {
+            let json = serde_json::to_string(&msg).expect("messages are plain data");
+            if let Err(e) = ws.send(Message::text(json)) {
+                break 'serve Err(Box::new(e));
+            }
+        }
+    };
+    client.leave(server);
+    result
+}
+
+/// Serves games on `port` until the process is stopped, the engine taking `movetime` per move
+pub fn run(port: u16, movetime: Duration) -> io::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    let (engine, jobs) = mpsc::channel();
+    let server = Arc::new(Server {
+        rooms: Mutex::new(HashMap::new()),
+        engine: Mutex::new(engine),
+        next_id: AtomicUsize::new(0),
+    });
+    let engine_server = Arc::clone(&server);
+    std::thread::spawn(move || run_engine(engine_server, jobs, movetime));
+
+    eprintln!("Serving games on ws://0.0.0.0:{}", port);
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("Failed to accept a connection: {}", e);
+                continue;
+            }
+        };
+        let server = Arc::clone(&server);
+        std::thread::spawn(move || {
+            let peer = stream.peer_addr().ok();
+            if let Err(e) = serve_client(&server, stream) {
+                tracing::info!("Client {:?} left: {}", peer, e);
+            }
+        });
+    }
+    Ok(())
+}
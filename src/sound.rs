@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use raylib::core::audio::{RaylibAudio, Sound};
+
+use crate::events::BoardEvent;
+use crate::packs::PackManager;
+
+/// Sounds a theme can provide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundKind {
+    Move,
+    Capture,
+    Check,
+    /// played by the clock when a player is running out of time
+    LowTime,
+}
+
+impl SoundKind {
+    const ALL: [SoundKind; 4] = [
+        SoundKind::Move,
+        SoundKind::Capture,
+        SoundKind::Check,
+        SoundKind::LowTime,
+    ];
+
+    /// File name of the sound inside a theme pack
+    fn file_name(self) -> &'static str {
+        match self {
+            SoundKind::Move => "sounds/move.wav",
+            SoundKind::Capture => "sounds/capture.wav",
+            SoundKind::Check => "sounds/check.wav",
+            SoundKind::LowTime => "sounds/low_time.wav",
+        }
+    }
+}
+
+/// The sound files of a theme, resolved through the [PackManager] so missing ones fall back to
+/// the default pack
+#[derive(Debug, Clone, Default)]
+pub struct SoundSet {
+    paths: HashMap<SoundKind, PathBuf>,
+}
+
+impl SoundSet {
+    pub fn resolve(packs: &PackManager, theme: &str) -> Self {
+        let paths = SoundKind::ALL
+            .iter()
+            .filter_map(|kind| Some((*kind, packs.resolve(theme, kind.file_name())?)))
+            .collect();
+        Self { paths }
+    }
+}
+
+/// Plays the sounds of the current theme in response to board events
+pub struct SoundPlayer {
+    audio: RaylibAudio,
+    sounds: HashMap<SoundKind, Sound>,
+    pub enabled: bool,
+}
+
+impl SoundPlayer {
+    pub fn new() -> Self {
+        Self {
+            audio: RaylibAudio::init_audio_device(),
+            sounds: HashMap::new(),
+            enabled: true,
+        }
+    }
+
+    /// Replaces the loaded sounds with the ones from `set`
+    pub fn load(&mut self, set: &SoundSet) {
+        self.sounds.clear();
+        for (kind, path) in set.paths.iter() {
+            match Sound::load_sound(&path.to_string_lossy()) {
+                Ok(sound) => {
+                    self.sounds.insert(*kind, sound);
+                }
+                Err(e) => tracing::warn!("Failed to load sound {:?}: {}", path, e),
+            }
+        }
+    }
+
+    pub fn play(&mut self, kind: SoundKind) {
+        if !self.enabled {
+            return;
+        }
+        if let Some(sound) = self.sounds.get(&kind) {
+            self.audio.play_sound(sound);
+        }
+    }
+
+    pub fn on_event(&mut self, event: &BoardEvent) {
+        if let BoardEvent::MoveMade { capture, check, .. } = event {
+            let kind = if *check {
+                SoundKind::Check
+            } else if *capture {
+                SoundKind::Capture
+            } else {
+                SoundKind::Move
+            };
+            self.play(kind);
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! Finished games of the user against the engine, an external engine or a network player,
+//! kept in a JSON file for the statistics screen: results overall, by color and by opening,
+//! and an informal rating from the games against the levels of the built-in engine
+//!
+//! ```json
+//! {
+//!   "games": [
+//!     {
+//!       "date": "2024.05.01",
+//!       "opponent": "ruche",
+//!       "color": "white",
+//!       "outcome": "win",
+//!       "opening": "C50 Italian Game",
+//!       "time_control": "5+3",
+//!       "level": "Casual"
+//!     }
+//!   ]
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::PieceColor;
+use crate::engine::Difficulty;
+
+/// Statistics read and written when `RUCHE_STATS` doesn't name another file
+const STATS_FILE: &str = "stats.json";
+/// Rating before the first game against the engine
+const START_RATING: f64 = 1200.0;
+/// How far a single game moves the rating
+const K_FACTOR: f64 = 32.0;
+
+/// Returns the path of the statistics, `RUCHE_STATS` or `stats.json` in the working directory
+pub fn stats_path() -> PathBuf {
+    std::env::var("RUCHE_STATS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(STATS_FILE))
+}
+
+/// Why the statistics couldn't be read or written
+#[derive(Debug)]
+pub enum StatsError {
+    Io(std::io::Error),
+    /// The file isn't statistics in JSON
+    Json(serde_json::Error),
+}
+
+impl Display for StatsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatsError::Io(e) => write!(f, "{}", e),
+            StatsError::Json(e) => write!(f, "invalid statistics: {}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for StatsError {
+    fn from(value: std::io::Error) -> Self {
+        StatsError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for StatsError {
+    fn from(value: serde_json::Error) -> Self {
+        StatsError::Json(value)
+    }
+}
+
+/// Side the user played
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    White,
+    Black,
+}
+
+impl From<PieceColor> for Side {
+    fn from(value: PieceColor) -> Self {
+        match value {
+            PieceColor::White => Side::White,
+            PieceColor::Black => Side::Black,
+        }
+    }
+}
+
+/// How a game went for the user
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Outcome {
+    /// Points scored, one for a win and a half for a draw
+    fn score(self) -> f64 {
+        match self {
+            Outcome::Win => 1.0,
+            Outcome::Draw => 0.5,
+            Outcome::Loss => 0.0,
+        }
+    }
+}
+
+/// A finished game
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub date: String,
+    pub opponent: String,
+    pub color: Side,
+    pub outcome: Outcome,
+    /// ECO code and name of the opening, when the table knows it
+    #[serde(default)]
+    pub opening: Option<String>,
+    #[serde(default)]
+    pub time_control: Option<String>,
+    /// level of the built-in engine, for the games against it
+    #[serde(default)]
+    pub level: Option<String>,
+}
+
+/// Wins, draws and losses of some games
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl Tally {
+    fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Draw => self.draws += 1,
+            Outcome::Loss => self.losses += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    /// Share of the points scored, in percent
+    pub fn score(&self) -> u32 {
+        (self.wins * 2 + self.draws) * 50 / self.games().max(1)
+    }
+}
+
+/// Rating the built-in engine is taken to play at on `level`, a rough guess for the informal
+/// rating of the user
+fn level_rating(level: Difficulty) -> f64 {
+    match level {
+        Difficulty::Beginner => 800.0,
+        Difficulty::Casual => 1100.0,
+        Difficulty::Intermediate => 1400.0,
+        Difficulty::Advanced => 1700.0,
+        Difficulty::Master => 2000.0,
+    }
+}
+
+/// The games recorded, oldest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub games: Vec<GameRecord>,
+}
+
+impl Stats {
+    /// Reads the statistics at `path`, none being recorded yet when there is no file
+    pub fn open(path: &Path) -> Result<Self, StatsError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Ok(serde_json::from_str(&text)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), StatsError> {
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Adds `record` to the statistics at `path`
+    pub fn append(path: &Path, record: GameRecord) -> Result<(), StatsError> {
+        let mut stats = Self::open(path)?;
+        stats.games.push(record);
+        stats.save(path)
+    }
+
+    pub fn total(&self) -> Tally {
+        let mut tally = Tally::default();
+        for game in &self.games {
+            tally.add(game.outcome);
+        }
+        tally
+    }
+
+    /// Results with white and with black
+    pub fn by_color(&self) -> (Tally, Tally) {
+        let (mut white, mut black) = (Tally::default(), Tally::default());
+        for game in &self.games {
+            match game.color {
+                Side::White => white.add(game.outcome),
+                Side::Black => black.add(game.outcome),
+            }
+        }
+        (white, black)
+    }
+
+    /// Results by opening, most played first. Games the table has no name for aren't counted
+    pub fn by_opening(&self) -> Vec<(String, Tally)> {
+        let mut openings = HashMap::<&str, Tally>::new();
+        for game in &self.games {
+            if let Some(opening) = &game.opening {
+                openings.entry(opening).or_default().add(game.outcome);
+            }
+        }
+        let mut openings = openings
+            .into_iter()
+            .map(|(name, tally)| (name.to_string(), tally))
+            .collect::<Vec<_>>();
+        openings.sort_by(|a, b| b.1.games().cmp(&a.1.games()).then(a.0.cmp(&b.0)));
+        openings
+    }
+
+    /// Informal rating after each game against a level of the built-in engine, updated as in
+    /// Elo against the rating taken for the level
+    pub fn rating_trend(&self) -> Vec<i32> {
+        let mut rating = START_RATING;
+        self.games
+            .iter()
+            .filter_map(|game| {
+                let level = Difficulty::ALL
+                    .into_iter()
+                    .find(|d| Some(d.name()) == game.level.as_deref())?;
+                let expected = 1.0 / (1.0 + 10f64.powf((level_rating(level) - rating) / 400.0));
+                rating += K_FACTOR * (game.outcome.score() - expected);
+                Some(rating.round() as i32)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,293 @@
+//! Named position suites: positions described in JSON with what ruche should find in them, the
+//! number of legal moves, moves that must or must not be legal, the best move and whether the
+//! game is over. Lighter than EPD for pinning down castling and en passant edge cases
+//!
+//! ```json
+//! {
+//!   "name": "edge cases",
+//!   "positions": [
+//!     {
+//!       "id": "en passant out of check",
+//!       "fen": "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+//!       "legal_moves": 9,
+//!       "includes": ["exd3"],
+//!       "state": "check"
+//!     }
+//!   ]
+//! }
+//! ```
+
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::board::{Board, Move};
+use crate::engine::{Params, SearchLimits, Searcher};
+use crate::san;
+
+/// Why a suite couldn't be loaded
+#[derive(Debug)]
+pub enum SuiteError {
+    Io(std::io::Error),
+    /// The file isn't a suite in JSON
+    Json(serde_json::Error),
+}
+
+impl Display for SuiteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SuiteError::Io(e) => write!(f, "{}", e),
+            SuiteError::Json(e) => write!(f, "invalid suite: {}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for SuiteError {
+    fn from(value: std::io::Error) -> Self {
+        SuiteError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SuiteError {
+    fn from(value: serde_json::Error) -> Self {
+        SuiteError::Json(value)
+    }
+}
+
+/// Whether the game goes on in a position, and how it ended if it doesn't
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameStatus {
+    Ongoing,
+    Check,
+    Checkmate,
+    Stalemate,
+}
+
+impl GameStatus {
+    pub fn of(board: &Board) -> Self {
+        let in_check = board.is_in_check(board.get_turn());
+        match (board.legal_moves().next().is_some(), in_check) {
+            (true, false) => GameStatus::Ongoing,
+            (true, true) => GameStatus::Check,
+            (false, true) => GameStatus::Checkmate,
+            (false, false) => GameStatus::Stalemate,
+        }
+    }
+}
+
+/// A position and what is expected of it, every expectation is optional
+#[derive(Debug, Clone, Deserialize)]
+pub struct SuitePosition {
+    pub id: String,
+    pub fen: String,
+    /// moves in SAN or coordinates played from the FEN before checking, to reach states a FEN can't describe
+    #[serde(default)]
+    pub moves: Vec<String>,
+    pub legal_moves: Option<usize>,
+    /// moves in SAN or coordinates that must be legal
+    #[serde(default)]
+    pub includes: Vec<String>,
+    /// moves in SAN or coordinates, e.g. `e1g1`, that must not be legal
+    #[serde(default)]
+    pub excludes: Vec<String>,
+    /// moves in SAN of which the search must find one
+    #[serde(default)]
+    pub best_move: Vec<String>,
+    pub state: Option<GameStatus>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Suite {
+    pub name: String,
+    pub positions: Vec<SuitePosition>,
+}
+
+impl Suite {
+    pub fn load(path: &Path) -> Result<Self, SuiteError> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
+/// One expectation of a position and what was found
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub check: String,
+    pub expected: String,
+    pub found: String,
+    pub passed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionReport {
+    pub id: String,
+    pub passed: bool,
+    pub checks: Vec<Check>,
+}
+
+/// Machine readable result of a suite
+#[derive(Debug, Clone, Serialize)]
+pub struct SuiteReport {
+    pub suite: String,
+    pub passed: usize,
+    pub total: usize,
+    pub positions: Vec<PositionReport>,
+}
+
+/// Finds the legal move written as `text`, in SAN or in coordinates
+fn find_move(board: &Board, text: &str) -> Option<Move> {
+    san::parse_san(board, text)
+        .ok()
+        .or_else(|| board.legal_moves().find(|m| m.to_string() == text))
+}
+
+/// Checks every expectation of `position`
+pub fn run_position(
+    position: &SuitePosition,
+    limits: SearchLimits,
+    params: &Params,
+) -> PositionReport {
+    let mut checks = vec![];
+    let mut board = Board::new();
+    board.load_position(position.fen.clone());
+    for text in position.moves.iter() {
+        let played = find_move(&board, text).is_some_and(|mv| board.play(&mv).is_ok());
+        if !played {
+            check(
+                &mut checks,
+                "moves",
+                text.clone(),
+                "illegal".to_string(),
+                false,
+            );
+            return report(position, checks);
+        }
+    }
+
+    if let Some(expected) = position.legal_moves {
+        let found = board.legal_moves().count();
+        check(
+            &mut checks,
+            "legal_moves",
+            expected.to_string(),
+            found.to_string(),
+            found == expected,
+        );
+    }
+    for text in position.includes.iter() {
+        let legal = find_move(&board, text).is_some();
+        let found = if legal { "legal" } else { "illegal" };
+        check(
+            &mut checks,
+            "includes",
+            text.clone(),
+            found.to_string(),
+            legal,
+        );
+    }
+    for text in position.excludes.iter() {
+        let legal = find_move(&board, text).is_some();
+        let found = if legal { "legal" } else { "illegal" };
+        check(
+            &mut checks,
+            "excludes",
+            text.clone(),
+            found.to_string(),
+            !legal,
+        );
+    }
+    if let Some(expected) = position.state {
+        let found = GameStatus::of(&board);
+        check(
+            &mut checks,
+            "state",
+            format!("{:?}", expected),
+            format!("{:?}", found),
+            found == expected,
+        );
+    }
+    if !position.best_move.is_empty() {
+        let mut searcher = Searcher::new(Arc::new(AtomicBool::new(false)), params.clone());
+        let found = searcher.search(&board, limits, |_| {});
+        let expected = position
+            .best_move
+            .iter()
+            .filter_map(|m| find_move(&board, m))
+            .collect::<Vec<_>>();
+        check(
+            &mut checks,
+            "best_move",
+            position.best_move.join(" "),
+            found
+                .as_ref()
+                .map_or("none".to_string(), |mv| san::to_san(&board, mv)),
+            found.is_some_and(|mv| expected.contains(&mv)),
+        );
+    }
+
+    if checks.is_empty() {
+        check(
+            &mut checks,
+            "nothing",
+            "an expectation".to_string(),
+            "none".to_string(),
+            false,
+        );
+    }
+    report(position, checks)
+}
+
+fn check(checks: &mut Vec<Check>, name: &str, expected: String, found: String, passed: bool) {
+    checks.push(Check {
+        check: name.to_string(),
+        expected,
+        found,
+        passed,
+    });
+}
+
+fn report(position: &SuitePosition, checks: Vec<Check>) -> PositionReport {
+    PositionReport {
+        id: position.id.clone(),
+        passed: checks.iter().all(|c| c.passed),
+        checks,
+    }
+}
+
+/// Runs every position of the suite at `path`, printing pass/fail per position to stderr
+pub fn run_suite(
+    path: &Path,
+    limits: SearchLimits,
+    params: &Params,
+) -> Result<SuiteReport, SuiteError> {
+    let suite = Suite::load(path)?;
+    let mut positions = vec![];
+    for position in suite.positions.iter() {
+        let report = run_position(position, limits, params);
+        eprintln!(
+            "{} {}",
+            if report.passed { "PASS" } else { "FAIL" },
+            report.id
+        );
+        for check in report.checks.iter().filter(|c| !c.passed) {
+            eprintln!(
+                "    {}: expected {}, found {}",
+                check.check, check.expected, check.found
+            );
+        }
+        positions.push(report);
+    }
+
+    let passed = positions.iter().filter(|p| p.passed).count();
+    eprintln!("{}/{} passed", passed, positions.len());
+    Ok(SuiteReport {
+        suite: suite.name,
+        passed,
+        total: positions.len(),
+        positions,
+    })
+}
@@ -0,0 +1,198 @@
+use raylib::core::color::Color;
+
+use crate::packs::{PackManager, DEFAULT_PACK};
+use crate::sound::SoundSet;
+
+/// Directory the theme packs live in
+pub const THEMES_DIR: &str = "./resource/themes";
+
+/// Colors of the squares and of what is drawn on them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub name: &'static str,
+    pub light: Color,
+    pub dark: Color,
+    /// square under the mouse
+    pub cursor: Color,
+    /// square of the selected piece
+    pub selection: Color,
+    /// dots and rings on the squares the selected piece can move to
+    pub legal_move: Color,
+    /// squares the last move left and reached, drawn over the square
+    pub last_move: Color,
+    /// glow under a king in check
+    pub check: Color,
+    /// squares of the replies queued as conditional moves, drawn over the square
+    pub premove: Color,
+    /// marks the selection, the last move, the queued replies and the check with outlines and
+    /// rings too, so that they can be told apart without relying on their hue
+    pub shapes: bool,
+}
+
+/// Palettes to choose from, the first being the default
+pub const PALETTES: [Palette; 6] = [
+    Palette {
+        name: "Green",
+        light: Color::new(235, 236, 208, 255),
+        dark: Color::new(115, 149, 82, 255),
+        cursor: Color::new(255, 238, 128, 255),
+        selection: Color::new(138, 183, 255, 255),
+        legal_move: Color::new(0, 0, 0, 51),
+        last_move: Color::new(255, 255, 51, 100),
+        check: Color::new(224, 48, 48, 255),
+        premove: Color::new(20, 85, 30, 128),
+        shapes: false,
+    },
+    Palette {
+        name: "Blue",
+        light: Color::new(222, 227, 230, 255),
+        dark: Color::new(140, 162, 173, 255),
+        cursor: Color::new(255, 238, 128, 255),
+        selection: Color::new(100, 140, 220, 255),
+        legal_move: Color::new(0, 0, 0, 51),
+        last_move: Color::new(155, 199, 0, 105),
+        check: Color::new(224, 48, 48, 255),
+        premove: Color::new(60, 40, 120, 128),
+        shapes: false,
+    },
+    Palette {
+        name: "Brown",
+        light: Color::new(240, 217, 181, 255),
+        dark: Color::new(181, 136, 99, 255),
+        cursor: Color::new(255, 238, 128, 255),
+        selection: Color::new(138, 183, 255, 255),
+        legal_move: Color::new(0, 0, 0, 51),
+        last_move: Color::new(155, 199, 0, 105),
+        check: Color::new(224, 48, 48, 255),
+        premove: Color::new(20, 85, 30, 128),
+        shapes: false,
+    },
+    Palette {
+        name: "High contrast",
+        light: Color::new(255, 255, 255, 255),
+        dark: Color::new(80, 80, 80, 255),
+        cursor: Color::new(255, 255, 0, 255),
+        selection: Color::new(0, 160, 255, 255),
+        legal_move: Color::new(255, 0, 255, 170),
+        last_move: Color::new(255, 140, 0, 140),
+        check: Color::new(255, 0, 0, 255),
+        premove: Color::new(0, 200, 0, 150),
+        shapes: true,
+    },
+    // blue and orange stay distinct without the green cone, red and green don't
+    Palette {
+        name: "Deuteranopia",
+        light: Color::new(238, 238, 228, 255),
+        dark: Color::new(86, 120, 180, 255),
+        cursor: Color::new(240, 228, 66, 255),
+        selection: Color::new(230, 159, 0, 255),
+        legal_move: Color::new(0, 0, 0, 90),
+        last_move: Color::new(240, 228, 66, 120),
+        check: Color::new(213, 94, 0, 255),
+        premove: Color::new(204, 121, 167, 140),
+        shapes: true,
+    },
+    // reds look dark without the red cone, the check is marked with a bright yellow instead
+    Palette {
+        name: "Protanopia",
+        light: Color::new(240, 240, 240, 255),
+        dark: Color::new(110, 125, 150, 255),
+        cursor: Color::new(86, 180, 233, 255),
+        selection: Color::new(0, 114, 178, 255),
+        legal_move: Color::new(0, 0, 0, 90),
+        last_move: Color::new(230, 159, 0, 120),
+        check: Color::new(255, 230, 0, 255),
+        premove: Color::new(0, 158, 115, 140),
+        shapes: true,
+    },
+];
+
+impl Default for Palette {
+    fn default() -> Self {
+        PALETTES[0]
+    }
+}
+
+impl Palette {
+    /// The palette called `name`, ignoring case
+    pub fn named(name: &str) -> Option<Self> {
+        PALETTES
+            .into_iter()
+            .find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The next palette, or the previous one for a negative `delta`, wrapping around
+    pub fn step(self, delta: isize) -> Self {
+        let idx = PALETTES.iter().position(|p| *p == self).unwrap_or(0) as isize;
+        PALETTES[(idx + delta).rem_euclid(PALETTES.len() as isize) as usize]
+    }
+}
+
+/// How big the marks drawn on the squares are: the legal move dots and rings, and the outlines
+/// of palettes marking with shapes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndicatorSize {
+    #[default]
+    Normal,
+    Large,
+    ExtraLarge,
+}
+
+impl IndicatorSize {
+    pub const ALL: [IndicatorSize; 3] = [
+        IndicatorSize::Normal,
+        IndicatorSize::Large,
+        IndicatorSize::ExtraLarge,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IndicatorSize::Normal => "Normal",
+            IndicatorSize::Large => "Large",
+            IndicatorSize::ExtraLarge => "Extra large",
+        }
+    }
+
+    /// The next size, or the previous one for a negative `delta`, stopping at either end
+    pub fn step(self, delta: isize) -> Self {
+        let idx = Self::ALL.iter().position(|s| *s == self).unwrap_or(0) as isize;
+        Self::ALL[(idx + delta).clamp(0, Self::ALL.len() as isize - 1) as usize]
+    }
+
+    /// Factor the marks are drawn bigger by
+    pub fn scale(self) -> f32 {
+        match self {
+            IndicatorSize::Normal => 1.0,
+            IndicatorSize::Large => 1.4,
+            IndicatorSize::ExtraLarge => 1.8,
+        }
+    }
+}
+
+/// A named look and feel for the board, its assets are resolved through the [PackManager]
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub sounds: SoundSet,
+    pub palette: Palette,
+}
+
+impl Theme {
+    pub fn load(packs: &PackManager, name: &str, palette: Palette) -> Self {
+        Self {
+            name: name.to_string(),
+            sounds: SoundSet::resolve(packs, name),
+            palette,
+        }
+    }
+
+    /// Returns the name of the theme after `current`, wrapping around
+    pub fn next_name(packs: &PackManager, current: &str) -> String {
+        let mut names = packs.packs();
+        if !names.iter().any(|n| n == DEFAULT_PACK) {
+            names.insert(0, DEFAULT_PACK.to_string());
+        }
+        let idx = names.iter().position(|n| n == current).map_or(0, |i| i + 1);
+        names[idx % names.len()].clone()
+    }
+}
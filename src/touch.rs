@@ -0,0 +1,76 @@
+//! Touch input mapped onto the mouse's: putting a finger down picks up or selects like a left
+//! click and lifting it drops like releasing the button, so tapping a piece then a square moves
+//! it as clicking does. Holding the finger still draws annotations like the right button.
+//!
+//! On the desktop raylib reports the mouse as the first touch point, and touchscreens click
+//! through the system, so only the holds are taken from touch there
+
+use std::time::{Duration, Instant};
+
+use raylib::core::math::Vector2;
+use raylib::core::RaylibHandle;
+
+/// How long a finger stays still before it draws annotations instead
+const HOLD_TIME: Duration = Duration::from_millis(500);
+/// How far in pixels a finger can wander and still be held
+const HOLD_SLOP: f32 = 10.0;
+
+/// Returns true if the platform delivers touches apart from the mouse
+pub const SEPARATE_TOUCH: bool = cfg!(any(target_os = "android", target_arch = "wasm32"));
+
+/// What the finger did this frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TouchEvent {
+    /// the finger went down, like pressing the left button
+    Press,
+    /// the finger was lifted before the hold, like releasing the left button
+    Release,
+    /// the finger stayed still long enough, like pressing the right button
+    HoldStart,
+    /// the held finger was lifted, like releasing the right button
+    HoldEnd,
+}
+
+/// Follows the first finger on the screen from frame to frame
+#[derive(Debug, Default)]
+pub struct Touch {
+    /// where and when the finger went down
+    start: Option<(Vector2, Instant)>,
+    /// the finger turned into a hold
+    holding: bool,
+}
+
+impl Touch {
+    /// Where the first finger is, none when the screen isn't touched
+    pub fn position(rl: &RaylibHandle) -> Option<Vector2> {
+        (rl.get_touch_points_count() > 0).then(|| rl.get_touch_position(0))
+    }
+
+    /// What the finger did since the last frame
+    pub fn poll(&mut self, rl: &RaylibHandle) -> Option<TouchEvent> {
+        match (self.start, Self::position(rl)) {
+            (None, Some(at)) => {
+                self.start = Some((at, Instant::now()));
+                self.holding = false;
+                Some(TouchEvent::Press)
+            }
+            (Some((from, since)), Some(at)) => {
+                let still = (at.x - from.x).hypot(at.y - from.y) < HOLD_SLOP;
+                if !self.holding && still && since.elapsed() >= HOLD_TIME {
+                    self.holding = true;
+                    return Some(TouchEvent::HoldStart);
+                }
+                None
+            }
+            (Some(_), None) => {
+                self.start = None;
+                Some(if self.holding {
+                    TouchEvent::HoldEnd
+                } else {
+                    TouchEvent::Release
+                })
+            }
+            (None, None) => None,
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Universal Chess Interface: `ruche --uci` reads commands on stdin and answers on stdout so
+//! the engine can be driven by a GUI or a tournament manager such as Cut Chess or Arena
+
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use crate::board::{Board, PieceColor, START_FEN};
+use crate::engine::{self, Params, SearchInfo, SearchLimits, Searcher};
+
+#[cfg(test)]
+mod uci_tests;
+
+/// Most lines the engine reports with the `MultiPV` option
+const MAX_MULTIPV: usize = 10;
+
+/// Search running on its own thread, so `stop` and `isready` are answered while it thinks
+struct RunningSearch {
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl RunningSearch {
+    /// Stops the search and waits for its `bestmove`
+    fn finish(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.thread.join();
+    }
+}
+
+/// Formats a search report as an `info` line
+fn info_line(info: &SearchInfo) -> String {
+    let score = match info.mate_in() {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", info.score),
+    };
+    format!(
+        "info depth {} multipv {} score {} nodes {} nps {} time {} pv {}",
+        info.depth,
+        info.multipv,
+        score,
+        info.nodes,
+        info.nps,
+        info.elapsed.as_millis(),
+        info.pv_string()
+    )
+}
+
+/// Why a `position` command couldn't be set up
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PositionError {
+    /// Neither `startpos` nor `fen` follows `position`
+    Malformed,
+    /// A move of the list that isn't legal where it is played
+    IllegalMove(String),
+}
+
+impl std::fmt::Display for PositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PositionError::Malformed => write!(f, "expected startpos or fen"),
+            PositionError::IllegalMove(mv) => write!(f, "illegal move {}", mv),
+        }
+    }
+}
+
+/// Sets up the position of `position startpos|fen <fen> [moves <move>...]`, the moves being
+/// matched against the legal moves of the position they are played in
+fn parse_position(args: &[&str]) -> Result<Board, PositionError> {
+    let moves_at = args
+        .iter()
+        .position(|a| *a == "moves")
+        .unwrap_or(args.len());
+    let fen = match args.first() {
+        Some(&"startpos") => START_FEN.to_string(),
+        Some(&"fen") => args[1..moves_at].join(" "),
+        _ => return Err(PositionError::Malformed),
+    };
+
+    let mut board = Board::new();
+    board.load_position(fen);
+    for text in args.iter().skip(moves_at + 1) {
+        let illegal = || PositionError::IllegalMove(text.to_string());
+        let mv = board
+            .legal_moves()
+            .find(|m| m.to_string() == *text)
+            .ok_or_else(illegal)?;
+        board.play(&mv).map_err(|_| illegal())?;
+    }
+    Ok(board)
+}
+
+/// The standard starting position, which the engine falls back to
+fn start_position() -> Board {
+    let mut board = Board::new();
+    board.load_position(START_FEN.to_string());
+    board
+}
+
+/// Splits `setoption name <name> value <value>` into the name and the value
+fn parse_option<'a>(args: &[&'a str]) -> Option<(&'a str, &'a str)> {
+    let value_at = args.iter().position(|a| *a == "value")?;
+    if args.first() != Some(&"name") || value_at < 2 {
+        return None;
+    }
+    Some((args[1], *args.get(value_at + 1)?))
+}
+
+/// Reads the limits of `go`: `depth`, `movetime`, `infinite` or the clock with `wtime`,
+/// `btime`, `winc`, `binc` and `movestogo`
+fn parse_go(args: &[&str], turn: PieceColor) -> SearchLimits {
+    let value = |name: &str| {
+        let idx = args.iter().position(|a| *a == name)?;
+        args.get(idx + 1)?.parse::<u64>().ok()
+    };
+    let millis = |name: &str| value(name).map(Duration::from_millis);
+
+    let (time, increment) = match turn {
+        PieceColor::White => ("wtime", "winc"),
+        PieceColor::Black => ("btime", "binc"),
+    };
+
+    let mut limits = if args.contains(&"infinite") {
+        SearchLimits::infinite()
+    } else if let Some(movetime) = millis("movetime") {
+        SearchLimits::movetime(movetime)
+    } else if let Some(remaining) = millis(time) {
+        SearchLimits::for_clock(
+            remaining,
+            millis(increment).unwrap_or_default(),
+            value("movestogo").map(|m| m as u32),
+        )
+    } else if value("depth").is_some() {
+        SearchLimits::infinite()
+    } else {
+        SearchLimits::default()
+    };
+    if let Some(depth) = value("depth") {
+        limits.depth = depth as u32;
+    }
+    limits
+}
+
+/// Answers UCI commands on stdin until `quit` or the end of input
+pub fn run() {
+    let params = Params::load_or_default(&engine::params_path());
+    let mut board = start_position();
+    let mut search: Option<RunningSearch> = None;
+    let mut multipv = 1;
+    let mut threads = 1;
+
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let Some((command, args)) = words.split_first() else {
+            continue;
+        };
+
+        match *command {
+            "uci" => {
+                println!("id name ruche {}", env!("CARGO_PKG_VERSION"));
+                println!("id author SaHHiiLL");
+                println!(
+                    "option name MultiPV type spin default 1 min 1 max {}",
+                    MAX_MULTIPV
+                );
+                println!(
+                    "option name Threads type spin default 1 min 1 max {}",
+                    engine::MAX_THREADS
+                );
+                println!("uciok");
+            }
+            "isready" => println!("readyok"),
+            "setoption" => match parse_option(args) {
+                Some(("MultiPV", value)) => match value.parse::<usize>() {
+                    Ok(lines) => multipv = lines.clamp(1, MAX_MULTIPV),
+                    Err(_) => println!("info string invalid MultiPV: {}", value),
+                },
+                Some(("Threads", value)) => match value.parse::<usize>() {
+                    Ok(count) => threads = count.clamp(1, engine::MAX_THREADS),
+                    Err(_) => println!("info string invalid Threads: {}", value),
+                },
+                _ => println!("info string unknown option: {}", line),
+            },
+            "ucinewgame" => {
+                if let Some(running) = search.take() {
+                    running.finish();
+                }
+                board = parse_position(&["startpos"]).expect("the starting position is valid");
+            }
+            "position" => {
+                if let Some(running) = search.take() {
+                    running.finish();
+                }
+                // a position that can't be set up leaves the starting position, not the last one
+                board = parse_position(args).unwrap_or_else(|e| {
+                    println!("info string invalid position, {}: {}", e, line);
+                    start_position()
+                });
+            }
+            "go" => {
+                if let Some(running) = search.take() {
+                    running.finish();
+                }
+                let limits = parse_go(args, board.get_turn())
+                    .with_multipv(multipv)
+                    .with_threads(threads);
+                let stop = Arc::new(AtomicBool::new(false));
+                let thread_stop = stop.clone();
+                let thread_board = board.clone();
+                let thread_params = params.clone();
+                let thread = std::thread::spawn(move || {
+                    let mut searcher = Searcher::new(thread_stop, thread_params);
+                    let best = searcher.search(&thread_board, limits, |info| {
+                        println!("{}", info_line(info));
+                    });
+                    match best {
+                        Some(mv) => println!("bestmove {}", mv),
+                        None => println!("bestmove 0000"),
+                    }
+                });
+                search = Some(RunningSearch { stop, thread });
+            }
+            "stop" => {
+                if let Some(running) = search.take() {
+                    running.finish();
+                }
+            }
+            "quit" => break,
+            _ => println!("info string unknown command: {}", line),
+        }
+    }
+
+    if let Some(running) = search.take() {
+        running.finish();
+    }
+}
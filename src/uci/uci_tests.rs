@@ -0,0 +1,63 @@
+//! Positions set up by the `position` command
+
+use super::{parse_position, PositionError};
+use crate::board::START_FEN;
+
+#[test]
+fn plays_the_moves_through_castling() {
+    let args = [
+        "startpos", "moves", "e2e4", "e7e5", "g1f3", "b8c6", "f1c4", "g8f6", "e1g1",
+    ];
+    let board = parse_position(&args).unwrap_or_else(|e| panic!("{}", e));
+    assert_eq!(
+        board.to_fen(),
+        "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQ1RK1 b kq - 5 4"
+    );
+}
+
+#[test]
+fn plays_the_moves_from_a_fen() {
+    let args = [
+        "fen",
+        "r3k2r/8/8/8/8/8/8/R3K2R",
+        "w",
+        "KQkq",
+        "-",
+        "0",
+        "1",
+        "moves",
+        "e1c1",
+        "e8g8",
+    ];
+    let board = parse_position(&args).unwrap_or_else(|e| panic!("{}", e));
+    assert_eq!(board.to_fen(), "r4rk1/8/8/8/8/8/8/2KR3R w - - 2 2");
+}
+
+#[test]
+fn rejects_what_cant_be_set_up() {
+    assert_eq!(
+        parse_position(&["startpos", "moves", "e2e4", "e2e4"]).err(),
+        Some(PositionError::IllegalMove("e2e4".to_string()))
+    );
+    // castling out of check
+    let args = [
+        "fen",
+        "4k3/8/8/8/4r3/8/8/4K2R",
+        "w",
+        "K",
+        "-",
+        "0",
+        "1",
+        "moves",
+        "e1g1",
+    ];
+    assert_eq!(
+        parse_position(&args).err(),
+        Some(PositionError::IllegalMove("e1g1".to_string()))
+    );
+    assert_eq!(
+        parse_position(&["somewhere"]).err(),
+        Some(PositionError::Malformed)
+    );
+    assert!(parse_position(&["startpos"]).is_ok_and(|b| b.to_fen() == START_FEN));
+}
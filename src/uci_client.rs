@@ -0,0 +1,230 @@
+//! Client side of the Universal Chess Interface: runs an external engine such as Stockfish as a
+//! child process and asks it for moves over its stdin and stdout
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+
+use crate::board::{Board, Move};
+
+/// Time the engine gets to exit after `quit` before it is killed
+const QUIT_GRACE: Duration = Duration::from_millis(500);
+
+/// Returns the path of the external engine, `RUCHE_UCI_ENGINE` or `stockfish` looked up in the
+/// `PATH`
+pub fn engine_path() -> PathBuf {
+    std::env::var("RUCHE_UCI_ENGINE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("stockfish"))
+}
+
+/// What the engine reported since the last poll
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UciEvent {
+    /// answer to [UciClient::go], `None` if the engine had no legal move
+    BestMove(Option<Move>),
+    /// the engine closed its output, usually because it crashed
+    Exited,
+}
+
+/// An external engine running as a child process. Its output is read on a thread of its own so
+/// polling never blocks the render loop
+pub struct UciClient {
+    child: Option<Child>,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    /// name the engine gave in `id name`
+    name: Option<String>,
+    /// true once the engine answered `readyok`
+    ready: bool,
+    /// search asked for before the engine was ready, sent once it is
+    pending: Option<String>,
+    /// position of the running search, its best move is read against it
+    searching: Option<Board>,
+    /// answers still to come from stopped searches, they are thrown away
+    stale: u32,
+}
+
+/// Runs the engine at `path` with its stdin and stdout piped
+#[cfg(feature = "process")]
+fn start(path: &Path) -> std::io::Result<Child> {
+    use std::process::{Command, Stdio};
+
+    Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+/// Builds without the `process` feature can't run other programs
+#[cfg(not(feature = "process"))]
+fn start(_path: &Path) -> std::io::Result<Child> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "external engines aren't supported in this build",
+    ))
+}
+
+impl UciClient {
+    /// Starts the engine at `path` and begins the handshake
+    pub fn spawn(path: &Path) -> std::io::Result<Self> {
+        let mut child = start(path)?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        let (tx, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut client = Self {
+            child: Some(child),
+            stdin,
+            lines,
+            name: None,
+            ready: false,
+            pending: None,
+            searching: None,
+            stale: 0,
+        };
+        client.send("uci")?;
+        client.send("isready")?;
+        Ok(client)
+    }
+
+    fn send(&mut self, command: &str) -> std::io::Result<()> {
+        tracing::debug!("uci > {}", command);
+        writeln!(self.stdin, "{}", command)?;
+        self.stdin.flush()
+    }
+
+    /// Name of the engine, once it has introduced itself
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns true while a search is running
+    pub fn is_searching(&self) -> bool {
+        self.searching.is_some()
+    }
+
+    /// Asks for a move in `board`, thinking for `movetime`. The answer comes as a
+    /// [UciEvent::BestMove] from [UciClient::poll]
+    pub fn go(&mut self, board: &Board, movetime: Duration) -> std::io::Result<()> {
+        let go = format!("go movetime {}", movetime.as_millis());
+        self.start_search(board, go)
+    }
+
+    /// Asks for a move in `board` playing on a clock, the sides having `white` and `black` left
+    /// and gaining `increment` after each move. The answer comes as from [UciClient::go]
+    pub fn go_clock(
+        &mut self,
+        board: &Board,
+        white: Duration,
+        black: Duration,
+        increment: Duration,
+    ) -> std::io::Result<()> {
+        let go = format!(
+            "go wtime {} btime {} winc {} binc {}",
+            white.as_millis(),
+            black.as_millis(),
+            increment.as_millis(),
+            increment.as_millis()
+        );
+        self.start_search(board, go)
+    }
+
+    /// Sends `board` and the `go` command, once the engine is ready
+    fn start_search(&mut self, board: &Board, go: String) -> std::io::Result<()> {
+        let command = format!("position fen {}\n{}", board.to_fen(), go);
+        self.searching = Some(board.clone());
+        if self.ready {
+            self.send(&command)
+        } else {
+            self.pending = Some(command);
+            Ok(())
+        }
+    }
+
+    /// Abandons the running search, its answer is ignored
+    pub fn stop(&mut self) -> std::io::Result<()> {
+        if self.searching.take().is_none() {
+            return Ok(());
+        }
+        if self.pending.take().is_none() {
+            self.stale += 1;
+            self.send("stop")?;
+        }
+        Ok(())
+    }
+
+    /// Reads the engine output without blocking, returning the first event it holds
+    pub fn poll(&mut self) -> Option<UciEvent> {
+        loop {
+            let line = match self.lines.try_recv() {
+                Ok(line) => line,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => return Some(UciEvent::Exited),
+            };
+            tracing::debug!("uci < {}", line);
+
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("id") if words.next() == Some("name") => {
+                    self.name = Some(words.collect::<Vec<_>>().join(" "));
+                }
+                Some("readyok") => {
+                    self.ready = true;
+                    if let Some(command) = self.pending.take() {
+                        if let Err(e) = self.send(&command) {
+                            tracing::error!("Failed to write to the engine: {}", e);
+                            return Some(UciEvent::Exited);
+                        }
+                    }
+                }
+                Some("bestmove") => {
+                    if self.stale > 0 {
+                        self.stale -= 1;
+                        continue;
+                    }
+                    let Some(board) = self.searching.take() else {
+                        continue;
+                    };
+                    let text = words.next().unwrap_or("0000");
+                    let best = board.legal_moves().find(|m| m.to_string() == text);
+                    if best.is_none() && text != "0000" && text != "(none)" {
+                        tracing::warn!("Engine played an illegal move: {}", text);
+                    }
+                    return Some(UciEvent::BestMove(best));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Drop for UciClient {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        std::thread::spawn(move || {
+            std::thread::sleep(QUIT_GRACE);
+            if let Ok(None) = child.try_wait() {
+                let _ = child.kill();
+            }
+            let _ = child.wait();
+        });
+    }
+}
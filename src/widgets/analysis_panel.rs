@@ -0,0 +1,98 @@
+use raylib::core::color::Color;
+
+use crate::board::Board;
+use crate::engine::SearchInfo;
+use crate::san;
+
+use super::{BoardRect, EVAL_BAR_WIDTH};
+
+/// Width of the panel, the window grows by this much while it is shown
+pub const ANALYSIS_PANEL_WIDTH: i32 = 260;
+/// Number of best lines searched while the panel is shown
+pub const ANALYSIS_LINES: usize = 3;
+/// Height of a line of text in the panel
+const LINE_HEIGHT: i32 = 18;
+
+/// Best lines of the position on screen, right of the evaluation bar, with white's score and the
+/// moves in SAN
+#[derive(Default)]
+pub struct AnalysisPanel {
+    pub visible: bool,
+    /// depth of the best line
+    depth: u32,
+    /// score and moves of each line, best first
+    lines: Vec<(String, String)>,
+}
+
+impl AnalysisPanel {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Forgets the lines of the previous position
+    pub fn clear(&mut self) {
+        self.depth = 0;
+        self.lines.clear();
+    }
+
+    /// Takes a line of a search of `board`, replacing the line of the same rank
+    pub fn on_info(&mut self, info: &SearchInfo, board: &Board) {
+        let rank = info.multipv.max(1);
+        if rank == 1 {
+            self.depth = info.depth;
+        }
+        let score = info.seen_by_white(board.get_turn()).score_string();
+        let line = (score, san::line_to_san(board, &info.pv));
+        if self.lines.len() < rank {
+            self.lines.resize(rank, (String::new(), String::new()));
+        }
+        self.lines[rank - 1] = line;
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        if !self.visible {
+            return;
+        }
+
+        let x = rect.x + rect.size + EVAL_BAR_WIDTH;
+        let background = Color::from_hex("2b2b2b").expect("Error parsing hex");
+        d.draw_rectangle(x, rect.y, ANALYSIS_PANEL_WIDTH, rect.size, background);
+
+        let header = match self.depth {
+            0 => "Analysis: thinking...".to_string(),
+            depth => format!("Analysis: depth {}", depth),
+        };
+        d.draw_text(&header, x + 8, rect.y + 8, 16, Color::WHITE);
+
+        let width = ANALYSIS_PANEL_WIDTH - 16;
+        let mut y = rect.y + 8 + LINE_HEIGHT * 2;
+        for (score, moves) in &self.lines {
+            if score.is_empty() {
+                continue;
+            }
+            d.draw_text(score, x + 8, y, 14, Color::GOLD);
+            y += LINE_HEIGHT;
+            // wrap the moves to the width of the panel
+            let mut row = String::new();
+            for word in moves.split(' ') {
+                let candidate = if row.is_empty() {
+                    word.to_string()
+                } else {
+                    format!("{} {}", row, word)
+                };
+                if raylib::core::text::measure_text(&candidate, 14) > width && !row.is_empty() {
+                    d.draw_text(&row, x + 8, y, 14, Color::LIGHTGRAY);
+                    y += LINE_HEIGHT;
+                    row = word.to_string();
+                } else {
+                    row = candidate;
+                }
+            }
+            d.draw_text(&row, x + 8, y, 14, Color::LIGHTGRAY);
+            y += LINE_HEIGHT + LINE_HEIGHT / 2;
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use raylib::core::color::Color;
+use raylib::core::math::Vector2;
+
+use super::{draw_arrow, BoardRect};
+
+/// Arrows and circled squares drawn over a position with the right mouse button
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Annotations {
+    arrows: Vec<(usize, usize)>,
+    squares: Vec<usize>,
+}
+
+impl Annotations {
+    pub fn is_empty(&self) -> bool {
+        self.arrows.is_empty() && self.squares.is_empty()
+    }
+
+    /// Adds an arrow from `from` to `to`, or removes it if it was already drawn
+    pub fn toggle_arrow(&mut self, from: usize, to: usize) {
+        match self.arrows.iter().position(|a| *a == (from, to)) {
+            Some(i) => {
+                self.arrows.remove(i);
+            }
+            None => self.arrows.push((from, to)),
+        }
+    }
+
+    /// Circles `square`, or removes its circle
+    pub fn toggle_square(&mut self, square: usize) {
+        match self.squares.iter().position(|s| *s == square) {
+            Some(i) => {
+                self.squares.remove(i);
+            }
+            None => self.squares.push(square),
+        }
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let color = annotation_color();
+        let half = rect.size as f32 / 16.0;
+        for square in &self.squares {
+            let (x, y) = rect.square_origin(*square);
+            let center = Vector2::new(x as f32 + half, y as f32 + half);
+            d.draw_ring(center, half * 0.85, half, 0.0, 360.0, 32, color);
+        }
+        for (from, to) in &self.arrows {
+            draw_arrow(d, rect, *from, *to, color);
+        }
+    }
+}
+
+/// Color of the annotations, also used for the arrow being drawn
+pub fn annotation_color() -> Color {
+    Color::from_hex("15781b")
+        .expect("Error parsing hex")
+        .fade(0.8)
+}
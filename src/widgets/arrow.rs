@@ -0,0 +1,38 @@
+use raylib::core::color::Color;
+use raylib::core::math::Vector2;
+
+use super::BoardRect;
+
+/// Draws an arrow from the center of the square `from` to the center of `to`
+pub fn draw_arrow<T>(d: &mut T, rect: BoardRect, from: usize, to: usize, color: Color)
+where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    let cell = rect.size as f32 / 8.0;
+    let center = |idx: usize| {
+        let (x, y) = rect.square_origin(idx);
+        Vector2::new(x as f32 + cell / 2.0, y as f32 + cell / 2.0)
+    };
+    let (start, end) = (center(from), center(to));
+
+    let length = (end - start).length();
+    if length == 0.0 {
+        return;
+    }
+    let dir = (end - start) / length;
+    // perpendicular pointing to the left of the arrow on screen
+    let normal = Vector2::new(dir.y, -dir.x);
+
+    let head_length = cell * 0.4;
+    let head_width = cell * 0.25;
+    let base = end - dir * head_length;
+
+    d.draw_line_ex(start, base, cell * 0.15, color);
+    // raylib wants the corners counter-clockwise
+    d.draw_triangle(
+        end,
+        base + normal * head_width,
+        base - normal * head_width,
+        color,
+    );
+}
@@ -0,0 +1,88 @@
+use std::time::SystemTime;
+
+use raylib::core::color::Color;
+
+use crate::autosave::Backup;
+
+use super::BoardRect;
+
+/// Overlay listing the autosave backups so one can be restored
+pub struct BackupPicker {
+    backups: Vec<Backup>,
+    selected: usize,
+}
+
+impl BackupPicker {
+    pub fn new(backups: Vec<Backup>) -> Self {
+        Self {
+            backups,
+            selected: 0,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.backups.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected(&self) -> Option<&Backup> {
+        self.backups.get(self.selected)
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.9));
+        d.draw_text(
+            "Restore backup (Up/Down, Enter, B to close)",
+            rect.x + 10,
+            rect.y + 10,
+            16,
+            Color::WHITE,
+        );
+
+        if self.backups.is_empty() {
+            d.draw_text("No backups yet", rect.x + 10, rect.y + 40, 18, Color::WHITE);
+            return;
+        }
+
+        for (i, backup) in self.backups.iter().enumerate() {
+            let y = rect.y + 40 + i as i32 * 28;
+            if i == self.selected {
+                d.draw_rectangle(rect.x + 5, y - 4, rect.size - 10, 26, highlight);
+            }
+
+            let name = backup
+                .path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let label = format!("{}  ({})", name, time_ago(backup.modified));
+            d.draw_text(&label, rect.x + 10, y, 18, Color::WHITE);
+        }
+    }
+}
+
+/// Formats how long ago `time` was e.g. "42s ago", "3m ago"
+fn time_ago(time: SystemTime) -> String {
+    let secs = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+    if secs < 60 {
+        format!("{}s ago", secs)
+    } else if secs < 60 * 60 {
+        format!("{}m ago", secs / 60)
+    } else {
+        format!("{}h ago", secs / (60 * 60))
+    }
+}
@@ -0,0 +1,27 @@
+use raylib::core::color::Color;
+
+use crate::book::BookMove;
+
+use super::BoardRect;
+
+/// Outlines the origin and destination squares of the book moves, with the share of the book
+/// weight of each move drawn on its destination
+pub fn draw_book_moves<T>(d: &mut T, rect: BoardRect, moves: &[BookMove])
+where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    let color = Color::from_hex("1f8a4c").expect("Error parsing hex");
+    let cell = rect.size / 8;
+    let total = moves.iter().map(|m| m.weight as u32).sum::<u32>().max(1);
+
+    for book_move in moves {
+        let (x, y) = rect.square_origin(book_move.mv.from);
+        d.draw_rectangle_lines(x + 2, y + 2, cell - 4, cell - 4, color);
+
+        let (x, y) = rect.square_origin(book_move.mv.to);
+        d.draw_rectangle_lines(x + 2, y + 2, cell - 4, cell - 4, color);
+        d.draw_rectangle_lines(x + 3, y + 3, cell - 6, cell - 6, color);
+        let share = book_move.weight as u32 * 100 / total;
+        d.draw_text(&format!("{}%", share), x + 5, y + 5, 14, color);
+    }
+}
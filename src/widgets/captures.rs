@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use raylib::core::color::Color;
+use raylib::core::math::Vector2;
+use raylib::core::texture::{RaylibTexture2D, Texture2D};
+
+use crate::board::{Board, Piece, PieceColor, PieceType};
+
+/// Height of the strip showing the pieces one side captured
+pub const CAPTURES_HEIGHT: i32 = 28;
+
+/// Piece types in the order captured pieces are shown, least valuable first
+const ORDER: [PieceType; 5] = [
+    PieceType::Pawn,
+    PieceType::Knight,
+    PieceType::Bishop,
+    PieceType::Rook,
+    PieceType::Queen,
+];
+
+/// Value of a piece in pawns, as the material difference is usually counted
+fn points(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight | PieceType::Bishop => 3,
+        PieceType::Rook => 5,
+        PieceType::Queen => 9,
+        PieceType::King | PieceType::None => 0,
+    }
+}
+
+fn count(board: &Board, piece: Piece) -> i32 {
+    board.pieces(piece).count_ones() as i32
+}
+
+/// Pieces `by` took from the other side since `start`, least valuable first. A piece the other
+/// side has more of than it started with was promoted, so one fewer of its pawns was captured
+pub fn captured_pieces(start: &Board, now: &Board, by: PieceColor) -> Vec<PieceType> {
+    let victim = by.opposite();
+    let promoted = ORDER[1..]
+        .iter()
+        .map(|t| (count(now, Piece::new(victim, *t)) - count(start, Piece::new(victim, *t))).max(0))
+        .sum::<i32>();
+
+    let mut captured = vec![];
+    for piece_type in ORDER {
+        let piece = Piece::new(victim, piece_type);
+        let mut missing = (count(start, piece) - count(now, piece)).max(0);
+        if piece_type == PieceType::Pawn {
+            missing = (missing - promoted).max(0);
+        }
+        captured.extend(std::iter::repeat_n(piece_type, missing as usize));
+    }
+    captured
+}
+
+/// Material of `color` minus the other side's, in pawns
+pub fn material_advantage(board: &Board, color: PieceColor) -> i32 {
+    ORDER
+        .iter()
+        .map(|t| {
+            points(*t)
+                * (count(board, Piece::new(color, *t))
+                    - count(board, Piece::new(color.opposite(), *t)))
+        })
+        .sum()
+}
+
+/// Draws the pieces `by` captured as small overlapping icons in a strip `width` wide at (`x`,
+/// `y`), followed by its material advantage if it is ahead
+pub fn draw_captures<T>(
+    d: &mut T,
+    x: i32,
+    y: i32,
+    width: i32,
+    textures: &HashMap<Piece, Texture2D>,
+    (start, now): (&Board, &Board),
+    by: PieceColor,
+) where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    let background = Color::from_hex("262421").expect("Error parsing hex");
+    d.draw_rectangle(x, y, width, CAPTURES_HEIGHT, background);
+
+    let icon = CAPTURES_HEIGHT - 6;
+    let mut cx = x + 6;
+    let mut previous = None;
+    for piece_type in captured_pieces(start, now, by) {
+        // pieces of a kind overlap, a new kind leaves a gap
+        if previous.is_some_and(|p| p != piece_type) {
+            cx += icon / 2;
+        }
+        previous = Some(piece_type);
+        if let Some(texture) = textures.get(&Piece::new(by.opposite(), piece_type)) {
+            let scale = icon as f32 / texture.width().max(1) as f32;
+            d.draw_texture_ex(
+                texture,
+                Vector2::new(cx as f32, (y + 3) as f32),
+                0.0,
+                scale,
+                Color::WHITE,
+            );
+        }
+        cx += icon / 2;
+    }
+
+    let advantage = material_advantage(now, by);
+    if advantage > 0 {
+        d.draw_text(
+            &format!("+{}", advantage),
+            cx + icon / 2 + 4,
+            y + 7,
+            14,
+            Color::LIGHTGRAY,
+        );
+    }
+}
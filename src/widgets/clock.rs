@@ -0,0 +1,87 @@
+use raylib::core::color::Color;
+
+use std::time::Duration;
+
+use crate::clock::{format_time, LOW_TIME, PRESETS};
+use crate::lifecycle::TimeControl;
+
+use super::{BoardRect, CAPTURES_HEIGHT};
+
+/// Overlay listing the time controls a new game can be played with
+#[derive(Default)]
+pub struct ClockSetup {
+    selected: usize,
+}
+
+impl ClockSetup {
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < PRESETS.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Time control selected, `None` for an untimed game
+    pub fn selected(&self) -> Option<TimeControl> {
+        PRESETS[self.selected]
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.9));
+        d.draw_text(
+            "New game (Up/Down, Enter, Esc to close)",
+            rect.x + 10,
+            rect.y + 10,
+            16,
+            Color::WHITE,
+        );
+
+        for (i, preset) in PRESETS.iter().enumerate() {
+            let y = rect.y + 40 + i as i32 * 28;
+            if i == self.selected {
+                d.draw_rectangle(rect.x + 5, y - 4, rect.size - 10, 26, highlight);
+            }
+            let label = match preset {
+                Some(time_control) => time_control.to_string(),
+                None => "Untimed".to_string(),
+            };
+            d.draw_text(&label, rect.x + 10, y, 18, Color::WHITE);
+        }
+    }
+}
+
+/// Draws the `time` left to a side at the right end of the strip of its captured pieces, `x`
+/// being where the strip ends, highlighted while it is running
+pub fn draw_clock<T>(d: &mut T, x: i32, y: i32, time: Duration, running: bool)
+where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    let text = format_time(time);
+    let width = raylib::core::text::measure_text(&text, 18);
+    let (background, color) = if running {
+        (Color::LIGHTGRAY, Color::BLACK)
+    } else {
+        (
+            Color::from_hex("3c3a37").expect("Error parsing hex"),
+            Color::GRAY,
+        )
+    };
+    let low = time < LOW_TIME;
+    d.draw_rectangle(x - width - 12, y, width + 12, CAPTURES_HEIGHT, background);
+    d.draw_text(
+        &text,
+        x - width - 6,
+        y + 5,
+        18,
+        if low { Color::MAROON } else { color },
+    );
+}
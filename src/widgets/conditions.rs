@@ -0,0 +1,40 @@
+use raylib::core::color::Color;
+
+use super::BoardRect;
+
+/// Height of a line of text in the panel
+const LINE_HEIGHT: i32 = 18;
+
+/// Lists the conditional moves in a panel along the bottom of the board, with a hint while a
+/// line is being recorded
+pub fn draw_conditions<T>(d: &mut T, rect: BoardRect, lines: &[String], recording: bool)
+where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    if lines.is_empty() && !recording {
+        return;
+    }
+
+    let mut rows = vec![];
+    if recording {
+        rows.push("Recording: their move, then your reply. C to save".to_string());
+    }
+    rows.extend(lines.iter().cloned());
+    if !recording {
+        rows.push("C: add  Backspace: remove last  Delete: clear".to_string());
+    }
+
+    let height = rows.len() as i32 * LINE_HEIGHT + 8;
+    let top = rect.y + rect.size - height;
+    let background = Color::from_hex("2b2b2b").expect("Error parsing hex");
+    d.draw_rectangle(rect.x, top, rect.size, height, background.fade(0.85));
+    for (i, row) in rows.iter().enumerate() {
+        d.draw_text(
+            row,
+            rect.x + 8,
+            top + 4 + i as i32 * LINE_HEIGHT,
+            14,
+            Color::WHITE,
+        );
+    }
+}
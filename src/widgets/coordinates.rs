@@ -0,0 +1,40 @@
+use raylib::core::color::Color;
+
+use super::BoardRect;
+
+/// Writes the files along the bottom edge of the board and the ranks along its left edge,
+/// inside the squares, each label in the color of the other squares so it reads on both
+pub fn draw_coordinates<T>(d: &mut T, rect: BoardRect, light: Color, dark: Color)
+where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    let cell = rect.size / 8;
+    let font_size = (cell / 5).max(10);
+
+    for idx in 0..64 {
+        let (col, row) = rect.screen_square(idx);
+        let (x, y) = rect.square_origin(idx);
+        // square 0 is h1, a light square
+        let color = if (idx % 8 + idx / 8) % 2 == 0 {
+            dark
+        } else {
+            light
+        };
+
+        if row == 7 {
+            let file = ((b'h' - (idx % 8) as u8) as char).to_string();
+            let width = raylib::core::text::measure_text(&file, font_size);
+            d.draw_text(
+                &file,
+                x + cell - width - 3,
+                y + cell - font_size - 2,
+                font_size,
+                color,
+            );
+        }
+        if col == 0 {
+            let rank = (idx / 8 + 1).to_string();
+            d.draw_text(&rank, x + 3, y + 2, font_size, color);
+        }
+    }
+}
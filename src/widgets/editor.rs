@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use raylib::core::color::Color;
+use raylib::core::texture::{RaylibTexture2D, Texture2D};
+
+use crate::board::{square_to_algebraic, BoardBuilder, Piece, PieceColor, PieceType};
+
+use super::BoardRect;
+
+/// Height of the strip above the board holding the pieces to place and the position's state
+const EDITOR_PANEL_HEIGHT: i32 = 100;
+/// Pieces offered by the panel, white then black, followed by the eraser
+const BRUSHES: usize = 13;
+
+/// Piece placed by the brush in `slot`, [Piece::new_none] for the eraser
+fn brush(slot: usize) -> Piece {
+    let kinds = [
+        PieceType::King,
+        PieceType::Queen,
+        PieceType::Rook,
+        PieceType::Bishop,
+        PieceType::Knight,
+        PieceType::Pawn,
+    ];
+    match slot {
+        0..=5 => Piece::new(PieceColor::White, kinds[slot]),
+        6..=11 => Piece::new(PieceColor::Black, kinds[slot - 6]),
+        _ => Piece::new_none(),
+    }
+}
+
+/// Strip above the board of the position editor: the pieces to place, then the side to move,
+/// castling rights and en passant square, then what is wrong with the position, if anything
+pub struct EditorPanel;
+
+impl EditorPanel {
+    /// Side of a brush and left edge of the first one
+    fn layout(rect: BoardRect) -> (i32, i32) {
+        let size = (rect.size / BRUSHES as i32).min(40);
+        (size, rect.x + (rect.size - size * BRUSHES as i32) / 2)
+    }
+
+    /// Piece of the brush at (`mouse_x`, `mouse_y`), if any
+    pub fn brush_at(rect: BoardRect, mouse_x: i32, mouse_y: i32) -> Option<Piece> {
+        let (size, left) = Self::layout(rect);
+        let top = rect.y - EDITOR_PANEL_HEIGHT + 4;
+        if !(top..top + size).contains(&mouse_y) || mouse_x < left {
+            return None;
+        }
+        let slot = ((mouse_x - left) / size) as usize;
+        (slot < BRUSHES).then(|| brush(slot))
+    }
+
+    pub fn draw<T>(
+        d: &mut T,
+        rect: BoardRect,
+        textures: &HashMap<Piece, Texture2D>,
+        (builder, selected): (&BoardBuilder, Piece),
+        problem: Option<&str>,
+    ) where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("262421").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+        let top = rect.y - EDITOR_PANEL_HEIGHT;
+        d.draw_rectangle(rect.x, top, rect.size, EDITOR_PANEL_HEIGHT, background);
+
+        let (size, left) = Self::layout(rect);
+        for slot in 0..BRUSHES {
+            let (x, y) = (left + slot as i32 * size, top + 4);
+            let piece = brush(slot);
+            if piece == selected {
+                d.draw_rectangle(x, y, size, size, highlight);
+            }
+            match textures.get(&piece) {
+                Some(texture) => {
+                    let scale = size as f32 / texture.width().max(1) as f32;
+                    d.draw_texture_ex(
+                        texture,
+                        raylib::core::math::Vector2::new(x as f32, y as f32),
+                        0.0,
+                        scale,
+                        Color::WHITE,
+                    );
+                }
+                // the eraser
+                None => d.draw_text("x", x + size / 3, y + size / 5, size * 2 / 3, Color::GRAY),
+            }
+        }
+
+        let rights = builder.castling_rights();
+        let castling = [
+            (rights.white_king_side, 'K'),
+            (rights.white_queen_side, 'Q'),
+            (rights.black_king_side, 'k'),
+            (rights.black_queen_side, 'q'),
+        ]
+        .iter()
+        .filter(|(right, _)| *right)
+        .map(|(_, c)| *c)
+        .collect::<String>();
+        let state = format!(
+            "{:?} to move (W)  Castling: {} (1-4)  En passant: {} (E)",
+            builder.turn(),
+            if castling.is_empty() {
+                "-"
+            } else {
+                castling.as_str()
+            },
+            builder
+                .en_passant_square()
+                .map_or("-".to_string(), square_to_algebraic)
+        );
+        let line = top + size + 6;
+        d.draw_text(&state, rect.x + 6, line, 14, Color::LIGHTGRAY);
+        d.draw_text(
+            "C: clear  S: start position  Enter: play  A: analyze  Esc: back",
+            rect.x + 6,
+            line + 16,
+            14,
+            Color::GRAY,
+        );
+        match problem {
+            Some(problem) => d.draw_text(problem, rect.x + 6, line + 32, 14, Color::RED),
+            None => d.draw_text("Position is valid", rect.x + 6, line + 32, 14, Color::GREEN),
+        }
+    }
+}
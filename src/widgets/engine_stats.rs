@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use raylib::core::color::Color;
+
+use crate::engine::{Difficulty, SearchInfo};
+
+/// Compact overlay showing what the engine is doing while it plays
+#[derive(Default)]
+pub struct EngineStats {
+    pub visible: bool,
+    info: Option<SearchInfo>,
+    /// time the engine is given per move
+    think_time: Duration,
+    difficulty: Difficulty,
+}
+
+/// Formats large counts e.g. 12345 -> "12.3k"
+fn short_count(n: u64) -> String {
+    if n >= 1_000_000 {
+        format!("{:.1}M", n as f64 / 1_000_000.0)
+    } else if n >= 1_000 {
+        format!("{:.1}k", n as f64 / 1_000.0)
+    } else {
+        n.to_string()
+    }
+}
+
+impl EngineStats {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn on_info(&mut self, info: SearchInfo) {
+        self.info = Some(info);
+    }
+
+    pub fn set_think_time(&mut self, think_time: Duration) {
+        self.think_time = think_time;
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    pub fn draw<T>(&self, d: &mut T, x: i32, y: i32)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        if !self.visible {
+            return;
+        }
+
+        let Some(info) = &self.info else {
+            let idle = format!(
+                "engine: idle, {:.1}s per move, {}",
+                self.think_time.as_secs_f32(),
+                self.difficulty.name()
+            );
+            d.draw_text(&idle, x, y, 14, Color::DARKGRAY);
+            return;
+        };
+
+        let stats = format!(
+            "depth {}  nodes {}  nps {}  time {:.1}/{:.1}s  eval {}",
+            info.depth,
+            short_count(info.nodes),
+            short_count(info.nps),
+            info.elapsed.as_secs_f32(),
+            self.think_time.as_secs_f32(),
+            info.score_string()
+        );
+        d.draw_text(&stats, x, y, 14, Color::DARKGRAY);
+        d.draw_text(
+            &format!("pv {}", info.pv_string()),
+            x,
+            y + 16,
+            14,
+            Color::DARKGRAY,
+        );
+        d.draw_text(
+            &format!(
+                "tt hits {:.0}%  fill {:.0}%",
+                info.tt.hit_rate * 100.0,
+                info.tt.fill * 100.0
+            ),
+            x,
+            y + 32,
+            14,
+            Color::DARKGRAY,
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use raylib::core::color::Color;
+
+use crate::board::PieceColor;
+use crate::engine::SearchInfo;
+
+use super::BoardRect;
+
+/// Width of the bar, the window leaves this much room right of the board
+pub const EVAL_BAR_WIDTH: i32 = 28;
+/// Centipawns that give a side about three quarters of the bar
+const SCALE: f32 = 400.0;
+
+/// Vertical bar right of the board, filled with white's share of the evaluation. White's part
+/// starts from the edge its pieces start on
+pub struct EvalBar {
+    pub visible: bool,
+    /// score from white's point of view and its label, `None` until the first report
+    score: Option<(i32, String)>,
+    /// moves until mate, positive when white mates
+    mate: Option<i32>,
+}
+
+impl Default for EvalBar {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            score: None,
+            mate: None,
+        }
+    }
+}
+
+impl EvalBar {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    /// Forgets the score of the previous position
+    pub fn clear(&mut self) {
+        self.score = None;
+        self.mate = None;
+    }
+
+    /// Takes the score of a report of a search of a position with `turn` to move
+    pub fn on_info(&mut self, info: &SearchInfo, turn: PieceColor) {
+        let info = info.seen_by_white(turn);
+        self.mate = info.mate_in();
+        let label = match self.mate {
+            Some(moves) => format!("#{}", moves),
+            None => format!("{:+.1}", info.score as f32 / 100.0),
+        };
+        self.score = Some((info.score, label));
+    }
+
+    /// Shows the static evaluation of the position, from white's point of view, until the
+    /// search reports
+    pub fn on_static_eval(&mut self, score: i32) {
+        self.mate = None;
+        self.score = Some((score, format!("{:+.1}", score as f32 / 100.0)));
+    }
+
+    /// White's share of the bar, between 0 and 1
+    fn white_share(&self) -> f32 {
+        match (self.mate, &self.score) {
+            (Some(moves), _) => {
+                if moves > 0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            (None, Some((score, _))) => 1.0 / (1.0 + 10f32.powf(-*score as f32 / SCALE)),
+            (None, None) => 0.5,
+        }
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        if !self.visible {
+            return;
+        }
+
+        let x = rect.x + rect.size;
+        let white_height = (rect.size as f32 * self.white_share()).round() as i32;
+        let white_y = match rect.bottom {
+            PieceColor::White => rect.y + rect.size - white_height,
+            PieceColor::Black => rect.y,
+        };
+        d.draw_rectangle(
+            x,
+            rect.y,
+            EVAL_BAR_WIDTH,
+            rect.size,
+            Color::from_hex("403d39").expect("Error parsing hex"),
+        );
+        d.draw_rectangle(
+            x,
+            white_y,
+            EVAL_BAR_WIDTH,
+            white_height,
+            Color::from_hex("f0f0f0").expect("Error parsing hex"),
+        );
+        d.draw_line(
+            x,
+            rect.y + rect.size / 2,
+            x + EVAL_BAR_WIDTH,
+            rect.y + rect.size / 2,
+            Color::GRAY,
+        );
+
+        let Some((score, label)) = &self.score else {
+            return;
+        };
+        // the label sits at the end of the side ahead, in the other side's color
+        let width = raylib::core::text::measure_text(label, 10);
+        let label_x = x + (EVAL_BAR_WIDTH - width) / 2;
+        let white_ahead = self.mate.map(|m| m > 0).unwrap_or(*score >= 0);
+        let ahead = if white_ahead {
+            PieceColor::White
+        } else {
+            PieceColor::Black
+        };
+        let label_y = if ahead == rect.bottom {
+            rect.y + rect.size - 14
+        } else {
+            rect.y + 4
+        };
+        let label_color = match ahead {
+            PieceColor::White => Color::BLACK,
+            PieceColor::Black => Color::WHITE,
+        };
+        d.draw_text(label, label_x, label_y, 10, label_color);
+    }
+}
@@ -0,0 +1,89 @@
+use raylib::core::color::Color;
+
+use super::MOVE_LIST_WIDTH;
+
+/// Height of the graph, taken from the bottom of the move list
+pub const EVAL_GRAPH_HEIGHT: i32 = 80;
+
+/// White's winning chances over the positions of a reviewed game, white's area filled from the
+/// bottom. Clicking the graph shows the position at that point of the game
+#[derive(Default)]
+pub struct EvalGraph {
+    /// white's winning chances between 0 and 1, one per position from the start, none until
+    /// the position is searched
+    chances: Vec<Option<f32>>,
+}
+
+impl EvalGraph {
+    pub fn set_chances(&mut self, chances: Vec<Option<f32>>) {
+        self.chances = chances;
+    }
+
+    /// Returns true if there is nothing to draw, the graph then leaves its room to the list
+    pub fn is_empty(&self) -> bool {
+        self.chances.len() < 2
+    }
+
+    /// Horizontal position of the position at `ply` in a graph starting at `x`
+    fn ply_x(&self, x: i32, ply: usize) -> i32 {
+        x + (ply * (MOVE_LIST_WIDTH - 1) as usize / (self.chances.len() - 1)) as i32
+    }
+
+    /// Number of moves played to reach the position under (`mouse_x`, `mouse_y`), (`x`, `y`)
+    /// being the top left corner of the graph
+    pub fn ply_at(&self, (x, y): (i32, i32), mouse_x: i32, mouse_y: i32) -> Option<usize> {
+        if self.is_empty()
+            || !(x..x + MOVE_LIST_WIDTH).contains(&mouse_x)
+            || !(y..y + EVAL_GRAPH_HEIGHT).contains(&mouse_y)
+        {
+            return None;
+        }
+        let last = self.chances.len() - 1;
+        let ply = ((mouse_x - x) as f32 / (MOVE_LIST_WIDTH - 1) as f32 * last as f32).round();
+        Some((ply as usize).min(last))
+    }
+
+    /// Draws the graph from (`x`, `y`), `shown` being the number of moves played to reach the
+    /// position on screen
+    pub fn draw<T>(&self, d: &mut T, (x, y): (i32, i32), shown: usize)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        if self.is_empty() {
+            return;
+        }
+        let background = Color::from_hex("3a3733").expect("Error parsing hex");
+        d.draw_rectangle(x, y, MOVE_LIST_WIDTH, EVAL_GRAPH_HEIGHT, background);
+
+        // positions not searched yet keep the last known chances
+        let mut chances = Vec::with_capacity(self.chances.len());
+        let mut last = 0.5;
+        for chance in &self.chances {
+            last = chance.unwrap_or(last);
+            chances.push(last);
+        }
+        let height = |chance: f32| (chance * EVAL_GRAPH_HEIGHT as f32) as i32;
+        for px in 0..MOVE_LIST_WIDTH {
+            let at = px as f32 / (MOVE_LIST_WIDTH - 1) as f32 * (chances.len() - 1) as f32;
+            let (i, t) = (at.floor() as usize, at.fract());
+            let chance = match chances.get(i + 1) {
+                Some(next) => chances[i] + (next - chances[i]) * t,
+                None => chances[i],
+            };
+            let h = height(chance);
+            d.draw_line(
+                x + px,
+                y + EVAL_GRAPH_HEIGHT - h,
+                x + px,
+                y + EVAL_GRAPH_HEIGHT,
+                Color::LIGHTGRAY,
+            );
+        }
+
+        let middle = y + EVAL_GRAPH_HEIGHT / 2;
+        d.draw_line(x, middle, x + MOVE_LIST_WIDTH, middle, Color::GRAY);
+        let cursor = self.ply_x(x, shown.min(self.chances.len() - 1));
+        let highlight = Color::from_hex("4a6e8a").expect("Error parsing hex");
+        d.draw_rectangle(cursor - 1, y, 2, EVAL_GRAPH_HEIGHT, highlight);
+    }
+}
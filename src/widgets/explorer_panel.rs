@@ -0,0 +1,125 @@
+use raylib::core::color::Color;
+
+use crate::board::{Board, Move};
+use crate::explorer::ExplorerMove;
+use crate::san;
+
+use super::BoardRect;
+
+/// Width of the panel, the window grows by this much while it is shown
+pub const EXPLORER_PANEL_WIDTH: i32 = 240;
+/// Height of a row of the table
+const ROW_HEIGHT: i32 = 20;
+/// Space above the first row, for the header and the column names
+const HEADER_HEIGHT: i32 = 52;
+/// Height of the bar of the results beside each move
+const BAR_HEIGHT: i32 = 8;
+
+/// Moves played from the position on screen in the game database, with the number of games
+/// and the share of white wins, draws and black wins of each. Clicking a move plays it
+#[derive(Default)]
+pub struct ExplorerPanel {
+    pub visible: bool,
+    /// what the database is up to, or its number of games
+    status: String,
+    /// moves of the position on screen with their SAN, most played first
+    moves: Vec<(ExplorerMove, String)>,
+}
+
+impl ExplorerPanel {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+    }
+
+    pub fn set_status(&mut self, status: String) {
+        self.status = status;
+    }
+
+    /// Lists the `moves` played from `board`
+    pub fn set_moves(&mut self, board: &Board, moves: Vec<ExplorerMove>) {
+        self.moves = moves
+            .into_iter()
+            .map(|m| {
+                let san = san::to_san(board, &m.mv);
+                (m, san)
+            })
+            .collect();
+    }
+
+    /// Rows fitting in the panel
+    fn fit(rect: BoardRect) -> usize {
+        ((rect.size - HEADER_HEIGHT) / ROW_HEIGHT).max(0) as usize
+    }
+
+    /// Move of the row at (`mouse_x`, `mouse_y`) of the panel drawn from `x`, if any
+    pub fn move_at(&self, x: i32, rect: BoardRect, mouse_x: i32, mouse_y: i32) -> Option<Move> {
+        let top = rect.y + HEADER_HEIGHT;
+        if !self.visible || !(x..x + EXPLORER_PANEL_WIDTH).contains(&mouse_x) || mouse_y < top {
+            return None;
+        }
+        let row = ((mouse_y - top) / ROW_HEIGHT) as usize;
+        if row >= Self::fit(rect) {
+            return None;
+        }
+        self.moves.get(row).map(|(m, _)| m.mv.clone())
+    }
+
+    /// Draws the panel from `x`, as high as the board
+    pub fn draw<T>(&self, d: &mut T, x: i32, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        if !self.visible {
+            return;
+        }
+
+        let background = Color::from_hex("262626").expect("Error parsing hex");
+        d.draw_rectangle(x, rect.y, EXPLORER_PANEL_WIDTH, rect.size, background);
+        d.draw_text("Explorer", x + 8, rect.y + 8, 16, Color::WHITE);
+        d.draw_text(&self.status, x + 84, rect.y + 10, 14, Color::GRAY);
+        d.draw_text("Move", x + 8, rect.y + 32, 14, Color::GRAY);
+        d.draw_text("Games", x + 64, rect.y + 32, 14, Color::GRAY);
+        d.draw_text(
+            "White / Draw / Black",
+            x + 116,
+            rect.y + 32,
+            12,
+            Color::GRAY,
+        );
+        if self.moves.is_empty() {
+            d.draw_text(
+                "No games from here",
+                x + 8,
+                rect.y + HEADER_HEIGHT,
+                14,
+                Color::LIGHTGRAY,
+            );
+            return;
+        }
+
+        let bar_width = EXPLORER_PANEL_WIDTH - 124;
+        for (i, (explorer_move, san)) in self.moves.iter().take(Self::fit(rect)).enumerate() {
+            let y = rect.y + HEADER_HEIGHT + i as i32 * ROW_HEIGHT;
+            let results = &explorer_move.results;
+            d.draw_text(san, x + 8, y, 14, Color::WHITE);
+            d.draw_text(
+                &results.games().to_string(),
+                x + 64,
+                y,
+                14,
+                Color::LIGHTGRAY,
+            );
+
+            // a bar split between white wins, draws and black wins
+            let (white, draws, _) = results.percentages();
+            let white = bar_width * white as i32 / 100;
+            let draws = bar_width * draws as i32 / 100;
+            // level with the middle of the 14 pixels high text
+            let bar_y = y + 7 - BAR_HEIGHT / 2;
+            let bar_x = x + 116;
+            d.draw_rectangle(bar_x, bar_y, bar_width, BAR_HEIGHT, Color::BLACK);
+            d.draw_rectangle(bar_x, bar_y, white, BAR_HEIGHT, Color::WHITE);
+            d.draw_rectangle(bar_x + white, bar_y, draws, BAR_HEIGHT, Color::GRAY);
+        }
+    }
+}
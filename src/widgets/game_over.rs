@@ -0,0 +1,127 @@
+use raylib::core::color::Color;
+
+use crate::lifecycle::GameState;
+
+use super::BoardRect;
+
+/// What the user can do once the game is over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameOverAction {
+    /// A new game from the standard position
+    NewGame,
+    /// The same game again with colors swapped
+    Rematch,
+    /// The game as PGN to the clipboard
+    CopyPgn,
+}
+
+const BUTTONS: [(GameOverAction, &str); 3] = [
+    (GameOverAction::NewGame, "New game"),
+    (GameOverAction::Rematch, "Rematch (swap colors)"),
+    (GameOverAction::CopyPgn, "Copy PGN"),
+];
+const BUTTON_HEIGHT: i32 = 32;
+
+/// Overlay shown when the game ends, with the result and buttons for what to do next
+pub struct GameOverDialog {
+    state: GameState,
+    /// names of the players of white and black
+    players: (String, String),
+}
+
+impl GameOverDialog {
+    pub fn new(state: GameState, players: (String, String)) -> Self {
+        Self { state, players }
+    }
+
+    /// Left edge, top and width of the dialog drawn over the board in `rect`
+    fn frame(rect: BoardRect) -> (i32, i32, i32) {
+        let width = (rect.size * 3 / 4).max(240);
+        let height = 70 + BUTTONS.len() as i32 * (BUTTON_HEIGHT + 8);
+        (
+            rect.x + (rect.size - width) / 2,
+            rect.y + (rect.size - height) / 2,
+            width,
+        )
+    }
+
+    /// Top left corner, width and height of each button
+    fn buttons(rect: BoardRect) -> impl Iterator<Item = (GameOverAction, &'static str, i32, i32)> {
+        let (x, y, _) = Self::frame(rect);
+        BUTTONS.iter().enumerate().map(move |(i, (action, label))| {
+            (
+                *action,
+                *label,
+                x + 15,
+                y + 60 + i as i32 * (BUTTON_HEIGHT + 8),
+            )
+        })
+    }
+
+    /// Button at (`mouse_x`, `mouse_y`), if any
+    pub fn button_at(&self, rect: BoardRect, mouse_x: i32, mouse_y: i32) -> Option<GameOverAction> {
+        let (_, _, width) = Self::frame(rect);
+        Self::buttons(rect)
+            .find(|(_, _, x, y)| {
+                (*x..x + width - 30).contains(&mouse_x)
+                    && (*y..y + BUTTON_HEIGHT).contains(&mouse_y)
+            })
+            .map(|(action, ..)| action)
+    }
+
+    /// Draws the dialog over the board, the button under the mouse highlighted
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect, (mouse_x, mouse_y): (i32, i32))
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let button = Color::from_hex("4a4a4a").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+
+        let (x, y, width) = Self::frame(rect);
+        let height = 70 + BUTTONS.len() as i32 * (BUTTON_HEIGHT + 8);
+        d.draw_rectangle(x, y, width, height, background.fade(0.95));
+
+        let title = self.state.to_string();
+        let title_width = raylib::core::text::measure_text(&title, 20);
+        d.draw_text(
+            &title,
+            x + (width - title_width) / 2,
+            y + 12,
+            20,
+            Color::WHITE,
+        );
+        let result = format!(
+            "{} {} {}",
+            self.players.0,
+            self.state.result(),
+            self.players.1
+        );
+        let result_width = raylib::core::text::measure_text(&result, 16);
+        d.draw_text(
+            &result,
+            x + (width - result_width) / 2,
+            y + 36,
+            16,
+            Color::LIGHTGRAY,
+        );
+
+        let hovered = self.button_at(rect, mouse_x, mouse_y);
+        for (action, label, bx, by) in Self::buttons(rect) {
+            let color = if hovered == Some(action) {
+                highlight
+            } else {
+                button
+            };
+            d.draw_rectangle(bx, by, width - 30, BUTTON_HEIGHT, color);
+            let label_width = raylib::core::text::measure_text(label, 18);
+            d.draw_text(
+                label,
+                bx + (width - 30 - label_width) / 2,
+                by + 7,
+                18,
+                Color::WHITE,
+            );
+        }
+    }
+}
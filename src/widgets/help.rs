@@ -0,0 +1,42 @@
+use raylib::core::color::Color;
+
+use super::BoardRect;
+use crate::input::{self, InputMap};
+
+/// Height of a row of the list
+const ROW_HEIGHT: i32 = 18;
+/// Space above the first row, for the title
+const HEADER_HEIGHT: i32 = 36;
+
+/// Draws the shortcuts over the board, each action with its keys, in two columns
+pub fn draw_help<T>(d: &mut T, rect: BoardRect, input: &InputMap)
+where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    let background = Color::from_hex("303030").expect("Error parsing hex");
+    let key_color = Color::from_hex("8ab7ff").expect("Error parsing hex");
+
+    d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.9));
+    d.draw_text(
+        "Keyboard shortcuts (? or Esc to close)",
+        rect.x + 10,
+        rect.y + 10,
+        16,
+        Color::WHITE,
+    );
+
+    let column_width = rect.size / 2;
+    let per_column = ((rect.size - HEADER_HEIGHT) / ROW_HEIGHT).max(1) as usize;
+    for (i, (action, bindings)) in input.entries().enumerate() {
+        let column = (i / per_column) as i32;
+        let x = rect.x + 10 + column * column_width;
+        let y = rect.y + HEADER_HEIGHT + (i % per_column) as i32 * ROW_HEIGHT;
+        let bindings = if bindings.is_empty() {
+            "-".to_string()
+        } else {
+            input::format_bindings(bindings)
+        };
+        d.draw_text(&bindings, x, y, 12, key_color);
+        d.draw_text(action.description(), x + 110, y, 12, Color::WHITE);
+    }
+}
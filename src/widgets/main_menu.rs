@@ -0,0 +1,144 @@
+use raylib::core::color::Color;
+
+use super::BoardRect;
+
+/// Entries of the main menu, in the order they are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    /// back to the game on the board, or to the game left unfinished on quit
+    Continue,
+    /// a new game against the engine
+    PlayEngine,
+    /// a new game with both sides played here
+    TwoPlayers,
+    /// a game against a player on the local network
+    Network,
+    /// puzzles solved on the board
+    Puzzles,
+    /// the lines of the repertoire played against its recorded replies
+    Repertoire,
+    /// the position on the board with either side's pieces free to move
+    Analysis,
+    /// the game played back move by move
+    Replay,
+    /// a game or puzzle fetched from lichess or chess.com
+    Import,
+    /// a game picked from a PGN file
+    Database,
+    /// the games put aside, to pick one up
+    Ongoing,
+    /// results of the games played and the rating against the engine
+    Statistics,
+    /// the player whose settings and games are used
+    Profiles,
+    /// boards side by side, each its own game
+    Simul,
+    /// setting up a position to play or analyze
+    Editor,
+    Settings,
+    Quit,
+}
+
+const ITEMS: [(MenuItem, &str); 17] = [
+    (MenuItem::Continue, "Continue"),
+    (MenuItem::PlayEngine, "Play the engine"),
+    (MenuItem::TwoPlayers, "Two players"),
+    (MenuItem::Network, "Network game"),
+    (MenuItem::Puzzles, "Puzzles"),
+    (MenuItem::Repertoire, "Repertoire drill"),
+    (MenuItem::Analysis, "Analysis board"),
+    (MenuItem::Replay, "Replay game"),
+    (MenuItem::Import, "Import from URL"),
+    (MenuItem::Database, "Game database"),
+    (MenuItem::Ongoing, "Ongoing games"),
+    (MenuItem::Statistics, "Statistics"),
+    (MenuItem::Profiles, "Profiles"),
+    (MenuItem::Simul, "Simul boards"),
+    (MenuItem::Editor, "Board editor"),
+    (MenuItem::Settings, "Settings"),
+    (MenuItem::Quit, "Quit"),
+];
+const ITEM_HEIGHT: i32 = 26;
+
+/// Menu shown over the board at startup and on Escape
+#[derive(Default)]
+pub struct MainMenu {
+    selected: usize,
+    /// where the mouse was last frame, the keys keep their selection while it stays put
+    mouse: (i32, i32),
+}
+
+impl MainMenu {
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < ITEMS.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected(&self) -> MenuItem {
+        ITEMS[self.selected].0
+    }
+
+    /// Top of the first entry
+    fn top(rect: BoardRect) -> i32 {
+        rect.y + (rect.size - ITEMS.len() as i32 * ITEM_HEIGHT) / 2
+    }
+
+    /// Entry at (`mouse_x`, `mouse_y`), if any
+    pub fn item_at(&self, rect: BoardRect, mouse_x: i32, mouse_y: i32) -> Option<MenuItem> {
+        let top = Self::top(rect);
+        if !(rect.x..rect.x + rect.size).contains(&mouse_x) || mouse_y < top {
+            return None;
+        }
+        let idx = ((mouse_y - top) / ITEM_HEIGHT) as usize;
+        ITEMS.get(idx).map(|(item, _)| *item)
+    }
+
+    /// Selects the entry under the mouse if it moved onto one
+    pub fn hover(&mut self, rect: BoardRect, mouse_x: i32, mouse_y: i32) {
+        if std::mem::replace(&mut self.mouse, (mouse_x, mouse_y)) == (mouse_x, mouse_y) {
+            return;
+        }
+        if let Some(item) = self.item_at(rect, mouse_x, mouse_y) {
+            self.selected = ITEMS.iter().position(|(i, _)| *i == item).unwrap_or(0);
+        }
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.9));
+        let title = "ruche";
+        let width = raylib::core::text::measure_text(title, 32);
+        d.draw_text(
+            title,
+            rect.x + (rect.size - width) / 2,
+            Self::top(rect) - 56,
+            32,
+            Color::WHITE,
+        );
+
+        for (i, (_, label)) in ITEMS.iter().enumerate() {
+            let y = Self::top(rect) + i as i32 * ITEM_HEIGHT;
+            if i == self.selected {
+                d.draw_rectangle(rect.x + 40, y, rect.size - 80, ITEM_HEIGHT - 2, highlight);
+            }
+            let width = raylib::core::text::measure_text(label, 20);
+            d.draw_text(
+                label,
+                rect.x + (rect.size - width) / 2,
+                y + (ITEM_HEIGHT - 22) / 2,
+                20,
+                Color::WHITE,
+            );
+        }
+    }
+}
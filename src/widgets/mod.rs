@@ -0,0 +1,81 @@
+use crate::board::PieceColor;
+
+mod analysis_panel;
+mod annotations;
+mod arrow;
+mod backup_picker;
+mod book_moves;
+mod captures;
+mod clock;
+mod conditions;
+mod coordinates;
+mod editor;
+mod engine_stats;
+mod eval_bar;
+mod eval_graph;
+mod explorer_panel;
+mod game_over;
+mod help;
+mod main_menu;
+mod move_input;
+mod move_list;
+mod notice;
+mod passed_pawns;
+mod pause_menu;
+mod pocket;
+mod settings;
+mod turn_indicator;
+
+pub use analysis_panel::{AnalysisPanel, ANALYSIS_LINES, ANALYSIS_PANEL_WIDTH};
+pub use annotations::{annotation_color, Annotations};
+pub use arrow::draw_arrow;
+pub use backup_picker::BackupPicker;
+pub use book_moves::draw_book_moves;
+pub use captures::{draw_captures, CAPTURES_HEIGHT};
+pub use clock::{draw_clock, ClockSetup};
+pub use conditions::draw_conditions;
+pub use coordinates::draw_coordinates;
+pub use editor::EditorPanel;
+pub use engine_stats::EngineStats;
+pub use eval_bar::{EvalBar, EVAL_BAR_WIDTH};
+pub use eval_graph::{EvalGraph, EVAL_GRAPH_HEIGHT};
+pub use explorer_panel::{ExplorerPanel, EXPLORER_PANEL_WIDTH};
+pub use game_over::{GameOverAction, GameOverDialog};
+pub use help::draw_help;
+pub use main_menu::{MainMenu, MenuItem};
+pub use move_input::MoveInput;
+pub use move_list::{MoveList, MOVE_LIST_WIDTH};
+pub use notice::Notice;
+pub use passed_pawns::draw_passed_pawns;
+pub use pause_menu::{PauseItem, PauseMenu};
+pub use pocket::{draw_pocket, pocket_slot_at};
+pub use settings::Settings;
+pub use turn_indicator::TurnIndicator;
+
+/// Screen space occupied by the board, in pixels
+#[derive(Debug, Clone, Copy)]
+pub struct BoardRect {
+    pub x: i32,
+    pub y: i32,
+    pub size: i32,
+    /// side whose pieces start at the bottom of the screen
+    pub bottom: PieceColor,
+}
+
+impl BoardRect {
+    /// Column and row of the square `idx` on screen, counted from the top left
+    pub fn screen_square(&self, idx: usize) -> (i32, i32) {
+        let (x, y) = ((idx % 8) as i32, (idx / 8) as i32);
+        match self.bottom {
+            PieceColor::White => (7 - x, 7 - y),
+            PieceColor::Black => (x, y),
+        }
+    }
+
+    /// Top left corner of the square `idx`, in pixels
+    pub fn square_origin(&self, idx: usize) -> (i32, i32) {
+        let cell = self.size / 8;
+        let (col, row) = self.screen_square(idx);
+        (self.x + col * cell, self.y + row * cell)
+    }
+}
@@ -0,0 +1,66 @@
+use raylib::core::color::Color;
+
+use super::BoardRect;
+
+/// Height of the input box
+const INPUT_HEIGHT: i32 = 32;
+/// Longest text accepted, more than any move needs
+const MAX_LENGTH: usize = 12;
+
+/// A box over the bottom of the board where a move is typed, in SAN or in coordinates, with the
+/// reason the last one was refused
+#[derive(Default)]
+pub struct MoveInput {
+    text: String,
+    error: Option<String>,
+}
+
+impl MoveInput {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Adds a typed character, ignoring the ones no move is written with
+    pub fn push(&mut self, c: char) {
+        if c.is_ascii_alphanumeric() || "-=+#".contains(c) {
+            if self.text.len() < MAX_LENGTH {
+                self.text.push(c);
+            }
+            self.error = None;
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.text.pop();
+        self.error = None;
+    }
+
+    /// Shows why the typed move was refused, keeping the text so it can be corrected
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.error = None;
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let error_color = Color::from_hex("ff8a80").expect("Error parsing hex");
+
+        let y = rect.y + rect.size - INPUT_HEIGHT;
+        d.draw_rectangle(rect.x, y, rect.size, INPUT_HEIGHT, background.fade(0.9));
+        let prompt = format!("Move: {}_", self.text);
+        d.draw_text(&prompt, rect.x + 10, y + 7, 18, Color::WHITE);
+        let hint = match &self.error {
+            Some(error) => (error.as_str(), error_color),
+            None => ("Enter to play, Esc to close", Color::GRAY),
+        };
+        let width = raylib::core::text::measure_text(hint.0, 16);
+        d.draw_text(hint.0, rect.x + rect.size - width - 10, y + 8, 16, hint.1);
+    }
+}
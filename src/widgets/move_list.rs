@@ -0,0 +1,175 @@
+use raylib::core::color::Color;
+
+use crate::board::{Board, Move, PieceColor};
+use crate::eco::Opening;
+use crate::review::MoveQuality;
+use crate::san;
+
+/// Width of the panel, always shown right of the other panels between the captured pieces of
+/// either side
+pub const MOVE_LIST_WIDTH: i32 = 180;
+/// Height of a row of the list, a move number with white's and black's moves
+const ROW_HEIGHT: i32 = 20;
+/// Space above the first row, for the header and the opening
+const HEADER_HEIGHT: i32 = 48;
+
+/// Moves of the game in SAN, two per row under their move number. The move leading to the
+/// position on screen is highlighted and clicking a move shows the position after it
+#[derive(Default)]
+pub struct MoveList {
+    /// moves in SAN, from the start of the game
+    moves: Vec<String>,
+    /// black moves first in games started from a position with black to move
+    black_first: bool,
+    first_number: usize,
+    /// opening of the position on screen, written under the header
+    opening: Option<Opening>,
+    /// grades of the moves once the game is reviewed
+    qualities: Vec<Option<MoveQuality>>,
+    /// progress of the review or the accuracy of both players, beside the header
+    review_summary: Option<String>,
+}
+
+impl MoveList {
+    /// Writes the moves of `history` played from `start` in SAN
+    pub fn set_moves(&mut self, start: &Board, history: &[Move]) {
+        self.black_first = start.get_turn() == PieceColor::Black;
+        self.first_number = start.fullmove_number();
+        self.moves.clear();
+        let mut board = start.clone();
+        for mv in history {
+            self.moves.push(san::to_san(&board, mv));
+            if board.play(mv).is_err() {
+                break;
+            }
+        }
+    }
+
+    pub fn set_opening(&mut self, opening: Option<Opening>) {
+        self.opening = opening;
+    }
+
+    /// Shows the grades of the moves of the review and its `summary`, none without a review
+    pub fn set_review(&mut self, qualities: Vec<Option<MoveQuality>>, summary: Option<String>) {
+        self.qualities = qualities;
+        self.review_summary = summary;
+    }
+
+    /// Grade of the move at `ply`, counted from 0, once reviewed
+    pub fn quality(&self, ply: usize) -> Option<MoveQuality> {
+        self.qualities.get(ply).copied().flatten()
+    }
+
+    /// Row and column of the move at `ply`, counted from 0
+    fn cell(&self, ply: usize) -> (usize, usize) {
+        let slot = ply + self.black_first as usize;
+        (slot / 2, slot % 2)
+    }
+
+    fn rows(&self) -> usize {
+        if self.moves.is_empty() {
+            return 0;
+        }
+        self.cell(self.moves.len() - 1).0 + 1
+    }
+
+    /// Rows fitting in a list `height` pixels high
+    fn fit(height: i32) -> usize {
+        ((height - HEADER_HEIGHT) / ROW_HEIGHT).max(1) as usize
+    }
+
+    /// First row shown, scrolled so that the move leading to position `shown` stays in view
+    fn first_row(&self, height: i32, shown: usize) -> usize {
+        let fit = Self::fit(height);
+        let current = self.cell(shown.saturating_sub(1)).0;
+        (current + 1)
+            .saturating_sub(fit)
+            .min(self.rows().saturating_sub(fit))
+    }
+
+    /// Ply of the move at (`mouse_x`, `mouse_y`), the number of moves played to reach the
+    /// position after it, (`x`, `y`) being the top left corner of a list `height` pixels high
+    pub fn ply_at(
+        &self,
+        (x, y, height): (i32, i32, i32),
+        shown: usize,
+        mouse_x: i32,
+        mouse_y: i32,
+    ) -> Option<usize> {
+        let column_width = (MOVE_LIST_WIDTH - 40) / 2;
+        if mouse_x < x + 40
+            || mouse_x >= x + MOVE_LIST_WIDTH
+            || mouse_y < y + HEADER_HEIGHT
+            || mouse_y >= y + height
+        {
+            return None;
+        }
+        let row =
+            self.first_row(height, shown) + ((mouse_y - y - HEADER_HEIGHT) / ROW_HEIGHT) as usize;
+        let column = ((mouse_x - x - 40) / column_width).min(1) as usize;
+        let slot = row * 2 + column;
+        let ply = slot.checked_sub(self.black_first as usize)?;
+        (ply < self.moves.len()).then_some(ply + 1)
+    }
+
+    /// Draws the list `height` pixels high from (`x`, `y`), `shown` being the number of moves
+    /// played to reach the position on screen
+    pub fn draw<T>(&self, d: &mut T, (x, y, height): (i32, i32, i32), shown: usize)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("262421").expect("Error parsing hex");
+        let highlight = Color::from_hex("4a6e8a").expect("Error parsing hex");
+        let column_width = (MOVE_LIST_WIDTH - 40) / 2;
+
+        d.draw_rectangle(x, y, MOVE_LIST_WIDTH, height, background);
+        d.draw_text("Moves", x + 8, y + 8, 16, Color::WHITE);
+        if let Some(summary) = &self.review_summary {
+            let width = raylib::core::text::measure_text(summary, 12);
+            d.draw_text(
+                summary,
+                x + MOVE_LIST_WIDTH - 8 - width,
+                y + 10,
+                12,
+                Color::GRAY,
+            );
+        }
+        if let Some(opening) = &self.opening {
+            // long names lose their last words to fit the panel
+            let mut text = format!("{} {}", opening.eco, opening.name);
+            while raylib::core::text::measure_text(&text, 12) > MOVE_LIST_WIDTH - 16 {
+                let Some(cut) = text.trim_end_matches("...").rfind(' ') else {
+                    break;
+                };
+                text.truncate(cut);
+                text.push_str("...");
+            }
+            d.draw_text(&text, x + 8, y + 28, 12, Color::LIGHTGRAY);
+        }
+
+        let first = self.first_row(height, shown);
+        for row in first..self.rows().min(first + Self::fit(height)) {
+            let y = y + HEADER_HEIGHT + (row - first) as i32 * ROW_HEIGHT;
+            let number = format!("{}.", self.first_number + row);
+            d.draw_text(&number, x + 8, y + 3, 14, Color::GRAY);
+
+            for column in 0..2 {
+                let Some(ply) = (row * 2 + column).checked_sub(self.black_first as usize) else {
+                    continue;
+                };
+                let Some(san) = self.moves.get(ply) else {
+                    continue;
+                };
+                let cx = x + 40 + column as i32 * column_width;
+                if ply + 1 == shown {
+                    d.draw_rectangle(cx - 4, y, column_width, ROW_HEIGHT, highlight);
+                }
+                d.draw_text(san, cx, y + 3, 14, Color::LIGHTGRAY);
+                if let Some(quality) = self.quality(ply) {
+                    let width = raylib::core::text::measure_text(san, 14);
+                    d.draw_text(quality.glyph(), cx + width + 2, y + 3, 14, quality.color());
+                }
+            }
+        }
+    }
+}
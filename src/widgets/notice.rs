@@ -0,0 +1,45 @@
+use std::time::{Duration, Instant};
+
+use raylib::core::color::Color;
+
+use super::BoardRect;
+
+/// How long a notice stays on screen
+const NOTICE_DURATION: Duration = Duration::from_secs(4);
+/// Height of the banner
+const NOTICE_HEIGHT: i32 = 28;
+
+/// A short message shown in a banner over the top of the board, e.g. when the host rejects a move
+#[derive(Default)]
+pub struct Notice {
+    message: Option<(String, Instant)>,
+}
+
+impl Notice {
+    pub fn show(&mut self, message: String) {
+        tracing::info!("{}", message);
+        self.message = Some((message, Instant::now()));
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let Some((message, shown_at)) = &self.message else {
+            return;
+        };
+        if shown_at.elapsed() > NOTICE_DURATION {
+            return;
+        }
+
+        let background = Color::from_hex("b3261e").expect("Error parsing hex");
+        d.draw_rectangle(
+            rect.x,
+            rect.y,
+            rect.size,
+            NOTICE_HEIGHT,
+            background.fade(0.9),
+        );
+        d.draw_text(message, rect.x + 8, rect.y + 6, 16, Color::WHITE);
+    }
+}
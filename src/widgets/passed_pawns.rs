@@ -0,0 +1,18 @@
+use raylib::core::color::Color;
+
+use super::BoardRect;
+
+/// Marks the squares of the passed pawns in `passed`, a bitboard of both sides' passed pawns
+pub fn draw_passed_pawns<T>(d: &mut T, rect: BoardRect, passed: u64)
+where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    let color = Color::from_hex("d9822b").expect("Error parsing hex");
+    let cell = rect.size / 8;
+
+    for idx in (0..64).filter(|idx| passed & (1u64 << idx) != 0) {
+        let (x, y) = rect.square_origin(idx);
+        d.draw_rectangle_lines(x + 2, y + 2, cell - 4, cell - 4, color);
+        d.draw_circle(x + cell - 10, y + 10, 5.0, color);
+    }
+}
@@ -0,0 +1,110 @@
+use raylib::core::color::Color;
+
+use super::BoardRect;
+
+/// Entries of the pause menu, in the order they are listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseItem {
+    /// back to the game, the clocks running again
+    Resume,
+    Settings,
+    /// the game so far as PGN in the working directory
+    SaveGame,
+    Resign,
+    MainMenu,
+    Quit,
+}
+
+const ITEMS: [(PauseItem, &str); 6] = [
+    (PauseItem::Resume, "Resume"),
+    (PauseItem::Settings, "Settings"),
+    (PauseItem::SaveGame, "Save game"),
+    (PauseItem::Resign, "Resign"),
+    (PauseItem::MainMenu, "Main menu"),
+    (PauseItem::Quit, "Quit"),
+];
+const ITEM_HEIGHT: i32 = 36;
+
+/// Menu shown over the game on Escape or when the window loses focus, the game standing still
+/// behind it
+#[derive(Default)]
+pub struct PauseMenu {
+    selected: usize,
+    /// where the mouse was last frame, the keys keep their selection while it stays put
+    mouse: (i32, i32),
+}
+
+impl PauseMenu {
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < ITEMS.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected(&self) -> PauseItem {
+        ITEMS[self.selected].0
+    }
+
+    /// Top of the first entry
+    fn top(rect: BoardRect) -> i32 {
+        rect.y + (rect.size - ITEMS.len() as i32 * ITEM_HEIGHT) / 2
+    }
+
+    /// Entry at (`mouse_x`, `mouse_y`), if any
+    pub fn item_at(&self, rect: BoardRect, mouse_x: i32, mouse_y: i32) -> Option<PauseItem> {
+        let top = Self::top(rect);
+        if !(rect.x..rect.x + rect.size).contains(&mouse_x) || mouse_y < top {
+            return None;
+        }
+        let idx = ((mouse_y - top) / ITEM_HEIGHT) as usize;
+        ITEMS.get(idx).map(|(item, _)| *item)
+    }
+
+    /// Selects the entry under the mouse if it moved onto one
+    pub fn hover(&mut self, rect: BoardRect, mouse_x: i32, mouse_y: i32) {
+        if std::mem::replace(&mut self.mouse, (mouse_x, mouse_y)) == (mouse_x, mouse_y) {
+            return;
+        }
+        if let Some(item) = self.item_at(rect, mouse_x, mouse_y) {
+            self.selected = ITEMS.iter().position(|(i, _)| *i == item).unwrap_or(0);
+        }
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.9));
+        let title = "Paused";
+        let width = raylib::core::text::measure_text(title, 32);
+        d.draw_text(
+            title,
+            rect.x + (rect.size - width) / 2,
+            Self::top(rect) - 56,
+            32,
+            Color::WHITE,
+        );
+
+        for (i, (_, label)) in ITEMS.iter().enumerate() {
+            let y = Self::top(rect) + i as i32 * ITEM_HEIGHT;
+            if i == self.selected {
+                d.draw_rectangle(rect.x + 40, y, rect.size - 80, ITEM_HEIGHT - 6, highlight);
+            }
+            let width = raylib::core::text::measure_text(label, 20);
+            d.draw_text(
+                label,
+                rect.x + (rect.size - width) / 2,
+                y + 6,
+                20,
+                Color::WHITE,
+            );
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use raylib::core::color::Color;
+use raylib::core::math::Vector2;
+use raylib::core::texture::{RaylibTexture2D, Texture2D};
+
+use super::CAPTURES_HEIGHT;
+use crate::board::{Piece, PieceColor, PieceType, Pocket, POCKET_PIECES};
+
+/// Width of the slot of a piece type, its icon and count
+const SLOT_WIDTH: i32 = 36;
+
+/// Piece type of the slot at `mouse_x`, in a pocket drawn from `x`
+pub fn pocket_slot_at(x: i32, mouse_x: i32) -> Option<PieceType> {
+    if mouse_x < x {
+        return None;
+    }
+    POCKET_PIECES
+        .get(((mouse_x - x) / SLOT_WIDTH) as usize)
+        .copied()
+}
+
+/// Draws the crazyhouse pocket of `color` in place of its captures, a slot per piece type in a
+/// strip `width` wide at (`x`, `y`). The types it has none of are faded and `selected` is framed
+pub fn draw_pocket<T>(
+    d: &mut T,
+    x: i32,
+    y: i32,
+    width: i32,
+    textures: &HashMap<Piece, Texture2D>,
+    (pocket, color): (&Pocket, PieceColor),
+    selected: Option<PieceType>,
+) where
+    T: raylib::core::drawing::RaylibDraw,
+{
+    let background = Color::from_hex("262421").expect("Error parsing hex");
+    let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+    d.draw_rectangle(x, y, width, CAPTURES_HEIGHT, background);
+
+    let icon = CAPTURES_HEIGHT - 6;
+    for (i, piece_type) in POCKET_PIECES.into_iter().enumerate() {
+        let sx = x + i as i32 * SLOT_WIDTH;
+        let count = pocket.count(piece_type);
+        if selected == Some(piece_type) {
+            d.draw_rectangle_lines(
+                sx + 1,
+                y + 1,
+                SLOT_WIDTH - 2,
+                CAPTURES_HEIGHT - 2,
+                highlight,
+            );
+        }
+        let tint = if count > 0 {
+            Color::WHITE
+        } else {
+            Color::WHITE.fade(0.25)
+        };
+        if let Some(texture) = textures.get(&Piece::new(color, piece_type)) {
+            let scale = icon as f32 / texture.width().max(1) as f32;
+            d.draw_texture_ex(
+                texture,
+                Vector2::new((sx + 3) as f32, (y + 3) as f32),
+                0.0,
+                scale,
+                tint,
+            );
+        }
+        if count > 1 {
+            d.draw_text(
+                &count.to_string(),
+                sx + icon + 2,
+                y + CAPTURES_HEIGHT - 14,
+                12,
+                Color::LIGHTGRAY,
+            );
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use raylib::core::color::Color;
+
+use super::BoardRect;
+
+/// Height of a row, the list scrolls when they don't all fit on the board
+const ROW_HEIGHT: i32 = 28;
+/// Space above the first row, for the title
+const HEADER_HEIGHT: i32 = 40;
+
+/// Overlay listing the settings, one per row, the selected one being changed with the
+/// arrow keys
+#[derive(Default)]
+pub struct Settings {
+    selected: usize,
+}
+
+impl Settings {
+    pub fn select_next(&mut self, rows: usize) {
+        if self.selected + 1 < rows {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /// Draws the `rows`, each a name and its current value
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect, rows: &[(&str, String)])
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let background = Color::from_hex("303030").expect("Error parsing hex");
+        let highlight = Color::from_hex("8ab7ff").expect("Error parsing hex");
+
+        d.draw_rectangle(rect.x, rect.y, rect.size, rect.size, background.fade(0.9));
+        d.draw_text(
+            "Settings (Up/Down, Left/Right, Esc to close)",
+            rect.x + 10,
+            rect.y + 10,
+            16,
+            Color::WHITE,
+        );
+
+        // scrolled so that the selected row stays in view
+        let fit = ((rect.size - HEADER_HEIGHT) / ROW_HEIGHT).max(1) as usize;
+        let first = (self.selected + 1).saturating_sub(fit);
+        for (i, (name, value)) in rows.iter().enumerate().skip(first).take(fit) {
+            let y = rect.y + HEADER_HEIGHT + (i - first) as i32 * ROW_HEIGHT;
+            if i == self.selected {
+                d.draw_rectangle(rect.x + 5, y - 4, rect.size - 10, 26, highlight);
+            }
+            d.draw_text(name, rect.x + 10, y, 18, Color::WHITE);
+            let value = format!("< {} >", value);
+            let width = raylib::core::text::measure_text(&value, 18);
+            d.draw_text(&value, rect.x + rect.size - 15 - width, y, 18, Color::WHITE);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Instant;
+
+use raylib::core::color::Color;
+
+use crate::board::PieceColor;
+use crate::events::BoardEvent;
+
+use super::BoardRect;
+
+/// How long the edge glow takes to fade in after the turn changes, in seconds
+const GLOW_FADE_IN: f32 = 0.3;
+/// Width of the glow drawn along the board edge
+const GLOW_WIDTH: i32 = 6;
+
+/// Shows whose turn it is: a nameplate for each side with the side to move highlighted, and a
+/// subtle glow along the board edge of the side to move
+pub struct TurnIndicator {
+    side_to_move: PieceColor,
+    changed_at: Instant,
+}
+
+impl TurnIndicator {
+    pub fn new(side_to_move: PieceColor) -> Self {
+        Self {
+            side_to_move,
+            changed_at: Instant::now(),
+        }
+    }
+
+    pub fn on_event(&mut self, event: &BoardEvent) {
+        if let BoardEvent::TurnChanged(color) = event {
+            self.side_to_move = *color;
+            self.changed_at = Instant::now();
+        }
+    }
+
+    pub fn draw<T>(&self, d: &mut T, rect: BoardRect)
+    where
+        T: raylib::core::drawing::RaylibDraw,
+    {
+        let highlight = Color::from_hex("f6c744").expect("Error parsing hex");
+        let plate = Color::from_hex("dddddd").expect("Error parsing hex");
+
+        let plate_height = 30;
+        let plate_width = rect.size / 2 - 10;
+        let plate_y = rect.y - plate_height - 10;
+
+        for (i, color) in [PieceColor::White, PieceColor::Black].iter().enumerate() {
+            let plate_x = rect.x + 5 + i as i32 * (rect.size / 2);
+            let to_move = *color == self.side_to_move;
+
+            d.draw_rectangle(
+                plate_x,
+                plate_y,
+                plate_width,
+                plate_height,
+                if to_move { highlight } else { plate },
+            );
+
+            let (dot, outline, name) = match color {
+                PieceColor::White => (Color::WHITE, Color::BLACK, "White"),
+                PieceColor::Black => (Color::BLACK, Color::WHITE, "Black"),
+            };
+            let dot_x = plate_x + plate_height / 2;
+            let dot_y = plate_y + plate_height / 2;
+            d.draw_circle(dot_x, dot_y, 9.0, outline);
+            d.draw_circle(dot_x, dot_y, 8.0, dot);
+
+            let label = if to_move {
+                format!("{} to move", name)
+            } else {
+                name.to_string()
+            };
+            d.draw_text(&label, dot_x + 15, dot_y - 8, 18, Color::BLACK);
+        }
+
+        // fade the glow in so the change of turn is noticeable without being distracting
+        let t = (self.changed_at.elapsed().as_secs_f32() / GLOW_FADE_IN).min(1.0);
+        let glow = highlight.fade(0.6 * t);
+
+        // along the edge the side to move starts from
+        let edge_y = if self.side_to_move == rect.bottom {
+            rect.y + rect.size - GLOW_WIDTH
+        } else {
+            rect.y
+        };
+        d.draw_rectangle(rect.x, edge_y, rect.size, GLOW_WIDTH, glow);
+    }
+}